@@ -0,0 +1,669 @@
+//! Alert rules and webhook notifications.
+//!
+//! [`Alerter`] evaluates the configured [`AlertRule`]s against the status
+//! snapshots and events published to [`Telemetry`]. Rules on a condition
+//! (hashrate, temperature, pool) fire once it has held for the rule's
+//! `for_s` and resolve when it clears. A board disconnect or underperforming
+//! chip has a natural end too, so those fire and resolve the same way, on
+//! the matching pair of events. Faults and found blocks have no ongoing
+//! condition and raise one-off alerts instead, with repeats of the same one
+//! suppressed for `dedupe_s`.
+//!
+//! Every alert is published as [`MinerEvent::Alert`], so it reaches API
+//! event stream subscribers, and is posted to each configured webhook.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::api_client::types::{Alert, AlertState, MinerEvent, MinerStatus};
+use crate::config::{AlertRule, AlertsConfig, WebhookConfig};
+use crate::history::unix_now;
+use crate::telemetry::Telemetry;
+use crate::tracing::prelude::*;
+use crate::types::HashRate;
+
+/// Interval at which conditions are evaluated against the latest status.
+const EVALUATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time allowed for a webhook to accept a notification.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hashrate window the `hashrate_below` rule compares against.
+const HASHRATE_WINDOW_S: u64 = 300;
+
+/// Evaluates alert rules and tracks which alerts are firing.
+pub struct Alerter {
+    config: AlertsConfig,
+
+    /// When each rule's condition started holding, by rule index and subject.
+    pending: HashMap<(usize, String), Instant>,
+
+    /// Firing alerts by rule index and subject, with when they were last
+    /// sent and the summary to repeat.
+    firing: HashMap<(usize, String), (Instant, Alert)>,
+
+    /// One-off alerts recently sent, by rule index and summary.
+    recent: HashMap<(usize, String), Instant>,
+}
+
+/// One subject's state under a condition rule.
+struct Observation {
+    subject: String,
+    holds: bool,
+    value: Option<f64>,
+    firing: String,
+    resolved: String,
+}
+
+impl Alerter {
+    pub fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+            firing: HashMap::new(),
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Evaluate condition rules against `status`, and repeat alerts still
+    /// firing if configured to.
+    pub fn on_status(&mut self, status: &MinerStatus, now: Instant, time: u64) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for (index, rule) in self.config.rules.clone().iter().enumerate() {
+            let (observations, for_s) = match rule {
+                // A paused miner is meant to be idle; keep the state as is
+                AlertRule::HashrateBelow { .. } if status.paused => continue,
+                AlertRule::HashrateBelow { hashrate, for_s } => {
+                    (vec![hashrate_observation(status, *hashrate)], *for_s)
+                }
+                AlertRule::TemperatureAbove { celsius, for_s } => {
+                    (temperature_observations(status, *celsius), *for_s)
+                }
+                AlertRule::PoolDown { for_s } => (pool_observations(status), *for_s),
+                _ => continue,
+            };
+
+            for observation in &observations {
+                let key = (index, observation.subject.clone());
+                if observation.holds {
+                    let since = *self.pending.entry(key.clone()).or_insert(now);
+                    if now.duration_since(since) >= Duration::from_secs(for_s) {
+                        alerts.extend(self.fire(
+                            index,
+                            &observation.subject,
+                            observation.firing.clone(),
+                            observation.value,
+                            now,
+                            time,
+                        ));
+                    }
+                } else {
+                    self.pending.remove(&key);
+                    alerts.extend(self.resolve(
+                        index,
+                        &observation.subject,
+                        observation.resolved.clone(),
+                        observation.value,
+                        time,
+                    ));
+                }
+            }
+
+            // Subjects no longer reported (removed board or pool) are clear
+            let gone: Vec<String> = self
+                .firing
+                .keys()
+                .chain(self.pending.keys())
+                .filter(|(i, subject)| {
+                    *i == index && !observations.iter().any(|o| &o.subject == subject)
+                })
+                .map(|(_, subject)| subject.clone())
+                .collect();
+            for subject in gone {
+                self.pending.remove(&(index, subject.clone()));
+                alerts.extend(self.resolve(
+                    index,
+                    &subject,
+                    format!("{subject} is no longer reported"),
+                    None,
+                    time,
+                ));
+            }
+        }
+
+        let repeat = Duration::from_secs(self.config.repeat_s);
+        if !repeat.is_zero() {
+            for (sent, alert) in self.firing.values_mut() {
+                if now.duration_since(*sent) >= repeat {
+                    *sent = now;
+                    alerts.push(Alert {
+                        time,
+                        ..alert.clone()
+                    });
+                }
+            }
+        }
+
+        alerts
+    }
+
+    /// Raise and resolve alerts for an event.
+    pub fn on_event(&mut self, event: &MinerEvent, now: Instant, time: u64) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for (index, rule) in self.config.rules.clone().iter().enumerate() {
+            match (rule, event) {
+                (AlertRule::BoardDisconnected, MinerEvent::BoardDisconnected { serial }) => {
+                    alerts.extend(self.fire(
+                        index,
+                        serial,
+                        format!("Board {serial} disconnected"),
+                        None,
+                        now,
+                        time,
+                    ));
+                }
+                (AlertRule::BoardDisconnected, MinerEvent::BoardConnected { serial, .. }) => {
+                    alerts.extend(self.resolve(
+                        index,
+                        serial,
+                        format!("Board {serial} reconnected"),
+                        None,
+                        time,
+                    ));
+                }
+                (
+                    AlertRule::BoardFault,
+                    MinerEvent::BoardFault {
+                        serial,
+                        component,
+                        fault,
+                        ..
+                    },
+                ) => {
+                    alerts.extend(self.once(
+                        index,
+                        serial,
+                        format!("Board {serial} fault in {component}: {fault}"),
+                        now,
+                        time,
+                    ));
+                }
+                (
+                    AlertRule::ChipUnderperforming,
+                    MinerEvent::ChipUnderperforming {
+                        thread,
+                        chip,
+                        domain,
+                        health,
+                    },
+                ) => {
+                    let subject = chip_subject(thread, *chip, *domain);
+                    alerts.extend(self.fire(
+                        index,
+                        &subject,
+                        format!(
+                            "{subject} underperforming ({:.0}% of expected)",
+                            health * 100.0
+                        ),
+                        Some(*health),
+                        now,
+                        time,
+                    ));
+                }
+                (
+                    AlertRule::ChipUnderperforming,
+                    MinerEvent::ChipRecovered {
+                        thread,
+                        chip,
+                        domain,
+                    },
+                ) => {
+                    let subject = chip_subject(thread, *chip, *domain);
+                    alerts.extend(self.resolve(
+                        index,
+                        &subject,
+                        format!("{subject} recovered"),
+                        None,
+                        time,
+                    ));
+                }
+                (AlertRule::BlockFound, MinerEvent::BlockFound { pool, thread, hash }) => {
+                    alerts.extend(self.once(
+                        index,
+                        thread,
+                        format!("Block {hash} found by {thread} for {pool}"),
+                        now,
+                        time,
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        alerts
+    }
+
+    /// Raise an alert unless it is already firing.
+    fn fire(
+        &mut self,
+        index: usize,
+        subject: &str,
+        summary: String,
+        value: Option<f64>,
+        now: Instant,
+        time: u64,
+    ) -> Option<Alert> {
+        let key = (index, subject.to_string());
+        if self.firing.contains_key(&key) {
+            return None;
+        }
+
+        let alert = Alert {
+            rule: self.rule_name(index),
+            state: AlertState::Firing,
+            subject: subject.to_string(),
+            summary,
+            value,
+            time,
+        };
+        self.firing.insert(key, (now, alert.clone()));
+        Some(alert)
+    }
+
+    /// Resolve an alert if it is firing.
+    fn resolve(
+        &mut self,
+        index: usize,
+        subject: &str,
+        summary: String,
+        value: Option<f64>,
+        time: u64,
+    ) -> Option<Alert> {
+        self.firing.remove(&(index, subject.to_string()))?;
+        Some(Alert {
+            rule: self.rule_name(index),
+            state: AlertState::Resolved,
+            subject: subject.to_string(),
+            summary,
+            value,
+            time,
+        })
+    }
+
+    /// Raise a one-off alert unless the same one was raised recently.
+    fn once(
+        &mut self,
+        index: usize,
+        subject: &str,
+        summary: String,
+        now: Instant,
+        time: u64,
+    ) -> Option<Alert> {
+        let dedupe = Duration::from_secs(self.config.dedupe_s);
+        self.recent
+            .retain(|_, sent| now.duration_since(*sent) < dedupe);
+        if self.recent.contains_key(&(index, summary.clone())) {
+            trace!(summary = %summary, "Duplicate alert suppressed");
+            return None;
+        }
+        self.recent.insert((index, summary.clone()), now);
+
+        Some(Alert {
+            rule: self.rule_name(index),
+            state: AlertState::Event,
+            subject: subject.to_string(),
+            summary,
+            value: None,
+            time,
+        })
+    }
+
+    fn rule_name(&self, index: usize) -> String {
+        let rule = &self.config.rules[index];
+        serde_json::to_value(rule)
+            .ok()
+            .and_then(|value| value["kind"].as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+fn hashrate_observation(status: &MinerStatus, threshold: f64) -> Observation {
+    let hashrate = status
+        .hashrate_windows
+        .iter()
+        .find(|w| w.window_s == HASHRATE_WINDOW_S)
+        .map_or(status.hashrate, |w| w.hashrate);
+    let human = |h: f64| HashRate(h as u64).to_human_readable();
+
+    Observation {
+        subject: "miner".to_string(),
+        holds: hashrate < threshold,
+        value: Some(hashrate),
+        firing: format!("Hashrate {} below {}", human(hashrate), human(threshold)),
+        resolved: format!("Hashrate recovered to {}", human(hashrate)),
+    }
+}
+
+fn temperature_observations(status: &MinerStatus, limit: f32) -> Vec<Observation> {
+    status
+        .boards
+        .iter()
+        .filter_map(|board| {
+            let celsius = board.asic_temp_c?;
+            Some(Observation {
+                subject: board.serial.clone(),
+                holds: celsius > limit,
+                value: Some(celsius as f64),
+                firing: format!(
+                    "Board {} ASIC temperature {celsius:.1} °C above {limit:.1} °C",
+                    board.serial
+                ),
+                resolved: format!(
+                    "Board {} ASIC temperature back to {celsius:.1} °C",
+                    board.serial
+                ),
+            })
+        })
+        .collect()
+}
+
+fn pool_observations(status: &MinerStatus) -> Vec<Observation> {
+    status
+        .pools
+        .iter()
+        .map(|pool| Observation {
+            subject: pool.name.clone(),
+            holds: !pool.connected,
+            value: None,
+            firing: format!("Pool {} has no work", pool.name),
+            resolved: format!("Pool {} is working again", pool.name),
+        })
+        .collect()
+}
+
+fn chip_subject(thread: &str, chip: u8, domain: Option<u8>) -> String {
+    match domain {
+        Some(domain) => format!("{thread} chip {chip} domain {domain}"),
+        None => format!("{thread} chip {chip}"),
+    }
+}
+
+/// Body of a webhook request for `alert`.
+///
+/// Without a template the alert itself is sent as JSON.
+pub fn render(webhook: &WebhookConfig, alert: &Alert) -> String {
+    let Some(template) = &webhook.template else {
+        return serde_json::to_string(alert).unwrap_or_default();
+    };
+
+    // Escape as the inside of a JSON string, without the quotes
+    let escape = |text: &str| {
+        let quoted = serde_json::to_string(text).unwrap_or_default();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    let state = match alert.state {
+        AlertState::Firing => "firing",
+        AlertState::Resolved => "resolved",
+        AlertState::Event => "event",
+    };
+
+    template
+        .replace("{{rule}}", &escape(&alert.rule))
+        .replace("{{state}}", state)
+        .replace("{{subject}}", &escape(&alert.subject))
+        .replace("{{summary}}", &escape(&alert.summary))
+        .replace(
+            "{{value}}",
+            &alert.value.map(|v| v.to_string()).unwrap_or_default(),
+        )
+        .replace("{{time}}", &alert.time.to_string())
+}
+
+/// Post `alert` to each webhook, logging failures.
+async fn notify(http: reqwest::Client, webhooks: Vec<WebhookConfig>, alert: Alert) {
+    for (index, webhook) in webhooks.iter().enumerate() {
+        let mut request = http
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, &webhook.content_type)
+            .body(render(webhook, &alert));
+        for (name, value) in &webhook.headers {
+            request = request.header(name, value);
+        }
+
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            // The URL may hold a token, so name the webhook by position
+            Ok(_) => debug!(webhook = index, summary = %alert.summary, "Alert delivered"),
+            Err(e) => warn!(
+                webhook = index,
+                error = %e.without_url(),
+                "Failed to deliver alert"
+            ),
+        }
+    }
+}
+
+/// Evaluate alert rules until shutdown, publishing and delivering alerts.
+pub async fn task(config: AlertsConfig, telemetry: Telemetry, shutdown: CancellationToken) {
+    let http = match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+        Ok(http) => http,
+        Err(e) => {
+            error!(error = %e, "Failed to create webhook client; alerts disabled");
+            return;
+        }
+    };
+    let webhooks = config.webhooks.clone();
+    let mut alerter = Alerter::new(config);
+    let mut events = telemetry.subscribe_events();
+    let mut interval = tokio::time::interval(EVALUATION_INTERVAL);
+
+    loop {
+        let alerts = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {
+                alerter.on_status(&telemetry.status(), Instant::now(), unix_now())
+            }
+            event = events.recv() => match event {
+                Ok(MinerEvent::Alert(_)) => continue,
+                Ok(event) => alerter.on_event(&event, Instant::now(), unix_now()),
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(missed, "Alerting fell behind; events missed");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        for alert in alerts {
+            match alert.state {
+                AlertState::Resolved => info!(rule = %alert.rule, "{}", alert.summary),
+                _ => warn!(rule = %alert.rule, "{}", alert.summary),
+            }
+            telemetry.emit(MinerEvent::Alert(alert.clone()));
+            if !webhooks.is_empty() {
+                // Deliver in the background so a slow webhook can't hold
+                // up evaluation
+                tokio::spawn(notify(http.clone(), webhooks.clone(), alert));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::api_client::types::{BoardStatus, PoolStatus};
+
+    fn alerter(rules: Vec<AlertRule>) -> Alerter {
+        Alerter::new(AlertsConfig {
+            rules,
+            ..Default::default()
+        })
+    }
+
+    fn board(serial: &str, celsius: f32) -> BoardStatus {
+        BoardStatus {
+            serial: serial.to_string(),
+            asic_temp_c: Some(celsius),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_condition_fires_after_duration_and_resolves() {
+        let mut alerter = alerter(vec![AlertRule::TemperatureAbove {
+            celsius: 75.0,
+            for_s: 60,
+        }]);
+        let start = Instant::now();
+        let at = |s| start + Duration::from_secs(s);
+        let mut status = MinerStatus {
+            boards: vec![board("a", 80.0), board("b", 60.0)],
+            ..Default::default()
+        };
+
+        assert!(alerter.on_status(&status, at(0), 0).is_empty());
+        assert!(alerter.on_status(&status, at(30), 30).is_empty());
+
+        let alerts = alerter.on_status(&status, at(60), 60);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule, "temperature_above");
+        assert_eq!(alerts[0].state, AlertState::Firing);
+        assert_eq!(alerts[0].subject, "a");
+        assert_eq!(alerts[0].value, Some(80.0));
+
+        // Firing alerts are not repeated by default
+        assert!(alerter.on_status(&status, at(90), 90).is_empty());
+
+        status.boards[0].asic_temp_c = Some(70.0);
+        let alerts = alerter.on_status(&status, at(120), 120);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].state, AlertState::Resolved);
+        assert_eq!(
+            alerts[0].summary,
+            "Board a ASIC temperature back to 70.0 °C"
+        );
+    }
+
+    #[test]
+    fn test_hashrate_rule_ignores_pause_and_repeats() {
+        let mut alerter = Alerter::new(AlertsConfig {
+            rules: vec![AlertRule::HashrateBelow {
+                hashrate: 1e12,
+                for_s: 0,
+            }],
+            repeat_s: 600,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let at = |s| start + Duration::from_secs(s);
+        let mut status = MinerStatus {
+            hashrate: 5e11,
+            paused: true,
+            ..Default::default()
+        };
+
+        assert!(alerter.on_status(&status, at(0), 0).is_empty());
+
+        status.paused = false;
+        assert_eq!(alerter.on_status(&status, at(10), 10).len(), 1);
+        assert!(alerter.on_status(&status, at(300), 300).is_empty());
+        let repeated = alerter.on_status(&status, at(610), 610);
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].state, AlertState::Firing);
+        assert_eq!(repeated[0].time, 610);
+    }
+
+    #[test]
+    fn test_pool_and_board_lifecycle() {
+        let mut alerter = alerter(vec![
+            AlertRule::PoolDown { for_s: 0 },
+            AlertRule::BoardDisconnected,
+        ]);
+        let now = Instant::now();
+        let status = MinerStatus {
+            pools: vec![PoolStatus {
+                name: "main".to_string(),
+                connected: false,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(alerter.on_status(&status, now, 0)[0].subject, "main");
+
+        // Removing the pool clears its alert
+        let alerts = alerter.on_status(&MinerStatus::default(), now, 0);
+        assert_eq!(alerts[0].state, AlertState::Resolved);
+
+        let disconnected = MinerEvent::BoardDisconnected {
+            serial: "a".to_string(),
+        };
+        assert_eq!(alerter.on_event(&disconnected, now, 0).len(), 1);
+        assert!(alerter.on_event(&disconnected, now, 0).is_empty());
+        let reconnected = MinerEvent::BoardConnected {
+            serial: "a".to_string(),
+            model: "Bitaxe".to_string(),
+        };
+        let alerts = alerter.on_event(&reconnected, now, 0);
+        assert_eq!(alerts[0].summary, "Board a reconnected");
+    }
+
+    #[test]
+    fn test_one_off_alerts_are_deduplicated() {
+        let mut alerter = alerter(vec![AlertRule::BoardFault]);
+        let start = Instant::now();
+        let fault = MinerEvent::BoardFault {
+            serial: "a".to_string(),
+            component: "power_controller".to_string(),
+            fault: "VOUT overvoltage".to_string(),
+            recoverable: false,
+        };
+
+        let alerts = alerter.on_event(&fault, start, 0);
+        assert_eq!(alerts[0].state, AlertState::Event);
+        assert_eq!(
+            alerts[0].summary,
+            "Board a fault in power_controller: VOUT overvoltage"
+        );
+        assert!(alerter
+            .on_event(&fault, start + Duration::from_secs(60), 60)
+            .is_empty());
+        assert_eq!(
+            alerter
+                .on_event(&fault, start + Duration::from_secs(301), 301)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_render_template() {
+        let alert = Alert {
+            rule: "block_found".to_string(),
+            state: AlertState::Event,
+            subject: "abc/0".to_string(),
+            summary: "Block \"0000\" found".to_string(),
+            value: None,
+            time: 1_700_000_000,
+        };
+        let mut webhook = WebhookConfig {
+            url: "http://localhost".to_string(),
+            template: None,
+            content_type: "application/json".to_string(),
+            headers: BTreeMap::new(),
+        };
+
+        let body: serde_json::Value = serde_json::from_str(&render(&webhook, &alert)).unwrap();
+        assert_eq!(body["state"], "event");
+
+        webhook.template = Some(r#"{"text": "[{{state}}] {{summary}} at {{time}}"}"#.to_string());
+        let body: serde_json::Value = serde_json::from_str(&render(&webhook, &alert)).unwrap();
+        assert_eq!(body["text"], "[event] Block \"0000\" found at 1700000000");
+    }
+}
@@ -0,0 +1,709 @@
+//! cgminer-compatible API server.
+//!
+//! Farm management software and most third-party monitoring tools speak the
+//! cgminer JSON API on TCP port 4028. This server answers the subset they
+//! rely on---`version`, `summary`, `devs`, `pools`, `stats`, `switchpool`,
+//! and `addpool`---from mujina's telemetry and scheduler, so mujina can be
+//! dropped into existing tooling.
+//!
+//! As with cgminer, each connection carries one request and is closed after
+//! the reply. Requests are JSON objects like
+//! `{"command": "switchpool", "parameter": "1"}`; the reply is a JSON object
+//! followed by a NUL byte. Commands joined with `+` (`summary+pools`) are
+//! answered together, keyed by command name. cgminer's older plain-text
+//! request form is not supported.
+//!
+//! Hashrates are reported as `MHS av` and over the 1, 5 and 15 minute
+//! windows; mujina estimates nothing shorter, so cgminer's `MHS 5s` is left
+//! out.
+//!
+//! Pools are identified by their position in the pool list, as cgminer
+//! tools expect. Commands that change state are refused unless the server is
+//! configured as writable, mirroring cgminer's `--api-allow W:` access.
+
+use anyhow::Result;
+use serde::{de::IgnoredAny, Deserialize};
+use serde_json::{json, Map, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::{send_command, ApiState};
+use crate::api_client::types::{AddPoolRequest, HashrateEstimate, MinerStatus};
+use crate::scheduler::SchedulerCommand;
+
+/// Version of the cgminer API this server imitates.
+const API_VERSION: &str = "3.7";
+
+/// Longest request accepted; anything longer is treated as complete.
+const MAX_REQUEST_LEN: usize = 8192;
+
+/// How long a client may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// cgminer status codes for the messages this server can produce
+const MSG_POOL: u32 = 7;
+const MSG_NOPOOL: u32 = 8;
+const MSG_DEVS: u32 = 9;
+const MSG_SUMM: u32 = 11;
+const MSG_INVCMD: u32 = 14;
+const MSG_VERSION: u32 = 22;
+const MSG_INVJSON: u32 = 23;
+const MSG_MISPID: u32 = 25;
+const MSG_INVPID: u32 = 26;
+const MSG_SWITCHP: u32 = 27;
+const MSG_ACCDENY: u32 = 45;
+const MSG_MISPDP: u32 = 52;
+const MSG_INVPDP: u32 = 53;
+const MSG_ADDPOOL: u32 = 55;
+const MSG_MINESTATS: u32 = 70;
+
+/// A request as sent by cgminer API clients.
+#[derive(Debug, Deserialize)]
+struct Request {
+    command: String,
+
+    /// Usually a string, but some clients send pool ids as numbers.
+    #[serde(default)]
+    parameter: Option<Value>,
+}
+
+/// Start the cgminer-compatible API server.
+///
+/// Listens on `cgminer_api.listen` from the daemon configuration and runs
+/// until the cancellation token is triggered.
+pub async fn serve(state: ApiState, shutdown: CancellationToken) -> Result<()> {
+    let listener = TcpListener::bind(&state.config.cgminer_api.listen).await?;
+    let addr = listener.local_addr()?;
+
+    info!(
+        %addr,
+        writable = state.config.cgminer_api.writable,
+        "cgminer API server listening."
+    );
+    if !addr.ip().is_loopback() {
+        warn!(
+            "cgminer API server is bound to a non-localhost address ({}). \
+             This exposes it to the network without authentication.",
+            addr.ip()
+        );
+    }
+
+    run(listener, state, shutdown).await
+}
+
+/// Accept connections on `listener` until cancelled.
+async fn run(listener: TcpListener, state: ApiState, shutdown: CancellationToken) -> Result<()> {
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = shutdown.cancelled() => return Ok(()),
+        };
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &state).await {
+                debug!(%peer, error = %e, "cgminer API connection failed.");
+            }
+        });
+    }
+}
+
+/// Read one request, write the reply, and close the connection.
+async fn handle_connection(mut stream: TcpStream, state: &ApiState) -> std::io::Result<()> {
+    let request = read_request(&mut stream).await?;
+    let reply = respond(state, &request).await;
+
+    let mut bytes = serde_json::to_vec(&reply)?;
+    bytes.push(0);
+    stream.write_all(&bytes).await?;
+    stream.shutdown().await
+}
+
+/// Read until the client has sent a complete JSON value, closed its side,
+/// or run out of time.
+///
+/// Clients differ in whether they half-close after the request, so a
+/// complete JSON value is taken as the end of it.
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    while request.len() < MAX_REQUEST_LEN {
+        let n = match tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut chunk)).await {
+            Ok(read) => read?,
+            Err(_) => break,
+        };
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..n]);
+        if serde_json::from_slice::<IgnoredAny>(trim(&request)).is_ok() {
+            break;
+        }
+    }
+
+    Ok(request)
+}
+
+/// Strip surrounding whitespace and NUL terminators.
+fn trim(bytes: &[u8]) -> &[u8] {
+    let is_padding = |b: &u8| b.is_ascii_whitespace() || *b == 0;
+    let start = bytes
+        .iter()
+        .position(|b| !is_padding(b))
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| !is_padding(b))
+        .map_or(start, |i| i + 1);
+    &bytes[start..end]
+}
+
+/// Build the reply to a raw request.
+async fn respond(state: &ApiState, request: &[u8]) -> Value {
+    let request: Request = match serde_json::from_slice(trim(request)) {
+        Ok(request) => request,
+        Err(_) => return reply("E", MSG_INVJSON, "Invalid JSON", None),
+    };
+    let parameter = match request.parameter {
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    };
+
+    debug!(command = %request.command, ?parameter, "cgminer API request.");
+
+    if !request.command.contains('+') {
+        return execute(state, &request.command, parameter.as_deref()).await;
+    }
+
+    // Joined commands: each is answered as if sent alone, without parameter
+    let mut joined = Map::new();
+    for command in request.command.split('+') {
+        if joined.contains_key(command) {
+            continue;
+        }
+        let response = if is_writing(command) {
+            reply(
+                "E",
+                MSG_INVCMD,
+                format!("Command '{command}' cannot be joined"),
+                None,
+            )
+        } else {
+            execute(state, command, None).await
+        };
+        joined.insert(command.to_string(), json!([response]));
+    }
+    Value::Object(joined)
+}
+
+/// Whether a command changes miner state.
+fn is_writing(command: &str) -> bool {
+    matches!(command, "switchpool" | "addpool")
+}
+
+/// Run a single command.
+async fn execute(state: &ApiState, command: &str, parameter: Option<&str>) -> Value {
+    if is_writing(command) && !state.config.cgminer_api.writable {
+        return reply(
+            "E",
+            MSG_ACCDENY,
+            format!("Access denied to '{command}' command"),
+            None,
+        );
+    }
+
+    let status = state.telemetry.status();
+    match command {
+        "version" => reply(
+            "S",
+            MSG_VERSION,
+            "CGMiner versions",
+            Some(("VERSION", vec![version()])),
+        ),
+        "summary" => reply(
+            "S",
+            MSG_SUMM,
+            "Summary",
+            Some(("SUMMARY", vec![summary(&status)])),
+        ),
+        "devs" => {
+            let devs = devs(&status);
+            reply(
+                "S",
+                MSG_DEVS,
+                format!("{} ASC(s)", devs.len()),
+                Some(("DEVS", devs)),
+            )
+        }
+        "pools" => {
+            let pools = pools(&status);
+            if pools.is_empty() {
+                reply("E", MSG_NOPOOL, "No pools", None)
+            } else {
+                reply(
+                    "S",
+                    MSG_POOL,
+                    format!("{} Pool(s)", pools.len()),
+                    Some(("POOLS", pools)),
+                )
+            }
+        }
+        "stats" => reply(
+            "S",
+            MSG_MINESTATS,
+            "CGMiner stats",
+            Some(("STATS", stats(&status))),
+        ),
+        "switchpool" => switch_pool(state, &status, parameter).await,
+        "addpool" => add_pool(state, parameter).await,
+        _ => reply("E", MSG_INVCMD, "Invalid command", None),
+    }
+}
+
+/// Assemble a cgminer reply: a status entry plus an optional data section.
+fn reply(
+    kind: &str,
+    code: u32,
+    msg: impl Into<String>,
+    section: Option<(&str, Vec<Value>)>,
+) -> Value {
+    let when = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    let mut reply = Map::new();
+    reply.insert(
+        "STATUS".into(),
+        json!([{
+            "STATUS": kind,
+            "When": when,
+            "Code": code,
+            "Msg": msg.into(),
+            "Description": concat!("mujina-miner ", env!("CARGO_PKG_VERSION")),
+        }]),
+    );
+    if let Some((name, items)) = section {
+        reply.insert(name.into(), Value::Array(items));
+    }
+    reply.insert("id".into(), json!(1));
+    Value::Object(reply)
+}
+
+fn version() -> Value {
+    json!({
+        "Miner": concat!("mujina-miner ", env!("CARGO_PKG_VERSION")),
+        "API": API_VERSION,
+    })
+}
+
+fn summary(status: &MinerStatus) -> Value {
+    let accepted: u64 = status.pools.iter().map(|p| p.shares_accepted).sum();
+    let rejected: u64 = status.pools.iter().map(|p| p.shares_rejected).sum();
+    let hardware_errors: u64 = status.threads.iter().map(|t| t.hardware_errors).sum();
+    let minutes = status.uptime_s as f64 / 60.0;
+
+    json!({
+        "Elapsed": status.uptime_s,
+        "MHS av": status.hashrate / 1e6,
+        "MHS 1m": window_mhs(&status.hashrate_windows, 60),
+        "MHS 5m": window_mhs(&status.hashrate_windows, 300),
+        "MHS 15m": window_mhs(&status.hashrate_windows, 900),
+        "Accepted": accepted,
+        "Rejected": rejected,
+        "Hardware Errors": hardware_errors,
+        "Utility": if minutes > 0.0 { accepted as f64 / minutes } else { 0.0 },
+        "Pool Rejected%": percent(rejected, accepted + rejected),
+        "Device Hardware%": percent(
+            hardware_errors,
+            hardware_errors + status.threads.iter().map(|t| t.shares).sum::<u64>(),
+        ),
+    })
+}
+
+/// One entry per board, reported as cgminer ASC devices.
+fn devs(status: &MinerStatus) -> Vec<Value> {
+    status
+        .boards
+        .iter()
+        .enumerate()
+        .map(|(id, board)| {
+            let threads = || status.threads.iter().filter(|t| t.board == board.serial);
+            let hashrate: f64 = threads().map(|t| t.hashrate).sum();
+            let enabled = threads().any(|t| t.active);
+
+            json!({
+                "ASC": id,
+                "Name": board.model,
+                "ID": id,
+                "Serial": board.serial,
+                "Enabled": if enabled { "Y" } else { "N" },
+                "Status": "Alive",
+                "Temperature": board.asic_temp_c.unwrap_or(0.0),
+                "MHS av": hashrate / 1e6,
+                "MHS 1m": window_mhs(&board.hashrate_windows, 60),
+                "MHS 5m": window_mhs(&board.hashrate_windows, 300),
+                "MHS 15m": window_mhs(&board.hashrate_windows, 900),
+                "Hardware Errors": threads().map(|t| t.hardware_errors).sum::<u64>(),
+                "Device Elapsed": status.uptime_s,
+            })
+        })
+        .collect()
+}
+
+fn pools(status: &MinerStatus) -> Vec<Value> {
+    status
+        .pools
+        .iter()
+        .enumerate()
+        .map(|(id, pool)| {
+            json!({
+                "POOL": id,
+                "Name": pool.name,
+                "URL": pool.url.as_deref().unwrap_or(&pool.name),
+                "Status": if pool.connected { "Alive" } else { "Dead" },
+                "Priority": id,
+                "Quota": 1,
+                "Accepted": pool.shares_accepted,
+                "Rejected": pool.shares_rejected,
+                "Stale": 0,
+                "Stratum Active": pool.active && pool.connected,
+                "Last Share Difficulty": pool.difficulty.unwrap_or(0.0),
+                "Pool Rejected%": percent(
+                    pool.shares_rejected,
+                    pool.shares_accepted + pool.shares_rejected,
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Board sensor readings, using the field names of cgminer ASIC drivers.
+fn stats(status: &MinerStatus) -> Vec<Value> {
+    status
+        .boards
+        .iter()
+        .enumerate()
+        .map(|(id, board)| {
+            json!({
+                "STATS": id,
+                "ID": format!("ASC{id}"),
+                "Type": board.model,
+                "Serial": board.serial,
+                "Elapsed": status.uptime_s,
+                "chain_acn1": board.chip_count,
+                "temp1": board.asic_temp_c,
+                "temp2": board.vr_temp_c,
+                "fan1": board.fan_rpm,
+                "fan_percent": board.fan_percent,
+                "power": board.power_w,
+                "current": board.current_a,
+                "voltage_in": board.input_voltage_v,
+                "voltage_core": board.core_voltage_v,
+            })
+        })
+        .collect()
+}
+
+/// Hashrate in MH/s over the window of `window_s` seconds, 0 if there is
+/// none.
+fn window_mhs(windows: &[HashrateEstimate], window_s: u64) -> f64 {
+    windows
+        .iter()
+        .find(|w| w.window_s == window_s)
+        .map_or(0.0, |w| w.hashrate / 1e6)
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// `switchpool|N`: make pool N the active pool.
+async fn switch_pool(state: &ApiState, status: &MinerStatus, parameter: Option<&str>) -> Value {
+    let Some(parameter) = parameter.filter(|p| !p.is_empty()) else {
+        return reply("E", MSG_MISPID, "Missing pool id parameter", None);
+    };
+
+    let last = status.pools.len().saturating_sub(1);
+    let pool = parameter
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|id| status.pools.get(id).map(|pool| (id, pool)));
+    let Some((id, pool)) = pool else {
+        return reply(
+            "E",
+            MSG_INVPID,
+            format!("Invalid pool id {parameter} - range is 0 - {last}"),
+            None,
+        );
+    };
+
+    let name = pool.name.clone();
+    let result = send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::SwitchPool {
+            name: name.clone(),
+            response,
+        }
+    })
+    .await
+    .and_then(|result| result);
+
+    match result {
+        Ok(()) => reply(
+            "S",
+            MSG_SWITCHP,
+            format!(
+                "Switching to pool {id}:'{}'",
+                pool.url.as_deref().unwrap_or(&name)
+            ),
+            None,
+        ),
+        Err(e) => reply(
+            "E",
+            MSG_INVPID,
+            format!("Cannot switch to pool {id}: {e}"),
+            None,
+        ),
+    }
+}
+
+/// `addpool|url,user,pass`: add a pool.
+async fn add_pool(state: &ApiState, parameter: Option<&str>) -> Value {
+    let Some(parameter) = parameter.filter(|p| !p.is_empty()) else {
+        return reply("E", MSG_MISPDP, "Missing addpool details", None);
+    };
+
+    let Some(request) = parse_addpool(parameter) else {
+        return reply(
+            "E",
+            MSG_INVPDP,
+            format!("Invalid addpool details '{parameter}'"),
+            None,
+        );
+    };
+
+    let url = request.url.clone();
+    let result = send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::AddPool { request, response }
+    })
+    .await
+    .and_then(|result| result);
+
+    match result {
+        Ok(pool) => {
+            // The scheduler publishes the new pool list before replying
+            let id = state
+                .telemetry
+                .status()
+                .pools
+                .iter()
+                .position(|p| p.name == pool.name)
+                .unwrap_or_default();
+            reply("S", MSG_ADDPOOL, format!("Added pool {id}: '{url}'"), None)
+        }
+        Err(e) => reply(
+            "E",
+            MSG_INVPDP,
+            format!("Cannot add pool '{url}': {e}"),
+            None,
+        ),
+    }
+}
+
+/// Parse `url,user[,pass]`, where `\` escapes a literal comma or backslash.
+fn parse_addpool(parameter: &str) -> Option<AddPoolRequest> {
+    let mut fields = vec![String::new()];
+    let mut chars = parameter.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => fields.last_mut()?.extend(chars.next()),
+            ',' => fields.push(String::new()),
+            c => fields.last_mut()?.push(c),
+        }
+    }
+
+    let mut fields = fields.into_iter().map(|f| f.trim().to_string());
+    let url = fields.next().filter(|f| !f.is_empty())?;
+    let worker = fields.next().filter(|f| !f.is_empty())?;
+    let password = fields.next().filter(|f| !f.is_empty());
+    if fields.next().is_some() {
+        return None;
+    }
+
+    Some(AddPoolRequest {
+        name: None,
+        url,
+        worker,
+        password,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::types::{BoardStatus, HashrateEstimate, PoolStatus, ThreadStatus};
+    use crate::telemetry::Telemetry;
+    use std::sync::Arc;
+
+    fn state(writable: bool) -> ApiState {
+        let telemetry = Telemetry::new();
+        telemetry.update_status(|status| {
+            status.uptime_s = 120;
+            status.hashrate = 500e9;
+            status.hashrate_windows = [(900, 480e9), (300, 510e9)]
+                .into_iter()
+                .map(|(window_s, hashrate)| HashrateEstimate {
+                    window_s,
+                    hashrate,
+                    ..Default::default()
+                })
+                .collect();
+            status.boards.push(BoardStatus {
+                serial: "ABC123".into(),
+                model: "Bitaxe Gamma".into(),
+                chip_count: 1,
+                asic_temp_c: Some(55.0),
+                ..Default::default()
+            });
+            status.threads.push(ThreadStatus {
+                name: "ABC123/0".into(),
+                board: "ABC123".into(),
+                hashrate: 500e9,
+                shares: 98,
+                hardware_errors: 2,
+                active: true,
+                ..Default::default()
+            });
+            for (name, connected) in [("pool-1", true), ("pool-2", false)] {
+                status.pools.push(PoolStatus {
+                    name: name.into(),
+                    url: Some(format!("stratum+tcp://{name}.example.com:3333")),
+                    connected,
+                    active: connected,
+                    difficulty: Some(1024.0),
+                    shares_submitted: 10,
+                    shares_accepted: 9,
+                    shares_rejected: 1,
+                });
+            }
+        });
+
+        let mut state = ApiState::for_test(telemetry);
+        let mut config = (*state.config).clone();
+        config.cgminer_api.writable = writable;
+        state.config = Arc::new(config);
+        state
+    }
+
+    async fn request(state: &ApiState, request: Value) -> Value {
+        respond(state, request.to_string().as_bytes()).await
+    }
+
+    fn status_code(reply: &Value) -> u64 {
+        reply["STATUS"][0]["Code"].as_u64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_summary_devs_and_pools() {
+        let state = state(false);
+
+        let summary = request(&state, json!({"command": "summary"})).await;
+        assert_eq!(summary["STATUS"][0]["STATUS"], "S");
+        assert_eq!(summary["SUMMARY"][0]["MHS av"], 500e3);
+        assert_eq!(summary["SUMMARY"][0]["MHS 5m"], 510e3);
+        assert_eq!(summary["SUMMARY"][0]["MHS 15m"], 480e3);
+        assert_eq!(summary["SUMMARY"][0]["MHS 1m"], 0.0);
+        assert!(summary["SUMMARY"][0].get("MHS 5s").is_none());
+        assert_eq!(summary["SUMMARY"][0]["Accepted"], 18);
+        assert_eq!(summary["SUMMARY"][0]["Hardware Errors"], 2);
+        assert_eq!(summary["SUMMARY"][0]["Device Hardware%"], 2.0);
+
+        let devs = request(&state, json!({"command": "devs"})).await;
+        assert_eq!(devs["DEVS"][0]["Serial"], "ABC123");
+        assert_eq!(devs["DEVS"][0]["Temperature"], 55.0);
+        assert_eq!(devs["DEVS"][0]["Enabled"], "Y");
+
+        let pools = request(&state, json!({"command": "pools"})).await;
+        assert_eq!(status_code(&pools), MSG_POOL as u64);
+        assert_eq!(pools["POOLS"][1]["POOL"], 1);
+        assert_eq!(pools["POOLS"][1]["Status"], "Dead");
+        assert_eq!(pools["POOLS"][0]["Stratum Active"], true);
+    }
+
+    #[tokio::test]
+    async fn test_joined_commands() {
+        let state = state(true);
+        let reply = request(&state, json!({"command": "summary+stats+switchpool"})).await;
+
+        assert_eq!(reply["summary"][0]["SUMMARY"][0]["Elapsed"], 120);
+        assert_eq!(reply["stats"][0]["STATS"][0]["ID"], "ASC0");
+        assert_eq!(status_code(&reply["switchpool"][0]), MSG_INVCMD as u64);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_requests() {
+        let state = state(false);
+
+        assert_eq!(
+            status_code(&respond(&state, b"summary").await),
+            MSG_INVJSON as u64
+        );
+        assert_eq!(
+            status_code(&request(&state, json!({"command": "restart"})).await),
+            MSG_INVCMD as u64
+        );
+        let denied = request(&state, json!({"command": "switchpool", "parameter": "1"})).await;
+        assert_eq!(status_code(&denied), MSG_ACCDENY as u64);
+
+        let state = self::state(true);
+        let invalid = request(&state, json!({"command": "switchpool", "parameter": 5})).await;
+        assert_eq!(status_code(&invalid), MSG_INVPID as u64);
+        assert_eq!(
+            invalid["STATUS"][0]["Msg"],
+            "Invalid pool id 5 - range is 0 - 1"
+        );
+        let missing = request(&state, json!({"command": "addpool"})).await;
+        assert_eq!(status_code(&missing), MSG_MISPDP as u64);
+    }
+
+    #[test]
+    fn test_parse_addpool() {
+        let request = parse_addpool(r"stratum+tcp://pool:3333,bc1q.worker,x\,y\\z").unwrap();
+        assert_eq!(request.url, "stratum+tcp://pool:3333");
+        assert_eq!(request.worker, "bc1q.worker");
+        assert_eq!(request.password.as_deref(), Some(r"x,y\z"));
+
+        assert!(parse_addpool("stratum+tcp://pool:3333,worker").is_some());
+        assert!(parse_addpool("stratum+tcp://pool:3333").is_none());
+        assert!(parse_addpool("url,user,pass,extra").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_serves_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = CancellationToken::new();
+        tokio::spawn(run(listener, state(false), shutdown.clone()));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(br#"{"command":"version"}"#).await.unwrap();
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await.unwrap();
+        shutdown.cancel();
+
+        assert_eq!(reply.pop(), Some(0));
+        let reply: Value = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(reply["VERSION"][0]["API"], API_VERSION);
+    }
+}
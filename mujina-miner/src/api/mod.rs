@@ -7,15 +7,87 @@
 //! The API binds to localhost only by default and does not require
 //! authentication for local access.
 
+pub mod cgminer;
+pub mod mqtt;
 mod v1;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use axum::Router;
 use tokio::net::TcpListener;
 use tokio_util::sync::CancellationToken;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 use tracing::{info, warn, Level};
 
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    backplane::BackplaneCommand, config::Config, error::CommandError, history::History,
+    schedule::ScheduleCommand, scheduler::SchedulerCommand, telemetry::Telemetry, tracing::Logs,
+};
+
+/// State shared by all API handlers.
+#[derive(Clone)]
+pub struct ApiState {
+    /// Miner status and event stream published by the daemon's actors
+    pub telemetry: Telemetry,
+
+    /// Control channel to the scheduler (pause, pools, board idle)
+    pub scheduler: mpsc::Sender<SchedulerCommand>,
+
+    /// Control channel to the backplane (board restart)
+    pub backplane: mpsc::Sender<BackplaneCommand>,
+
+    /// Control channel to the operating mode schedule (status, override)
+    pub schedule: mpsc::Sender<ScheduleCommand>,
+
+    /// Configuration the daemon was started with
+    pub config: Arc<Config>,
+
+    /// Recorded statistics history
+    pub history: History,
+
+    /// Recent log events and the runtime log filter
+    pub logs: Logs,
+}
+
+#[cfg(test)]
+impl ApiState {
+    /// State with default config whose control channels are not connected
+    /// to anything, so commands fail with 503.
+    pub(crate) fn for_test(telemetry: Telemetry) -> Self {
+        let (scheduler, _) = mpsc::channel(1);
+        let (backplane, _) = mpsc::channel(1);
+        let (schedule, _) = mpsc::channel(1);
+        Self {
+            telemetry,
+            scheduler,
+            backplane,
+            schedule,
+            config: Arc::new(Config::default()),
+            history: History::in_memory(),
+            logs: Logs::new(100),
+        }
+    }
+}
+
+/// Send a command to an actor and wait for its reply.
+///
+/// `component` names the actor in the error if it has stopped.
+pub(crate) async fn send_command<C, T>(
+    tx: &mpsc::Sender<C>,
+    component: &'static str,
+    command: impl FnOnce(oneshot::Sender<T>) -> C,
+) -> Result<T, CommandError> {
+    let (response_tx, response_rx) = oneshot::channel();
+    tx.send(command(response_tx))
+        .await
+        .map_err(|_| CommandError::Unavailable(component))?;
+    response_rx
+        .await
+        .map_err(|_| CommandError::Unavailable(component))
+}
+
 /// API server configuration.
 #[derive(Debug, Clone)]
 pub struct ApiConfig {
@@ -32,15 +104,15 @@ impl Default for ApiConfig {
     }
 }
 
-/// Start the API server.
+/// Bind the API server's listening socket.
 ///
-/// This function starts the HTTP API server and runs until the provided
-/// cancellation token is triggered. It binds to localhost only by default for
-/// security.
-pub async fn serve(config: ApiConfig, shutdown: CancellationToken) -> Result<()> {
-    let app = build_router();
-
-    let listener = TcpListener::bind(&config.bind_addr).await?;
+/// Binding ahead of [`serve`] lets the daemon fail to start, rather than run
+/// without an API, when the address is taken. It binds to localhost only by
+/// default for security.
+pub async fn bind(config: &ApiConfig) -> Result<TcpListener> {
+    let listener = TcpListener::bind(&config.bind_addr)
+        .await
+        .with_context(|| format!("cannot bind API server to {}", config.bind_addr))?;
     let actual_addr = listener.local_addr()?;
 
     info!(url = %format!("http://{}", actual_addr), "API server listening.");
@@ -54,6 +126,20 @@ pub async fn serve(config: ApiConfig, shutdown: CancellationToken) -> Result<()>
         );
     }
 
+    Ok(listener)
+}
+
+/// Start the API server.
+///
+/// This function serves the HTTP API on `listener` until the provided
+/// cancellation token is triggered.
+pub async fn serve(
+    listener: TcpListener,
+    state: ApiState,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let app = build_router(state);
+
     // Run server with graceful shutdown
     axum::serve(listener, app)
         .with_graceful_shutdown(async move {
@@ -65,10 +151,12 @@ pub async fn serve(config: ApiConfig, shutdown: CancellationToken) -> Result<()>
 }
 
 /// Build the application router with all API routes.
-fn build_router() -> Router {
-    Router::new().nest("/api/v1", v1::routes()).layer(
-        TraceLayer::new_for_http()
-            .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
-            .on_response(DefaultOnResponse::new().level(Level::INFO)),
-    )
+pub(crate) fn build_router(state: ApiState) -> Router {
+    Router::new()
+        .nest("/api/v1", v1::routes().with_state(state))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                .on_response(DefaultOnResponse::new().level(Level::INFO)),
+        )
 }
@@ -0,0 +1,917 @@
+//! MQTT telemetry publisher and command subscriber.
+//!
+//! Home miners often run inside Home Assistant, whose native integration
+//! path is MQTT. This client publishes the miner's state, and each board's,
+//! as JSON to a broker, announces matching entities through Home Assistant's
+//! MQTT discovery, and listens on command topics so the miner can be paused
+//! and resumed, board fans and frequencies set, and power curtailed for
+//! demand response, from an automation.
+//!
+//! Topics, under the configured prefix (`mujina` by default):
+//!
+//! | Topic                              | Direction | Payload                  |
+//! |------------------------------------|-----------|--------------------------|
+//! | `availability`                     | out       | `online` / `offline`     |
+//! | `state`                            | out       | miner state JSON         |
+//! | `board/<serial>/state`             | out       | board state JSON         |
+//! | `alert`                            | out       | alert JSON               |
+//! | `command/mining`                   | in        | `ON`/`resume`, `OFF`/`pause` |
+//! | `command/curtail`                  | in        | curtail request JSON, `end` |
+//! | `board/<serial>/command/fan`       | in        | duty cycle 0--100, `auto` |
+//! | `board/<serial>/command/frequency` | in        | target MHz               |
+//!
+//! A curtail request is the JSON body of `PUT /api/v1/curtailment`, e.g.
+//! `{"target_w": 500, "duration_s": 900}`, or `{"target_w": null, ...}` to
+//! stop hashing. The miner state reports total input power and how the
+//! curtailment is met.
+//!
+//! `availability` is retained and doubles as the connection's last will, so
+//! Home Assistant marks every entity unavailable when the miner goes away.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, EventLoop, LastWill, MqttOptions, Packet, QoS};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::{send_command, ApiState};
+use crate::api_client::types::{BoardStatus, CurtailRequest, FanMode, MinerEvent, MinerStatus};
+use crate::backplane::BackplaneCommand;
+use crate::config::MqttConfig;
+use crate::schedule::ScheduleCommand;
+use crate::scheduler::SchedulerCommand;
+
+/// Capacity of the client's outgoing request queue.
+const QUEUE_CAPACITY: usize = 64;
+
+/// Keep-alive interval agreed with the broker.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// Delay before reconnecting after the connection fails.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Hashrate window published as the current hashrate.
+const HASHRATE_WINDOW_S: u64 = 300;
+
+/// A command received on a command topic.
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    /// Resume (`true`) or pause (`false`) mining
+    Mining(bool),
+
+    /// Set a board's fan duty cycle or return it to closed-loop control
+    Fan { serial: String, mode: FanMode },
+
+    /// Set a board's target frequency
+    Frequency { serial: String, mhz: f32 },
+
+    /// Curtail power for a demand-response event
+    Curtail(CurtailRequest),
+
+    /// End the curtailment early
+    EndCurtailment,
+}
+
+/// Topic names derived from the configured prefixes.
+struct Topics {
+    prefix: String,
+    discovery_prefix: String,
+}
+
+impl Topics {
+    fn new(config: &MqttConfig) -> Self {
+        Self {
+            prefix: config.topic_prefix.clone(),
+            discovery_prefix: config.discovery_prefix.clone(),
+        }
+    }
+
+    fn availability(&self) -> String {
+        format!("{}/availability", self.prefix)
+    }
+
+    fn state(&self) -> String {
+        format!("{}/state", self.prefix)
+    }
+
+    fn board_state(&self, serial: &str) -> String {
+        format!("{}/board/{serial}/state", self.prefix)
+    }
+
+    fn alert(&self) -> String {
+        format!("{}/alert", self.prefix)
+    }
+
+    fn mining_command(&self) -> String {
+        format!("{}/command/mining", self.prefix)
+    }
+
+    fn curtail_command(&self) -> String {
+        format!("{}/command/curtail", self.prefix)
+    }
+
+    fn board_command(&self, serial: &str, command: &str) -> String {
+        format!("{}/board/{serial}/command/{command}", self.prefix)
+    }
+
+    /// Filters matching every command topic.
+    fn command_filters(&self) -> [String; 3] {
+        [
+            self.mining_command(),
+            self.curtail_command(),
+            format!("{}/board/+/command/+", self.prefix),
+        ]
+    }
+
+    fn discovery(&self, component: &str, node: &str, object: &str) -> String {
+        format!(
+            "{}/{component}/{node}/{object}/config",
+            self.discovery_prefix
+        )
+    }
+
+    /// Parse a message received on a command topic.
+    fn parse_command(&self, topic: &str, payload: &[u8]) -> Result<Command, String> {
+        let payload = std::str::from_utf8(payload)
+            .map_err(|_| "payload is not UTF-8".to_string())?
+            .trim();
+
+        if topic == self.mining_command() {
+            return match payload.to_ascii_lowercase().as_str() {
+                "on" | "resume" => Ok(Command::Mining(true)),
+                "off" | "pause" => Ok(Command::Mining(false)),
+                _ => Err(format!("expected ON or OFF, got {payload:?}")),
+            };
+        }
+
+        if topic == self.curtail_command() {
+            if payload.eq_ignore_ascii_case("end") {
+                return Ok(Command::EndCurtailment);
+            }
+            return serde_json::from_str(payload)
+                .map(Command::Curtail)
+                .map_err(|e| format!("expected a curtail request or end: {e}"));
+        }
+
+        let board_command = topic
+            .strip_prefix(&self.prefix)
+            .and_then(|rest| rest.strip_prefix("/board/"))
+            .and_then(|rest| rest.split_once("/command/"));
+        match board_command {
+            Some((serial, "fan")) => {
+                let mode = if payload.eq_ignore_ascii_case("auto") {
+                    FanMode::Auto
+                } else {
+                    let percent = payload
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|p| (0.0..=100.0).contains(p))
+                        .ok_or_else(|| {
+                            format!("expected a duty cycle 0-100 or auto, got {payload:?}")
+                        })?;
+                    FanMode::Manual {
+                        percent: percent.round() as u8,
+                    }
+                };
+                Ok(Command::Fan {
+                    serial: serial.to_string(),
+                    mode,
+                })
+            }
+            Some((serial, "frequency")) => {
+                let mhz = payload
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|mhz| *mhz > 0.0)
+                    .ok_or_else(|| format!("expected a frequency in MHz, got {payload:?}"))?;
+                Ok(Command::Frequency {
+                    serial: serial.to_string(),
+                    mhz,
+                })
+            }
+            _ => Err("unknown command topic".to_string()),
+        }
+    }
+}
+
+/// Run the MQTT client until shutdown.
+///
+/// Connection failures are logged and retried; they never stop the miner.
+pub async fn task(state: ApiState, shutdown: CancellationToken) {
+    let config = state.config.mqtt.clone();
+    let topics = Topics::new(&config);
+
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(KEEP_ALIVE);
+    options.set_last_will(LastWill::new(
+        topics.availability(),
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    let (client, mut eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+
+    info!(host = %config.host, port = config.port, "MQTT client started");
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_s));
+    let mut events = state.telemetry.subscribe_events();
+    let mut announced = HashSet::new();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+
+            event = eventloop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("Connected to MQTT broker");
+                    // Sessions are clean, so subscriptions and discovery are
+                    // redone on every connection
+                    announced.clear();
+                    publish(&client, &topics.availability(), true, "online");
+                    for filter in topics.command_filters() {
+                        if let Err(e) = client.try_subscribe(filter, QoS::AtLeastOnce) {
+                            warn!(error = %e, "Failed to subscribe to MQTT command topics");
+                        }
+                    }
+                    if config.discovery {
+                        announce_miner(&client, &topics);
+                    }
+                    publish_state(&client, &topics, &config, &state.telemetry.status(), &mut announced);
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    match topics.parse_command(&message.topic, &message.payload) {
+                        Ok(command) => {
+                            execute(&state, command).await;
+                            publish_state(&client, &topics, &config, &state.telemetry.status(), &mut announced);
+                        }
+                        Err(e) => warn!(topic = %message.topic, error = %e, "Invalid MQTT command"),
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(error = %e, "MQTT connection failed; retrying");
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+                    }
+                }
+            },
+
+            _ = interval.tick() => {
+                publish_state(&client, &topics, &config, &state.telemetry.status(), &mut announced);
+            }
+
+            event = events.recv() => match event {
+                Ok(MinerEvent::Alert(alert)) => {
+                    if let Ok(payload) = serde_json::to_string(&alert) {
+                        publish(&client, &topics.alert(), false, payload);
+                    }
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    // Leave cleanly so the broker doesn't publish the last will
+    publish(&client, &topics.availability(), true, "offline");
+    let _ = client.try_disconnect();
+    flush(&mut eventloop).await;
+}
+
+/// Drive the event loop briefly so queued messages reach the broker.
+async fn flush(eventloop: &mut EventLoop) {
+    let _ = tokio::time::timeout(Duration::from_secs(1), async {
+        while eventloop.poll().await.is_ok() {}
+    })
+    .await;
+}
+
+/// Queue a message without waiting; the event loop sends it.
+fn publish(client: &AsyncClient, topic: &str, retain: bool, payload: impl Into<Vec<u8>>) {
+    if let Err(e) = client.try_publish(topic, QoS::AtLeastOnce, retain, payload) {
+        debug!(topic, error = %e, "MQTT message dropped");
+    }
+}
+
+async fn execute(state: &ApiState, command: Command) {
+    let result = match &command {
+        Command::Mining(true) => {
+            send_command(&state.scheduler, "scheduler", |response| {
+                SchedulerCommand::Resume { response }
+            })
+            .await
+        }
+        Command::Mining(false) => {
+            send_command(&state.scheduler, "scheduler", |response| {
+                SchedulerCommand::Pause { response }
+            })
+            .await
+        }
+        Command::Fan { serial, mode } => send_command(&state.backplane, "backplane", |response| {
+            BackplaneCommand::SetFanMode {
+                serial: serial.clone(),
+                mode: *mode,
+                response,
+            }
+        })
+        .await
+        .and_then(|result| result),
+        Command::Frequency { serial, mhz } => {
+            send_command(&state.backplane, "backplane", |response| {
+                BackplaneCommand::SetFrequency {
+                    serial: serial.clone(),
+                    frequency_mhz: *mhz,
+                    response,
+                }
+            })
+            .await
+            .and_then(|result| result)
+        }
+        Command::Curtail(request) => send_command(&state.schedule, "schedule", |response| {
+            ScheduleCommand::Curtail {
+                request: *request,
+                response,
+            }
+        })
+        .await
+        .and_then(|result| result.map(|_| ())),
+        Command::EndCurtailment => send_command(&state.schedule, "schedule", |response| {
+            ScheduleCommand::EndCurtailment { response }
+        })
+        .await
+        .and_then(|result| result),
+    };
+
+    match result {
+        Ok(()) => info!(command = ?command, "MQTT command executed"),
+        Err(e) => warn!(command = ?command, error = %e, "MQTT command failed"),
+    }
+}
+
+/// Publish the miner's and each board's state, announcing new boards.
+fn publish_state(
+    client: &AsyncClient,
+    topics: &Topics,
+    config: &MqttConfig,
+    status: &MinerStatus,
+    announced: &mut HashSet<String>,
+) {
+    publish(
+        client,
+        &topics.state(),
+        false,
+        miner_state(status).to_string(),
+    );
+
+    for board in &status.boards {
+        if config.discovery && announced.insert(board.serial.clone()) {
+            announce_board(client, topics, board);
+        }
+        publish(
+            client,
+            &topics.board_state(&board.serial),
+            false,
+            board_state(board).to_string(),
+        );
+    }
+}
+
+fn window_hashrate(windows: &[crate::api_client::types::HashrateEstimate]) -> Option<f64> {
+    windows
+        .iter()
+        .find(|w| w.window_s == HASHRATE_WINDOW_S)
+        .map(|w| w.hashrate)
+}
+
+fn miner_state(status: &MinerStatus) -> Value {
+    let pool = status.pools.iter().find(|p| p.active);
+    let readings: Vec<f32> = status.boards.iter().filter_map(|b| b.power_w).collect();
+    let curtailment = status.curtailment.as_ref();
+    json!({
+        "hashrate": window_hashrate(&status.hashrate_windows).unwrap_or(status.hashrate),
+        "uptime_s": status.uptime_s,
+        "shares_submitted": status.shares_submitted,
+        "paused": status.paused,
+        "pool": pool.map(|p| &p.name),
+        "pool_connected": pool.is_some_and(|p| p.connected),
+        "shares_accepted": pool.map_or(0, |p| p.shares_accepted),
+        "shares_rejected": pool.map_or(0, |p| p.shares_rejected),
+        "power_w": (!readings.is_empty()).then(|| readings.iter().sum::<f32>()),
+        "curtailed": curtailment.is_some(),
+        "curtailment_target_w": curtailment.and_then(|c| c.target_w),
+        "curtailment_until": curtailment.map(|c| c.until),
+        "curtailment_met": curtailment.map(|c| c.met),
+    })
+}
+
+fn board_state(board: &BoardStatus) -> Value {
+    json!({
+        "hashrate": window_hashrate(&board.hashrate_windows),
+        "asic_temp_c": board.asic_temp_c,
+        "vr_temp_c": board.vr_temp_c,
+        "power_w": board.power_w,
+        "current_a": board.current_a,
+        "input_voltage_v": board.input_voltage_v,
+        "core_voltage_v": board.core_voltage_v,
+        "fan_percent": board.fan_percent,
+        "fan_rpm": board.fan_rpm,
+    })
+}
+
+/// Characters Home Assistant accepts in node and object ids.
+fn object_id(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// A sensor reading `field` from the JSON state on `state_topic`.
+fn sensor(
+    name: &str,
+    unique_id: &str,
+    state_topic: &str,
+    field: &str,
+    unit: Option<&str>,
+    device_class: Option<&str>,
+) -> Value {
+    let mut config = json!({
+        "name": name,
+        "unique_id": unique_id,
+        "state_topic": state_topic,
+        "value_template": format!("{{{{ value_json.{field} }}}}"),
+        "state_class": "measurement",
+    });
+    if let Some(unit) = unit {
+        config["unit_of_measurement"] = unit.into();
+    }
+    if let Some(device_class) = device_class {
+        config["device_class"] = device_class.into();
+    }
+    config
+}
+
+/// A hashrate sensor, converted from H/s to TH/s.
+fn hashrate_sensor(unique_id: &str, state_topic: &str) -> Value {
+    let mut config = sensor(
+        "Hashrate",
+        unique_id,
+        state_topic,
+        "hashrate",
+        Some("TH/s"),
+        None,
+    );
+    config["value_template"] =
+        "{{ (value_json.hashrate / 1e12) | round(3) if value_json.hashrate is not none }}".into();
+    config["icon"] = "mdi:pickaxe".into();
+    config
+}
+
+/// Discovery configuration for the miner as a whole: `(topic, config)`.
+fn miner_discovery(topics: &Topics) -> Vec<(String, Value)> {
+    let node = object_id(&topics.prefix);
+    let state = topics.state();
+    let id = |object: &str| format!("{node}_{object}");
+
+    let mut shares = sensor(
+        "Shares submitted",
+        &id("shares"),
+        &state,
+        "shares_submitted",
+        None,
+        None,
+    );
+    shares["state_class"] = "total_increasing".into();
+
+    let entities = [
+        (
+            "sensor",
+            "hashrate",
+            hashrate_sensor(&id("hashrate"), &state),
+        ),
+        ("sensor", "shares", shares),
+        (
+            "sensor",
+            "power",
+            sensor(
+                "Power",
+                &id("power"),
+                &state,
+                "power_w",
+                Some("W"),
+                Some("power"),
+            ),
+        ),
+        (
+            "binary_sensor",
+            "pool_connected",
+            json!({
+                "name": "Pool connected",
+                "unique_id": id("pool_connected"),
+                "state_topic": state,
+                "value_template": "{{ 'ON' if value_json.pool_connected else 'OFF' }}",
+                "device_class": "connectivity",
+            }),
+        ),
+        (
+            "binary_sensor",
+            "curtailed",
+            json!({
+                "name": "Curtailed",
+                "unique_id": id("curtailed"),
+                "state_topic": state,
+                "value_template": "{{ 'ON' if value_json.curtailed else 'OFF' }}",
+                "icon": "mdi:transmission-tower-off",
+            }),
+        ),
+        (
+            "switch",
+            "mining",
+            json!({
+                "name": "Mining",
+                "unique_id": id("mining"),
+                "state_topic": state,
+                "value_template": "{{ 'OFF' if value_json.paused else 'ON' }}",
+                "command_topic": topics.mining_command(),
+                "icon": "mdi:pickaxe",
+            }),
+        ),
+    ];
+
+    let device = json!({
+        "identifiers": [node],
+        "name": "Mujina miner",
+        "manufacturer": "Mujina",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    entities
+        .into_iter()
+        .map(|(component, object, config)| {
+            (
+                topics.discovery(component, &node, object),
+                with_device(config, topics, &device),
+            )
+        })
+        .collect()
+}
+
+/// Discovery configuration for one board: `(topic, config)`.
+fn board_discovery(topics: &Topics, board: &BoardStatus) -> Vec<(String, Value)> {
+    let miner = object_id(&topics.prefix);
+    let node = format!("{miner}_{}", object_id(&board.serial));
+    let state = topics.board_state(&board.serial);
+    let id = |object: &str| format!("{node}_{object}");
+
+    let entities = [
+        (
+            "sensor",
+            "hashrate",
+            hashrate_sensor(&id("hashrate"), &state),
+        ),
+        (
+            "sensor",
+            "asic_temp",
+            sensor(
+                "ASIC temperature",
+                &id("asic_temp"),
+                &state,
+                "asic_temp_c",
+                Some("°C"),
+                Some("temperature"),
+            ),
+        ),
+        (
+            "sensor",
+            "vr_temp",
+            sensor(
+                "VR temperature",
+                &id("vr_temp"),
+                &state,
+                "vr_temp_c",
+                Some("°C"),
+                Some("temperature"),
+            ),
+        ),
+        (
+            "sensor",
+            "power",
+            sensor(
+                "Power",
+                &id("power"),
+                &state,
+                "power_w",
+                Some("W"),
+                Some("power"),
+            ),
+        ),
+        (
+            "sensor",
+            "core_voltage",
+            sensor(
+                "Core voltage",
+                &id("core_voltage"),
+                &state,
+                "core_voltage_v",
+                Some("V"),
+                Some("voltage"),
+            ),
+        ),
+        (
+            "sensor",
+            "fan_rpm",
+            sensor(
+                "Fan speed",
+                &id("fan_rpm"),
+                &state,
+                "fan_rpm",
+                Some("RPM"),
+                None,
+            ),
+        ),
+        (
+            "number",
+            "fan",
+            json!({
+                "name": "Fan duty cycle",
+                "unique_id": id("fan"),
+                "state_topic": state,
+                "value_template": "{{ value_json.fan_percent }}",
+                "command_topic": topics.board_command(&board.serial, "fan"),
+                "min": 0,
+                "max": 100,
+                "unit_of_measurement": "%",
+                "icon": "mdi:fan",
+            }),
+        ),
+    ];
+
+    let device = json!({
+        "identifiers": [node],
+        "name": format!("{} {}", board.model, board.serial),
+        "model": board.model,
+        "serial_number": board.serial,
+        "manufacturer": "Mujina",
+        "via_device": miner,
+    });
+    entities
+        .into_iter()
+        .map(|(component, object, config)| {
+            (
+                topics.discovery(component, &node, object),
+                with_device(config, topics, &device),
+            )
+        })
+        .collect()
+}
+
+fn with_device(mut config: Value, topics: &Topics, device: &Value) -> Value {
+    config["device"] = device.clone();
+    config["availability_topic"] = topics.availability().into();
+    config
+}
+
+fn announce_miner(client: &AsyncClient, topics: &Topics) {
+    for (topic, config) in miner_discovery(topics) {
+        publish(client, &topic, true, config.to_string());
+    }
+}
+
+fn announce_board(client: &AsyncClient, topics: &Topics, board: &BoardStatus) {
+    debug!(serial = %board.serial, "Announcing board to Home Assistant");
+    for (topic, config) in board_discovery(topics, board) {
+        publish(client, &topic, true, config.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::types::{HashrateEstimate, PoolStatus};
+
+    fn topics() -> Topics {
+        Topics::new(&MqttConfig::default())
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let topics = topics();
+
+        assert_eq!(
+            topics.parse_command("mujina/command/mining", b"OFF"),
+            Ok(Command::Mining(false))
+        );
+        assert_eq!(
+            topics.parse_command("mujina/command/mining", b"resume\n"),
+            Ok(Command::Mining(true))
+        );
+        assert_eq!(
+            topics.parse_command("mujina/board/abc123/command/fan", b"62.6"),
+            Ok(Command::Fan {
+                serial: "abc123".to_string(),
+                mode: FanMode::Manual { percent: 63 }
+            })
+        );
+        assert_eq!(
+            topics.parse_command("mujina/board/abc123/command/fan", b"AUTO"),
+            Ok(Command::Fan {
+                serial: "abc123".to_string(),
+                mode: FanMode::Auto
+            })
+        );
+        assert_eq!(
+            topics.parse_command("mujina/board/abc123/command/frequency", b"550"),
+            Ok(Command::Frequency {
+                serial: "abc123".to_string(),
+                mhz: 550.0
+            })
+        );
+
+        assert_eq!(
+            topics.parse_command(
+                "mujina/command/curtail",
+                br#"{"target_w": 500, "duration_s": 900}"#
+            ),
+            Ok(Command::Curtail(CurtailRequest {
+                target_w: Some(500.0),
+                duration_s: Some(900),
+                until: None,
+            }))
+        );
+        assert_eq!(
+            topics.parse_command("mujina/command/curtail", b"End"),
+            Ok(Command::EndCurtailment)
+        );
+
+        assert!(topics
+            .parse_command("mujina/command/mining", b"maybe")
+            .is_err());
+        assert!(topics
+            .parse_command("mujina/command/curtail", b"500")
+            .is_err());
+        assert!(topics
+            .parse_command("mujina/board/abc123/command/fan", b"150")
+            .is_err());
+        assert!(topics
+            .parse_command("mujina/board/abc123/command/voltage", b"1.2")
+            .is_err());
+    }
+
+    #[test]
+    fn test_state_payloads() {
+        let status = MinerStatus {
+            hashrate: 1.0e12,
+            hashrate_windows: vec![HashrateEstimate {
+                window_s: 300,
+                hashrate: 1.1e12,
+                ..Default::default()
+            }],
+            paused: true,
+            pools: vec![PoolStatus {
+                name: "main".to_string(),
+                active: true,
+                connected: true,
+                shares_accepted: 5,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let state = miner_state(&status);
+        assert_eq!(state["hashrate"], 1.1e12);
+        assert_eq!(state["paused"], true);
+        assert_eq!(state["pool"], "main");
+        assert_eq!(state["pool_connected"], true);
+        assert_eq!(state["shares_accepted"], 5);
+        assert_eq!(state["power_w"], Value::Null);
+        assert_eq!(state["curtailed"], false);
+
+        let board = BoardStatus {
+            serial: "abc".to_string(),
+            asic_temp_c: Some(55.5),
+            ..Default::default()
+        };
+        let state = board_state(&board);
+        assert_eq!(state["asic_temp_c"], 55.5);
+        assert_eq!(state["hashrate"], Value::Null);
+    }
+
+    #[test]
+    fn test_discovery() {
+        let topics = topics();
+        let board = BoardStatus {
+            serial: "ab-12".to_string(),
+            model: "Bitaxe Gamma".to_string(),
+            ..Default::default()
+        };
+
+        let miner = miner_discovery(&topics);
+        let (topic, mining) = miner
+            .iter()
+            .find(|(topic, _)| topic.starts_with("homeassistant/switch/"))
+            .unwrap();
+        assert_eq!(topic, "homeassistant/switch/mujina/mining/config");
+        assert_eq!(mining["command_topic"], "mujina/command/mining");
+        assert_eq!(mining["availability_topic"], "mujina/availability");
+
+        let entities = board_discovery(&topics, &board);
+        let (topic, temp) = &entities[1];
+        assert_eq!(topic, "homeassistant/sensor/mujina_ab_12/asic_temp/config");
+        assert_eq!(temp["state_topic"], "mujina/board/ab-12/state");
+        assert_eq!(temp["value_template"], "{{ value_json.asic_temp_c }}");
+        assert_eq!(temp["device"]["via_device"], "mujina");
+
+        // Every entity has a unique id
+        let ids: HashSet<_> = miner
+            .iter()
+            .chain(&entities)
+            .map(|(_, config)| config["unique_id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ids.len(), miner.len() + entities.len());
+    }
+
+    /// Integration test: publish to and command through a real broker.
+    ///
+    /// # Running
+    ///
+    /// ```bash
+    /// # With a broker on localhost:1883, e.g. `mosquitto -v`
+    /// MUJINA_MQTT_BROKER=localhost:1883 \
+    /// cargo test --lib test_broker_from_env -- --ignored --nocapture
+    /// ```
+    #[tokio::test]
+    #[ignore]
+    async fn test_broker_from_env() {
+        use crate::telemetry::Telemetry;
+        use std::sync::Arc;
+
+        let broker = std::env::var("MUJINA_MQTT_BROKER").expect("MUJINA_MQTT_BROKER not set");
+        let (host, port) = broker.rsplit_once(':').expect("expected host:port");
+        let mqtt = MqttConfig {
+            enabled: true,
+            host: host.to_string(),
+            port: port.parse().unwrap(),
+            client_id: "mujina-test".to_string(),
+            topic_prefix: "mujina-test".to_string(),
+            interval_s: 1,
+            ..Default::default()
+        };
+
+        let telemetry = Telemetry::new();
+        telemetry.update_status(|s| s.shares_submitted = 42);
+        let (scheduler_tx, mut scheduler_rx) = tokio::sync::mpsc::channel(1);
+        let mut state = ApiState::for_test(telemetry);
+        state.scheduler = scheduler_tx;
+        state.config = Arc::new(crate::config::Config {
+            mqtt: mqtt.clone(),
+            ..Default::default()
+        });
+
+        let shutdown = CancellationToken::new();
+        let miner = tokio::spawn(task(state, shutdown.clone()));
+
+        // Observe the miner's state and pause it from a second client
+        let mut options = MqttOptions::new("mujina-test-observer", &mqtt.host, mqtt.port);
+        options.set_keep_alive(KEEP_ALIVE);
+        let (observer, mut eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+        observer
+            .subscribe("mujina-test/state", QoS::AtLeastOnce)
+            .await
+            .unwrap();
+
+        let state = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Event::Incoming(Packet::Publish(message)) = eventloop.poll().await.unwrap() {
+                    return serde_json::from_slice::<Value>(&message.payload).unwrap();
+                }
+            }
+        })
+        .await
+        .expect("no state published");
+        assert_eq!(state["shares_submitted"], 42);
+
+        observer
+            .publish("mujina-test/command/mining", QoS::AtLeastOnce, false, "OFF")
+            .await
+            .unwrap();
+        let command = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                tokio::select! {
+                    command = scheduler_rx.recv() => return command.unwrap(),
+                    _ = eventloop.poll() => {}
+                }
+            }
+        })
+        .await
+        .expect("no command received");
+        assert!(matches!(command, SchedulerCommand::Pause { .. }));
+
+        shutdown.cancel();
+        miner.await.unwrap();
+    }
+}
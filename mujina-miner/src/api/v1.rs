@@ -1,36 +1,250 @@
 //! API version 1 endpoints.
+//!
+//! Every handler here carries a `#[utoipa::path]` attribute, and [`ApiDoc`]
+//! collects them into an OpenAPI 3 document served at `/api/v1/openapi.json`.
+//! Routes are registered from a single table so a test can check that the
+//! router and the document describe the same set of paths.
 
 use axum::{
-    extract::Json,
-    routing::{get, post},
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put, MethodRouter},
     Router,
 };
-use serde::{Deserialize, Serialize};
+use futures::Stream;
+use serde::Deserialize;
+use tokio_stream::{
+    wrappers::{BroadcastStream, WatchStream},
+    StreamExt,
+};
+use utoipa::{IntoParams, OpenApi};
+
+use super::{send_command, ApiState};
+use crate::api_client::types::{
+    AddPoolRequest, Alert, AlertState, BoardStatus, ChipHealth, CurtailRequest, CurtailmentStatus,
+    EchoRequest, EchoResponse, ErrorResponse, FanMode, HashrateEstimate, HistoryMetric,
+    HistoryPoint, HistoryResponse, LogEntry, LogLevels, LogsQuery, MinerEvent, MinerStatus,
+    OperatingMode, PerformanceProfile, PoolStatus, ScheduleOverride, ScheduleStatus,
+    SetLogLevelRequest, SetProfileRequest, SetScheduleOverrideRequest, ThreadStatus,
+};
+use crate::backplane::BackplaneCommand;
+use crate::config::{
+    AlertRule, AlertsConfig, ApiConfig, CgminerApiConfig, ChipProfiles, Config, DaemonConfig,
+    FanControlConfig, HardwareConfig, HistoryConfig, LogFileConfig, LogFormat, LogRotation,
+    LoggingConfig, MqttConfig, OtlpConfig, PoolConfig, ProfileSettings, ScheduleConfig,
+    ScheduleRule, TuningConfig, TuningGoal, WebhookConfig,
+};
+use crate::error::CommandError;
+use crate::history;
+use crate::schedule::ScheduleCommand;
+use crate::scheduler::SchedulerCommand;
+use crate::tracing::LogFilter;
+
+/// Events returned by the log endpoint when the query sets no limit.
+const DEFAULT_LOG_LIMIT: usize = 1000;
+
+/// OpenAPI description of the v1 API.
+///
+/// Paths are relative to the `/api/v1` server URL, matching how the routes
+/// are nested in the top-level router.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "mujina-miner API",
+        description = "REST API for monitoring and controlling mujina-miner."
+    ),
+    servers((url = "/api/v1")),
+    paths(
+        echo,
+        health,
+        openapi,
+        status,
+        boards,
+        board,
+        board_idle,
+        board_restart,
+        board_fan,
+        board_profile,
+        threads,
+        pools,
+        add_pool,
+        remove_pool,
+        switch_pool,
+        pause,
+        resume,
+        schedule,
+        set_schedule_override,
+        clear_schedule_override,
+        curtailment,
+        curtail,
+        end_curtailment,
+        config,
+        history,
+        events,
+        logs,
+        log_levels,
+        set_log_level,
+        reset_log_level
+    ),
+    components(schemas(
+        EchoRequest,
+        EchoResponse,
+        ErrorResponse,
+        MinerStatus,
+        BoardStatus,
+        FanMode,
+        PerformanceProfile,
+        SetProfileRequest,
+        OperatingMode,
+        ScheduleStatus,
+        ScheduleOverride,
+        SetScheduleOverrideRequest,
+        CurtailRequest,
+        CurtailmentStatus,
+        ThreadStatus,
+        ChipHealth,
+        HashrateEstimate,
+        PoolStatus,
+        AddPoolRequest,
+        MinerEvent,
+        Alert,
+        AlertState,
+        HistoryMetric,
+        HistoryPoint,
+        HistoryResponse,
+        LogEntry,
+        LogLevels,
+        SetLogLevelRequest,
+        Config,
+        DaemonConfig,
+        PoolConfig,
+        HardwareConfig,
+        FanControlConfig,
+        TuningConfig,
+        TuningGoal,
+        ChipProfiles,
+        ProfileSettings,
+        ApiConfig,
+        CgminerApiConfig,
+        HistoryConfig,
+        AlertsConfig,
+        AlertRule,
+        WebhookConfig,
+        MqttConfig,
+        LoggingConfig,
+        LogFormat,
+        LogFileConfig,
+        LogRotation,
+        OtlpConfig,
+        ScheduleConfig,
+        ScheduleRule
+    ))
+)]
+pub struct ApiDoc;
+
+/// Build the v1 API routes.
+pub fn routes() -> Router<ApiState> {
+    route_table()
+        .into_iter()
+        .fold(Router::new(), |router, (path, method_router)| {
+            router.route(path, method_router)
+        })
+}
 
-/// Echo request payload.
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct EchoRequest {
-    /// The message to echo back.
-    pub message: String,
+/// All v1 routes, keyed by path relative to `/api/v1`.
+///
+/// Add new endpoints here and to the `paths(...)` list of [`ApiDoc`]; the
+/// `routes_match_openapi_spec` test fails if the two disagree.
+fn route_table() -> Vec<(&'static str, MethodRouter<ApiState>)> {
+    vec![
+        ("/echo", post(echo)),
+        ("/health", get(health)),
+        ("/openapi.json", get(openapi)),
+        ("/status", get(status)),
+        ("/boards", get(boards)),
+        ("/boards/:serial", get(board)),
+        ("/boards/:serial/idle", post(board_idle)),
+        ("/boards/:serial/restart", post(board_restart)),
+        ("/boards/:serial/fan", put(board_fan)),
+        ("/boards/:serial/profile", put(board_profile)),
+        ("/threads", get(threads)),
+        ("/pools", get(pools).post(add_pool)),
+        ("/pools/:name", delete(remove_pool)),
+        ("/pools/:name/switch", post(switch_pool)),
+        ("/pause", post(pause)),
+        ("/resume", post(resume)),
+        ("/schedule", get(schedule)),
+        (
+            "/schedule/override",
+            put(set_schedule_override).delete(clear_schedule_override),
+        ),
+        (
+            "/curtailment",
+            get(curtailment).put(curtail).delete(end_curtailment),
+        ),
+        ("/config", get(config)),
+        ("/history", get(history)),
+        ("/events", get(events)),
+        ("/logs", get(logs)),
+        ("/logs/levels", get(log_levels)),
+        (
+            "/logs/levels/:target",
+            put(set_log_level).delete(reset_log_level),
+        ),
+    ]
 }
 
-/// Echo response payload.
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct EchoResponse {
-    /// The echoed message.
-    pub message: String,
+/// Errors returned by v1 handlers, rendered as an [`ErrorResponse`] body.
+#[derive(Debug)]
+enum ApiError {
+    /// The requested resource does not exist.
+    NotFound(String),
+
+    /// The request itself is malformed.
+    BadRequest(String),
+
+    /// A control command was refused.
+    Command(CommandError),
 }
 
-/// Build the v1 API routes.
-pub fn routes() -> Router {
-    Router::new()
-        .route("/echo", post(echo))
-        .route("/health", get(health))
+impl From<CommandError> for ApiError {
+    fn from(e: CommandError) -> Self {
+        ApiError::Command(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            ApiError::NotFound(error) => (StatusCode::NOT_FOUND, error),
+            ApiError::BadRequest(error) => (StatusCode::BAD_REQUEST, error),
+            ApiError::Command(e) => {
+                let status = match e {
+                    CommandError::NotFound(_) => StatusCode::NOT_FOUND,
+                    CommandError::Conflict(_) => StatusCode::CONFLICT,
+                    CommandError::Invalid(_) => StatusCode::BAD_REQUEST,
+                    CommandError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                };
+                (status, e.to_string())
+            }
+        };
+        (status, Json(ErrorResponse { error })).into_response()
+    }
 }
 
 /// Echo endpoint handler.
 ///
 /// Echoes back the provided message. Useful for testing API connectivity.
+#[utoipa::path(
+    post,
+    path = "/echo",
+    request_body = EchoRequest,
+    responses((status = 200, description = "The echoed message", body = EchoResponse))
+)]
 async fn echo(Json(req): Json<EchoRequest>) -> Json<EchoResponse> {
     Json(EchoResponse {
         message: req.message,
@@ -40,6 +254,831 @@ async fn echo(Json(req): Json<EchoRequest>) -> Json<EchoResponse> {
 /// Health check endpoint handler.
 ///
 /// Returns a simple OK status to verify the API is running.
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses((status = 200, description = "API is running", body = String, example = "OK"))
+)]
 async fn health() -> &'static str {
     "OK"
 }
+
+/// OpenAPI document endpoint handler.
+///
+/// Returns the machine-readable description of this API version.
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    responses((status = 200, description = "OpenAPI 3 document", content_type = "application/json"))
+)]
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Miner status endpoint handler.
+///
+/// Returns the full status snapshot: totals, boards, threads, and pools.
+#[utoipa::path(
+    get,
+    path = "/status",
+    responses((status = 200, description = "Current miner status", body = MinerStatus))
+)]
+async fn status(State(state): State<ApiState>) -> Json<MinerStatus> {
+    Json(state.telemetry.status())
+}
+
+/// Board list endpoint handler.
+#[utoipa::path(
+    get,
+    path = "/boards",
+    responses((status = 200, description = "Connected boards", body = Vec<BoardStatus>))
+)]
+async fn boards(State(state): State<ApiState>) -> Json<Vec<BoardStatus>> {
+    Json(state.telemetry.status().boards)
+}
+
+/// Single board endpoint handler.
+#[utoipa::path(
+    get,
+    path = "/boards/{serial}",
+    params(("serial" = String, Path, description = "Board serial number")),
+    responses(
+        (status = 200, description = "The board", body = BoardStatus),
+        (status = 404, description = "No board with this serial", body = ErrorResponse)
+    )
+)]
+async fn board(
+    State(state): State<ApiState>,
+    Path(serial): Path<String>,
+) -> Result<Json<BoardStatus>, ApiError> {
+    state
+        .telemetry
+        .status()
+        .boards
+        .into_iter()
+        .find(|board| board.serial == serial)
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("no board with serial {serial}")))
+}
+
+/// Hash thread list endpoint handler.
+#[utoipa::path(
+    get,
+    path = "/threads",
+    responses((status = 200, description = "Registered hash threads", body = Vec<ThreadStatus>))
+)]
+async fn threads(State(state): State<ApiState>) -> Json<Vec<ThreadStatus>> {
+    Json(state.telemetry.status().threads)
+}
+
+/// Pool list endpoint handler.
+#[utoipa::path(
+    get,
+    path = "/pools",
+    responses((status = 200, description = "Registered job sources", body = Vec<PoolStatus>))
+)]
+async fn pools(State(state): State<ApiState>) -> Json<Vec<PoolStatus>> {
+    Json(state.telemetry.status().pools)
+}
+
+/// Board idle endpoint handler.
+///
+/// Idles the board's threads until the board is restarted.
+#[utoipa::path(
+    post,
+    path = "/boards/{serial}/idle",
+    params(("serial" = String, Path, description = "Board serial number")),
+    responses(
+        (status = 204, description = "Board idled"),
+        (status = 404, description = "No board with this serial", body = ErrorResponse)
+    )
+)]
+async fn board_idle(
+    State(state): State<ApiState>,
+    Path(serial): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::IdleBoard { serial, response }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Board restart endpoint handler.
+///
+/// Shuts the board down and re-initializes it, which also ends an idle.
+#[utoipa::path(
+    post,
+    path = "/boards/{serial}/restart",
+    params(("serial" = String, Path, description = "Board serial number")),
+    responses(
+        (status = 204, description = "Board restarted"),
+        (status = 404, description = "No board with this serial", body = ErrorResponse),
+        (status = 400, description = "Board failed to come back", body = ErrorResponse)
+    )
+)]
+async fn board_restart(
+    State(state): State<ApiState>,
+    Path(serial): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    send_command(&state.backplane, "backplane", |response| {
+        BackplaneCommand::RestartBoard { serial, response }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Board fan endpoint handler.
+///
+/// Sets a fixed duty cycle, overriding closed-loop control, or returns the
+/// fan to closed-loop control.
+#[utoipa::path(
+    put,
+    path = "/boards/{serial}/fan",
+    params(("serial" = String, Path, description = "Board serial number")),
+    request_body = FanMode,
+    responses(
+        (status = 204, description = "Fan mode set"),
+        (status = 404, description = "No board with this serial", body = ErrorResponse),
+        (status = 400, description = "Invalid duty cycle or no fan control", body = ErrorResponse)
+    )
+)]
+async fn board_fan(
+    State(state): State<ApiState>,
+    Path(serial): Path<String>,
+    Json(mode): Json<FanMode>,
+) -> Result<StatusCode, ApiError> {
+    if matches!(mode, FanMode::Manual { percent } if percent > 100) {
+        return Err(ApiError::BadRequest(
+            "duty cycle must be 0-100 percent".into(),
+        ));
+    }
+    send_command(&state.backplane, "backplane", |response| {
+        BackplaneCommand::SetFanMode {
+            serial,
+            mode,
+            response,
+        }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Board performance profile endpoint handler.
+///
+/// Selects one of the configured profiles for the board's chips, or clears
+/// the selection to return to the default or tuned operating point. The
+/// board ramps to the new frequency without re-initializing its chips.
+#[utoipa::path(
+    put,
+    path = "/boards/{serial}/profile",
+    params(("serial" = String, Path, description = "Board serial number")),
+    request_body = SetProfileRequest,
+    responses(
+        (status = 204, description = "Profile set"),
+        (status = 404, description = "No board with this serial", body = ErrorResponse),
+        (status = 400, description = "No profiles configured for the board's chips", body = ErrorResponse)
+    )
+)]
+async fn board_profile(
+    State(state): State<ApiState>,
+    Path(serial): Path<String>,
+    Json(request): Json<SetProfileRequest>,
+) -> Result<StatusCode, ApiError> {
+    send_command(&state.backplane, "backplane", |response| {
+        BackplaneCommand::SetProfile {
+            serial,
+            profile: request.profile,
+            response,
+        }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Add pool endpoint handler.
+#[utoipa::path(
+    post,
+    path = "/pools",
+    request_body = AddPoolRequest,
+    responses(
+        (status = 201, description = "Pool added", body = PoolStatus),
+        (status = 409, description = "A pool with this name exists", body = ErrorResponse)
+    )
+)]
+async fn add_pool(
+    State(state): State<ApiState>,
+    Json(request): Json<AddPoolRequest>,
+) -> Result<(StatusCode, Json<PoolStatus>), ApiError> {
+    let pool = send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::AddPool { request, response }
+    })
+    .await??;
+    Ok((StatusCode::CREATED, Json(pool)))
+}
+
+/// Remove pool endpoint handler.
+#[utoipa::path(
+    delete,
+    path = "/pools/{name}",
+    params(("name" = String, Path, description = "Pool name")),
+    responses(
+        (status = 204, description = "Pool removed"),
+        (status = 404, description = "No pool with this name", body = ErrorResponse)
+    )
+)]
+async fn remove_pool(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::RemovePool { name, response }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Switch pool endpoint handler.
+///
+/// Makes the named pool the one all threads work for.
+#[utoipa::path(
+    post,
+    path = "/pools/{name}/switch",
+    params(("name" = String, Path, description = "Pool name")),
+    responses(
+        (status = 204, description = "Switched to the pool"),
+        (status = 404, description = "No pool with this name", body = ErrorResponse)
+    )
+)]
+async fn switch_pool(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::SwitchPool { name, response }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Pause endpoint handler.
+///
+/// Idles all threads; jobs keep arriving but are not assigned.
+#[utoipa::path(
+    post,
+    path = "/pause",
+    responses((status = 204, description = "Mining paused"))
+)]
+async fn pause(State(state): State<ApiState>) -> Result<StatusCode, ApiError> {
+    send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::Pause { response }
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resume endpoint handler.
+#[utoipa::path(
+    post,
+    path = "/resume",
+    responses((status = 204, description = "Mining resumed"))
+)]
+async fn resume(State(state): State<ApiState>) -> Result<StatusCode, ApiError> {
+    send_command(&state.scheduler, "scheduler", |response| {
+        SchedulerCommand::Resume { response }
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Schedule endpoint handler.
+///
+/// Returns the operating mode rules, which one is in effect and which starts
+/// next, and any override.
+#[utoipa::path(
+    get,
+    path = "/schedule",
+    responses((status = 200, description = "Operating mode schedule", body = ScheduleStatus))
+)]
+async fn schedule(State(state): State<ApiState>) -> Result<Json<ScheduleStatus>, ApiError> {
+    let status = send_command(&state.schedule, "schedule", |response| {
+        ScheduleCommand::Status { response }
+    })
+    .await?;
+    Ok(Json(status))
+}
+
+/// Schedule override endpoint handler.
+///
+/// Puts every board in the given mode instead of the scheduled one, for the
+/// given duration or until the next rule starts.
+#[utoipa::path(
+    put,
+    path = "/schedule/override",
+    request_body = SetScheduleOverrideRequest,
+    responses(
+        (status = 200, description = "Override in place", body = ScheduleStatus),
+        (status = 400, description = "Invalid mode", body = ErrorResponse)
+    )
+)]
+async fn set_schedule_override(
+    State(state): State<ApiState>,
+    Json(request): Json<SetScheduleOverrideRequest>,
+) -> Result<Json<ScheduleStatus>, ApiError> {
+    let status = send_command(&state.schedule, "schedule", |response| {
+        ScheduleCommand::SetOverride { request, response }
+    })
+    .await??;
+    Ok(Json(status))
+}
+
+/// Schedule override removal endpoint handler.
+#[utoipa::path(
+    delete,
+    path = "/schedule/override",
+    responses(
+        (status = 204, description = "Back on schedule"),
+        (status = 404, description = "No override in place", body = ErrorResponse)
+    )
+)]
+async fn clear_schedule_override(State(state): State<ApiState>) -> Result<StatusCode, ApiError> {
+    send_command(&state.schedule, "schedule", |response| {
+        ScheduleCommand::ClearOverride { response }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Curtailment endpoint handler.
+///
+/// Returns the demand-response curtailment in effect and the total input
+/// power achieved.
+#[utoipa::path(
+    get,
+    path = "/curtailment",
+    responses(
+        (status = 200, description = "Curtailment in effect", body = CurtailmentStatus),
+        (status = 404, description = "No curtailment in effect", body = ErrorResponse)
+    )
+)]
+async fn curtailment(State(state): State<ApiState>) -> Result<Json<CurtailmentStatus>, ApiError> {
+    state
+        .telemetry
+        .status()
+        .curtailment
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("no curtailment in effect".into()))
+}
+
+/// Curtail endpoint handler.
+///
+/// Holds total input power under a target, or stops hashing, until the
+/// curtailment expires; replaces any curtailment already in effect.
+#[utoipa::path(
+    put,
+    path = "/curtailment",
+    request_body = CurtailRequest,
+    responses(
+        (status = 200, description = "Curtailment in effect", body = CurtailmentStatus),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+async fn curtail(
+    State(state): State<ApiState>,
+    Json(request): Json<CurtailRequest>,
+) -> Result<Json<CurtailmentStatus>, ApiError> {
+    let status = send_command(&state.schedule, "schedule", |response| {
+        ScheduleCommand::Curtail { request, response }
+    })
+    .await??;
+    Ok(Json(status))
+}
+
+/// Curtailment removal endpoint handler.
+#[utoipa::path(
+    delete,
+    path = "/curtailment",
+    responses(
+        (status = 204, description = "Curtailment ended"),
+        (status = 404, description = "No curtailment in effect", body = ErrorResponse)
+    )
+)]
+async fn end_curtailment(State(state): State<ApiState>) -> Result<StatusCode, ApiError> {
+    send_command(&state.schedule, "schedule", |response| {
+        ScheduleCommand::EndCurtailment { response }
+    })
+    .await??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Configuration endpoint handler.
+///
+/// Returns the configuration the daemon is running with, passwords redacted.
+#[utoipa::path(
+    get,
+    path = "/config",
+    responses((status = 200, description = "Effective configuration", body = Config))
+)]
+async fn config(State(state): State<ApiState>) -> Json<Config> {
+    Json(state.config.redacted())
+}
+
+/// Query parameters of the history endpoint.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HistoryQuery {
+    /// Metric to return
+    metric: HistoryMetric,
+
+    /// How far back to go, e.g. `30m`, `24h` or `7d`; defaults to `1h`
+    range: Option<String>,
+}
+
+/// Statistics history endpoint handler.
+///
+/// Returns the metric at the finest resolution that covers the range:
+/// one-minute intervals up to a day, one-hour intervals up to 30 days,
+/// one-day intervals beyond.
+#[utoipa::path(
+    get,
+    path = "/history",
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Recorded series", body = HistoryResponse),
+        (status = 400, description = "Unknown metric or malformed range", body = ErrorResponse)
+    )
+)]
+async fn history(
+    State(state): State<ApiState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<HistoryResponse>, ApiError> {
+    let range = query.range.as_deref().unwrap_or("1h");
+    let range = history::parse_range(range)
+        .ok_or_else(|| ApiError::BadRequest(format!("invalid range '{range}'")))?;
+    Ok(Json(state.history.query(
+        query.metric,
+        range,
+        history::unix_now(),
+    )))
+}
+
+/// Event stream endpoint handler.
+///
+/// Server-sent events, one JSON-encoded [`MinerEvent`] per `data:` line. The
+/// stream opens with a `status` event carrying the current snapshot and
+/// sends another whenever the status changes. Slow clients may miss
+/// discrete events but always converge on the latest status.
+#[utoipa::path(
+    get,
+    path = "/events",
+    responses((
+        status = 200,
+        description = "Stream of miner events",
+        content_type = "text/event-stream",
+        body = MinerEvent
+    ))
+)]
+async fn events(
+    State(state): State<ApiState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let status = WatchStream::new(state.telemetry.subscribe_status()).map(MinerEvent::Status);
+    let events =
+        BroadcastStream::new(state.telemetry.subscribe_events()).filter_map(|event| event.ok());
+
+    let stream = status
+        .merge(events)
+        .map(|event| Event::default().json_data(event));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Log endpoint handler.
+///
+/// Returns recent events from the in-memory log buffer, oldest first. With
+/// `follow`, responds with server-sent events instead: the matching backlog,
+/// then each new matching event as it is logged, one JSON-encoded
+/// [`LogEntry`] per `data:` line.
+#[utoipa::path(
+    get,
+    path = "/logs",
+    params(LogsQuery),
+    responses(
+        (status = 200, description = "Matching log events", content(
+            (Vec<LogEntry> = "application/json"),
+            (LogEntry = "text/event-stream")
+        )),
+        (status = 400, description = "Unknown level", body = ErrorResponse)
+    )
+)]
+async fn logs(
+    State(state): State<ApiState>,
+    Query(query): Query<LogsQuery>,
+) -> Result<Response, ApiError> {
+    let level = query
+        .level
+        .map(|level| {
+            level
+                .parse()
+                .map_err(|_| ApiError::BadRequest(format!("invalid level '{level}'")))
+        })
+        .transpose()?;
+    let filter = LogFilter {
+        level,
+        target: query.target,
+        since: query.since,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LOG_LIMIT);
+
+    if !query.follow {
+        return Ok(Json(state.logs.entries(&filter, limit)).into_response());
+    }
+
+    // Subscribe before reading the backlog so nothing falls between them
+    let live = BroadcastStream::new(state.logs.subscribe());
+    let backlog = state.logs.entries(&filter, limit);
+    let last_seq = backlog.last().map(|entry| entry.seq);
+    let live = live.filter_map(move |entry| {
+        entry
+            .ok()
+            .filter(|entry| last_seq.is_none_or(|seq| entry.seq > seq) && filter.matches(entry))
+    });
+
+    let stream = tokio_stream::iter(backlog)
+        .chain(live)
+        .map(|entry| Event::default().json_data(entry));
+    Ok(Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// Log filter endpoint handler.
+#[utoipa::path(
+    get,
+    path = "/logs/levels",
+    responses(
+        (status = 200, description = "Log filter in effect", body = LogLevels),
+        (status = 503, description = "Log filter is not adjustable", body = ErrorResponse)
+    )
+)]
+async fn log_levels(State(state): State<ApiState>) -> Result<Json<LogLevels>, ApiError> {
+    state
+        .logs
+        .levels()
+        .map(Json)
+        .ok_or(ApiError::Command(CommandError::Unavailable("log filter")))
+}
+
+/// Log level override endpoint handler.
+///
+/// Logs the module and everything below it at the given level until reset
+/// or restart. Applies to every log output, not only the buffer.
+#[utoipa::path(
+    put,
+    path = "/logs/levels/{target}",
+    params(("target" = String, Path, description = "Module path, e.g. `mujina_miner::asic`")),
+    request_body = SetLogLevelRequest,
+    responses(
+        (status = 204, description = "Level set"),
+        (status = 400, description = "Invalid module path or level", body = ErrorResponse),
+        (status = 503, description = "Log filter is not adjustable", body = ErrorResponse)
+    )
+)]
+async fn set_log_level(
+    State(state): State<ApiState>,
+    Path(target): Path<String>,
+    Json(request): Json<SetLogLevelRequest>,
+) -> Result<StatusCode, ApiError> {
+    state.logs.set_level(&target, Some(&request.level))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Log level reset endpoint handler.
+///
+/// Removes a runtime override, returning the module to its configured level.
+#[utoipa::path(
+    delete,
+    path = "/logs/levels/{target}",
+    params(("target" = String, Path, description = "Module path, e.g. `mujina_miner::asic`")),
+    responses(
+        (status = 204, description = "Override removed"),
+        (status = 503, description = "Log filter is not adjustable", body = ErrorResponse)
+    )
+)]
+async fn reset_log_level(
+    State(state): State<ApiState>,
+    Path(target): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state.logs.set_level(&target, None)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::telemetry::Telemetry;
+
+    fn app() -> Router {
+        routes().with_state(ApiState::for_test(Telemetry::new()))
+    }
+
+    /// Convert an OpenAPI path template (`/boards/{serial}`) to the axum
+    /// route syntax (`/boards/:serial`).
+    fn to_axum_path(openapi_path: &str) -> String {
+        openapi_path.replace('{', ":").replace('}', "")
+    }
+
+    /// Substitute a placeholder value for each path parameter so the path
+    /// can be requested.
+    fn to_request_path(openapi_path: &str) -> String {
+        openapi_path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') {
+                    "test"
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn documented_operations() -> Vec<(String, Method)> {
+        let spec = ApiDoc::openapi();
+        let mut operations = Vec::new();
+
+        for (path, item) in &spec.paths.paths {
+            let methods = [
+                (&item.get, Method::GET),
+                (&item.post, Method::POST),
+                (&item.put, Method::PUT),
+                (&item.delete, Method::DELETE),
+                (&item.patch, Method::PATCH),
+            ];
+            for (operation, method) in methods {
+                if operation.is_some() {
+                    operations.push((path.clone(), method));
+                }
+            }
+        }
+
+        operations
+    }
+
+    #[test]
+    fn routes_match_openapi_spec() {
+        let routed: BTreeSet<String> = route_table()
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+
+        let documented: BTreeSet<String> = ApiDoc::openapi()
+            .paths
+            .paths
+            .keys()
+            .map(|path| to_axum_path(path))
+            .collect();
+
+        assert_eq!(
+            routed, documented,
+            "v1 routes and OpenAPI paths have diverged"
+        );
+    }
+
+    #[tokio::test]
+    async fn documented_operations_are_routed() {
+        for (path, method) in documented_operations() {
+            let request = Request::builder()
+                .method(method.clone())
+                .uri(to_request_path(&path))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = app().oneshot(request).await.unwrap();
+
+            assert_ne!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{method} {path} is documented but routed with a different method"
+            );
+
+            // Handlers answer unknown resources with 404 and an error body;
+            // the router's own 404 has an empty body.
+            if response.status() == StatusCode::NOT_FOUND {
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                assert!(
+                    serde_json::from_slice::<ErrorResponse>(&body).is_ok(),
+                    "{method} {path} is documented but not routed"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_openapi_document() {
+        let request = Request::builder()
+            .uri("/openapi.json")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+        assert!(doc["paths"]["/echo"]["post"].is_object());
+        assert!(doc["components"]["schemas"]["EchoRequest"].is_object());
+    }
+
+    #[tokio::test]
+    async fn board_lookup_by_serial() {
+        let telemetry = Telemetry::new();
+        telemetry.update_status(|status| {
+            status.boards.push(BoardStatus {
+                serial: "abc123".into(),
+                model: "Bitaxe Gamma".into(),
+                chip_count: 1,
+                ..Default::default()
+            })
+        });
+        let app = routes().with_state(ApiState::for_test(telemetry));
+
+        let request = Request::builder()
+            .uri("/boards/abc123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let board: BoardStatus = serde_json::from_slice(&body).unwrap();
+        assert_eq!(board.model, "Bitaxe Gamma");
+
+        let request = Request::builder()
+            .uri("/boards/missing")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert!(error.error.contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn logs_filtered_by_query() {
+        let state = ApiState::for_test(Telemetry::new());
+        for (level, target) in [
+            ("info", "mujina_miner::daemon"),
+            ("warn", "mujina_miner::board::bitaxe"),
+            ("debug", "mujina_miner::board::bitaxe"),
+        ] {
+            state.logs.push(LogEntry {
+                seq: 0,
+                time: 1.0,
+                level: level.into(),
+                target: target.into(),
+                message: "test".into(),
+                fields: Default::default(),
+            });
+        }
+        let app = routes().with_state(state);
+
+        let request = Request::builder()
+            .uri("/logs?level=info&target=mujina_miner::board")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let entries: Vec<LogEntry> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].seq, 1);
+
+        let request = Request::builder()
+            .uri("/logs?level=loud")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
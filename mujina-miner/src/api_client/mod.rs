@@ -1,9 +1,697 @@
 //! API client library.
 //!
 //! This module provides a Rust client for the miner's HTTP API, used by
-//! the CLI and TUI binaries. It handles authentication, request/response
-//! serialization, and WebSocket connections for real-time data.
+//! the CLI and TUI binaries. Requests and responses use the DTOs in
+//! [`types`], the same types the server serializes, so the two sides cannot
+//! drift apart silently.
 
 pub mod types;
 
-// TODO: Implement API client with reqwest
+use std::collections::VecDeque;
+
+use futures::Stream;
+use reqwest::{Response, StatusCode};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::config::Config;
+use types::{
+    AddPoolRequest, BoardStatus, CurtailRequest, CurtailmentStatus, EchoRequest, EchoResponse,
+    ErrorResponse, FanMode, HistoryMetric, HistoryResponse, LogEntry, LogLevels, LogsQuery,
+    MinerEvent, MinerStatus, PerformanceProfile, PoolStatus, ScheduleStatus, SetLogLevelRequest,
+    SetProfileRequest, SetScheduleOverrideRequest, ThreadStatus,
+};
+
+/// Default API base URL.
+/// Port 7785 represents ASCII 'M' (77) and 'U' (85).
+pub const DEFAULT_API_URL: &str = "http://127.0.0.1:7785";
+
+/// Environment variable that overrides the API base URL.
+pub const API_URL_ENV: &str = "MUJINA_API_URL";
+
+/// Errors returned by [`ApiClient`].
+#[derive(Error, Debug)]
+pub enum ApiClientError {
+    /// The request could not be sent or the response could not be read.
+    #[error("request to the miner API failed")]
+    Http(#[from] reqwest::Error),
+
+    /// The server answered with a non-success status.
+    #[error("API returned {status}: {message}")]
+    Status { status: StatusCode, message: String },
+
+    /// The response body was not the expected JSON.
+    #[error("invalid response from the miner API")]
+    Decode(#[from] serde_json::Error),
+}
+
+impl ApiClientError {
+    /// HTTP status returned by the server, if the request got that far.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiClientError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+/// Result type for API client operations.
+pub type Result<T> = std::result::Result<T, ApiClientError>;
+
+/// Typed async client for the v1 API.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    /// Create a client for the API at `base_url`, e.g. `http://127.0.0.1:7785`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self {
+            base_url,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a client using `MUJINA_API_URL`, falling back to
+    /// [`DEFAULT_API_URL`].
+    pub fn from_env() -> Self {
+        Self::new(std::env::var(API_URL_ENV).unwrap_or_else(|_| DEFAULT_API_URL.to_string()))
+    }
+
+    /// Base URL this client talks to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Echo a message back from the server.
+    pub async fn echo(&self, message: impl Into<String>) -> Result<String> {
+        let request = EchoRequest {
+            message: message.into(),
+        };
+        let response = self
+            .http
+            .post(self.url("/echo"))
+            .json(&request)
+            .send()
+            .await?;
+        let echo: EchoResponse = decode(response).await?;
+        Ok(echo.message)
+    }
+
+    /// Check that the API is up.
+    pub async fn health(&self) -> Result<()> {
+        let response = self.http.get(self.url("/health")).send().await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Fetch the OpenAPI document describing the API.
+    pub async fn openapi(&self) -> Result<serde_json::Value> {
+        self.get("/openapi.json").await
+    }
+
+    /// Fetch the full miner status.
+    pub async fn status(&self) -> Result<MinerStatus> {
+        self.get("/status").await
+    }
+
+    /// List connected boards.
+    pub async fn boards(&self) -> Result<Vec<BoardStatus>> {
+        self.get("/boards").await
+    }
+
+    /// Fetch one board by serial number.
+    pub async fn board(&self, serial: &str) -> Result<BoardStatus> {
+        self.get(&format!("/boards/{}", segment(serial))).await
+    }
+
+    /// Idle a board's threads until it is restarted.
+    pub async fn board_idle(&self, serial: &str) -> Result<()> {
+        self.post_empty(&format!("/boards/{}/idle", segment(serial)))
+            .await
+    }
+
+    /// Shut a board down and re-initialize it.
+    pub async fn board_restart(&self, serial: &str) -> Result<()> {
+        self.post_empty(&format!("/boards/{}/restart", segment(serial)))
+            .await
+    }
+
+    /// Set a board's fan to a fixed duty cycle or closed-loop control.
+    pub async fn set_fan_mode(&self, serial: &str, mode: FanMode) -> Result<()> {
+        let response = self
+            .http
+            .put(self.url(&format!("/boards/{}/fan", segment(serial))))
+            .json(&mode)
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Select a board's performance profile, or clear it with `None`.
+    pub async fn set_profile(
+        &self,
+        serial: &str,
+        profile: Option<PerformanceProfile>,
+    ) -> Result<()> {
+        let response = self
+            .http
+            .put(self.url(&format!("/boards/{}/profile", segment(serial))))
+            .json(&SetProfileRequest { profile })
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// List hash threads.
+    pub async fn threads(&self) -> Result<Vec<ThreadStatus>> {
+        self.get("/threads").await
+    }
+
+    /// List job sources.
+    pub async fn pools(&self) -> Result<Vec<PoolStatus>> {
+        self.get("/pools").await
+    }
+
+    /// Add a Stratum v1 pool, returning its initial status.
+    pub async fn add_pool(&self, request: &AddPoolRequest) -> Result<PoolStatus> {
+        let response = self
+            .http
+            .post(self.url("/pools"))
+            .json(request)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Remove a pool by name.
+    pub async fn remove_pool(&self, name: &str) -> Result<()> {
+        let response = self
+            .http
+            .delete(self.url(&format!("/pools/{}", segment(name))))
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Make the named pool the active one.
+    pub async fn switch_pool(&self, name: &str) -> Result<()> {
+        self.post_empty(&format!("/pools/{}/switch", segment(name)))
+            .await
+    }
+
+    /// Pause mining (idle all threads).
+    pub async fn pause(&self) -> Result<()> {
+        self.post_empty("/pause").await
+    }
+
+    /// Resume mining after a pause.
+    pub async fn resume(&self) -> Result<()> {
+        self.post_empty("/resume").await
+    }
+
+    /// Fetch the operating mode schedule and the mode in effect.
+    pub async fn schedule(&self) -> Result<ScheduleStatus> {
+        self.get("/schedule").await
+    }
+
+    /// Override the scheduled operating mode, returning the new status.
+    pub async fn set_schedule_override(
+        &self,
+        request: &SetScheduleOverrideRequest,
+    ) -> Result<ScheduleStatus> {
+        let response = self
+            .http
+            .put(self.url("/schedule/override"))
+            .json(request)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Return to the scheduled operating mode.
+    pub async fn clear_schedule_override(&self) -> Result<()> {
+        let response = self
+            .http
+            .delete(self.url("/schedule/override"))
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Fetch the demand-response curtailment in effect.
+    pub async fn curtailment(&self) -> Result<CurtailmentStatus> {
+        self.get("/curtailment").await
+    }
+
+    /// Curtail power for a demand-response event.
+    pub async fn curtail(&self, request: &CurtailRequest) -> Result<CurtailmentStatus> {
+        let response = self
+            .http
+            .put(self.url("/curtailment"))
+            .json(request)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// End the curtailment early.
+    pub async fn end_curtailment(&self) -> Result<()> {
+        let response = self.http.delete(self.url("/curtailment")).send().await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Fetch the daemon's configuration (secrets redacted).
+    pub async fn config(&self) -> Result<Config> {
+        self.get("/config").await
+    }
+
+    /// Fetch the recorded series for `metric` over `range` (e.g. `24h`).
+    pub async fn history(&self, metric: HistoryMetric, range: &str) -> Result<HistoryResponse> {
+        let response = self
+            .http
+            .get(self.url("/history"))
+            .query(&[("metric", metric)])
+            .query(&[("range", range)])
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Subscribe to the server-sent event stream.
+    ///
+    /// The first item is a [`MinerEvent::Status`] snapshot. The stream ends
+    /// when the server closes the connection.
+    pub async fn events(&self) -> Result<impl Stream<Item = Result<MinerEvent>>> {
+        let response = self.http.get(self.url("/events")).send().await?;
+        Ok(sse_stream(check(response).await?))
+    }
+
+    /// Fetch buffered log events matching `query`, oldest first.
+    ///
+    /// `query.follow` is ignored; use [`follow_logs`](Self::follow_logs) to
+    /// keep streaming.
+    pub async fn logs(&self, query: &LogsQuery) -> Result<Vec<LogEntry>> {
+        let query = LogsQuery {
+            follow: false,
+            ..query.clone()
+        };
+        let response = self
+            .http
+            .get(self.url("/logs"))
+            .query(&query)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Stream log events matching `query`: the buffered backlog, then each
+    /// new event as it is logged. The stream ends when the server closes the
+    /// connection.
+    pub async fn follow_logs(
+        &self,
+        query: &LogsQuery,
+    ) -> Result<impl Stream<Item = Result<LogEntry>>> {
+        let query = LogsQuery {
+            follow: true,
+            ..query.clone()
+        };
+        let response = self
+            .http
+            .get(self.url("/logs"))
+            .query(&query)
+            .send()
+            .await?;
+        Ok(sse_stream(check(response).await?))
+    }
+
+    /// Fetch the log filter in effect, including runtime overrides.
+    pub async fn log_levels(&self) -> Result<LogLevels> {
+        self.get("/logs/levels").await
+    }
+
+    /// Log `target` and the modules below it at `level` until reset.
+    pub async fn set_log_level(&self, target: &str, level: &str) -> Result<()> {
+        let request = SetLogLevelRequest {
+            level: level.to_string(),
+        };
+        let response = self
+            .http
+            .put(self.url(&format!("/logs/levels/{}", segment(target))))
+            .json(&request)
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Remove the runtime level override for `target`.
+    pub async fn reset_log_level(&self, target: &str) -> Result<()> {
+        let response = self
+            .http
+            .delete(self.url(&format!("/logs/levels/{}", segment(target))))
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let response = self.http.get(self.url(path)).send().await?;
+        decode(response).await
+    }
+
+    async fn post_empty(&self, path: &str) -> Result<()> {
+        let response = self.http.post(self.url(path)).send().await?;
+        check(response).await.map(|_| ())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url, path)
+    }
+}
+
+/// Percent-encode `value` for use as a single path segment, so serials and
+/// pool names containing `/`, `?` or spaces address the intended resource.
+fn segment(value: &str) -> String {
+    let mut url = reqwest::Url::parse("http://localhost/").expect("valid base URL");
+    url.path_segments_mut()
+        .expect("HTTP URLs have a path")
+        .push(value);
+    url.path()[1..].to_string()
+}
+
+/// Pass successful responses through; turn others into
+/// [`ApiClientError::Status`], using the server's error message if present.
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<ErrorResponse>(&body)
+        .map(|e| e.error)
+        .unwrap_or(body);
+    Err(ApiClientError::Status { status, message })
+}
+
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let body = check(response).await?.bytes().await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Decode a `text/event-stream` body into its JSON payloads.
+///
+/// The stream ends when the server closes the connection, or after the
+/// first transport error.
+fn sse_stream<T: DeserializeOwned>(response: Response) -> impl Stream<Item = Result<T>> {
+    // State is None once the connection has failed, ending the stream
+    let state = Some((response, SseDecoder::default(), VecDeque::<String>::new()));
+    futures::stream::unfold(state, |state| async move {
+        let (mut response, mut decoder, mut pending) = state?;
+        loop {
+            if let Some(data) = pending.pop_front() {
+                let item = serde_json::from_str::<T>(&data).map_err(Into::into);
+                return Some((item, Some((response, decoder, pending))));
+            }
+
+            match response.chunk().await {
+                Ok(Some(chunk)) => pending.extend(decoder.push(&chunk)),
+                Ok(None) => return None,
+                Err(e) => return Some((Err(e.into()), None)),
+            }
+        }
+    })
+}
+
+/// Incremental decoder for `text/event-stream` bodies.
+///
+/// Collects `data:` lines and yields each event's payload once the blank
+/// line terminating it arrives. Other fields (`event:`, `id:`, comments
+/// used as keep-alives) are ignored.
+#[derive(Debug, Default)]
+struct SseDecoder {
+    /// Bytes of the current incomplete line (may split a UTF-8 character)
+    buffer: Vec<u8>,
+    /// `data:` lines of the current event
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feed bytes from the body, returning the payloads of completed events.
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut complete = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    complete.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+
+        complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use tokio::{net::TcpListener, sync::mpsc};
+    use tokio_util::sync::CancellationToken;
+
+    use super::*;
+    use crate::api::{build_router, ApiState};
+    use crate::scheduler::{self, SourceRegistration};
+    use crate::systemd::Heartbeat;
+    use crate::telemetry::Telemetry;
+
+    /// Serve the real router on an ephemeral port and return a client for it.
+    async fn serve_state(state: ApiState) -> ApiClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = build_router(state);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        ApiClient::new(format!("http://{addr}"))
+    }
+
+    async fn serve(telemetry: Telemetry) -> ApiClient {
+        serve_state(ApiState::for_test(telemetry)).await
+    }
+
+    #[test]
+    fn test_sse_decoder_splits_events() {
+        let mut decoder = SseDecoder::default();
+
+        assert!(decoder.push(b"data: {\"a\"").is_empty());
+        assert_eq!(decoder.push(b":1}\n\n:keep-alive\n\n"), vec!["{\"a\":1}"]);
+        assert_eq!(
+            decoder.push(b"data:x\r\ndata: y\r\n\r\ndata: z\n\n"),
+            vec!["x\ny", "z"]
+        );
+    }
+
+    #[test]
+    fn test_path_segments_are_encoded() {
+        assert_eq!(segment("pool-1"), "pool-1");
+        assert_eq!(segment("a/b c?d#e%"), "a%2Fb%20c%3Fd%23e%25");
+    }
+
+    #[tokio::test]
+    async fn test_client_round_trip() {
+        let telemetry = Telemetry::new();
+        telemetry.update_status(|status| {
+            status.shares_submitted = 7;
+            status.pools.push(PoolStatus {
+                name: "pool".into(),
+                connected: true,
+                ..Default::default()
+            });
+        });
+        let client = serve(telemetry).await;
+
+        client.health().await.unwrap();
+        assert_eq!(client.echo("hello").await.unwrap(), "hello");
+        assert_eq!(client.status().await.unwrap().shares_submitted, 7);
+        assert_eq!(client.pools().await.unwrap()[0].name, "pool");
+        assert!(client.boards().await.unwrap().is_empty());
+        assert!(client.threads().await.unwrap().is_empty());
+        assert!(client.openapi().await.unwrap()["paths"]["/status"].is_object());
+
+        let err = client.board("missing").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let telemetry = Telemetry::new();
+        let client = serve(telemetry.clone()).await;
+
+        let mut events = Box::pin(client.events().await.unwrap());
+
+        // Stream opens with the current status
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            MinerEvent::Status(MinerStatus::default())
+        );
+
+        telemetry.emit(MinerEvent::ShareAccepted {
+            pool: "pool".into(),
+        });
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            MinerEvent::ShareAccepted {
+                pool: "pool".into()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_logs() {
+        let state = ApiState::for_test(Telemetry::new());
+        let logs = state.logs.clone();
+        let entry = |level: &str, target: &str| LogEntry {
+            seq: 0,
+            time: 1.0,
+            level: level.into(),
+            target: target.into(),
+            message: "test".into(),
+            fields: Default::default(),
+        };
+        logs.push(entry("info", "mujina_miner::daemon"));
+        logs.push(entry("debug", "mujina_miner::board::bitaxe"));
+        let client = serve_state(state).await;
+
+        let query = LogsQuery {
+            target: Some("mujina_miner::board".into()),
+            ..Default::default()
+        };
+        let entries = client.logs(&query).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].seq, 1);
+
+        let query = LogsQuery {
+            level: Some("info".into()),
+            ..Default::default()
+        };
+        assert_eq!(client.logs(&query).await.unwrap().len(), 1);
+
+        // Following replays the backlog, then streams new events
+        let mut live = Box::pin(client.follow_logs(&query).await.unwrap());
+        assert_eq!(live.next().await.unwrap().unwrap().seq, 0);
+        logs.push(entry("warn", "mujina_miner::board::bitaxe"));
+        assert_eq!(live.next().await.unwrap().unwrap().seq, 2);
+
+        // No filter is attached outside the daemon
+        let err = client.log_levels().await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        let err = client
+            .set_log_level("mujina_miner::asic", "loud")
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+        let err = client
+            .reset_log_level("mujina_miner::asic")
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[tokio::test]
+    async fn test_pool_and_pause_commands() {
+        let telemetry = Telemetry::new();
+        let running = CancellationToken::new();
+
+        // Real scheduler with two sources that never send jobs
+        let (_thread_tx, thread_rx) = mpsc::channel(1);
+        let (source_reg_tx, source_reg_rx) = mpsc::channel(2);
+        let (scheduler_tx, scheduler_rx) = mpsc::channel(1);
+        tokio::spawn(scheduler::task(
+            running.clone(),
+            thread_rx,
+            source_reg_rx,
+            scheduler_rx,
+            telemetry.clone(),
+            Heartbeat::new(),
+        ));
+
+        let mut source_channels = Vec::new();
+        for name in ["a", "b"] {
+            let (event_tx, event_rx) = mpsc::channel(1);
+            let (command_tx, command_rx) = mpsc::channel(1);
+            source_reg_tx
+                .send(SourceRegistration {
+                    name: name.into(),
+                    url: None,
+                    event_rx,
+                    command_tx,
+                    shutdown: CancellationToken::new(),
+                })
+                .await
+                .unwrap();
+            source_channels.push((event_tx, command_rx));
+        }
+
+        let mut state = ApiState::for_test(telemetry);
+        state.scheduler = scheduler_tx;
+        let client = serve_state(state).await;
+
+        // Wait for both registrations to be published
+        let pools = loop {
+            let pools = client.pools().await.unwrap();
+            if pools.len() == 2 {
+                break pools;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert!(pools.iter().any(|p| p.name == "a" && p.active));
+
+        client.switch_pool("b").await.unwrap();
+        let pools = client.pools().await.unwrap();
+        assert!(pools.iter().any(|p| p.name == "b" && p.active));
+
+        client.remove_pool("b").await.unwrap();
+        let pools = client.pools().await.unwrap();
+        assert_eq!(pools.len(), 1);
+        assert!(pools[0].active, "remaining pool takes over");
+
+        let err = client.remove_pool("b").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+
+        client.pause().await.unwrap();
+        assert!(client.status().await.unwrap().paused);
+        client.resume().await.unwrap();
+        assert!(!client.status().await.unwrap().paused);
+
+        let err = client.board_idle("missing").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+
+        // Backplane is not running in this test
+        let err = client.board_restart("missing").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        let err = client
+            .set_fan_mode("missing", FanMode::Manual { percent: 150 })
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+
+        let err = client
+            .set_profile("missing", Some(PerformanceProfile::Eco))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        running.cancel();
+    }
+}
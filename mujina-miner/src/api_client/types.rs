@@ -1,6 +1,614 @@
 //! API data transfer objects and models.
 //!
 //! This module defines the types used for API requests and responses,
-//! shared between the server and client implementations.
+//! shared between the server and client implementations. The server
+//! serializes exactly these types, and the OpenAPI document is generated
+//! from them, so a field added here shows up everywhere at once.
 
-// TODO: Define API request/response types
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::config::ScheduleRule;
+
+/// Echo request payload.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct EchoRequest {
+    /// The message to echo back.
+    pub message: String,
+}
+
+/// Echo response payload.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct EchoResponse {
+    /// The echoed message.
+    pub message: String,
+}
+
+/// Error body returned with non-2xx responses.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// Human-readable description of what went wrong.
+    pub error: String,
+}
+
+/// Snapshot of the whole miner.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct MinerStatus {
+    /// Seconds since the scheduler started.
+    pub uptime_s: u64,
+
+    /// Lifetime average hashrate across all threads, in H/s.
+    pub hashrate: f64,
+
+    /// Total hashrate over recent windows (1m, 5m, 15m, 1h).
+    #[serde(default)]
+    pub hashrate_windows: Vec<HashrateEstimate>,
+
+    /// Shares submitted to pools since startup.
+    pub shares_submitted: u64,
+
+    /// Whether mining is paused (threads idle, jobs not assigned).
+    pub paused: bool,
+
+    /// Connected hash boards.
+    pub boards: Vec<BoardStatus>,
+
+    /// Hash threads registered with the scheduler.
+    pub threads: Vec<ThreadStatus>,
+
+    /// Job sources registered with the scheduler.
+    pub pools: Vec<PoolStatus>,
+
+    /// Demand-response curtailment in effect, if any.
+    #[serde(default)]
+    pub curtailment: Option<CurtailmentStatus>,
+}
+
+/// Status and latest telemetry of one hash board.
+///
+/// Readings are `None` when the board has no such sensor or the last read
+/// failed.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct BoardStatus {
+    /// Board serial number (the board's identity in the API).
+    pub serial: String,
+
+    /// Board model, e.g. "Bitaxe Gamma".
+    pub model: String,
+
+    /// Number of ASIC chips discovered on the board.
+    pub chip_count: usize,
+
+    /// ASIC temperature in degrees Celsius.
+    pub asic_temp_c: Option<f32>,
+
+    /// Voltage regulator temperature in degrees Celsius.
+    pub vr_temp_c: Option<f32>,
+
+    /// Fan duty cycle in percent.
+    pub fan_percent: Option<u8>,
+
+    /// Fan speed in RPM.
+    pub fan_rpm: Option<u32>,
+
+    /// How the fan duty cycle is chosen, `null` without fan control.
+    #[serde(default)]
+    pub fan_mode: Option<FanMode>,
+
+    /// Selected performance profile, `null` for the tuned or default
+    /// operating point.
+    #[serde(default)]
+    pub profile: Option<PerformanceProfile>,
+
+    /// Board input power in watts.
+    pub power_w: Option<f32>,
+
+    /// Core output current in amperes.
+    pub current_a: Option<f32>,
+
+    /// Board input voltage in volts.
+    pub input_voltage_v: Option<f32>,
+
+    /// ASIC core voltage in volts.
+    pub core_voltage_v: Option<f32>,
+
+    /// Board hashrate over recent windows (1m, 5m, 15m, 1h).
+    #[serde(default)]
+    pub hashrate_windows: Vec<HashrateEstimate>,
+}
+
+/// How a board's fan duty cycle is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FanMode {
+    /// Closed-loop control toward the configured target temperature.
+    Auto,
+
+    /// Fixed duty cycle in percent, set by the operator.
+    Manual { percent: u8 },
+}
+
+/// Named performance profile.
+///
+/// Each profile is a frequency, core voltage and fan target temperature,
+/// configured per chip type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceProfile {
+    /// Lower frequency and voltage for the best efficiency.
+    Eco,
+
+    /// The chip's stock operating point.
+    Balanced,
+
+    /// Higher frequency and voltage for the most hashrate.
+    Turbo,
+}
+
+impl PerformanceProfile {
+    /// Profile name as used in the API and configuration.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Eco => "eco",
+            Self::Balanced => "balanced",
+            Self::Turbo => "turbo",
+        }
+    }
+}
+
+/// Request to select a board's performance profile.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct SetProfileRequest {
+    /// Profile to run, `null` to return to the tuned or default operating
+    /// point.
+    pub profile: Option<PerformanceProfile>,
+}
+
+/// Operating mode the schedule puts every board in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+pub struct OperatingMode {
+    /// Performance profile, `null` for the tuned or default operating point.
+    pub profile: Option<PerformanceProfile>,
+
+    /// Input power limit in watts, `null` for the configured
+    /// `hardware.power_limit`.
+    pub power_limit: Option<f32>,
+
+    /// Stop hashing.
+    pub idle: bool,
+}
+
+/// The operating mode schedule and what it currently selects.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ScheduleStatus {
+    /// Time zone the rules are evaluated in.
+    pub timezone: String,
+
+    /// Configured rules, in order.
+    pub rules: Vec<ScheduleRule>,
+
+    /// Rule that started most recently, `null` if none has.
+    pub active_rule: Option<String>,
+
+    /// Rule that starts next, `null` if none will.
+    pub next_rule: Option<String>,
+
+    /// When the next rule starts, Unix time in seconds.
+    pub next_change: Option<u64>,
+
+    /// Override in place of the scheduled mode, if any.
+    pub active_override: Option<ScheduleOverride>,
+
+    /// Mode in effect, `null` if the schedule leaves boards at their
+    /// configured operating point.
+    pub mode: Option<OperatingMode>,
+}
+
+/// Mode set through the API in place of the scheduled one.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ScheduleOverride {
+    /// Mode in effect while the override lasts.
+    pub mode: OperatingMode,
+
+    /// When the override ends, Unix time in seconds; `null` for when it is
+    /// cleared.
+    pub until: Option<u64>,
+}
+
+/// Request to override the scheduled operating mode.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct SetScheduleOverrideRequest {
+    /// Mode to run.
+    pub mode: OperatingMode,
+
+    /// How long to keep it, in seconds; `null` for until the next rule
+    /// starts.
+    pub duration_s: Option<u64>,
+}
+
+/// Request to curtail the miner's power draw for a demand-response event.
+///
+/// Give exactly one of `duration_s` and `until`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+pub struct CurtailRequest {
+    /// Total input power to stay under, in watts; `null` to stop hashing.
+    pub target_w: Option<f32>,
+
+    /// How long to curtail, in seconds.
+    pub duration_s: Option<u64>,
+
+    /// When to resume, Unix time in seconds.
+    pub until: Option<u64>,
+}
+
+/// Demand-response curtailment in effect and how well it is met.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct CurtailmentStatus {
+    /// Total input power to stay under, in watts; `null` if hashing is
+    /// stopped.
+    pub target_w: Option<f32>,
+
+    /// When normal operation resumes, Unix time in seconds.
+    pub until: u64,
+
+    /// Total input power of all boards, in watts; `null` without readings.
+    pub power_w: Option<f32>,
+
+    /// Whether power is under the target, or hashing has stopped.
+    pub met: bool,
+}
+
+/// Status of one hash thread.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ThreadStatus {
+    /// Thread name, `<board serial>/<index>`.
+    pub name: String,
+
+    /// Serial number of the board this thread runs on.
+    pub board: String,
+
+    /// Lifetime average hashrate in H/s, measured from shares.
+    pub hashrate: f64,
+
+    /// Thread hashrate over recent windows (1m, 5m, 15m, 1h).
+    #[serde(default)]
+    pub hashrate_windows: Vec<HashrateEstimate>,
+
+    /// Shares reported by the thread (at the thread's share target).
+    pub shares: u64,
+
+    /// Hardware errors reported by the thread.
+    pub hardware_errors: u64,
+
+    /// Recent fraction of chip nonces that were hardware errors.
+    #[serde(default)]
+    pub hardware_error_rate: f64,
+
+    /// Whether the thread is currently hashing.
+    pub active: bool,
+
+    /// Nonce counts and health of each chip the thread drives.
+    #[serde(default)]
+    pub chips: Vec<ChipHealth>,
+}
+
+/// Nonce counts and health of one chip.
+///
+/// Measured from every nonce the chip reports at its own ticket mask
+/// difficulty, which is far more frequent than shares.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ChipHealth {
+    /// Chip address on the serial bus.
+    pub address: u8,
+
+    /// Nonces reported since the thread started.
+    pub nonces: u64,
+
+    /// Hashrate measured from recent nonces, in H/s.
+    pub hashrate: f64,
+
+    /// Hashrate expected from frequency and core count, in H/s, if known.
+    pub expected_hashrate: Option<f64>,
+
+    /// Measured over expected hashrate, `null` until enough nonces were
+    /// expected to judge.
+    pub health: Option<f64>,
+
+    /// Nonces whose hash didn't meet the chip's ticket mask, i.e. were
+    /// miscomputed, since the thread started.
+    pub hardware_errors: u64,
+
+    /// Recent fraction of the chip's nonces that were hardware errors.
+    pub hardware_error_rate: f64,
+
+    /// Nonces reported by each core domain, for chips that report it.
+    pub domain_nonces: Vec<u64>,
+
+    /// Nonces reported by each engine within a domain, summed over domains.
+    pub engine_nonces: Vec<u64>,
+
+    /// Core domains reporting far fewer nonces than the chip's average.
+    pub weak_domains: Vec<u8>,
+}
+
+/// Hashrate measured over one window, in H/s.
+///
+/// Shares arrive at random, so a window holding few of them says little;
+/// `lower` and `upper` bound the true rate with 95% confidence.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct HashrateEstimate {
+    /// Window length in seconds.
+    pub window_s: u64,
+
+    /// Mean over the window (shorter while measurement is younger).
+    pub hashrate: f64,
+
+    /// Exponentially weighted average with the window as time constant.
+    pub ewma: f64,
+
+    /// Lower bound of the 95% confidence interval, `null` before any share.
+    pub lower: Option<f64>,
+
+    /// Upper bound of the 95% confidence interval, `null` before any share.
+    pub upper: Option<f64>,
+
+    /// Shares found in the window.
+    pub shares: u64,
+}
+
+/// Status of one job source (pool or local source).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct PoolStatus {
+    /// Source name.
+    pub name: String,
+
+    /// Pool URL, if the source is a remote pool.
+    pub url: Option<String>,
+
+    /// Whether the source is currently providing work.
+    pub connected: bool,
+
+    /// Whether this is the source the miner is working for.
+    pub active: bool,
+
+    /// Share difficulty of the current job, if any.
+    pub difficulty: Option<f64>,
+
+    /// Shares submitted to this source.
+    pub shares_submitted: u64,
+
+    /// Shares the source accepted.
+    pub shares_accepted: u64,
+
+    /// Shares the source rejected.
+    pub shares_rejected: u64,
+}
+
+/// Request to add a Stratum v1 pool at runtime.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct AddPoolRequest {
+    /// Name to refer to the pool by; generated if omitted.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Pool URL (stratum+tcp://host:port).
+    pub url: String,
+
+    /// Worker username.
+    pub worker: String,
+
+    /// Worker password, "x" if omitted.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// Events published on the `/api/v1/events` stream.
+///
+/// Serialized with a `type` tag, e.g. `{"type":"share_accepted","pool":"..."}`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MinerEvent {
+    /// Full status snapshot, sent on connect and whenever status changes.
+    Status(MinerStatus),
+
+    /// A hash board was connected and its threads started.
+    BoardConnected { serial: String, model: String },
+
+    /// A hash board was removed.
+    BoardDisconnected { serial: String },
+
+    /// A hash board reported a fault.
+    BoardFault {
+        serial: String,
+        component: String,
+        fault: String,
+        recoverable: bool,
+    },
+
+    /// A share meeting the pool's target was submitted.
+    ShareSubmitted {
+        pool: String,
+        thread: String,
+        job_id: String,
+        difficulty: f64,
+    },
+
+    /// The pool accepted a share.
+    ShareAccepted { pool: String },
+
+    /// The pool rejected a share.
+    ShareRejected { pool: String, reason: String },
+
+    /// A chip, or one of its core domains, is hashing well below what its
+    /// frequency and core count should deliver.
+    ChipUnderperforming {
+        thread: String,
+        chip: u8,
+        domain: Option<u8>,
+        health: f64,
+    },
+
+    /// A chip or core domain reported earlier as underperforming has
+    /// recovered.
+    ChipRecovered {
+        thread: String,
+        chip: u8,
+        domain: Option<u8>,
+    },
+    /// A share met the network target: a block was found.
+    BlockFound {
+        pool: String,
+        thread: String,
+        hash: String,
+    },
+
+    /// An alert rule fired or resolved.
+    Alert(Alert),
+}
+
+/// A notification raised by an alert rule.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct Alert {
+    /// Kind of rule that raised the alert, e.g. `temperature_above`.
+    pub rule: String,
+
+    /// Whether the condition started, ended, or was a one-off event.
+    pub state: AlertState,
+
+    /// What the alert is about: a board serial, pool name, thread, or
+    /// `miner`.
+    pub subject: String,
+
+    /// Human-readable description.
+    pub summary: String,
+
+    /// The measurement that triggered the alert, if any.
+    pub value: Option<f64>,
+
+    /// When the alert was raised (Unix time, seconds).
+    pub time: u64,
+}
+
+/// Lifecycle of an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    /// The condition has held long enough to alert on.
+    Firing,
+
+    /// A firing condition no longer holds.
+    Resolved,
+
+    /// Something happened that has no ongoing condition (e.g. a block
+    /// was found).
+    Event,
+}
+
+/// Quantities recorded in the statistics history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryMetric {
+    /// Mean total hashrate in hashes per second.
+    Hashrate,
+
+    /// Shares submitted during the interval.
+    Shares,
+
+    /// Mean of the hottest board's ASIC temperature in degrees Celsius.
+    Temperature,
+
+    /// Mean total board power in watts.
+    Power,
+}
+
+/// One interval of a statistics history series.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct HistoryPoint {
+    /// Start of the interval (Unix time, seconds).
+    pub time: u64,
+
+    /// Value over the interval, `null` if nothing was measured.
+    pub value: Option<f64>,
+}
+
+/// A statistics history series.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct HistoryResponse {
+    /// The metric the points are for.
+    pub metric: HistoryMetric,
+
+    /// Length of each interval in seconds.
+    pub resolution_s: u64,
+
+    /// Intervals with data, oldest first. The last may still be in
+    /// progress.
+    pub points: Vec<HistoryPoint>,
+}
+
+/// Query parameters of the log endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogsQuery {
+    /// Least severe level to include: `error`, `warn`, `info`, `debug` or
+    /// `trace`
+    pub level: Option<String>,
+
+    /// Module path prefix, e.g. `mujina_miner::asic`
+    pub target: Option<String>,
+
+    /// Only events logged after this Unix time, in seconds
+    pub since: Option<f64>,
+
+    /// Return at most this many of the newest matching events; defaults to
+    /// 1000
+    pub limit: Option<usize>,
+
+    /// Keep the connection open and stream new events as they are logged
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// One log event held in the daemon's in-memory log buffer.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct LogEntry {
+    /// Position in the log, increasing by one per event since startup.
+    pub seq: u64,
+
+    /// When the event was logged (Unix time, seconds).
+    pub time: f64,
+
+    /// Level: `error`, `warn`, `info`, `debug` or `trace`.
+    pub level: String,
+
+    /// Module path the event was logged from.
+    pub target: String,
+
+    /// The event's message.
+    pub message: String,
+
+    /// The event's structured fields.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Log filter in effect.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct LogLevels {
+    /// Filter directives from `RUST_LOG` or the configuration, e.g.
+    /// `info,mujina_miner::asic=debug`.
+    pub directives: String,
+
+    /// Levels set at runtime by module path, applied over `directives`.
+    pub overrides: BTreeMap<String, String>,
+}
+
+/// Request to change a module's log level at runtime.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct SetLogLevelRequest {
+    /// `off`, `error`, `warn`, `info`, `debug` or `trace`.
+    pub level: String,
+}
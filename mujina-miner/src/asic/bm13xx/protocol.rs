@@ -151,8 +151,8 @@ impl From<PllConfig> for [u8; 4] {
 /// Known chip types in the BM13xx family
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChipType {
-    /// BM1362 - Used in Antminer S19 J Pro (126 chips)
-    /// Core count unknown
+    /// BM1362 - Used in Antminer S19 J Pro (126 chips) and EmberOne
+    /// 2,048 hash engines, inferred as 128 domains of 16 engines each
     BM1362,
     /// BM1366 - Newer generation chip
     BM1366,
@@ -166,6 +166,17 @@ pub enum ChipType {
 }
 
 impl ChipType {
+    /// Lowercase model name, e.g. `bm1370`, as used in configuration
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::BM1362 => Some("bm1362"),
+            Self::BM1366 => Some("bm1366"),
+            Self::BM1370 => Some("bm1370"),
+            Self::BM1397 => Some("bm1397"),
+            Self::Unknown(_) => None,
+        }
+    }
+
     /// Get the raw chip ID bytes
     pub fn id_bytes(&self) -> [u8; 2] {
         match self {
@@ -178,12 +189,48 @@ impl ChipType {
     }
 
     /// Get expected hash engine count for this chip type, if known
+    ///
+    /// The BM1362's is inferred rather than documented: its nonces carry the
+    /// BM1370's 7-bit domain field, and 128 domains of 16 engines at ~400 MHz
+    /// match an S19 J Pro's rated 104 TH/s over 126 chips.
     pub fn core_count(&self) -> Option<u32> {
         match self {
+            Self::BM1362 => Some(2048), // 128 domains x 16 engines
             Self::BM1370 => Some(1280), // 80 domains x 16 engines
             _ => None,
         }
     }
+
+    /// Get the number of core domains for this chip type, if known
+    pub fn domain_count(&self) -> Option<u32> {
+        match self {
+            Self::BM1362 => Some(128),
+            Self::BM1370 => Some(80),
+            _ => None,
+        }
+    }
+
+    /// Core domain ("main core") that found a nonce, for chips that report
+    /// it.
+    ///
+    /// The BM1362 and BM1370 put the domain in bits 31:25 of the nonce; the
+    /// engine within the domain is the response's `subcore_id`.
+    pub fn nonce_domain(&self, nonce: u32) -> Option<u8> {
+        match self {
+            Self::BM1362 | Self::BM1370 => Some(((nonce >> 25) & 0x7f) as u8),
+            _ => None,
+        }
+    }
+}
+
+/// Address of the chip in a chain that found `nonce`.
+///
+/// Chips stamp their address into the nonce bits just below the core domain
+/// (bits 24:17). Only the bits above the chain's address interval identify
+/// the chip, so callers match it to the chip with the highest address not
+/// above it.
+pub fn nonce_chip_address(nonce: u32) -> u8 {
+    ((nonce >> 17) & 0xff) as u8
 }
 
 impl From<[u8; 2]> for ChipType {
@@ -362,6 +409,11 @@ impl ReportingInterval {
     pub const fn exponent(&self) -> u8 {
         self.exponent
     }
+
+    /// Expected number of hashes per reported nonce
+    pub fn hashes_per_nonce(&self) -> f64 {
+        2f64.powi(self.exponent as i32)
+    }
 }
 
 impl std::fmt::Display for ReportingInterval {
@@ -398,6 +450,25 @@ impl TicketMask {
         }
     }
 
+    /// Check whether a hash meets this mask
+    ///
+    /// The chip only reports nonces whose hash has at least `32 + zero_bits`
+    /// leading zero bits (most significant first, as the hash is displayed).
+    /// A reported nonce whose recomputed hash falls short was miscomputed by
+    /// the chip: a hardware error.
+    pub fn is_met_by(&self, hash: &bitcoin::BlockHash) -> bool {
+        let required = 32 + self.zero_bits as u32;
+        let mut zeros = 0;
+        // Byte array is little-endian; the most significant byte is last
+        for byte in hash.to_byte_array().iter().rev() {
+            zeros += byte.leading_zeros();
+            if *byte != 0 || zeros >= required {
+                break;
+            }
+        }
+        zeros >= required
+    }
+
     /// Encode ticket mask to wire format bytes
     pub fn to_wire_bytes(&self) -> [u8; 4] {
         if self.zero_bits == 0 {
@@ -1319,49 +1390,28 @@ mod init_tests {
     use super::*;
 
     #[test]
-    fn multi_chip_init_sequence() {
-        let protocol = BM13xxProtocol::new();
-        let commands = protocol.multi_chip_init(65); // S21 Pro has 65 chips
-
-        // Verify the sequence starts with version rolling enable
-        assert!(matches!(
-            &commands[0],
-            Command::WriteRegister {
-                broadcast: true,
-                chip_address: 0x00,
-                register: Register::VersionMask(_),
-            }
-        ));
-
-        // Verify chain inactive command
-        let chain_inactive_pos = commands
-            .iter()
-            .position(|c| matches!(c, Command::ChainInactive))
-            .expect("ChainInactive command not found in initialization sequence");
-        assert!(chain_inactive_pos > 0);
-
-        // Verify chip addressing starts after chain inactive
-        let first_address_pos = chain_inactive_pos + 1;
-        assert!(matches!(
-            &commands[first_address_pos],
-            Command::SetChipAddress { chip_address: 0x00 }
-        ));
-
-        // Verify we have 65 address assignments
-        let address_commands: Vec<_> = commands[first_address_pos..first_address_pos + 65]
-            .iter()
-            .collect();
-        assert_eq!(address_commands.len(), 65);
-
-        // Verify addresses increment by 2
-        for (i, cmd) in address_commands.iter().enumerate() {
-            match cmd {
-                Command::SetChipAddress { chip_address } => {
-                    assert_eq!(*chip_address, (i * 2) as u8);
-                }
-                _ => panic!("Expected SetChipAddress command, got {:?}", cmd),
-            }
+    fn chip_addresses_spread_over_address_space() {
+        assert_eq!(BM13xxProtocol::chip_addresses(1), vec![0x00]);
+
+        // S21 Pro captures address 65 chips 2 apart, with domains of five
+        // chips starting at 0x00, 0x0A and 0x14
+        let s21 = BM13xxProtocol::chip_addresses(65);
+        assert_eq!(BM13xxProtocol::address_interval(65), 2);
+        assert_eq!(s21[1], 0x02);
+        assert_eq!([s21[0], s21[5], s21[10]], [0x00, 0x0A, 0x14]);
+        assert_eq!(s21[64], 0x80);
+
+        // Power-of-two chains match esp-miner's 256 / chain_length
+        for chain_length in [2, 4, 8, 64] {
+            assert_eq!(
+                BM13xxProtocol::address_interval(chain_length),
+                256 / chain_length
+            );
         }
+        assert_eq!(BM13xxProtocol::chip_addresses(4), [0x00, 0x40, 0x80, 0xC0]);
+
+        assert_eq!(BM13xxProtocol::address_interval(12), 16);
+        assert_eq!(BM13xxProtocol::chip_addresses(12)[11], 0xB0);
     }
 
     #[test]
@@ -1399,6 +1449,37 @@ mod init_tests {
         }
     }
 
+    #[test]
+    fn domain_configuration_follows_chain_addresses() {
+        // 12 chips spaced 16 apart, four domains of three
+        let protocol = BM13xxProtocol::new();
+        let commands = protocol.configure_domains(12, 3);
+
+        let targets = |register: RegisterAddress| -> Vec<u8> {
+            commands
+                .iter()
+                .filter_map(|c| match c {
+                    Command::WriteRegister {
+                        broadcast: false,
+                        chip_address,
+                        register: written,
+                    } if written.address() as u8 == register as u8 => Some(*chip_address),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(
+            targets(RegisterAddress::IoDriverStrength),
+            [0x20, 0x50, 0x80, 0xB0]
+        );
+        assert_eq!(
+            targets(RegisterAddress::UartRelay),
+            [0x00, 0x20, 0x30, 0x50, 0x60, 0x80, 0x90, 0xB0]
+        );
+
+        assert!(protocol.configure_domains(0, 3).is_empty());
+    }
+
     #[test]
     fn pll_calculation_produces_valid_frequencies() {
         // Test cases from serial captures showing PLL values sent by esp-miner
@@ -1473,71 +1554,17 @@ mod init_tests {
 
     #[test]
     fn nonce_range_configuration() {
-        let protocol = BM13xxProtocol::new();
-
-        // Test single chip - full range
-        let commands = protocol.configure_nonce_ranges(1);
-        assert_eq!(commands.len(), 1);
-        if let Command::WriteRegister {
-            register: Register::NonceRange(config),
-            broadcast: true,
-            ..
-        } = &commands[0]
-        {
-            let config_bytes: [u8; 4] = (*config).into();
-            assert_eq!(config_bytes, [0xff, 0xff, 0xff, 0xff]);
-        }
+        // Single chip - full range
+        let config_bytes: [u8; 4] = NonceRangeConfig::multi_chip(1).into();
+        assert_eq!(config_bytes, [0xff, 0xff, 0xff, 0xff]);
 
-        // Test S21 Pro configuration (65 chips)
-        let commands = protocol.configure_nonce_ranges(65);
-        assert_eq!(commands.len(), 1);
-        if let Command::WriteRegister {
-            register: Register::NonceRange(config),
-            ..
-        } = &commands[0]
-        {
-            let config_bytes: [u8; 4] = (*config).into();
-            assert_eq!(config_bytes, [0x00, 0x00, 0x1e, 0xb5]);
-        }
+        // S21 Pro configuration (65 chips)
+        let config_bytes: [u8; 4] = NonceRangeConfig::multi_chip(65).into();
+        assert_eq!(config_bytes, [0x00, 0x00, 0x1e, 0xb5]);
 
-        // Test small chain
-        let commands = protocol.configure_nonce_ranges(8);
-        if let Command::WriteRegister {
-            register: Register::NonceRange(config),
-            ..
-        } = &commands[0]
-        {
-            let config_bytes: [u8; 4] = (*config).into();
-            assert_eq!(config_bytes, [0xff, 0xff, 0xff, 0x1f]);
-        }
-    }
-
-    #[test]
-    fn multi_chip_init_includes_nonce_range() {
-        let protocol = BM13xxProtocol::new();
-        let commands = protocol.multi_chip_init(65);
-
-        // Find the nonce range configuration
-        let nonce_range_cmd = commands.iter().find(|c| {
-            matches!(
-                c,
-                Command::WriteRegister {
-                    register: Register::NonceRange { .. },
-                    ..
-                }
-            )
-        });
-
-        assert!(nonce_range_cmd.is_some());
-
-        if let Some(Command::WriteRegister {
-            register: Register::NonceRange(config),
-            ..
-        }) = nonce_range_cmd
-        {
-            let config_bytes: [u8; 4] = (*config).into();
-            assert_eq!(config_bytes, [0x00, 0x00, 0x1e, 0xb5]); // S21 Pro value
-        }
+        // Small chain
+        let config_bytes: [u8; 4] = NonceRangeConfig::multi_chip(8).into();
+        assert_eq!(config_bytes, [0xff, 0xff, 0xff, 0x1f]);
     }
 }
 
@@ -1994,8 +2021,7 @@ mod response_tests {
         assert_eq!(version, GeneralPurposeBits::new([0x22, 0xF9]));
 
         // Verify main core extraction
-        let main_core = (nonce >> 25) & 0x7f;
-        assert_eq!(main_core, 32);
+        assert_eq!(ChipType::BM1370.nonce_domain(nonce), Some(32));
     }
 
     #[test]
@@ -2300,6 +2326,25 @@ mod response_tests {
         }
     }
 
+    #[test]
+    fn decode_bm1362_example_response() {
+        // Example BM1362 response quoted in PROTOCOL.md
+        let Some(Response::Nonce {
+            nonce,
+            job_id,
+            subcore_id,
+            ..
+        }) = decode_frame(&[
+            0xaa, 0x55, 0x6d, 0xb8, 0x8e, 0xe1, 0x01, 0x04, 0x03, 0x54, 0x94,
+        ])
+        else {
+            panic!("Expected nonce response");
+        };
+        assert_eq!(nonce, 0xE18E_B86D);
+        assert_eq!(job_id, 0);
+        assert_eq!(subcore_id, 4);
+    }
+
     #[test]
     fn decode_nonce_response_from_esp_miner_capture() {
         use crate::asic::bm13xx::test_data::esp_miner_job;
@@ -2487,6 +2532,15 @@ mod ticket_mask_tests {
         assert_eq!(interval.exponent(), 39);
     }
 
+    #[test]
+    fn test_reporting_interval_hashes_per_nonce() {
+        let interval = ReportingInterval::from_rate(
+            Hashrate::gibihashes_per_sec(512.0),
+            ReportingRate::nonces_per_sec(1.0),
+        );
+        assert_eq!(interval.hashes_per_nonce(), 549_755_813_888.0); // 2^39
+    }
+
     #[test]
     fn test_reporting_interval_display() {
         let interval = ReportingInterval::from_rate(
@@ -2496,6 +2550,30 @@ mod ticket_mask_tests {
         assert_eq!(format!("{}", interval), "2^39");
     }
 
+    #[test]
+    fn test_ticket_mask_is_met_by() {
+        use bitcoin::BlockHash;
+
+        // 2^40: 40 leading zero bits required
+        let mask = TicketMask::new(ReportingInterval::from_rate(
+            Hashrate::tebihashes_per_sec(1.0),
+            ReportingRate::nonces_per_sec(1.0),
+        ));
+
+        // Five zero bytes, then 0x80: exactly 40 leading zeros
+        let mut bytes = [0xffu8; 32];
+        bytes[27..].fill(0);
+        bytes[26] = 0x80;
+        assert!(mask.is_met_by(&BlockHash::from_byte_array(bytes)));
+
+        // Four zero bytes, then 0x01: 39 leading zeros
+        bytes[26..].fill(0);
+        bytes[27] = 0x01;
+        assert!(!mask.is_met_by(&BlockHash::from_byte_array(bytes)));
+
+        assert!(mask.is_met_by(&BlockHash::from_byte_array([0; 32])));
+    }
+
     #[test]
     fn test_ticket_mask_wire_encoding() {
         // Test case 1: 40 bits total (8 zero_bits)
@@ -2600,7 +2678,6 @@ impl BM13xxProtocol {
     }
 
     /// Helper to create a targeted write command
-    #[cfg_attr(not(test), allow(dead_code))]
     fn write_to(&self, chip_address: u8, register: Register) -> Command {
         Command::WriteRegister {
             broadcast: false,
@@ -2628,94 +2705,27 @@ impl BM13xxProtocol {
         commands
     }
 
-    /// Initialize a multi-chip chain (e.g., S21 Pro, S19 J Pro).
-    ///
-    /// This follows the initialization sequence from production miners:
-    /// 1. Enable version rolling on all chips
-    /// 2. Configure initial settings
-    /// 3. Set chain inactive and assign addresses
-    /// 4. Configure domain boundaries
-    /// 5. Ramp up frequency gradually
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn multi_chip_init(&self, chain_length: usize) -> Vec<Command> {
-        // Multi-chip initialization register values
-        const INIT_CONTROL_VALUE: u32 = 0x00000700;
-        const MISC_CONTROL_MULTI_CHIP: u32 = 0x0000c1f0;
-        const CORE_REG_INIT_1: u32 = 0x00008b80;
-        const CORE_REG_INIT_2: u32 = 0x0c800080;
-        const ADDRESS_INCREMENT: u8 = 2;
-
-        // Pre-allocate for efficiency (rough estimate of commands)
-        let mut commands = Vec::with_capacity(10 + chain_length);
-
-        // Step 1: Enable version rolling on all chips (broadcast)
-        commands.push(self.broadcast_write(Register::VersionMask(VersionMask::full_rolling())));
-
-        // Step 2: Configure init control register
-        commands.push(self.broadcast_write(Register::InitControl {
-            raw_value: INIT_CONTROL_VALUE,
-        }));
-
-        // Step 3: Configure misc control
-        commands.push(self.broadcast_write(Register::MiscControl {
-            raw_value: MISC_CONTROL_MULTI_CHIP,
-        }));
-
-        // Step 4: Set chain inactive for address assignment
-        commands.push(Command::ChainInactive);
-
-        // Step 5: Assign addresses (increment by 2)
-        for i in 0..chain_length {
-            let address = (i as u8) * ADDRESS_INCREMENT;
-            commands.push(Command::SetChipAddress {
-                chip_address: address,
-            });
-        }
-
-        // Step 6: Configure core registers on all chips
-        commands.push(self.broadcast_write(Register::Core {
-            raw_value: CORE_REG_INIT_1,
-        }));
-        commands.push(self.broadcast_write(Register::Core {
-            raw_value: CORE_REG_INIT_2,
-        }));
-
-        // Step 7: Set ticket mask (difficulty)
-        // Use 2^40 reporting interval (8 zero_bits)
-        let interval = ReportingInterval::from_rate(
-            Hashrate::tebihashes_per_sec(1.0),
-            ReportingRate::nonces_per_sec(1.0),
-        );
-        commands.push(self.broadcast_write(Register::TicketMask(TicketMask::new(interval))));
-
-        // Step 8: Configure IO driver strength on all chips
-        commands.push(self.broadcast_write(Register::IoDriverStrength(IoDriverStrength::normal())));
-
-        // Step 9: Configure nonce range partitioning
-        commands.extend(self.configure_nonce_ranges(chain_length));
-
-        commands
-    }
-
     /// Configure domain boundaries for a multi-chip chain.
     ///
     /// Domains are groups of chips that share signal integrity settings.
-    /// This configures IO driver strength and UART relay for domain boundaries.
-    #[cfg_attr(not(test), allow(dead_code))]
+    /// This configures IO driver strength and UART relay for domain boundaries,
+    /// addressing chips as [`chip_addresses`](Self::chip_addresses) does.
     pub fn configure_domains(&self, chain_length: usize, chips_per_domain: usize) -> Vec<Command> {
         const UART_RELAY_BASE: u32 = 0x03000000;
-        const ADDRESS_INCREMENT: u8 = 2;
 
         let mut commands = Vec::new();
+        if chain_length == 0 || chips_per_domain == 0 {
+            return commands;
+        }
+        let addresses = Self::chip_addresses(chain_length);
         let num_domains = chain_length.div_ceil(chips_per_domain);
 
         // Configure IO driver strength at domain boundaries
         for domain in 0..num_domains {
             let last_chip_in_domain = ((domain + 1) * chips_per_domain - 1).min(chain_length - 1);
-            let chip_address = (last_chip_in_domain as u8) * ADDRESS_INCREMENT;
 
             commands.push(self.write_to(
-                chip_address,
+                addresses[last_chip_in_domain],
                 Register::IoDriverStrength(IoDriverStrength::domain_boundary()),
             ));
         }
@@ -2726,10 +2736,9 @@ impl BM13xxProtocol {
             let last_chip = ((domain + 1) * chips_per_domain - 1).min(chain_length - 1);
 
             // Configure first chip in domain
-            let first_address = (first_chip as u8) * ADDRESS_INCREMENT;
             let relay_offset = (domain * chips_per_domain) as u32;
             commands.push(self.write_to(
-                first_address,
+                addresses[first_chip],
                 Register::UartRelay {
                     raw_value: UART_RELAY_BASE | (relay_offset << 8),
                 },
@@ -2737,9 +2746,8 @@ impl BM13xxProtocol {
 
             // Configure last chip in domain
             if first_chip != last_chip {
-                let last_address = (last_chip as u8) * ADDRESS_INCREMENT;
                 commands.push(self.write_to(
-                    last_address,
+                    addresses[last_chip],
                     Register::UartRelay {
                         raw_value: UART_RELAY_BASE | (relay_offset << 8),
                     },
@@ -2750,23 +2758,6 @@ impl BM13xxProtocol {
         commands
     }
 
-    /// Configure nonce range partitioning for multi-chip operation.
-    ///
-    /// This distributes the 32-bit nonce space across all chips in the chain
-    /// to avoid duplicate work. Each chip searches a unique portion of the nonce space.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn configure_nonce_ranges(&self, chain_length: usize) -> Vec<Command> {
-        let mut commands = Vec::new();
-
-        // Calculate nonce range based on chain length
-        let nonce_config = NonceRangeConfig::multi_chip(chain_length);
-
-        // Write nonce range to all chips
-        commands.push(self.broadcast_write(Register::NonceRange(nonce_config)));
-
-        commands
-    }
-
     /// Create a command to read a register.
     pub fn read_register(&self, chip_address: u8, register: RegisterAddress) -> Command {
         Command::ReadRegister {
@@ -2848,4 +2839,28 @@ impl BM13xxProtocol {
             register_address: RegisterAddress::ChipId,
         }
     }
+
+    /// Spacing between the addresses of a chain of `chain_length` chips.
+    ///
+    /// Chips are spread over the 8-bit address space at the largest
+    /// power-of-two interval that fits them all, e.g. 2 on a 65-chip S21 Pro
+    /// and 16 on a 12-chip EmberOne.
+    ///
+    /// esp-miner uses `256 / chain_length`, which agrees for every
+    /// power-of-two chain (64 apart for 4 chips, 4 apart for 64), but the
+    /// S21 Pro captures address 65 chips 2 apart rather than 3, so the
+    /// interval is rounded down to a power of two. Chains of other lengths,
+    /// such as the EmberOne's, have not been checked against a capture.
+    pub fn address_interval(chain_length: usize) -> usize {
+        let per_chip = 256 / chain_length.clamp(1, 256);
+        1 << per_chip.ilog2()
+    }
+
+    /// Addresses assigned to a chain of `chain_length` chips, in chain order.
+    pub fn chip_addresses(chain_length: usize) -> Vec<u8> {
+        let interval = Self::address_interval(chain_length);
+        (0..chain_length.min(256))
+            .map(|index| (index * interval) as u8)
+            .collect()
+    }
 }
@@ -6,14 +6,21 @@
 //! lifecycle (hotplug, emergency shutdown, etc.).
 
 use crate::{
-    board::{Board, BoardDescriptor},
-    error::Result,
-    hash_thread::HashThread,
+    api_client::types::{BoardStatus, FanMode, MinerEvent, PerformanceProfile},
+    board::{Board, BoardDescriptor, BoardEvent},
+    config::HardwareConfig,
+    error::{CommandError, Result},
+    scheduler::ThreadRegistration,
+    telemetry::Telemetry,
     tracing::prelude::*,
     transport::{usb::TransportEvent as UsbTransportEvent, TransportEvent, UsbDeviceInfo},
 };
-use std::collections::HashMap;
-use tokio::sync::mpsc;
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt, StreamMap};
+
+/// Interval at which board sensor readings are published to [`Telemetry`].
+const TELEMETRY_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Board registry that uses inventory to find registered boards.
 pub struct BoardRegistry;
@@ -33,6 +40,46 @@ impl BoardRegistry {
     }
 }
 
+/// Commands for controlling boards at runtime.
+///
+/// Sent by the API server and the operating mode schedule; each carries a
+/// oneshot channel for the result.
+pub enum BackplaneCommand {
+    /// Shut a board down and re-create it from its USB device.
+    RestartBoard {
+        serial: String,
+        response: oneshot::Sender<std::result::Result<(), CommandError>>,
+    },
+
+    /// Set a board's fan to a fixed duty cycle or closed-loop control.
+    SetFanMode {
+        serial: String,
+        mode: FanMode,
+        response: oneshot::Sender<std::result::Result<(), CommandError>>,
+    },
+
+    /// Set a board's target frequency in MHz.
+    SetFrequency {
+        serial: String,
+        frequency_mhz: f32,
+        response: oneshot::Sender<std::result::Result<(), CommandError>>,
+    },
+
+    /// Select a board's performance profile, or clear it with `None`.
+    SetProfile {
+        serial: String,
+        profile: Option<PerformanceProfile>,
+        response: oneshot::Sender<std::result::Result<(), CommandError>>,
+    },
+
+    /// Set a board's input power limit in watts, or lift it with `None`.
+    SetPowerLimit {
+        serial: String,
+        limit_w: Option<f32>,
+        response: oneshot::Sender<std::result::Result<(), CommandError>>,
+    },
+}
+
 /// Backplane that connects boards to the scheduler.
 ///
 /// Acts as the communication substrate between mining boards and the work
@@ -42,31 +89,69 @@ pub struct Backplane {
     registry: BoardRegistry,
     /// Active boards managed by the backplane
     boards: HashMap<String, Box<dyn Board + Send>>,
+    /// USB device each active board was created from, for restarts
+    devices: HashMap<String, UsbDeviceInfo>,
     event_rx: mpsc::Receiver<TransportEvent>,
+    /// Control commands from the API
+    command_rx: mpsc::Receiver<BackplaneCommand>,
+    /// Events from active boards, keyed by serial number
+    board_events: StreamMap<String, ReceiverStream<BoardEvent>>,
     /// Channel to send hash threads to the scheduler
-    scheduler_tx: mpsc::Sender<Vec<Box<dyn HashThread>>>,
+    scheduler_tx: mpsc::Sender<ThreadRegistration>,
+    /// Where board status and events are published
+    telemetry: Telemetry,
+    /// Limits passed to every board created
+    hardware: HardwareConfig,
 }
 
 impl Backplane {
     /// Create a new backplane.
     pub fn new(
         event_rx: mpsc::Receiver<TransportEvent>,
-        scheduler_tx: mpsc::Sender<Vec<Box<dyn HashThread>>>,
+        command_rx: mpsc::Receiver<BackplaneCommand>,
+        scheduler_tx: mpsc::Sender<ThreadRegistration>,
+        telemetry: Telemetry,
+        hardware: HardwareConfig,
     ) -> Self {
         Self {
             registry: BoardRegistry,
             boards: HashMap::new(),
+            devices: HashMap::new(),
             event_rx,
+            command_rx,
+            board_events: StreamMap::new(),
             scheduler_tx,
+            telemetry,
+            hardware,
         }
     }
 
     /// Run the backplane event loop.
     pub async fn run(&mut self) -> Result<()> {
-        while let Some(event) = self.event_rx.recv().await {
-            match event {
-                TransportEvent::Usb(usb_event) => {
-                    self.handle_usb_event(usb_event).await?;
+        let mut telemetry_interval = tokio::time::interval(TELEMETRY_INTERVAL);
+        telemetry_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                event = self.event_rx.recv() => {
+                    match event {
+                        Some(TransportEvent::Usb(usb_event)) => {
+                            self.handle_usb_event(usb_event).await?;
+                        }
+                        None => break,
+                    }
+                }
+
+                Some(command) = self.command_rx.recv() => {
+                    self.handle_command(command).await;
+                }
+
+                Some((serial, event)) = self.board_events.next() => {
+                    self.handle_board_event(serial, event);
+                }
+
+                _ = telemetry_interval.tick() => {
+                    self.publish_board_status();
                 }
             }
         }
@@ -74,6 +159,64 @@ impl Backplane {
         Ok(())
     }
 
+    /// Handle an event reported by a board.
+    fn handle_board_event(&mut self, serial: String, event: BoardEvent) {
+        match event {
+            BoardEvent::BoardFault {
+                component,
+                fault,
+                recoverable,
+            } => {
+                warn!(
+                    serial = %serial,
+                    component = %component,
+                    fault = %fault,
+                    recoverable,
+                    "Board fault reported."
+                );
+                self.telemetry.emit(MinerEvent::BoardFault {
+                    serial,
+                    component,
+                    fault,
+                    recoverable,
+                });
+            }
+            other => {
+                trace!(serial = %serial, event = ?other, "Board event");
+            }
+        }
+    }
+
+    /// Publish the status and latest sensor readings of all boards.
+    fn publish_board_status(&self) {
+        let mut boards: Vec<BoardStatus> = self
+            .boards
+            .iter()
+            .map(|(serial, board)| board_status(serial, board.as_ref()))
+            .collect();
+        boards.sort_by(|a, b| a.serial.cmp(&b.serial));
+
+        // Hashrate is measured by the scheduler; keep what it last published
+        self.telemetry.update_status(|status| {
+            for board in &mut boards {
+                if let Some(old) = status.boards.iter_mut().find(|b| b.serial == board.serial) {
+                    board.hashrate_windows = std::mem::take(&mut old.hashrate_windows);
+                }
+            }
+            status.boards = boards;
+        });
+    }
+
+    /// Remove a board from the published status and announce its removal.
+    fn board_removed(&mut self, serial: &str) {
+        self.devices.remove(serial);
+        self.board_events.remove(serial);
+        self.publish_board_status();
+        self.telemetry.emit(MinerEvent::BoardDisconnected {
+            serial: serial.to_string(),
+        });
+    }
+
     /// Shutdown all boards managed by this backplane.
     pub async fn shutdown_all_boards(&mut self) {
         let board_ids: Vec<String> = self.boards.keys().cloned().collect();
@@ -86,6 +229,7 @@ impl Backplane {
                 match board.shutdown().await {
                     Ok(()) => {
                         debug!(board = %model, serial = %board_id, "Board shutdown complete");
+                        self.board_removed(&board_id);
                     }
                     Err(e) => {
                         error!(
@@ -100,71 +244,198 @@ impl Backplane {
         }
     }
 
-    /// Handle USB transport events.
-    async fn handle_usb_event(&mut self, event: UsbTransportEvent) -> Result<()> {
-        match event {
-            UsbTransportEvent::UsbDeviceConnected(device_info) => {
-                // Check if this device matches any registered board pattern
-                let Some(descriptor) = self.registry.find_descriptor(&device_info) else {
-                    // No match - this is expected for most USB devices
-                    return Ok(());
-                };
+    /// Create a board for a newly connected device and start its threads.
+    async fn connect_board(&mut self, device_info: UsbDeviceInfo) -> Result<()> {
+        // Check if this device matches any registered board pattern
+        let Some(descriptor) = self.registry.find_descriptor(&device_info) else {
+            // No match - this is expected for most USB devices
+            return Ok(());
+        };
 
-                // Pattern matched - log the match
-                info!(
+        // Pattern matched - log the match
+        info!(
+            board = descriptor.name,
+            vid = %format!("{:04x}", device_info.vid),
+            pid = %format!("{:04x}", device_info.pid),
+            manufacturer = ?device_info.manufacturer,
+            product = ?device_info.product,
+            serial = ?device_info.serial_number,
+            "Hash board connected via USB."
+        );
+
+        // One span covers bring-up, from creation to running threads
+        let span = info_span!(
+            "board_init",
+            board = descriptor.name,
+            serial = ?device_info.serial_number
+        );
+
+        // Create the board using the descriptor's factory function
+        let mut board = match (descriptor.create_fn)(device_info.clone(), self.hardware.clone())
+            .instrument(span.clone())
+            .await
+        {
+            Ok(board) => board,
+            Err(e) => {
+                error!(
                     board = descriptor.name,
-                    vid = %format!("{:04x}", device_info.vid),
-                    pid = %format!("{:04x}", device_info.pid),
-                    manufacturer = ?device_info.manufacturer,
-                    product = ?device_info.product,
-                    serial = ?device_info.serial_number,
-                    "Hash board connected via USB."
+                    error = %e,
+                    "Failed to create board"
                 );
+                return Ok(());
+            }
+        };
 
-                // Create the board using the descriptor's factory function
-                let mut board = match (descriptor.create_fn)(device_info).await {
-                    Ok(board) => board,
-                    Err(e) => {
-                        error!(
-                            board = descriptor.name,
-                            error = %e,
-                            "Failed to create board"
-                        );
-                        return Ok(());
-                    }
-                };
+        let board_info = board.board_info();
+        let board_id = board_info
+            .serial_number
+            .clone()
+            .unwrap_or_else(|| "unknown".to_string());
 
-                let board_info = board.board_info();
-                let board_id = board_info
-                    .serial_number
-                    .clone()
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                // Create hash threads from the board
-                match board.create_hash_threads().await {
-                    Ok(threads) => {
-                        // Store board for lifecycle management
-                        self.boards.insert(board_id.clone(), board);
-
-                        // Send threads to scheduler
-                        if let Err(e) = self.scheduler_tx.send(threads).await {
-                            tracing::error!(
-                                board = %board_info.model,
-                                error = %e,
-                                "Failed to send threads to scheduler"
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            board = %board_info.model,
-                            serial = %board_id,
-                            error = %e,
-                            "Hash board failed to start."
-                        );
-                    }
+        // Create hash threads from the board
+        match board.create_hash_threads().instrument(span).await {
+            Ok(threads) => {
+                if let Some(events) = board.take_event_receiver() {
+                    self.board_events
+                        .insert(board_id.clone(), ReceiverStream::new(events));
+                }
+
+                // Store board for lifecycle management
+                self.boards.insert(board_id.clone(), board);
+                self.devices.insert(board_id.clone(), device_info);
+                self.publish_board_status();
+                self.telemetry.emit(MinerEvent::BoardConnected {
+                    serial: board_id.clone(),
+                    model: board_info.model.clone(),
+                });
+
+                // Send threads to scheduler
+                let registration = ThreadRegistration {
+                    board: board_id.clone(),
+                    threads,
+                };
+                if let Err(e) = self.scheduler_tx.send(registration).await {
+                    tracing::error!(
+                        board = %board_info.model,
+                        error = %e,
+                        "Failed to send threads to scheduler"
+                    );
                 }
             }
+            Err(e) => {
+                tracing::error!(
+                    board = %board_info.model,
+                    serial = %board_id,
+                    error = %e,
+                    "Hash board failed to start."
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle a control command.
+    async fn handle_command(&mut self, command: BackplaneCommand) {
+        match command {
+            BackplaneCommand::RestartBoard { serial, response } => {
+                let _ = response.send(self.restart_board(&serial).await);
+            }
+            BackplaneCommand::SetFanMode {
+                serial,
+                mode,
+                response,
+            } => {
+                let result = match self.boards.get_mut(&serial) {
+                    Some(board) => board
+                        .set_fan_mode(mode)
+                        .await
+                        .map_err(|e| CommandError::Invalid(e.to_string())),
+                    None => Err(CommandError::NotFound(format!("board {serial}"))),
+                };
+                let _ = response.send(result);
+            }
+            BackplaneCommand::SetFrequency {
+                serial,
+                frequency_mhz,
+                response,
+            } => {
+                let result = match self.boards.get_mut(&serial) {
+                    Some(board) => board
+                        .set_frequency(frequency_mhz)
+                        .await
+                        .map_err(|e| CommandError::Invalid(e.to_string())),
+                    None => Err(CommandError::NotFound(format!("board {serial}"))),
+                };
+                let _ = response.send(result);
+            }
+            BackplaneCommand::SetProfile {
+                serial,
+                profile,
+                response,
+            } => {
+                let result = match self.boards.get_mut(&serial) {
+                    Some(board) => board
+                        .set_profile(profile)
+                        .await
+                        .map_err(|e| CommandError::Invalid(e.to_string())),
+                    None => Err(CommandError::NotFound(format!("board {serial}"))),
+                };
+                let _ = response.send(result);
+            }
+            BackplaneCommand::SetPowerLimit {
+                serial,
+                limit_w,
+                response,
+            } => {
+                let result = match self.boards.get_mut(&serial) {
+                    Some(board) => board
+                        .set_power_limit(limit_w)
+                        .await
+                        .map_err(|e| CommandError::Invalid(e.to_string())),
+                    None => Err(CommandError::NotFound(format!("board {serial}"))),
+                };
+                let _ = response.send(result);
+            }
+        }
+    }
+
+    /// Shut a board down and bring it back up from its USB device info.
+    ///
+    /// The board gets fresh hash threads, which the scheduler picks up as a
+    /// new registration.
+    async fn restart_board(&mut self, serial: &str) -> std::result::Result<(), CommandError> {
+        let Some(device_info) = self.devices.get(serial).cloned() else {
+            return Err(CommandError::NotFound(format!("board {serial}")));
+        };
+
+        info!(serial = %serial, "Restarting board.");
+        if let Some(mut board) = self.boards.remove(serial) {
+            if let Err(e) = board.shutdown().await {
+                warn!(serial = %serial, error = %e, "Failed to shutdown board for restart");
+            }
+        }
+        self.board_removed(serial);
+
+        self.connect_board(device_info)
+            .await
+            .map_err(|e| CommandError::Invalid(format!("restart failed: {e}")))?;
+
+        if self.boards.contains_key(serial) {
+            Ok(())
+        } else {
+            Err(CommandError::Invalid(format!(
+                "board {serial} did not come back after restart"
+            )))
+        }
+    }
+
+    /// Handle USB transport events.
+    async fn handle_usb_event(&mut self, event: UsbTransportEvent) -> Result<()> {
+        match event {
+            UsbTransportEvent::UsbDeviceConnected(device_info) => {
+                self.connect_board(device_info).await?;
+            }
             UsbTransportEvent::UsbDeviceDisconnected { device_path: _ } => {
                 // Find and shutdown the board
                 // Note: Current design uses serial number as key, but we get device_path
@@ -194,6 +465,7 @@ impl Backplane {
                             }
                         }
                         // Don't re-insert - board is removed
+                        self.board_removed(&board_id);
                         break; // For now, assume one board per device
                     }
                 }
@@ -203,3 +475,26 @@ impl Backplane {
         Ok(())
     }
 }
+
+/// Build the API status of a board from its info and latest telemetry.
+fn board_status(serial: &str, board: &(dyn Board + Send)) -> BoardStatus {
+    let info = board.board_info();
+    let telemetry = board.telemetry();
+
+    BoardStatus {
+        serial: serial.to_string(),
+        model: info.model,
+        chip_count: board.chip_count(),
+        asic_temp_c: telemetry.asic_temp_c,
+        vr_temp_c: telemetry.vr_temp_c,
+        fan_percent: telemetry.fan_percent,
+        fan_rpm: telemetry.fan_rpm,
+        fan_mode: telemetry.fan_mode,
+        profile: telemetry.profile,
+        power_w: telemetry.power_w,
+        current_a: telemetry.current_a,
+        input_voltage_v: telemetry.input_voltage_v,
+        core_voltage_v: telemetry.core_voltage_v,
+        hashrate_windows: Vec::new(),
+    }
+}
@@ -1,90 +1,942 @@
 //! Command-line interface for mujina-miner.
 //!
 //! This binary provides a CLI for controlling and monitoring the miner
-//! daemon via the HTTP API.
+//! daemon via the HTTP API. Output is a human-readable table by default, or
+//! the API's JSON with `--json` for scripting.
+//!
+//! Exit codes:
+//!
+//! | Code | Meaning                                        |
+//! |------|------------------------------------------------|
+//! | 0    | Success                                        |
+//! | 1    | Other error                                    |
+//! | 2    | Invalid command-line usage                     |
+//! | 3    | Daemon not reachable                           |
+//! | 4    | Board, pool, or other resource not found       |
+//! | 5    | Request rejected by the daemon (e.g., conflict) |
+//! | 6    | Configuration file invalid                     |
 
-use anyhow::{Context, Result};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::env;
 use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use futures::StreamExt;
+use mujina_miner::api_client::{
+    types::{
+        AddPoolRequest, AlertState, BoardStatus, CurtailRequest, CurtailmentStatus, FanMode,
+        HashrateEstimate, LogEntry, LogLevels, LogsQuery, MinerEvent, MinerStatus, OperatingMode,
+        PerformanceProfile, PoolStatus, ScheduleStatus, SetScheduleOverrideRequest, ThreadStatus,
+    },
+    ApiClient, ApiClientError, API_URL_ENV, DEFAULT_API_URL,
+};
+use mujina_miner::config::Config;
+use mujina_miner::history;
+use mujina_miner::types::HashRate;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+const EXIT_ERROR: u8 = 1;
+const EXIT_UNREACHABLE: u8 = 3;
+const EXIT_NOT_FOUND: u8 = 4;
+const EXIT_REJECTED: u8 = 5;
+const EXIT_INVALID_CONFIG: u8 = 6;
+
+/// Control and monitor a running mujina-miner daemon.
+#[derive(Parser, Debug)]
+#[command(name = "mujina-cli", version)]
+struct Cli {
+    /// Base URL of the daemon's API
+    #[arg(long, global = true, env = API_URL_ENV, default_value = DEFAULT_API_URL)]
+    url: String,
+
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show miner totals, boards, and pools
+    Status,
+
+    /// List connected boards
+    Boards,
+
+    /// Control a single board
+    Board {
+        #[command(subcommand)]
+        action: BoardAction,
+    },
+
+    /// List hash threads
+    Threads,
+
+    /// List and manage pools
+    Pools {
+        #[command(subcommand)]
+        action: Option<PoolAction>,
+    },
+
+    /// Pause mining (idle all threads)
+    Pause,
+
+    /// Resume mining after a pause
+    Resume,
+
+    /// Show or override the operating mode schedule
+    Schedule {
+        #[command(subcommand)]
+        action: Option<ScheduleAction>,
+    },
+
+    /// Show or request a demand-response curtailment
+    Curtail {
+        #[command(subcommand)]
+        action: Option<CurtailAction>,
+    },
 
-/// Echo request payload.
-#[derive(Debug, Serialize)]
-struct EchoRequest {
-    message: String,
+    /// Print daemon events (board hotplug, faults, shares) as they happen
+    Events,
+
+    /// Show recent log events, or adjust log levels
+    #[command(args_conflicts_with_subcommands = true)]
+    Logs {
+        /// Least severe level to show: error, warn, info, debug or trace
+        #[arg(long)]
+        level: Option<String>,
+
+        /// Only events from this module and below, e.g. mujina_miner::asic
+        #[arg(long)]
+        target: Option<String>,
+
+        /// Only events from the last duration, e.g. 30m or 2h
+        #[arg(long, value_parser = parse_duration)]
+        since: Option<u64>,
+
+        /// Show at most this many of the newest events
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Keep streaming new events as they are logged
+        #[arg(long, short)]
+        follow: bool,
+
+        #[command(subcommand)]
+        action: Option<LogsAction>,
+    },
+
+    /// Show or check configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Echo a message through the API (reads stdin if no message)
+    Echo { message: Vec<String> },
 }
 
-/// Echo response payload.
-#[derive(Debug, Deserialize)]
-struct EchoResponse {
-    message: String,
+#[derive(Subcommand, Debug)]
+enum BoardAction {
+    /// Idle the board's threads until it is restarted
+    Idle { serial: String },
+
+    /// Shut the board down and re-initialize it
+    Restart { serial: String },
+
+    /// Set a fixed fan duty cycle, or `auto` for temperature control
+    Fan {
+        serial: String,
+
+        /// Duty cycle in percent (0-100) or `auto`
+        #[arg(value_parser = parse_fan_mode)]
+        mode: FanMode,
+    },
+
+    /// Select a performance profile, or `none` for the default
+    Profile {
+        serial: String,
+
+        /// `eco`, `balanced`, `turbo` or `none`
+        // Spelled out so clap keeps the argument required
+        #[arg(value_parser = parse_profile)]
+        profile: std::option::Option<PerformanceProfile>,
+    },
 }
 
-/// Default API base URL.
-/// Port 7785 represents ASCII 'M' (77) and 'U' (85).
-const DEFAULT_API_URL: &str = "http://127.0.0.1:7785";
+/// Parse `auto` or a duty cycle in percent.
+fn parse_fan_mode(value: &str) -> std::result::Result<FanMode, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(FanMode::Auto);
+    }
+    match value.trim_end_matches('%').parse::<u8>() {
+        Ok(percent) if percent <= 100 => Ok(FanMode::Manual { percent }),
+        _ => Err("expected a duty cycle 0-100 or auto".to_string()),
+    }
+}
+
+/// Parse a profile name, or `none` to clear the selection.
+fn parse_profile(value: &str) -> std::result::Result<Option<PerformanceProfile>, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(None),
+        "eco" => Ok(Some(PerformanceProfile::Eco)),
+        "balanced" => Ok(Some(PerformanceProfile::Balanced)),
+        "turbo" => Ok(Some(PerformanceProfile::Turbo)),
+        _ => Err("expected eco, balanced, turbo or none".to_string()),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum PoolAction {
+    /// List pools (the default)
+    List,
+
+    /// Add a Stratum v1 pool
+    Add {
+        /// Pool URL, e.g. stratum+tcp://pool.example.com:3333
+        url: String,
+
+        /// Worker username
+        #[arg(long)]
+        worker: String,
+
+        /// Worker password
+        #[arg(long)]
+        password: Option<String>,
+
+        /// Name to refer to the pool by (generated if omitted)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Remove a pool
+    Remove { name: String },
+
+    /// Make a pool the active one
+    Switch { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum ScheduleAction {
+    /// Show the rules, the mode in effect and the next change (the default)
+    Show,
+
+    /// Run a mode in place of the scheduled one
+    Override {
+        /// `eco`, `balanced`, `turbo` or `none` for the default
+        #[arg(long, value_parser = parse_profile, default_value = "none")]
+        profile: std::option::Option<PerformanceProfile>,
+
+        /// Input power limit in watts
+        #[arg(long)]
+        power_limit: Option<f32>,
+
+        /// Stop hashing
+        #[arg(long)]
+        idle: bool,
+
+        /// How long to keep it, e.g. 30m or 2h (default: until the next rule
+        /// starts)
+        #[arg(long = "for", value_parser = parse_duration)]
+        duration: Option<u64>,
+    },
+
+    /// Return to the scheduled mode
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+enum CurtailAction {
+    /// Show the curtailment in effect and the power achieved (the default)
+    Show,
+
+    /// Hold total input power under a target, or stop hashing
+    Start {
+        /// Total input power in watts
+        #[arg(long, required_unless_present = "off")]
+        target: Option<f32>,
+
+        /// Stop hashing instead
+        #[arg(long, conflicts_with = "target")]
+        off: bool,
+
+        /// How long to curtail, e.g. 15m or 2h
+        #[arg(long = "for", value_parser = parse_duration)]
+        duration: u64,
+    },
+
+    /// End the curtailment early
+    End,
+}
+
+/// Parse a duration such as `30m` or `2h` into seconds.
+fn parse_duration(value: &str) -> std::result::Result<u64, String> {
+    history::parse_range(value)
+        .map(|duration| duration.as_secs())
+        .ok_or_else(|| "expected a duration like 30m, 2h or 1d".to_string())
+}
+
+#[derive(Subcommand, Debug)]
+enum LogsAction {
+    /// Show the log filter and runtime level overrides
+    Levels,
+
+    /// Log a module and everything below it at a level until reset
+    SetLevel {
+        /// Module path, e.g. mujina_miner::asic
+        target: String,
+
+        /// off, error, warn, info, debug or trace
+        level: String,
+    },
+
+    /// Return a module to its configured level
+    ResetLevel {
+        /// Module path, e.g. mujina_miner::asic
+        target: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Show the configuration the daemon is running with
+    Show,
+
+    /// Check a configuration file without contacting the daemon
+    Validate {
+        /// File to check (defaults to the file the daemon would load)
+        path: Option<PathBuf>,
+    },
+}
+
+/// Marker error for an invalid configuration file.
+#[derive(Debug, thiserror::Error)]
+#[error("{0:#}")]
+struct InvalidConfig(anyhow::Error);
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
 
-    if args.len() < 2 {
-        eprintln!("Usage: mujina-cli <command> [args...]");
-        eprintln!();
-        eprintln!("Commands:");
-        eprintln!("  echo [message]    Echo a message (reads from stdin if no args)");
-        std::process::exit(1);
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            ExitCode::from(exit_code(&e))
+        }
     }
+}
 
-    let command = &args[1];
+/// Map an error to the documented exit code.
+fn exit_code(error: &anyhow::Error) -> u8 {
+    if error.downcast_ref::<InvalidConfig>().is_some() {
+        return EXIT_INVALID_CONFIG;
+    }
 
-    match command.as_str() {
-        "echo" => cmd_echo(&args[2..]).await?,
-        _ => {
-            eprintln!("Unknown command: {}", command);
-            eprintln!("Run without arguments to see usage.");
-            std::process::exit(1);
+    match error.downcast_ref::<ApiClientError>() {
+        Some(ApiClientError::Http(e)) if e.is_connect() || e.is_timeout() => EXIT_UNREACHABLE,
+        Some(ApiClientError::Status { status, .. }) if *status == StatusCode::NOT_FOUND => {
+            EXIT_NOT_FOUND
+        }
+        Some(ApiClientError::Status { status, .. }) if status.is_client_error() => EXIT_REJECTED,
+        _ => EXIT_ERROR,
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let client = ApiClient::new(&cli.url);
+    let json = cli.json;
+
+    match cli.command {
+        Command::Status => {
+            let status = client.status().await?;
+            output(json, &status, print_status)?;
+        }
+        Command::Boards => {
+            let boards = client.boards().await?;
+            output(json, &boards, |b| print_boards(b))?;
+        }
+        Command::Board { action } => match action {
+            BoardAction::Idle { serial } => {
+                client.board_idle(&serial).await?;
+                done(json, &format!("Board {serial} idled."));
+            }
+            BoardAction::Restart { serial } => {
+                client.board_restart(&serial).await?;
+                done(json, &format!("Board {serial} restarted."));
+            }
+            BoardAction::Fan { serial, mode } => {
+                client.set_fan_mode(&serial, mode).await?;
+                let message = match mode {
+                    FanMode::Auto => format!("Board {serial} fan under temperature control."),
+                    FanMode::Manual { percent } => format!("Board {serial} fan set to {percent}%."),
+                };
+                done(json, &message);
+            }
+            BoardAction::Profile { serial, profile } => {
+                client.set_profile(&serial, profile).await?;
+                let message = match profile {
+                    Some(profile) => format!("Board {serial} set to {} profile.", profile.as_str()),
+                    None => format!("Board {serial} profile cleared."),
+                };
+                done(json, &message);
+            }
+        },
+        Command::Threads => {
+            let threads = client.threads().await?;
+            output(json, &threads, |t| print_threads(t))?;
+        }
+        Command::Pools { action } => match action.unwrap_or(PoolAction::List) {
+            PoolAction::List => {
+                let pools = client.pools().await?;
+                output(json, &pools, |p| print_pools(p))?;
+            }
+            PoolAction::Add {
+                url,
+                worker,
+                password,
+                name,
+            } => {
+                let request = AddPoolRequest {
+                    name,
+                    url,
+                    worker,
+                    password,
+                };
+                let pool = client.add_pool(&request).await?;
+                output(json, &pool, |p| println!("Pool {} added.", p.name))?;
+            }
+            PoolAction::Remove { name } => {
+                client.remove_pool(&name).await?;
+                done(json, &format!("Pool {name} removed."));
+            }
+            PoolAction::Switch { name } => {
+                client.switch_pool(&name).await?;
+                done(json, &format!("Switched to pool {name}."));
+            }
+        },
+        Command::Pause => {
+            client.pause().await?;
+            done(json, "Mining paused.");
+        }
+        Command::Resume => {
+            client.resume().await?;
+            done(json, "Mining resumed.");
+        }
+        Command::Schedule { action } => match action.unwrap_or(ScheduleAction::Show) {
+            ScheduleAction::Show => {
+                let schedule = client.schedule().await?;
+                output(json, &schedule, print_schedule)?;
+            }
+            ScheduleAction::Override {
+                profile,
+                power_limit,
+                idle,
+                duration,
+            } => {
+                let request = SetScheduleOverrideRequest {
+                    mode: OperatingMode {
+                        profile,
+                        power_limit,
+                        idle,
+                    },
+                    duration_s: duration,
+                };
+                let schedule = client.set_schedule_override(&request).await?;
+                output(json, &schedule, print_schedule)?;
+            }
+            ScheduleAction::Clear => {
+                client.clear_schedule_override().await?;
+                done(json, "Back on schedule.");
+            }
+        },
+        Command::Curtail { action } => match action.unwrap_or(CurtailAction::Show) {
+            CurtailAction::Show => {
+                let curtailment = client.curtailment().await?;
+                output(json, &curtailment, print_curtailment)?;
+            }
+            CurtailAction::Start {
+                target,
+                off: _,
+                duration,
+            } => {
+                let request = CurtailRequest {
+                    target_w: target,
+                    duration_s: Some(duration),
+                    until: None,
+                };
+                let curtailment = client.curtail(&request).await?;
+                output(json, &curtailment, print_curtailment)?;
+            }
+            CurtailAction::End => {
+                client.end_curtailment().await?;
+                done(json, "Curtailment ended.");
+            }
+        },
+        Command::Events => {
+            let mut events = Box::pin(client.events().await?);
+            while let Some(event) = events.next().await {
+                let event = event?;
+                if json {
+                    println!("{}", serde_json::to_string(&event)?);
+                } else if let Some(line) = describe_event(&event) {
+                    println!("{line}");
+                }
+            }
+        }
+        Command::Logs {
+            level,
+            target,
+            since,
+            limit,
+            follow,
+            action,
+        } => match action {
+            None => {
+                let query = LogsQuery {
+                    level,
+                    target,
+                    since: since.map(|since| history::unix_now().saturating_sub(since) as f64),
+                    limit,
+                    follow,
+                };
+                if follow {
+                    let mut entries = Box::pin(client.follow_logs(&query).await?);
+                    while let Some(entry) = entries.next().await {
+                        print_log_entry(json, &entry?)?;
+                    }
+                } else {
+                    for entry in client.logs(&query).await? {
+                        print_log_entry(json, &entry)?;
+                    }
+                }
+            }
+            Some(LogsAction::Levels) => {
+                let levels = client.log_levels().await?;
+                output(json, &levels, print_log_levels)?;
+            }
+            Some(LogsAction::SetLevel { target, level }) => {
+                client.set_log_level(&target, &level).await?;
+                done(json, &format!("Logging {target} at {level}."));
+            }
+            Some(LogsAction::ResetLevel { target }) => {
+                client.reset_log_level(&target).await?;
+                done(json, &format!("{target} back to its configured level."));
+            }
+        },
+        Command::Config { action } => match action {
+            ConfigAction::Show => {
+                let config = client.config().await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&config)?);
+                } else {
+                    print!("{}", toml::to_string(&config)?);
+                }
+            }
+            ConfigAction::Validate { path } => {
+                let path = path
+                    .or_else(Config::default_path)
+                    .context("no configuration file found; pass a path or set MUJINA_CONFIG")?;
+                Config::load_from(&path).map_err(InvalidConfig)?;
+                done(json, &format!("{} is valid.", path.display()));
+            }
+        },
+        Command::Echo { message } => {
+            let message = if message.is_empty() {
+                // Read from stdin
+                let mut buffer = String::new();
+                io::stdin()
+                    .read_to_string(&mut buffer)
+                    .context("Failed to read from stdin")?;
+                buffer.trim().to_string()
+            } else {
+                message.join(" ")
+            };
+
+            let echoed = client.echo(message).await?;
+            println!("{}", echoed);
         }
     }
 
     Ok(())
 }
 
-/// Execute the echo command.
-async fn cmd_echo(args: &[String]) -> Result<()> {
-    let message = if args.is_empty() {
-        // Read from stdin
-        let mut buffer = String::new();
-        io::stdin()
-            .read_to_string(&mut buffer)
-            .context("Failed to read from stdin")?;
-        buffer.trim().to_string()
+/// Print `value` as JSON or with the given human formatter.
+fn output<T: Serialize>(json: bool, value: &T, human: impl FnOnce(&T)) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
     } else {
-        // Join args with spaces
-        args.join(" ")
-    };
+        human(value);
+    }
+    Ok(())
+}
 
-    let api_url = env::var("MUJINA_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-    let url = format!("{}/api/v1/echo", api_url);
+/// Report success of a command that returns no data.
+fn done(json: bool, message: &str) {
+    if json {
+        println!("{}", serde_json::json!({ "ok": true }));
+    } else {
+        println!("{message}");
+    }
+}
 
-    let client = Client::new();
-    let request = EchoRequest { message };
+fn print_status(status: &MinerStatus) {
+    let state = if status.paused { "paused" } else { "mining" };
+    println!(
+        "{} | uptime {} | {} | {} shares",
+        state,
+        format_duration(status.uptime_s),
+        format_hashrate(status.hashrate),
+        status.shares_submitted
+    );
+    if !status.hashrate_windows.is_empty() {
+        println!("{}", format_windows(&status.hashrate_windows));
+    }
+    if let Some(curtailment) = &status.curtailment {
+        print!("curtailed: ");
+        print_curtailment(curtailment);
+    }
 
-    let response = client
-        .post(&url)
-        .json(&request)
-        .send()
-        .await
-        .context("Failed to send request to API")?;
+    println!();
+    print_boards(&status.boards);
+    println!();
+    print_pools(&status.pools);
+}
+
+/// One line of windowed hashrates with their 95% confidence intervals.
+fn format_windows(windows: &[HashrateEstimate]) -> String {
+    windows
+        .iter()
+        .map(|w| {
+            let bound = |b: Option<f64>| b.map_or("?".to_string(), format_hashrate);
+            format!(
+                "{}m: {} [{} .. {}]",
+                w.window_s / 60,
+                format_hashrate(w.hashrate),
+                bound(w.lower),
+                bound(w.upper)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn print_boards(boards: &[BoardStatus]) {
+    let rows = boards
+        .iter()
+        .map(|b| {
+            vec![
+                b.serial.clone(),
+                b.model.clone(),
+                b.chip_count.to_string(),
+                reading(b.asic_temp_c, |t| format!("{t:.1} C")),
+                reading(b.vr_temp_c, |t| format!("{t:.0} C")),
+                match b.fan_mode {
+                    Some(FanMode::Manual { percent }) => format!("{percent}% (manual)"),
+                    _ => reading(b.fan_percent, |p| format!("{p}%")),
+                },
+                reading(b.fan_rpm, |r| r.to_string()),
+                reading(b.profile, |p| p.as_str().to_string()),
+                reading(b.power_w, |w| format!("{w:.1} W")),
+                reading(b.core_voltage_v, |v| format!("{v:.3} V")),
+            ]
+        })
+        .collect();
+    print_table(
+        &[
+            "SERIAL", "MODEL", "CHIPS", "ASIC", "VR", "FAN", "RPM", "PROFILE", "POWER", "VCORE",
+        ],
+        rows,
+    );
+}
+
+fn print_threads(threads: &[ThreadStatus]) {
+    let rows = threads
+        .iter()
+        .map(|t| {
+            vec![
+                t.name.clone(),
+                format_hashrate(t.hashrate),
+                t.shares.to_string(),
+                t.hardware_errors.to_string(),
+                format!("{:.2}%", t.hardware_error_rate * 100.0),
+                if t.active { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+    print_table(
+        &["THREAD", "HASHRATE", "SHARES", "HW ERR", "HW%", "ACTIVE"],
+        rows,
+    );
+}
+
+fn print_pools(pools: &[PoolStatus]) {
+    let rows = pools
+        .iter()
+        .map(|p| {
+            vec![
+                if p.active { "*" } else { "" }.to_string(),
+                p.name.clone(),
+                p.url.clone().unwrap_or_else(|| "-".to_string()),
+                if p.connected { "yes" } else { "no" }.to_string(),
+                reading(p.difficulty, |d| format!("{d:.0}")),
+                p.shares_accepted.to_string(),
+                p.shares_rejected.to_string(),
+            ]
+        })
+        .collect();
+    print_table(
+        &["", "POOL", "URL", "WORK", "DIFF", "ACCEPTED", "REJECTED"],
+        rows,
+    );
+}
 
-    if !response.status().is_success() {
-        anyhow::bail!("API request failed: {}", response.status());
+fn print_schedule(schedule: &ScheduleStatus) {
+    let timezone = jiff::tz::TimeZone::get(&schedule.timezone)
+        .unwrap_or_else(|_| jiff::tz::TimeZone::system());
+    let format_time = |time: u64| {
+        jiff::Timestamp::from_second(time as i64)
+            .map(|t| {
+                t.to_zoned(timezone.clone())
+                    .strftime("%a %Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| time.to_string())
+    };
+
+    let mode = schedule
+        .mode
+        .map_or("configured operating point".to_string(), |mode| {
+            format_mode(&mode)
+        });
+    match (&schedule.active_override, &schedule.active_rule) {
+        (Some(active), _) => println!(
+            "{mode} | override until {}",
+            active.until.map_or("cleared".to_string(), format_time)
+        ),
+        (None, Some(rule)) => println!("{mode} | rule {rule}"),
+        (None, None) => println!("{mode}"),
+    }
+    if let (Some(rule), Some(time)) = (&schedule.next_rule, schedule.next_change) {
+        println!("next: {rule} at {}", format_time(time));
     }
+    println!("time zone: {}", schedule.timezone);
+
+    println!();
+    let rows = schedule
+        .rules
+        .iter()
+        .map(|rule| {
+            vec![
+                if schedule.active_rule.as_ref() == Some(&rule.name) {
+                    "*"
+                } else {
+                    ""
+                }
+                .to_string(),
+                rule.name.clone(),
+                rule.cron.clone(),
+                format_mode(&rule.mode()),
+            ]
+        })
+        .collect();
+    print_table(&["", "RULE", "CRON", "MODE"], rows);
+}
 
-    let echo_response: EchoResponse = response.json().await.context("Failed to parse response")?;
+fn print_curtailment(curtailment: &CurtailmentStatus) {
+    let until = jiff::Timestamp::from_second(curtailment.until as i64)
+        .map(|t| {
+            t.to_zoned(jiff::tz::TimeZone::system())
+                .strftime("%a %Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| curtailment.until.to_string());
+    let target = curtailment
+        .target_w
+        .map_or("off".to_string(), |target| format!("{target:.0} W"));
+    let power = reading(curtailment.power_w, |p| format!("{p:.1} W"));
+    let met = if curtailment.met { "met" } else { "not met" };
+    println!("{target} until {until} | drawing {power} ({met})");
+}
+
+fn format_mode(mode: &OperatingMode) -> String {
+    let mut parts = Vec::new();
+    if mode.idle {
+        parts.push("idle".to_string());
+    }
+    if let Some(profile) = mode.profile {
+        parts.push(profile.as_str().to_string());
+    }
+    if let Some(limit) = mode.power_limit {
+        parts.push(format!("{limit:.0} W limit"));
+    }
+    if parts.is_empty() {
+        "default".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
 
-    println!("{}", echo_response.message);
+/// Print one log event per line, as JSON or `time level target: message`.
+fn print_log_entry(json: bool, entry: &LogEntry) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(entry)?);
+        return Ok(());
+    }
 
+    let time = jiff::Timestamp::from_microsecond((entry.time * 1e6) as i64)
+        .map(|t| {
+            t.to_zoned(jiff::tz::TimeZone::system())
+                .strftime("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string()
+        })
+        .unwrap_or_else(|_| entry.time.to_string());
+    let mut line = format!(
+        "{time} {:>5} {}: {}",
+        entry.level.to_uppercase(),
+        entry.target,
+        entry.message
+    );
+    for (name, value) in &entry.fields {
+        line.push_str(&format!(" {name}={value}"));
+    }
+    println!("{line}");
     Ok(())
 }
+
+fn print_log_levels(levels: &LogLevels) {
+    println!("filter: {}", levels.directives);
+    if levels.overrides.is_empty() {
+        return;
+    }
+
+    println!();
+    let rows = levels
+        .overrides
+        .iter()
+        .map(|(target, level)| vec![target.clone(), level.clone()])
+        .collect();
+    print_table(&["TARGET", "LEVEL"], rows);
+}
+
+/// One-line description of an event, or None for status snapshots.
+fn describe_event(event: &MinerEvent) -> Option<String> {
+    Some(match event {
+        MinerEvent::Status(_) => return None,
+        MinerEvent::BoardConnected { serial, model } => {
+            format!("board {serial} connected ({model})")
+        }
+        MinerEvent::BoardDisconnected { serial } => format!("board {serial} disconnected"),
+        MinerEvent::BoardFault {
+            serial,
+            component,
+            fault,
+            recoverable,
+        } => format!(
+            "board {serial} fault in {component}: {fault}{}",
+            if *recoverable {
+                ""
+            } else {
+                " (not recoverable)"
+            }
+        ),
+        MinerEvent::ShareSubmitted {
+            pool,
+            thread,
+            job_id,
+            difficulty,
+        } => {
+            format!("share from {thread} submitted to {pool} (job {job_id}, diff {difficulty:.0})")
+        }
+        MinerEvent::ShareAccepted { pool } => format!("share accepted by {pool}"),
+        MinerEvent::ShareRejected { pool, reason } => {
+            format!("share rejected by {pool}: {reason}")
+        }
+        MinerEvent::ChipUnderperforming {
+            thread,
+            chip,
+            domain,
+            health,
+        } => format!(
+            "{} on {thread} underperforming ({:.0}% of expected)",
+            chip_name(*chip, *domain),
+            health * 100.0
+        ),
+        MinerEvent::ChipRecovered {
+            thread,
+            chip,
+            domain,
+        } => format!("{} on {thread} recovered", chip_name(*chip, *domain)),
+        MinerEvent::BlockFound { pool, thread, hash } => {
+            format!("block found by {thread} for {pool}: {hash}")
+        }
+        MinerEvent::Alert(alert) => match alert.state {
+            AlertState::Firing => format!("ALERT: {}", alert.summary),
+            AlertState::Resolved => format!("resolved: {}", alert.summary),
+            AlertState::Event => alert.summary.clone(),
+        },
+    })
+}
+
+fn chip_name(chip: u8, domain: Option<u8>) -> String {
+    match domain {
+        Some(domain) => format!("chip {chip} domain {domain}"),
+        None => format!("chip {chip}"),
+    }
+}
+
+/// Print rows as left-aligned columns under a header.
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        println!("(none)");
+        return;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(headers.to_vec()));
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
+fn reading<T>(value: Option<T>, format: impl FnOnce(T) -> String) -> String {
+    value.map(format).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_hashrate(hashes_per_second: f64) -> String {
+    HashRate(hashes_per_second as u64).to_human_readable()
+}
+
+fn format_duration(seconds: u64) -> String {
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let (hours, rest) = (rest / 3600, rest % 3600);
+    let (minutes, seconds) = (rest / 60, rest % 60);
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m {seconds}s")
+    }
+}
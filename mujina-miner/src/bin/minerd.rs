@@ -1,11 +1,12 @@
 //! Main entry point for the mujina-miner daemon.
 
-use mujina_miner::{daemon::Daemon, tracing};
+use mujina_miner::{config::Config, daemon::Daemon, tracing};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing::init_journald_or_stdout();
+    let config = Config::load()?;
+    let log_guard = tracing::init(&config);
 
-    let daemon = Daemon::new();
+    let daemon = Daemon::new(config).with_logs(log_guard.logs());
     daemon.run().await
 }
@@ -3,20 +3,725 @@
 //! This binary provides an interactive terminal dashboard for monitoring
 //! the miner. Built with ratatui, it shows real-time hashrate, temperature,
 //! and other statistics.
+//!
+//! All data comes from the API's event stream, so the dashboard works the
+//! same against a local or remote daemon (`--url` or `MUJINA_API_URL`). If
+//! the connection drops, the last known state stays on screen and the
+//! dashboard reconnects in the background.
+//!
+//! Keys: `↑`/`↓` or `k`/`j` select a board, `q` or `Esc` quits.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
 
 use anyhow::Result;
+use clap::Parser;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures::StreamExt;
+use mujina_miner::api_client::{
+    types::{AlertState, BoardStatus, ChipHealth, MinerEvent, MinerStatus, PoolStatus},
+    ApiClient, API_URL_ENV, DEFAULT_API_URL,
+};
+use mujina_miner::hash_thread::health::UNDERPERFORMING;
+use mujina_miner::types::HashRate;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Sparkline, Table, Wrap},
+    DefaultTerminal, Frame,
+};
+use tokio::sync::mpsc;
+
+/// Hashrate samples kept per series (one per status update, ~5 s apart).
+const HISTORY_LEN: usize = 360;
+
+/// Event log lines kept for the log panel.
+const EVENT_LOG_LEN: usize = 200;
+
+/// Delay before reconnecting after the event stream fails.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Terminal dashboard for a mujina-miner daemon.
+#[derive(Parser, Debug)]
+#[command(name = "mujina-tui", version)]
+struct Cli {
+    /// Base URL of the daemon's API
+    #[arg(long, env = API_URL_ENV, default_value = DEFAULT_API_URL)]
+    url: String,
+}
+
+/// Messages from the API connection task to the UI.
+#[derive(Debug)]
+enum Update {
+    Connected,
+    Disconnected(String),
+    Event(MinerEvent),
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("mujina-tui: Terminal UI for mujina-miner");
-    println!("This is a placeholder - TUI implementation coming soon!");
+    let cli = Cli::parse();
+    let client = ApiClient::new(&cli.url);
+
+    let (update_tx, update_rx) = mpsc::channel(256);
+    let connection = tokio::spawn(follow_events(client, update_tx));
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, App::new(cli.url), update_rx).await;
+    ratatui::restore();
+
+    connection.abort();
+    result
+}
 
-    // TODO: Implement TUI with ratatui
-    // - Dashboard with hashrate graphs
-    // - Temperature and power monitoring
-    // - Pool status and shares
-    // - Board overview
-    // - Keyboard navigation
+/// Stream events from the daemon into the UI, reconnecting on failure.
+async fn follow_events(client: ApiClient, updates: mpsc::Sender<Update>) {
+    loop {
+        let error = match client.events().await {
+            Ok(events) => {
+                if updates.send(Update::Connected).await.is_err() {
+                    return;
+                }
+
+                let mut events = Box::pin(events);
+                loop {
+                    match events.next().await {
+                        Some(Ok(event)) => {
+                            if updates.send(Update::Event(event)).await.is_err() {
+                                return;
+                            }
+                        }
+                        Some(Err(e)) => break e.to_string(),
+                        None => break "connection closed".to_string(),
+                    }
+                }
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if updates.send(Update::Disconnected(error)).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    mut app: App,
+    mut updates: mpsc::Receiver<Update>,
+) -> Result<()> {
+    let mut input = EventStream::new();
+
+    // Redraw periodically even without updates so the clock keeps moving
+    let mut redraw = tokio::time::interval(Duration::from_secs(1));
+
+    while !app.quit {
+        terminal.draw(|frame| draw(frame, &app))?;
+
+        tokio::select! {
+            Some(update) = updates.recv() => app.apply(update),
+            Some(event) = input.next() => {
+                if let Event::Key(key) = event? {
+                    app.handle_key(key);
+                }
+            }
+            _ = redraw.tick() => {}
+        }
+    }
 
     Ok(())
 }
+
+/// Dashboard state, built entirely from API updates.
+struct App {
+    url: String,
+    connected: bool,
+    last_error: Option<String>,
+    status: MinerStatus,
+
+    /// Total hashrate history in H/s
+    total_history: VecDeque<u64>,
+
+    /// Per-board hashrate history in H/s, keyed by serial
+    board_history: HashMap<String, VecDeque<u64>>,
+
+    /// Human-readable lines for discrete events, newest last
+    event_log: VecDeque<String>,
+
+    /// Index into `status.boards` of the board shown in detail
+    selected_board: usize,
+
+    quit: bool,
+}
+
+impl App {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            connected: false,
+            last_error: None,
+            status: MinerStatus::default(),
+            total_history: VecDeque::new(),
+            board_history: HashMap::new(),
+            event_log: VecDeque::new(),
+            selected_board: 0,
+            quit: false,
+        }
+    }
+
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Connected => {
+                self.connected = true;
+                self.last_error = None;
+            }
+            Update::Disconnected(error) => {
+                if self.connected {
+                    self.log(format!("disconnected: {error}"));
+                }
+                self.connected = false;
+                self.last_error = Some(error);
+            }
+            Update::Event(MinerEvent::Status(status)) => self.apply_status(status),
+            Update::Event(event) => {
+                if let Some(line) = describe_event(&event) {
+                    self.log(line);
+                }
+            }
+        }
+    }
+
+    fn apply_status(&mut self, status: MinerStatus) {
+        push_sample(&mut self.total_history, status.hashrate);
+
+        let mut per_board: HashMap<&str, f64> = HashMap::new();
+        for thread in &status.threads {
+            *per_board.entry(thread.board.as_str()).or_default() += thread.hashrate;
+        }
+        for board in &status.boards {
+            let rate = per_board.get(board.serial.as_str()).copied().unwrap_or(0.0);
+            push_sample(
+                self.board_history.entry(board.serial.clone()).or_default(),
+                rate,
+            );
+        }
+        self.board_history
+            .retain(|serial, _| status.boards.iter().any(|b| &b.serial == serial));
+
+        self.status = status;
+        self.selected_board = self
+            .selected_board
+            .min(self.status.boards.len().saturating_sub(1));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j')
+                if self.selected_board + 1 < self.status.boards.len() =>
+            {
+                self.selected_board += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected_board = self.selected_board.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+
+    fn log(&mut self, line: String) {
+        if self.event_log.len() == EVENT_LOG_LEN {
+            self.event_log.pop_front();
+        }
+        self.event_log.push_back(line);
+    }
+
+    fn selected(&self) -> Option<&BoardStatus> {
+        self.status.boards.get(self.selected_board)
+    }
+}
+
+fn push_sample(history: &mut VecDeque<u64>, hashrate: f64) {
+    if history.len() == HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(hashrate as u64);
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [header, graph, middle, pools, log, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(7),
+        Constraint::Min(10),
+        Constraint::Length(pools_height(&app.status.pools)),
+        Constraint::Length(8),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_header(frame, header, app);
+    draw_sparkline(
+        frame,
+        graph,
+        &format!(" Hashrate {} ", format_hashrate(app.status.hashrate)),
+        &app.total_history,
+    );
+
+    let [boards, detail] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(middle);
+    draw_boards(frame, boards, app);
+    draw_board_detail(frame, detail, app);
+
+    draw_pools(frame, pools, &app.status.pools);
+    draw_log(frame, log, app);
+
+    frame.render_widget(Line::from(" ↑/↓ select board   q quit").dark_gray(), footer);
+}
+
+fn draw_header(frame: &mut Frame, area: Rect, app: &App) {
+    let connection = if app.connected {
+        Span::styled("● connected", Style::new().green())
+    } else {
+        Span::styled("● disconnected", Style::new().red())
+    };
+
+    let state = if app.status.paused {
+        Span::styled("paused", Style::new().yellow().bold())
+    } else {
+        Span::raw("mining")
+    };
+
+    let mut spans = vec![
+        " mujina ".bold(),
+        connection,
+        format!("  {}  ", app.url).dark_gray(),
+        state,
+        format!(
+            "  uptime {}  shares {}",
+            format_duration(app.status.uptime_s),
+            app.status.shares_submitted
+        )
+        .into(),
+    ];
+    if let Some(error) = &app.last_error {
+        spans.push(format!("  {error}").red());
+    }
+
+    frame.render_widget(Line::from(spans), area);
+}
+
+fn draw_sparkline(frame: &mut Frame, area: Rect, title: &str, history: &VecDeque<u64>) {
+    // Show the most recent samples that fit in the panel
+    let width = area.width.saturating_sub(2) as usize;
+    let data: Vec<u64> = history
+        .iter()
+        .skip(history.len().saturating_sub(width))
+        .copied()
+        .collect();
+
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(title.to_string()))
+            .data(&data)
+            .style(Style::new().cyan()),
+        area,
+    );
+}
+
+fn draw_boards(frame: &mut Frame, area: Rect, app: &App) {
+    let rows = app.status.boards.iter().enumerate().map(|(i, board)| {
+        let style = if i == app.selected_board {
+            Style::new().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::new()
+        };
+        Row::new(vec![
+            Cell::from(board.serial.clone()),
+            Cell::from(board.model.clone()),
+            temperature_cell(board.asic_temp_c),
+            Cell::from(reading(board.power_w, |w| format!("{w:.1} W"))),
+            Cell::from(reading(board.fan_percent, |p| format!("{p}%"))),
+        ])
+        .style(style)
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Min(12),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(5),
+        ],
+    )
+    .header(Row::new(["SERIAL", "MODEL", "ASIC", "POWER", "FAN"]).bold())
+    .block(Block::bordered().title(format!(" Boards ({}) ", app.status.boards.len())));
+
+    frame.render_widget(table, area);
+}
+
+fn draw_board_detail(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(board) = app.selected() else {
+        frame.render_widget(
+            Paragraph::new("No boards connected.")
+                .block(Block::bordered().title(" Board "))
+                .dark_gray(),
+            area,
+        );
+        return;
+    };
+
+    let block = Block::bordered().title(format!(" {} ({}) ", board.serial, board.model));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [graph, readings, chips] = Layout::vertical([
+        Constraint::Length(5),
+        Constraint::Length(4),
+        Constraint::Min(2),
+    ])
+    .areas(inner);
+
+    let history = app
+        .board_history
+        .get(&board.serial)
+        .cloned()
+        .unwrap_or_default();
+    let rate = history.back().copied().unwrap_or(0);
+    draw_sparkline(
+        frame,
+        graph,
+        &format!(" {} ", HashRate(rate).to_human_readable()),
+        &history,
+    );
+
+    let lines = vec![
+        Line::from(vec![
+            "ASIC ".dark_gray(),
+            temperature_span(board.asic_temp_c),
+            "   VR ".dark_gray(),
+            temperature_span(board.vr_temp_c),
+        ]),
+        Line::from(vec![
+            "Power ".dark_gray(),
+            reading(board.power_w, |w| format!("{w:.1} W")).into(),
+            "   Current ".dark_gray(),
+            reading(board.current_a, |a| format!("{a:.2} A")).into(),
+        ]),
+        Line::from(vec![
+            "Vin ".dark_gray(),
+            reading(board.input_voltage_v, |v| format!("{v:.2} V")).into(),
+            "   Vcore ".dark_gray(),
+            reading(board.core_voltage_v, |v| format!("{v:.3} V")).into(),
+        ]),
+        Line::from(vec![
+            "Fan ".dark_gray(),
+            reading(board.fan_percent, |p| format!("{p}%")).into(),
+            "   ".into(),
+            reading(board.fan_rpm, |r| format!("{r} RPM")).into(),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(lines), readings);
+
+    let health: Vec<&ChipHealth> = app
+        .status
+        .threads
+        .iter()
+        .filter(|thread| thread.board == board.serial)
+        .flat_map(|thread| &thread.chips)
+        .collect();
+    draw_chip_grid(frame, chips, board, &health);
+}
+
+/// One cell per chip, coloured by health.
+///
+/// `health` is in chain order; chips without a score yet are drawn as "no
+/// data".
+fn draw_chip_grid(frame: &mut Frame, area: Rect, board: &BoardStatus, health: &[&ChipHealth]) {
+    let cells: Vec<Span> = (0..board.chip_count)
+        .map(|i| {
+            let style = match health.get(i) {
+                Some(chip) => chip_style(chip),
+                None => Style::new().black().on_dark_gray(),
+            };
+            Span::styled(format!("{i:>2} "), style)
+        })
+        .collect();
+
+    frame.render_widget(
+        Paragraph::new(Line::from(cells))
+            .wrap(Wrap { trim: false })
+            .block(Block::new().borders(Borders::TOP).title(" Chips ")),
+        area,
+    );
+}
+
+fn chip_style(chip: &ChipHealth) -> Style {
+    match chip.health {
+        None => Style::new().black().on_dark_gray(),
+        Some(h) if h < UNDERPERFORMING => Style::new().black().on_red(),
+        Some(h) if h < 0.9 || !chip.weak_domains.is_empty() => Style::new().black().on_yellow(),
+        Some(_) => Style::new().black().on_green(),
+    }
+}
+
+fn pools_height(pools: &[PoolStatus]) -> u16 {
+    // Borders and header plus one row per pool
+    pools.len().max(1) as u16 + 3
+}
+
+fn draw_pools(frame: &mut Frame, area: Rect, pools: &[PoolStatus]) {
+    let rows = pools.iter().map(|pool| {
+        let total = pool.shares_accepted + pool.shares_rejected;
+        let acceptance = if total > 0 {
+            format!("{:.1}%", pool.shares_accepted as f64 * 100.0 / total as f64)
+        } else {
+            "-".to_string()
+        };
+
+        Row::new(vec![
+            Cell::from(if pool.active { "▶" } else { "" }),
+            Cell::from(pool.name.clone()),
+            Cell::from(pool.url.clone().unwrap_or_else(|| "-".to_string())),
+            if pool.connected {
+                Cell::from("working").green()
+            } else {
+                Cell::from("no work").yellow()
+            },
+            Cell::from(reading(pool.difficulty, |d| format!("{d:.0}"))),
+            Cell::from(pool.shares_accepted.to_string()),
+            Cell::from(pool.shares_rejected.to_string()),
+            Cell::from(acceptance),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Min(8),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(7),
+        ],
+    )
+    .header(
+        Row::new([
+            "", "POOL", "URL", "STATE", "DIFF", "ACCEPT", "REJECT", "RATE",
+        ])
+        .bold(),
+    )
+    .block(Block::bordered().title(" Pools "));
+
+    frame.render_widget(table, area);
+}
+
+fn draw_log(frame: &mut Frame, area: Rect, app: &App) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = app
+        .event_log
+        .iter()
+        .skip(app.event_log.len().saturating_sub(visible))
+        .map(|line| ListItem::new(line.as_str()))
+        .collect();
+
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title(" Events ")),
+        area,
+    );
+}
+
+/// One-line description of an event, or None for status snapshots.
+fn describe_event(event: &MinerEvent) -> Option<String> {
+    Some(match event {
+        MinerEvent::Status(_) => return None,
+        MinerEvent::BoardConnected { serial, model } => {
+            format!("board {serial} connected ({model})")
+        }
+        MinerEvent::BoardDisconnected { serial } => format!("board {serial} disconnected"),
+        MinerEvent::BoardFault {
+            serial,
+            component,
+            fault,
+            ..
+        } => format!("board {serial} fault in {component}: {fault}"),
+        MinerEvent::ShareSubmitted {
+            pool,
+            thread,
+            difficulty,
+            ..
+        } => format!("share from {thread} to {pool} (diff {difficulty:.0})"),
+        MinerEvent::ShareAccepted { pool } => format!("share accepted by {pool}"),
+        MinerEvent::ShareRejected { pool, reason } => {
+            format!("share rejected by {pool}: {reason}")
+        }
+        MinerEvent::ChipUnderperforming {
+            thread,
+            chip,
+            domain,
+            health,
+        } => format!(
+            "{} on {thread} underperforming ({:.0}% of expected)",
+            chip_name(*chip, *domain),
+            health * 100.0
+        ),
+        MinerEvent::ChipRecovered {
+            thread,
+            chip,
+            domain,
+        } => format!("{} on {thread} recovered", chip_name(*chip, *domain)),
+        MinerEvent::BlockFound { pool, thread, hash } => {
+            format!("block found by {thread} for {pool}: {hash}")
+        }
+        MinerEvent::Alert(alert) => match alert.state {
+            AlertState::Firing => format!("ALERT: {}", alert.summary),
+            AlertState::Resolved => format!("resolved: {}", alert.summary),
+            AlertState::Event => alert.summary.clone(),
+        },
+    })
+}
+
+fn chip_name(chip: u8, domain: Option<u8>) -> String {
+    match domain {
+        Some(domain) => format!("chip {chip} domain {domain}"),
+        None => format!("chip {chip}"),
+    }
+}
+
+fn temperature_style(celsius: f32) -> Style {
+    match celsius {
+        t if t >= 75.0 => Style::new().red().bold(),
+        t if t >= 65.0 => Style::new().yellow(),
+        _ => Style::new().green(),
+    }
+}
+
+fn temperature_span(celsius: Option<f32>) -> Span<'static> {
+    match celsius {
+        Some(t) => Span::styled(format!("{t:.1} °C"), temperature_style(t)),
+        None => Span::raw("-"),
+    }
+}
+
+fn temperature_cell(celsius: Option<f32>) -> Cell<'static> {
+    Cell::from(Line::from(temperature_span(celsius)))
+}
+
+fn reading<T>(value: Option<T>, format: impl FnOnce(T) -> String) -> String {
+    value.map(format).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_hashrate(hashes_per_second: f64) -> String {
+    HashRate(hashes_per_second as u64).to_human_readable()
+}
+
+fn format_duration(seconds: u64) -> String {
+    let (hours, rest) = (seconds / 3600, seconds % 3600);
+    format!("{hours}h {:02}m {:02}s", rest / 60, rest % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mujina_miner::api_client::types::ThreadStatus;
+
+    fn board(serial: &str) -> BoardStatus {
+        BoardStatus {
+            serial: serial.into(),
+            model: "Bitaxe Gamma".into(),
+            ..Default::default()
+        }
+    }
+
+    fn thread(board: &str, hashrate: f64) -> ThreadStatus {
+        ThreadStatus {
+            name: format!("{board}/0"),
+            board: board.into(),
+            hashrate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_status_builds_per_board_history() {
+        let mut app = App::new("http://test".into());
+
+        app.apply(Update::Event(MinerEvent::Status(MinerStatus {
+            hashrate: 3e9,
+            boards: vec![board("a"), board("b")],
+            threads: vec![thread("a", 1e9), thread("b", 2e9)],
+            ..Default::default()
+        })));
+
+        assert_eq!(app.total_history, [3_000_000_000]);
+        assert_eq!(app.board_history["a"], [1_000_000_000]);
+        assert_eq!(app.board_history["b"], [2_000_000_000]);
+
+        // Removed boards drop their history and the selection follows
+        app.selected_board = 1;
+        app.apply(Update::Event(MinerEvent::Status(MinerStatus {
+            boards: vec![board("a")],
+            ..Default::default()
+        })));
+        assert!(!app.board_history.contains_key("b"));
+        assert_eq!(app.selected_board, 0);
+    }
+
+    #[test]
+    fn test_event_log_is_bounded() {
+        let mut app = App::new("http://test".into());
+
+        for _ in 0..EVENT_LOG_LEN + 5 {
+            app.apply(Update::Event(MinerEvent::ShareAccepted {
+                pool: "pool".into(),
+            }));
+        }
+
+        assert_eq!(app.event_log.len(), EVENT_LOG_LEN);
+    }
+
+    #[test]
+    fn test_draw_renders_dashboard() {
+        let mut app = App::new("http://test".into());
+        app.apply(Update::Connected);
+        app.apply(Update::Event(MinerEvent::Status(MinerStatus {
+            boards: vec![BoardStatus {
+                chip_count: 1,
+                asic_temp_c: Some(61.5),
+                ..board("abc123")
+            }],
+            pools: vec![PoolStatus {
+                name: "pool".into(),
+                active: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        })));
+
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("abc123"));
+        assert!(screen.contains("61.5"));
+        assert!(screen.contains("connected"));
+    }
+}
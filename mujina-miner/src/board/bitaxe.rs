@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use futures::sink::SinkExt;
 use std::{
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, RwLock},
     task::{Context, Poll},
     time::Duration,
 };
@@ -11,15 +11,19 @@ use tokio::{
     sync::{mpsc, watch, Mutex},
     time,
 };
-use tokio_stream::StreamExt;
 use tokio_util::codec::{FramedRead, FramedWrite};
 
 use crate::{
+    api_client::types::{FanMode, PerformanceProfile},
     asic::{
-        bm13xx::{self, protocol::Command, BM13xxProtocol},
+        bm13xx::{self, protocol::Command},
         ChipInfo,
     },
-    hash_thread::{bm13xx::BM13xxThread, HashThread},
+    config::{ChipProfiles, HardwareConfig, TuningGoal},
+    hash_thread::{
+        bm13xx::{self as bm13xx_thread, BM13xxControl, BM13xxThread},
+        HashThread, HashThreadStatus,
+    },
     hw_trait::{
         gpio::{Gpio, GpioPin, PinValue},
         i2c::I2c,
@@ -40,8 +44,12 @@ use crate::{
 };
 
 use super::{
+    fan_control::{self, FanController},
     pattern::{Match, StringMatch},
-    Board, BoardError, BoardEvent, BoardInfo,
+    power::{lowest_limit, PowerGovernor},
+    thermal::{ThermalDecision, ThermalGovernor},
+    tuning::{self, Autotuner, TunedPoint, TuningReading, TuningUpdate},
+    Board, BoardError, BoardEvent, BoardInfo, BoardTelemetry,
 };
 
 /// Thread removal signal sent via watch channel from board to thread.
@@ -72,9 +80,10 @@ pub enum ThreadRemovalSignal {
 
 /// Peripheral handles shared between board and hash thread.
 ///
-/// Board-specific design choice for Bitaxe: the thread needs direct access to
-/// the reset pin and voltage regulator for real-time chip control. Other boards
-/// may use different cooperation patterns.
+/// Board-specific design choice for Bitaxe, shared by the EmberOne's
+/// bitaxe-raw control port: the thread needs direct access to the reset pin
+/// and voltage regulator for real-time chip control. Other boards may use
+/// different cooperation patterns.
 #[derive(Clone)]
 pub struct BitaxePeripherals {
     /// ASIC reset (active low)
@@ -82,6 +91,9 @@ pub struct BitaxePeripherals {
 
     /// Voltage regulator for core voltage tuning (cached state requires Mutex)
     pub regulator: Arc<Mutex<Tps546<BitaxeRawI2c>>>,
+
+    /// Frequency cap from the board's governors and tuning (`None`: uncapped)
+    pub frequency_limit: watch::Receiver<Option<f32>>,
 }
 
 /// A wrapper around AsyncRead that traces raw bytes as they're read
@@ -138,6 +150,12 @@ pub struct BitaxeBoard {
     i2c: BitaxeRawI2c,
     /// Fan controller (board-controlled only, not shared with thread)
     fan_controller: Option<Emc2101<BitaxeRawI2c>>,
+    /// Fan mode, followed by the fan control task
+    fan_mode: watch::Sender<FanMode>,
+    /// Handle for the fan control task
+    fan_task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Limits and control settings from the configuration
+    hardware: HardwareConfig,
     /// Voltage regulator (shared with thread, cached state)
     regulator: Option<Arc<Mutex<Tps546<BitaxeRawI2c>>>>,
     /// Writer for sending commands to chips (transferred to hash thread)
@@ -154,9 +172,23 @@ pub struct BitaxeBoard {
     /// Channel for receiving board events (populated during initialization)
     event_rx: Option<mpsc::Receiver<BoardEvent>>,
     /// Thread shutdown signal (board-to-thread implementation detail)
-    thread_shutdown: Option<watch::Sender<ThreadRemovalSignal>>,
+    thread_shutdown: watch::Sender<ThreadRemovalSignal>,
+    /// Frequency cap for the hash thread, set by the statistics task
+    frequency_limit: watch::Sender<Option<f32>>,
+    /// Handle to the hash thread once created, for profiles and tuning
+    thread: watch::Sender<Option<BM13xxControl>>,
+    /// Selected performance profile, applied by the statistics task
+    profile: watch::Sender<Option<PerformanceProfile>>,
+    /// Frequency set by hand, overriding the profile's or tuned one
+    manual_frequency: watch::Sender<Option<f32>>,
+    /// Input power limit, followed by the statistics task's governor
+    power_limit: watch::Sender<Option<f32>>,
+    /// Fan target temperature, set from the active profile
+    fan_target: watch::Sender<f32>,
     /// Handle for the statistics task
     stats_task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Latest sensor readings (written by the statistics task)
+    telemetry: Arc<RwLock<BoardTelemetry>>,
     /// Serial number from USB device info
     serial_number: Option<String>,
 }
@@ -171,13 +203,20 @@ impl BitaxeBoard {
     const TARGET_BAUD_RATE: u32 = 1_000_000;
     #[expect(dead_code, reason = "will be used when baud rate change is fixed")]
     const CHIP_BAUD_REGISTER: bm13xx::protocol::BaudRate = bm13xx::protocol::BaudRate::Baud1M;
-    const EXPECTED_CHIP_ID: [u8; 2] = [0x13, 0x70]; // BM1370
+
+    /// Chips per voltage domain; multi-chip Bitaxes stack one chip per domain
+    const CHIPS_PER_DOMAIN: usize = 1;
+
+    /// Core voltage, default for BM1370 from esp-miner
+    const CORE_VOLTAGE_V: f32 = 1.15;
 
     /// Creates a new BitaxeBoard instance with the provided serial streams.
     ///
     /// # Arguments
     /// * `control` - Serial stream for sending board control commands
     /// * `data_path` - Path to the data serial port (e.g., "/dev/ttyACM1")
+    /// * `serial_number` - Serial number from USB device info
+    /// * `hardware` - Temperature, fan and power settings from the config
     ///
     /// # Returns
     /// A new BitaxeBoard instance ready for hardware operations
@@ -189,7 +228,11 @@ impl BitaxeBoard {
         control: tokio_serial::SerialStream,
         data_path: &str,
         serial_number: Option<String>,
+        hardware: HardwareConfig,
     ) -> Result<Self, BoardError> {
+        let fan_target = watch::Sender::new(hardware.fan.target_temp);
+        let power_limit = watch::Sender::new(hardware.power_limit);
+
         // Create control channel and I2C controller
         let control_channel = ControlChannel::new(control);
         let i2c = BitaxeRawI2c::new(control_channel.clone());
@@ -208,6 +251,9 @@ impl BitaxeBoard {
             asic_nrst: None,
             i2c,
             fan_controller: None,
+            fan_mode: watch::Sender::new(FanMode::Auto),
+            fan_task_handle: None,
+            hardware,
             regulator: None,
             data_writer: Some(FramedWrite::new(data_writer, bm13xx::FrameCodec::default())),
             data_reader: Some(FramedRead::new(
@@ -218,8 +264,15 @@ impl BitaxeBoard {
             chip_infos: Vec::new(),
             event_tx: None,
             event_rx: None,
-            thread_shutdown: None,
+            thread_shutdown: watch::Sender::new(ThreadRemovalSignal::Running),
+            frequency_limit: watch::Sender::new(None),
+            thread: watch::Sender::new(None),
+            profile: watch::Sender::new(None),
+            manual_frequency: watch::Sender::new(None),
+            power_limit,
+            fan_target,
             stats_task_handle: None,
+            telemetry: Arc::new(RwLock::new(BoardTelemetry::default())),
             serial_number,
         })
     }
@@ -281,10 +334,6 @@ impl BitaxeBoard {
         Ok(())
     }
 
-    /// Discover chips connected to this board.
-    ///
-    /// Sends broadcast ReadRegister commands and collects responses
-    /// to identify all chips on the serial bus.
     /// Send a configuration command to the chips.
     ///
     /// This is used during initialization to configure PLL, version rolling, etc.
@@ -308,72 +357,28 @@ impl BitaxeBoard {
         Ok(())
     }
 
+    /// Discover chips connected to this board.
+    ///
+    /// Counts the chips on the serial bus with a broadcast `ChipId` read and
+    /// records each with the address the hash thread will give it.
+    #[instrument(skip_all)]
     async fn discover_chips(&mut self) -> Result<(), BoardError> {
-        // Get a mutable reference to the reader
         let reader = self.data_reader.as_mut().ok_or_else(|| {
             BoardError::InitializationFailed("Data reader already taken".to_string())
         })?;
-
-        // Send a broadcast read to discover chips
-        let discover_cmd = BM13xxProtocol::discover_chips();
-
-        self.data_writer
+        let writer = self
+            .data_writer
             .as_mut()
-            .expect("data_writer should be available during chip discovery")
-            .send(discover_cmd)
-            .await
-            .map_err(BoardError::Communication)?;
-
-        // Wait a bit for responses
-        let timeout = Duration::from_millis(500);
-        let deadline = tokio::time::Instant::now() + timeout;
-
-        while tokio::time::Instant::now() < deadline {
-            tokio::select! {
-                response = reader.next() => {
-                    match response {
-                        Some(Ok(bm13xx::Response::ReadRegister {
-                            chip_address: _,
-                            register: bm13xx::Register::ChipId { chip_type, core_count, address }
-                        })) => {
-                            let chip_id = chip_type.id_bytes();
-                            debug!("Discovered chip {:?} ({:02x}{:02x}) at address {address}",
-                                         chip_type, chip_id[0], chip_id[1]);
-
-                            let chip_info = ChipInfo {
-                                chip_id,
-                                core_count: core_count.into(),
-                                address,
-                                supports_version_rolling: true, // BM1370 supports this
-                            };
-
-                            self.chip_infos.push(chip_info);
-                        }
-                        Some(Ok(_)) => {
-                            warn!("Unexpected response during chip discovery");
-                        }
-                        Some(Err(e)) => {
-                            error!("Error during chip discovery: {e}");
-                        }
-                        None => break,
-                    }
-                }
-                _ = tokio::time::sleep_until(deadline) => {
-                    break;
-                }
-            }
-        }
+            .expect("data_writer should be available during chip discovery");
 
-        if self.chip_infos.is_empty() {
-            Err(BoardError::InitializationFailed(
-                "No chips discovered".to_string(),
-            ))
-        } else {
-            Ok(())
-        }
+        self.chip_infos = bm13xx_thread::enumerate_chain(reader, writer)
+            .await
+            .map_err(|e| BoardError::InitializationFailed(e.to_string()))?;
+        Ok(())
     }
 
     /// Initialize the power controller
+    #[instrument(skip_all)]
     async fn init_power_controller(&mut self) -> Result<(), BoardError> {
         // Clone the I2C bus for the power controller
         let power_i2c = self.i2c.clone();
@@ -435,11 +440,10 @@ impl BitaxeBoard {
                 // Delay before setting voltage
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-                // Set initial output voltage, default for BM1370 from esp-miner
-                const DEFAULT_VOUT: f32 = 1.15;
-                match tps546.set_vout(DEFAULT_VOUT).await {
+                // Set initial output voltage
+                match tps546.set_vout(Self::CORE_VOLTAGE_V).await {
                     Ok(()) => {
-                        debug!("Core voltage set to {DEFAULT_VOUT}V");
+                        debug!("Core voltage set to {}V", Self::CORE_VOLTAGE_V);
 
                         // Wait for voltage to stabilize
                         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -478,6 +482,7 @@ impl BitaxeBoard {
     }
 
     /// Initialize the fan controller
+    #[instrument(skip_all)]
     async fn init_fan_controller(&mut self) -> Result<(), BoardError> {
         // Clone the I2C bus for the fan controller
         let fan_i2c = self.i2c.clone();
@@ -486,7 +491,7 @@ impl BitaxeBoard {
         // Initialize the EMC2101
         match fan.init().await {
             Ok(()) => {
-                // Set fan to full speed until closed-loop control is implemented
+                // Full speed until the fan control task takes over
                 match fan.set_fan_speed(Percent::FULL).await {
                     Ok(()) => {
                         debug!("Fan speed set to 100%");
@@ -507,127 +512,24 @@ impl BitaxeBoard {
         }
     }
 
-    /// Generate frequency ramp steps for smooth PLL transitions
+    /// Spawn a task to periodically read and log management statistics
     ///
-    /// Calculates PLL configurations for each frequency step from start to target.
-    /// Uses the same algorithm as esp-miner for BM1370 chips.
-    #[cfg(test)]
-    fn generate_frequency_ramp_steps(
-        start_mhz: f32,
-        target_mhz: f32,
-        step_mhz: f32,
-    ) -> Vec<bm13xx::protocol::PllConfig> {
-        let mut configs = Vec::new();
-        let mut current = start_mhz;
-
-        // Generate steps from start to target
-        while current <= target_mhz {
-            if let Some(config) = Self::calculate_pll_for_frequency(current) {
-                configs.push(config);
-            } else {
-                warn!("Failed to calculate PLL for {:.2} MHz, skipping", current);
-            }
-
-            current += step_mhz;
-
-            // Prevent overshoot while ensuring target is included
-            if current > target_mhz && (current - step_mhz) < target_mhz {
-                current = target_mhz;
-            }
-        }
-
-        configs
-    }
-
-    /// Calculate PLL configuration for a specific frequency
+    /// Readings are published to the shared telemetry snapshot every few
+    /// seconds so the API sees fresh values; the summary log line is emitted
+    /// less often to keep the log readable.
     ///
-    /// This follows the BM1370/esp-miner algorithm exactly:
-    /// - Crystal frequency: 25 MHz
-    /// - ref_div: 2 or 1 (prefer 2)
-    /// - post_div1: 1-7 (must be >= post_div2)
-    /// - post_div2: 1-7
-    /// - fb_div: 0xa0-0xef (160-239)
-    ///
-    /// Formula: freq = 25 * fb_div / (ref_div * post_div1 * post_div2)
-    ///
-    /// Note: esp-miner uses a "first-found" algorithm rather than finding the optimal
-    /// configuration. This implementation matches that behavior for consistency.
-    #[cfg(test)]
-    fn calculate_pll_for_frequency(target_freq: f32) -> Option<bm13xx::protocol::PllConfig> {
-        const CRYSTAL_FREQ: f32 = 25.0;
-        const MAX_FREQ_ERROR: f32 = 1.0; // Maximum acceptable frequency error in MHz
-
-        let mut best_fb_div = 0u8;
-        let mut best_ref_div = 0u8;
-        let mut best_post_div1 = 0u8;
-        let mut best_post_div2 = 0u8;
-        let mut min_error = 10.0; // esp-miner starts with min_difference = 10
-
-        // Follow esp-miner's exact loop order to get same results
-        // Stops at first solution under max_diff (1.0 MHz)
-        for ref_div in [2, 1] {
-            if best_fb_div != 0 {
-                break;
-            } // Stop once solution is found
-
-            for post_div1 in (1..=7).rev() {
-                if best_fb_div != 0 {
-                    break;
-                }
-
-                for post_div2 in (1..=7).rev() {
-                    if best_fb_div != 0 {
-                        break;
-                    }
-
-                    if post_div1 >= post_div2 {
-                        // Calculate required feedback divider (esp-miner uses round())
-                        let fb_div_f =
-                            (post_div1 * post_div2) as f32 * target_freq * ref_div as f32
-                                / CRYSTAL_FREQ;
-                        let fb_div = fb_div_f.round() as u8;
-
-                        // Check if fb_div is in valid range
-                        if (0xa0..=0xef).contains(&fb_div) {
-                            // Calculate actual frequency with this configuration
-                            let actual_freq = CRYSTAL_FREQ * fb_div as f32
-                                / (ref_div * post_div1 * post_div2) as f32;
-                            let error = (actual_freq - target_freq).abs();
-
-                            // esp-miner accepts first solution with error < min_difference AND < max_diff
-                            if error < min_error && error < MAX_FREQ_ERROR {
-                                best_fb_div = fb_div;
-                                best_ref_div = ref_div;
-                                best_post_div1 = post_div1;
-                                best_post_div2 = post_div2;
-                                min_error = error;
-                                // esp-miner stops at first solution meeting criteria
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        if best_fb_div == 0 {
-            return None;
-        }
-
-        // Encode post dividers as per hardware format
-        let post_div = ((best_post_div1 - 1) << 4) | (best_post_div2 - 1);
-
-        // PllConfig::new() automatically calculates the flag (0x40 or 0x50) based on VCO frequency
-        Some(bm13xx::protocol::PllConfig::new(
-            best_fb_div,
-            best_ref_div,
-            post_div,
-        ))
-    }
-
-    /// Spawn a task to periodically log management statistics
+    /// The task also owns the chips' operating point. Its base is the
+    /// selected performance profile, else the tuned point, else the stock
+    /// frequency and voltage; the [`ThermalGovernor`] and [`PowerGovernor`]
+    /// cap it, or the [`Autotuner`] replaces it while a tuning sweep runs.
+    /// Frequency changes go to the hash thread, and core voltage changes are
+    /// applied after the chips slow down or before they speed up. On
+    /// overtemperature the task removes the thread, holds the chips in reset,
+    /// turns off the core voltage and reports a [`BoardEvent::BoardFault`].
     fn spawn_stats_monitor(&mut self) {
         // Clone data needed for the monitoring task
         let i2c = self.i2c.clone();
+        let telemetry = Arc::clone(&self.telemetry);
 
         // Clone event channel for reporting critical faults
         let event_tx = self.event_tx.clone();
@@ -638,15 +540,101 @@ impl BitaxeBoard {
             .clone()
             .expect("Regulator must be initialized before spawning stats monitor");
 
+        // Profiles, tuning and limits act on the thread, chips and regulator
+        let stock = TunedPoint {
+            frequency_mhz: bm13xx_thread::target_frequency_mhz(&self.chip_infos),
+            core_voltage_v: Self::CORE_VOLTAGE_V,
+        };
+        let mut thermal = ThermalGovernor::new(&self.hardware, stock.frequency_mhz);
+        let mut power =
+            PowerGovernor::new(&self.hardware, stock.frequency_mhz, stock.core_voltage_v);
+        let hardware = self.hardware.clone();
+        let chip_profiles = self.chip_profiles().cloned();
+        let thread = self.thread.subscribe();
+        let mut profile_rx = self.profile.subscribe();
+        let mut manual_rx = self.manual_frequency.subscribe();
+        let mut power_limit_rx = self.power_limit.subscribe();
+        let fan_target = self.fan_target.clone();
+        let frequency_limit = self.frequency_limit.clone();
+        let thread_shutdown = self.thread_shutdown.clone();
+        let mut asic_nrst = self
+            .asic_nrst
+            .clone()
+            .expect("Reset pin must be initialized before spawning stats monitor");
+
         // Capture board info for logging
         let board_info = self.board_info();
         let board_model = board_info.model.clone();
         let board_serial = board_info.serial_number.clone();
 
         let handle = tokio::spawn(async move {
-            const STATS_INTERVAL: Duration = Duration::from_secs(30);
-            let mut interval = tokio::time::interval(STATS_INTERVAL);
+            const TELEMETRY_INTERVAL: Duration = Duration::from_secs(5);
+            const LOG_EVERY_N_READINGS: u32 = 6; // 30 seconds
+            let mut interval = tokio::time::interval(TELEMETRY_INTERVAL);
             interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut readings: u32 = 0;
+            let mut overtemperature = false;
+            let mut thermal_limit: Option<f32> = None;
+            let mut applied = AppliedPoint {
+                core_voltage_v: stock.core_voltage_v,
+                target_mhz: None,
+            };
+            let mut governed = stock;
+            let mut active_profile: Option<PerformanceProfile> = None;
+            let mut manual_mhz: Option<f32> = None;
+
+            // Run at a tuned profile, or sweep for one
+            let mut tuned: Option<TunedPoint> = None;
+            let mut tuner: Option<Autotuner> = None;
+            let tuning = &hardware.tuning;
+            if tuning.goal != TuningGoal::Off {
+                let profile = match board_serial.clone() {
+                    Some(serial) => {
+                        let dir = tuning.dir.clone();
+                        tokio::task::spawn_blocking(move || tuning::load_profile(&dir, &serial))
+                            .await
+                            .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+                            .unwrap_or_else(|e| {
+                                warn!(error = %e, "Cannot read tuned profile; tuning again.");
+                                None
+                            })
+                    }
+                    None => None,
+                };
+                let profile = match profile {
+                    Some(profile)
+                        if profile.goal == tuning.goal
+                            && !profile.within(tuning, stock.frequency_mhz) =>
+                    {
+                        warn!(
+                            frequency_mhz = profile.frequency_mhz,
+                            core_voltage_v = profile.core_voltage_v,
+                            "Tuned profile is outside the tuning limits; tuning again."
+                        );
+                        None
+                    }
+                    profile => profile,
+                };
+                match profile {
+                    Some(profile) if profile.goal == tuning.goal => {
+                        info!(
+                            goal = ?profile.goal,
+                            frequency_mhz = profile.frequency_mhz,
+                            core_voltage_v = profile.core_voltage_v,
+                            "Using tuned profile."
+                        );
+                        tuned = Some(profile.point());
+                    }
+                    _ => {
+                        info!(goal = ?tuning.goal, "Starting autotuning sweep.");
+                        tuner = Some(Autotuner::new(
+                            &hardware,
+                            stock.frequency_mhz,
+                            time::Instant::now().into_std(),
+                        ));
+                    }
+                }
+            }
 
             // Create fan controller for the stats task
             let mut fan = Emc2101::new(i2c);
@@ -655,67 +643,254 @@ impl BitaxeBoard {
             interval.tick().await;
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    Ok(()) = profile_rx.changed() => {}
+                    Ok(()) = manual_rx.changed() => {}
+                    Ok(()) = power_limit_rx.changed() => {}
+                }
 
-                // Read temperature
-                let temp = match fan.get_external_temperature().await {
-                    Ok(t) => format!("{:.1} degC", t),
-                    Err(_) => "N/A".to_string(),
-                };
+                // A selected profile or manual frequency takes over from tuning
+                let selected = *profile_rx.borrow_and_update();
+                let manual = *manual_rx.borrow_and_update();
+                if (selected, manual) != (active_profile, manual_mhz) {
+                    if (selected.is_some() || manual.is_some()) && tuner.take().is_some() {
+                        info!("Autotuning sweep abandoned for a chosen operating point.");
+                    }
+                    active_profile = selected;
+                    manual_mhz = manual;
+                }
+                let settings = active_profile
+                    .and_then(|profile| chip_profiles.as_ref().map(|p| p.get(profile)));
+                let mut base = settings.map_or(tuned.unwrap_or(stock), |settings| TunedPoint {
+                    frequency_mhz: settings.frequency_mhz,
+                    core_voltage_v: settings.core_voltage_v,
+                });
+                if let Some(mhz) = manual_mhz {
+                    base.frequency_mhz = mhz;
+                }
+                if base != governed {
+                    thermal = ThermalGovernor::new(&hardware, base.frequency_mhz);
+                    power = PowerGovernor::new(&hardware, base.frequency_mhz, base.core_voltage_v);
+                    governed = base;
+                }
+                let power_limit = *power_limit_rx.borrow_and_update();
+                if power_limit != power.limit() {
+                    power.set_limit(power_limit);
+                }
+                fan_target.send_if_modified(|target| {
+                    let temp = settings.map_or(hardware.fan.target_temp, |s| s.fan_target_temp);
+                    let changed = *target != temp;
+                    *target = temp;
+                    changed
+                });
 
-                // Read fan speed
-                let fan_speed = match fan.get_fan_speed().await {
-                    Ok(speed_percent) => format!("{}%", u8::from(speed_percent)),
-                    Err(_) => "N/A".to_string(),
-                };
+                let asic_temp_c = fan.get_external_temperature().await.ok();
+                let fan_percent = fan.get_fan_speed().await.ok().map(u8::from);
 
                 // Read fan RPM (if TACH is connected)
                 let fan_rpm = match fan.get_tach_count().await {
                     Ok(count) => {
                         trace!("TACH count: 0x{:04x}", count);
-                        match fan.get_rpm().await {
-                            Ok(rpm) if rpm > 0 => format!("{} RPM", rpm),
-                            Ok(_) => format!("0 RPM (TACH: 0x{:04x})", count),
-                            Err(_) => "N/A".to_string(),
-                        }
+                        fan.get_rpm().await.ok()
                     }
                     Err(e) => {
                         trace!("Failed to read TACH: {}", e);
-                        "N/A".to_string()
+                        None
                     }
                 };
 
                 // Read power stats using the shared regulator
-                let vin = match regulator.lock().await.get_vin().await {
-                    Ok(mv) => format!("{:.2}V", mv as f32 / 1000.0),
-                    Err(_) => "N/A".to_string(),
+                let input_voltage_v = regulator
+                    .lock()
+                    .await
+                    .get_vin()
+                    .await
+                    .ok()
+                    .map(|mv| mv as f32 / 1000.0);
+                let core_voltage_v = regulator
+                    .lock()
+                    .await
+                    .get_vout()
+                    .await
+                    .ok()
+                    .map(|mv| mv as f32 / 1000.0);
+                let current_a = regulator
+                    .lock()
+                    .await
+                    .get_iout()
+                    .await
+                    .ok()
+                    .map(|ma| ma as f32 / 1000.0);
+                let power_w = regulator
+                    .lock()
+                    .await
+                    .get_power()
+                    .await
+                    .ok()
+                    .map(|mw| mw as f32 / 1000.0);
+                let vr_temp_c = regulator
+                    .lock()
+                    .await
+                    .get_temperature()
+                    .await
+                    .ok()
+                    .map(|t| t as f32);
+
+                *telemetry.write().unwrap() = BoardTelemetry {
+                    asic_temp_c,
+                    vr_temp_c,
+                    fan_percent,
+                    fan_rpm,
+                    fan_mode: None, // Added by telemetry() from the board's state
+                    profile: None,  // Likewise
+                    power_w,
+                    current_a,
+                    input_voltage_v,
+                    core_voltage_v,
                 };
 
-                let vout = match regulator.lock().await.get_vout().await {
-                    Ok(mv) => {
-                        let volts = mv as f32 / 1000.0;
-                        if volts < 1.0 {
-                            warn!("Core voltage low: {:.3}V", volts);
+                match thermal.assess(asic_temp_c, vr_temp_c) {
+                    ThermalDecision::Run {
+                        frequency_limit_mhz,
+                    } if frequency_limit_mhz != thermal_limit => {
+                        match frequency_limit_mhz {
+                            Some(mhz) => warn!(
+                                asic_temp_c,
+                                vr_temp_c,
+                                frequency_mhz = mhz,
+                                "Approaching temperature limit; throttling chips."
+                            ),
+                            None => info!(
+                                asic_temp_c,
+                                vr_temp_c, "Temperatures back in range; thermal throttling lifted."
+                            ),
                         }
-                        format!("{:.3}V", volts)
+                        thermal_limit = frequency_limit_mhz;
                     }
-                    Err(_) => "N/A".to_string(),
-                };
+                    ThermalDecision::Run { .. } => {}
+                    ThermalDecision::Overtemperature { fault } if !overtemperature => {
+                        overtemperature = true;
+                        error!(%fault, "CRITICAL: Overtemperature; shutting down chips.");
+
+                        thread_shutdown.send_replace(ThreadRemovalSignal::HardwareFault {
+                            description: fault.clone(),
+                        });
+                        if let Err(e) = asic_nrst.write(PinValue::Low).await {
+                            error!("Failed to hold chips in reset: {}", e);
+                        }
+                        if let Err(e) = regulator.lock().await.set_vout(0.0).await {
+                            error!("Failed to turn off core voltage: {}", e);
+                        }
 
-                let iout = match regulator.lock().await.get_iout().await {
-                    Ok(ma) => format!("{:.2}A", ma as f32 / 1000.0),
-                    Err(_) => "N/A".to_string(),
-                };
+                        if let Some(ref tx) = event_tx {
+                            let fault_event = BoardEvent::BoardFault {
+                                component: "thermal".to_string(),
+                                fault,
+                                recoverable: false,
+                            };
+                            if let Err(send_err) = tx.send(fault_event).await {
+                                error!("Failed to send board fault event: {}", send_err);
+                            }
+                        }
+                    }
+                    // Already shut down; keep reporting temperatures
+                    ThermalDecision::Overtemperature { .. } => {}
+                }
 
-                let power_w = match regulator.lock().await.get_power().await {
-                    Ok(mw) => format!("{:.1}W", mw as f32 / 1000.0),
-                    Err(_) => "N/A".to_string(),
-                };
+                if !overtemperature {
+                    if let Some(sweep) = tuner.as_mut() {
+                        let status = thread.borrow().as_ref().map(BM13xxControl::status);
+                        let reading =
+                            tuning_reading(status.as_ref(), power_w, asic_temp_c, vr_temp_c);
+                        match sweep.update(time::Instant::now().into_std(), reading) {
+                            Some(TuningUpdate::Try(point)) => info!(
+                                frequency_mhz = point.frequency_mhz,
+                                core_voltage_v = point.core_voltage_v,
+                                "Autotuning: trying next point."
+                            ),
+                            Some(TuningUpdate::Finished(Some(profile))) => {
+                                info!(
+                                    goal = ?profile.goal,
+                                    frequency_mhz = profile.frequency_mhz,
+                                    core_voltage_v = profile.core_voltage_v,
+                                    hashrate = profile.hashrate,
+                                    efficiency_j_th = profile.efficiency_j_th(),
+                                    "Autotuning finished."
+                                );
+                                match board_serial.as_deref() {
+                                    Some(serial) => {
+                                        // Off the runtime threads; nothing waits for the write
+                                        let dir = tuning.dir.clone();
+                                        let serial = serial.to_string();
+                                        let profile = profile.clone();
+                                        tokio::task::spawn_blocking(move || {
+                                            if let Err(e) =
+                                                tuning::save_profile(&dir, &serial, &profile)
+                                            {
+                                                warn!(error = %e, "Failed to save tuned profile.");
+                                            }
+                                        });
+                                    }
+                                    None => warn!(
+                                        "Board has no serial number; tuned profile not saved."
+                                    ),
+                                }
+                                tuned = Some(profile.point());
+                                tuner = None;
+                            }
+                            Some(TuningUpdate::Finished(None)) => {
+                                warn!("Autotuning found no stable point; using defaults.");
+                                tuner = None;
+                            }
+                            None => {}
+                        }
+                    } else if let Some(point) = power.update(power_w) {
+                        match point.frequency_limit_mhz {
+                            Some(mhz) => info!(
+                                power_w,
+                                frequency_mhz = mhz,
+                                core_voltage_v = point.core_voltage_v,
+                                "Adjusting chips to the power limit."
+                            ),
+                            None => info!(power_w, "Power headroom restored; chips at full speed."),
+                        }
+                    }
 
-                let vr_temp = match regulator.lock().await.get_temperature().await {
-                    Ok(t) => format!("{} degC", t),
-                    Err(_) => "N/A".to_string(),
-                };
+                    // A sweep caps the stock frequency rather than retargeting
+                    let (core_voltage_v, target_mhz, limit) = match &tuner {
+                        Some(sweep) => (
+                            sweep.point().core_voltage_v,
+                            stock.frequency_mhz,
+                            Some(sweep.point().frequency_mhz),
+                        ),
+                        None => {
+                            let point = power.operating_point();
+                            (
+                                point.core_voltage_v,
+                                governed.frequency_mhz,
+                                point.frequency_limit_mhz,
+                            )
+                        }
+                    };
+                    let control = thread.borrow().clone();
+                    set_operating_point(
+                        &regulator,
+                        &frequency_limit,
+                        control.as_ref(),
+                        &mut applied,
+                        core_voltage_v,
+                        target_mhz,
+                        lowest_limit(thermal_limit, limit),
+                    )
+                    .await;
+                }
+
+                if let Some(volts) = core_voltage_v {
+                    if volts < 1.0 {
+                        warn!("Core voltage low: {:.3}V", volts);
+                    }
+                }
 
                 // Check power status - critical faults will return error
                 if let Err(e) = regulator.lock().await.check_status().await {
@@ -745,17 +920,28 @@ impl BitaxeBoard {
                     continue;
                 }
 
+                readings += 1;
+                if !readings.is_multiple_of(LOG_EVERY_N_READINGS) {
+                    continue;
+                }
+
+                let fan_rpm = match fan_rpm {
+                    Some(rpm) if rpm > 0 => format!("{} RPM", rpm),
+                    Some(_) => "0 RPM".to_string(),
+                    None => "N/A".to_string(),
+                };
+
                 info!(
                     board = %board_model,
                     serial = ?board_serial,
-                    asic_temp = %temp,
-                    fan_speed = %fan_speed,
+                    asic_temp = %format_reading(asic_temp_c, |t| format!("{:.1} degC", t)),
+                    fan_speed = %format_reading(fan_percent, |p| format!("{}%", p)),
                     fan_rpm = %fan_rpm,
-                    vr_temp = %vr_temp,
-                    power = %power_w,
-                    current = %iout,
-                    vin = %vin,
-                    vout = %vout,
+                    vr_temp = %format_reading(vr_temp_c, |t| format!("{} degC", t)),
+                    power = %format_reading(power_w, |w| format!("{:.1}W", w)),
+                    current = %format_reading(current_a, |a| format!("{:.2}A", a)),
+                    vin = %format_reading(input_voltage_v, |v| format!("{:.2}V", v)),
+                    vout = %format_reading(core_voltage_v, |v| format!("{:.3}V", v)),
                     "Board status."
                 );
             }
@@ -763,6 +949,198 @@ impl BitaxeBoard {
 
         self.stats_task_handle = Some(handle);
     }
+
+    /// Configured performance profiles for the discovered chip type.
+    fn chip_profiles(&self) -> Option<&ChipProfiles> {
+        let chip = self.chip_infos.first()?;
+        let name = bm13xx::protocol::ChipType::from(chip.chip_id).name()?;
+        self.hardware.profiles.get(name)
+    }
+
+    /// Spawn the fan control task.
+    ///
+    /// In [`FanMode::Auto`] the duty cycle follows a [`FanController`] fed
+    /// with the ASIC temperature; a manual mode holds its duty until the mode
+    /// changes. Any mode change restarts the loop from full speed.
+    fn spawn_fan_control(&mut self) {
+        const FAN_CONTROL_INTERVAL: Duration = Duration::from_secs(2);
+
+        let mut fan = Emc2101::new(self.i2c.clone());
+        let mut controller = FanController::new(&self.hardware);
+        let mut mode_rx = self.fan_mode.subscribe();
+        let mut target_rx = self.fan_target.subscribe();
+
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(FAN_CONTROL_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut last_update = time::Instant::now();
+            let mut applied: Option<u8> = None;
+            let mut sensor_ok = true;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    changed = mode_rx.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        controller.reset();
+                    }
+                    Ok(()) = target_rx.changed() => {
+                        controller.set_target_temp(*target_rx.borrow_and_update());
+                    }
+                }
+
+                let now = time::Instant::now();
+                let dt = now - last_update;
+                last_update = now;
+
+                let mode = *mode_rx.borrow_and_update();
+                let duty = match mode {
+                    FanMode::Manual { percent } => percent,
+                    FanMode::Auto => {
+                        let temp_c = match fan.get_external_temperature().await {
+                            Ok(t) if fan_control::is_plausible(t) => Some(t),
+                            Ok(t) => {
+                                if sensor_ok {
+                                    warn!(temp_c = t, "Implausible ASIC temperature; fan to 100%.");
+                                }
+                                None
+                            }
+                            Err(e) => {
+                                if sensor_ok {
+                                    warn!(error = %e, "Cannot read ASIC temperature; fan to 100%.");
+                                }
+                                None
+                            }
+                        };
+                        if temp_c.is_some() && !sensor_ok {
+                            info!("ASIC temperature readable again; resuming fan control.");
+                        }
+                        sensor_ok = temp_c.is_some();
+
+                        let rpm = fan.get_rpm().await.ok();
+                        controller.update(temp_c, rpm, dt)
+                    }
+                };
+
+                if applied != Some(duty) {
+                    match fan.set_fan_speed(Percent::new_clamped(duty)).await {
+                        Ok(()) => {
+                            trace!(duty, ?mode, "Fan duty set.");
+                            applied = Some(duty);
+                        }
+                        Err(e) => {
+                            warn!(error = %e, "Failed to set fan speed.");
+                            applied = None;
+                        }
+                    }
+                }
+            }
+        });
+
+        self.fan_task_handle = Some(handle);
+    }
+}
+
+/// Core voltage and thread target frequency last applied.
+#[derive(Debug, Clone, Copy)]
+struct AppliedPoint {
+    core_voltage_v: f32,
+    /// `None` until the thread exists and has accepted a target
+    target_mhz: Option<f32>,
+}
+
+/// Move the chips to `core_voltage_v`, and the hash thread to `target_mhz`
+/// capped at `limit`.
+///
+/// Voltage is lowered only after the chips have slowed down and raised
+/// before they speed up, so they never run faster than it supports.
+async fn set_operating_point(
+    regulator: &Mutex<Tps546<BitaxeRawI2c>>,
+    frequency_limit: &watch::Sender<Option<f32>>,
+    thread: Option<&BM13xxControl>,
+    applied: &mut AppliedPoint,
+    core_voltage_v: f32,
+    target_mhz: f32,
+    limit: Option<f32>,
+) {
+    const VOLTAGE_SETTLE: Duration = Duration::from_secs(1);
+
+    if core_voltage_v == applied.core_voltage_v {
+        set_frequency(frequency_limit, thread, applied, target_mhz, limit).await;
+        return;
+    }
+
+    let lowering = core_voltage_v < applied.core_voltage_v;
+    if lowering && set_frequency(frequency_limit, thread, applied, target_mhz, limit).await {
+        time::sleep(VOLTAGE_SETTLE).await;
+    }
+    match regulator.lock().await.set_vout(core_voltage_v).await {
+        Ok(()) => applied.core_voltage_v = core_voltage_v,
+        Err(e) => warn!("Failed to set core voltage: {}", e),
+    }
+    if !lowering {
+        time::sleep(VOLTAGE_SETTLE).await;
+        set_frequency(frequency_limit, thread, applied, target_mhz, limit).await;
+    }
+}
+
+/// Publish the frequency cap and send the hash thread its target, if either
+/// changed; returns whether anything did.
+async fn set_frequency(
+    frequency_limit: &watch::Sender<Option<f32>>,
+    thread: Option<&BM13xxControl>,
+    applied: &mut AppliedPoint,
+    target_mhz: f32,
+    limit: Option<f32>,
+) -> bool {
+    let mut changed = frequency_limit.send_if_modified(|current| {
+        let changed = *current != limit;
+        *current = limit;
+        changed
+    });
+
+    if let Some(thread) = thread {
+        if applied.target_mhz != Some(target_mhz) {
+            match thread.set_frequency(target_mhz).await {
+                Ok(_) => {
+                    applied.target_mhz = Some(target_mhz);
+                    changed = true;
+                }
+                Err(e) => warn!(error = %e, target_mhz, "Failed to set chip frequency."),
+            }
+        }
+    }
+    changed
+}
+
+/// Autotuner reading from the hash thread's status, if it exists yet, and
+/// the board's sensors.
+fn tuning_reading(
+    status: Option<&HashThreadStatus>,
+    power_w: Option<f32>,
+    asic_temp_c: Option<f32>,
+    vr_temp_c: Option<f32>,
+) -> TuningReading {
+    let mut reading = TuningReading {
+        power_w,
+        asic_temp_c,
+        vr_temp_c,
+        ..Default::default()
+    };
+    if let Some(status) = status {
+        reading.hashing = status.is_active;
+        reading.hashes = status.hashes;
+        reading.nonces = status.chips.iter().map(|chip| chip.nonces).sum();
+        reading.hardware_errors = status.chips.iter().map(|chip| chip.hardware_errors).sum();
+    }
+    reading
+}
+
+/// Format an optional sensor reading for logging, "N/A" if unavailable.
+fn format_reading<T>(reading: Option<T>, format: impl FnOnce(T) -> String) -> String {
+    reading.map(format).unwrap_or_else(|| "N/A".to_string())
 }
 
 #[async_trait]
@@ -829,17 +1207,6 @@ impl Board for BitaxeBoard {
 
         debug!(count = self.chip_infos.len(), "Discovered chips");
 
-        // Verify expected BM1370 chip was found
-        if let Some(first_chip) = self.chip_infos.first() {
-            if first_chip.chip_id != Self::EXPECTED_CHIP_ID {
-                return Err(BoardError::InitializationFailed(format!(
-                    "Wrong chip type for Bitaxe Gamma: expected BM1370 ({:02x}{:02x}), found {:02x}{:02x}",
-                    Self::EXPECTED_CHIP_ID[0], Self::EXPECTED_CHIP_ID[1],
-                    first_chip.chip_id[0], first_chip.chip_id[1]
-                )));
-            }
-        }
-
         // Put chip back in reset
         self.hold_in_reset().await?;
 
@@ -851,6 +1218,11 @@ impl Board for BitaxeBoard {
         // Spawn statistics monitoring task
         self.spawn_stats_monitor();
 
+        // Hand the fan over to closed-loop control
+        if self.fan_controller.is_some() {
+            self.spawn_fan_control();
+        }
+
         // Return a dummy receiver for trait compatibility
         let (dummy_tx, dummy_rx) = tokio::sync::mpsc::channel(1);
         drop(dummy_tx);
@@ -873,19 +1245,27 @@ impl Board for BitaxeBoard {
         }
     }
 
+    fn telemetry(&self) -> BoardTelemetry {
+        let mut telemetry = self.telemetry.read().unwrap().clone();
+        telemetry.fan_mode = self
+            .fan_controller
+            .as_ref()
+            .map(|_| *self.fan_mode.borrow());
+        telemetry.profile = *self.profile.borrow();
+        telemetry
+    }
+
     fn take_event_receiver(&mut self) -> Option<tokio::sync::mpsc::Receiver<BoardEvent>> {
         self.event_rx.take()
     }
 
     async fn shutdown(&mut self) -> Result<(), BoardError> {
         // Signal hash threads to shut down gracefully
-        if let Some(ref tx) = self.thread_shutdown {
-            if let Err(e) = tx.send(ThreadRemovalSignal::Shutdown) {
-                warn!("Failed to send shutdown signal to threads: {}", e);
-            } else {
-                debug!("Sent shutdown signal to hash threads");
-                tokio::time::sleep(Duration::from_millis(200)).await;
-            }
+        if self.thread_shutdown.receiver_count() > 0 {
+            self.thread_shutdown
+                .send_replace(ThreadRemovalSignal::Shutdown);
+            debug!("Sent shutdown signal to hash threads");
+            tokio::time::sleep(Duration::from_millis(200)).await;
         }
 
         // Hold chips in reset
@@ -900,6 +1280,9 @@ impl Board for BitaxeBoard {
         }
 
         // Reduce fan speed (no more heat generation)
+        if let Some(handle) = self.fan_task_handle.take() {
+            handle.abort();
+        }
         if let Some(ref mut fan) = self.fan_controller {
             let shutdown_speed = Percent::new_clamped(25);
             if let Err(e) = fan.set_fan_speed(shutdown_speed).await {
@@ -915,12 +1298,63 @@ impl Board for BitaxeBoard {
         Ok(())
     }
 
-    async fn create_hash_threads(&mut self) -> Result<Vec<Box<dyn HashThread>>, BoardError> {
-        // Create removal signal channel (starts as Running)
-        let (removal_tx, removal_rx) = watch::channel(ThreadRemovalSignal::Running);
+    async fn set_fan_mode(&mut self, mode: FanMode) -> Result<(), BoardError> {
+        if self.fan_task_handle.is_none() {
+            return Err(BoardError::HardwareControl(
+                "fan controller not available".into(),
+            ));
+        }
+        let mode = match mode {
+            FanMode::Manual { percent } => FanMode::Manual {
+                percent: percent.min(100),
+            },
+            FanMode::Auto => FanMode::Auto,
+        };
+        self.fan_mode.send_replace(mode);
+        info!(?mode, "Fan mode set.");
+        Ok(())
+    }
 
-        // Store removal signal sender for later shutdown
-        self.thread_shutdown = Some(removal_tx);
+    async fn set_profile(&mut self, profile: Option<PerformanceProfile>) -> Result<(), BoardError> {
+        if profile.is_some() && self.chip_profiles().is_none() {
+            return Err(BoardError::HardwareControl(
+                "no performance profiles configured for this chip".into(),
+            ));
+        }
+        self.profile.send_replace(profile);
+        self.manual_frequency.send_replace(None);
+        info!(
+            profile = profile.map(PerformanceProfile::as_str),
+            "Performance profile set."
+        );
+        Ok(())
+    }
+
+    async fn set_power_limit(&mut self, limit_w: Option<f32>) -> Result<(), BoardError> {
+        if matches!(limit_w, Some(limit) if limit <= 0.0) {
+            return Err(BoardError::HardwareControl(
+                "power limit must be positive".into(),
+            ));
+        }
+        self.power_limit.send_replace(limit_w);
+        info!(power_limit_w = limit_w, "Power limit set.");
+        Ok(())
+    }
+
+    async fn set_frequency(&mut self, frequency_mhz: f32) -> Result<(), BoardError> {
+        if bm13xx_thread::calculate_pll_for_frequency(frequency_mhz).is_none() {
+            return Err(BoardError::HardwareControl(format!(
+                "no PLL setting for {frequency_mhz} MHz"
+            )));
+        }
+        self.manual_frequency.send_replace(Some(frequency_mhz));
+        info!(frequency_mhz, "Target frequency set.");
+        Ok(())
+    }
+
+    async fn create_hash_threads(&mut self) -> Result<Vec<Box<dyn HashThread>>, BoardError> {
+        // Subscribe to the removal signal (starts as Running)
+        let removal_rx = self.thread_shutdown.subscribe();
 
         // Take ownership of serial I/O streams
         let data_reader = self
@@ -951,11 +1385,20 @@ impl Board for BitaxeBoard {
                 .ok_or(BoardError::InitializationFailed(
                     "Voltage regulator not initialized".into(),
                 ))?,
+            frequency_limit: self.frequency_limit.subscribe(),
         };
 
         // Create BM13xxThread with streams and peripherals
-        let thread = BM13xxThread::new(data_reader, data_writer, peripherals, removal_rx);
+        let thread = BM13xxThread::new(
+            data_reader,
+            data_writer,
+            peripherals,
+            removal_rx,
+            &self.chip_infos,
+            Self::CHIPS_PER_DOMAIN,
+        );
 
+        self.thread.send_replace(Some(thread.control()));
         debug!("Created BM13xx hash thread from BitaxeBoard");
 
         Ok(vec![Box::new(thread)])
@@ -965,6 +1408,7 @@ impl Board for BitaxeBoard {
 // Factory function to create a Bitaxe board from USB device info
 async fn create_from_usb(
     device: crate::transport::UsbDeviceInfo,
+    hardware: HardwareConfig,
 ) -> crate::error::Result<Box<dyn Board + Send>> {
     use tokio_serial::SerialPortBuilderExt;
 
@@ -990,8 +1434,13 @@ async fn create_from_usb(
     let control_port = tokio_serial::new(&serial_ports[0], 115200).open_native_async()?;
 
     // Create the board with the control port and data port path
-    let mut board = BitaxeBoard::new(control_port, &serial_ports[1], device.serial_number.clone())
-        .map_err(|e| crate::error::Error::Hardware(format!("Failed to create board: {}", e)))?;
+    let mut board = BitaxeBoard::new(
+        control_port,
+        &serial_ports[1],
+        device.serial_number.clone(),
+        hardware,
+    )
+    .map_err(|e| crate::error::Error::Hardware(format!("Failed to create board: {}", e)))?;
 
     // Initialize the board (reset, discover chips, start event monitoring)
     let _dummy_rx = board
@@ -1020,123 +1469,6 @@ inventory::submit! {
             serial_pattern: Match::Any,
         },
         name: "Bitaxe Gamma",
-        create_fn: |device| Box::pin(create_from_usb(device)),
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_pll_calculations_match_reference() {
-        // Test cases from the Bitaxe Gamma protocol capture
-        // Format: (freq_mhz, expected_fb_div, expected_ref_div, expected_post_div)
-        // Note: The capture shows bytes [55 AA 51 09 00 08 FLAG FB_DIV REF POST CRC]
-        // Format: (freq_mhz, expected_flag, expected_fb_div, expected_ref_div, expected_post_div)
-        let test_cases = vec![
-            // From the capture document:
-            (62.50, 0x50, 0xD2, 0x02, 0x65), // tx: [55 AA 51 09 00 08 50 D2 02 65 05]
-            (68.75, 0x50, 0xE7, 0x02, 0x65), // tx: [55 AA 51 09 00 08 50 E7 02 65 1C]
-            (75.00, 0x50, 0xD2, 0x02, 0x64), // tx: [55 AA 51 09 00 08 50 D2 02 64 00]
-            (81.25, 0x50, 0xE4, 0x02, 0x64), // tx: [55 AA 51 09 00 08 50 E4 02 64 14]
-            (87.50, 0x50, 0xC4, 0x02, 0x63), // tx: [55 AA 51 09 00 08 50 C4 02 63 18]
-            (93.75, 0x50, 0xD2, 0x02, 0x63), // tx: [55 AA 51 09 00 08 50 D2 02 63 1B]
-            (100.00, 0x50, 0xE0, 0x02, 0x63), // tx: [55 AA 51 09 00 08 50 E0 02 63 00]
-            (525.00, 0x50, 0xD2, 0x02, 0x40), // tx: [55 AA 51 09 00 08 50 D2 02 40 05] (final)
-        ];
-
-        for (freq_mhz, expected_flag, expected_fb, expected_ref, expected_post) in test_cases {
-            let config = BitaxeBoard::calculate_pll_for_frequency(freq_mhz)
-                .unwrap_or_else(|| panic!("Failed to calculate PLL for {} MHz", freq_mhz));
-
-            assert_eq!(
-                config.flag, expected_flag,
-                "Flag mismatch for {} MHz: expected 0x{:02X}, got 0x{:02X}",
-                freq_mhz, expected_flag, config.flag
-            );
-
-            assert_eq!(
-                config.fb_div, expected_fb,
-                "FB divider mismatch for {} MHz: expected 0x{:02X}, got 0x{:02X}",
-                freq_mhz, expected_fb, config.fb_div
-            );
-
-            assert_eq!(
-                config.ref_div, expected_ref,
-                "Ref divider mismatch for {} MHz: expected {}, got {}",
-                freq_mhz, expected_ref, config.ref_div
-            );
-
-            assert_eq!(
-                config.post_div, expected_post,
-                "Post divider mismatch for {} MHz: expected 0x{:02X}, got 0x{:02X}",
-                freq_mhz, expected_post, config.post_div
-            );
-
-            // Verify the frequency calculation
-            let post_div1 = ((config.post_div >> 4) & 0xF) + 1;
-            let post_div2 = (config.post_div & 0xF) + 1;
-            let calculated_freq =
-                25.0 * config.fb_div as f32 / (config.ref_div * post_div1 * post_div2) as f32;
-
-            assert!(
-                (calculated_freq - freq_mhz).abs() < 1.0,
-                "Frequency calculation error for {} MHz: calculated {} MHz",
-                freq_mhz,
-                calculated_freq
-            );
-        }
-    }
-
-    #[test]
-    fn test_frequency_ramp_generation() {
-        // Verify correct number of steps are generated
-        let steps = BitaxeBoard::generate_frequency_ramp_steps(56.25, 525.0, 6.25);
-
-        // Should have steps from 56.25 to 525.0 in 6.25 MHz increments
-        // That's (525 - 56.25) / 6.25 + 1 = 75.0 steps
-        assert_eq!(steps.len(), 76, "Expected 76 frequency steps");
-
-        // Verify first and last frequencies by calculating them back
-        if let Some(first) = steps.first() {
-            let post_div1 = ((first.post_div >> 4) & 0xF) + 1;
-            let post_div2 = (first.post_div & 0xF) + 1;
-            let first_freq =
-                25.0 * first.fb_div as f32 / (first.ref_div * post_div1 * post_div2) as f32;
-            assert!(
-                (first_freq - 56.25).abs() < 1.0,
-                "First frequency should be ~56.25 MHz"
-            );
-        }
-
-        if let Some(last) = steps.last() {
-            let post_div1 = ((last.post_div >> 4) & 0xF) + 1;
-            let post_div2 = (last.post_div & 0xF) + 1;
-            let last_freq =
-                25.0 * last.fb_div as f32 / (last.ref_div * post_div1 * post_div2) as f32;
-            assert!(
-                (last_freq - 525.0).abs() < 1.0,
-                "Last frequency should be ~525 MHz"
-            );
-        }
-    }
-
-    #[test]
-    fn test_pll_flag_setting() {
-        // Test that the VCO-based flag is set correctly
-        // Flag is 0x50 when VCO frequency >= 2400 MHz, 0x40 otherwise
-        // VCO frequency = fb_div * 25.0 / ref_div
-
-        // Low frequency (100 MHz) - VCO will be >= 2400, so should use 0x50
-        let low_freq = BitaxeBoard::calculate_pll_for_frequency(100.0).unwrap();
-        assert_eq!(low_freq.flag, 0x50, "Should have 0x50 flag for 100 MHz");
-
-        // High frequency (525 MHz) - VCO will be >= 2400, so should use 0x50
-        let high_freq = BitaxeBoard::calculate_pll_for_frequency(525.0).unwrap();
-        assert_eq!(high_freq.flag, 0x50, "Should have 0x50 flag for 525 MHz");
-
-        // The 0x40 flag would be used for configurations with VCO < 2400 MHz,
-        // which aren't typically reached in the 56.25-525 MHz output range
+        create_fn: |device, hardware| Box::pin(create_from_usb(device, hardware)),
     }
 }
@@ -1,124 +1,773 @@
 //! EmberOne mining board support.
 //!
-//! The EmberOne is a mining board with 12 BM1362 ASIC chips, communicating via
-//! USB using the bitaxe-raw protocol (same as Bitaxe boards).
+//! The EmberOne is a mining board with a chain of 12 BM1362 ASIC chips,
+//! communicating via USB using the bitaxe-raw protocol (same as Bitaxe
+//! boards): a control port carries GPIO and I2C for the chip reset line,
+//! the TPS546 core regulator and the EMC2101 fan controller, and a data port
+//! carries the chips' serial bus.
 //!
-//! ## Current Implementation Status
+//! The whole chain is driven by a single [`BM13xxThread`], which addresses
+//! each chip and splits the nonce space between them. A [`PowerGovernor`]
+//! holds the board under its power limit by capping the chain's frequency
+//! and lowering the core voltage.
 //!
-//! This is currently a stub implementation that:
-//! - Gets discovered via USB (VID 0xc0de, PID 0xcafe)
-//! - Logs when connected
-//! - Returns errors for all operations (not yet implemented)
-//!
-//! ## Future Implementation
-//!
-//! Will support:
-//! - 12 BM1362 ASIC chips in a chain configuration
-//! - Dual serial ports (control + data)
-//! - bitaxe-raw management protocol
-//! - Temperature and power monitoring
-//! - Full mining operations
+//! The BM1362 initialization sequence is built from the register values in
+//! `asic/bm13xx/PROTOCOL.md` and has not yet been checked against a capture
+//! from EmberOne hardware.
 
 use async_trait::async_trait;
-use tokio::sync::mpsc;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, watch, Mutex},
+    time,
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
 
 use super::{
+    bitaxe::{BitaxePeripherals, ThreadRemovalSignal},
+    fan_control::FanController,
     pattern::{Match, StringMatch},
-    Board, BoardError, BoardEvent, BoardInfo,
+    power::{lowest_limit, PowerGovernor},
+    thermal::{ThermalDecision, ThermalGovernor},
+    Board, BoardError, BoardEvent, BoardInfo, BoardTelemetry,
+};
+use crate::{
+    asic::{
+        bm13xx::{self, protocol::ChipType},
+        ChipInfo,
+    },
+    config::HardwareConfig,
+    hash_thread::{
+        bm13xx::{self as bm13xx_thread, BM13xxControl, BM13xxThread},
+        HashThread,
+    },
+    hw_trait::{
+        gpio::{Gpio, GpioPin, PinValue},
+        i2c::I2c,
+    },
+    mgmt_protocol::{
+        bitaxe_raw::{
+            gpio::{BitaxeRawGpioController, BitaxeRawGpioPin},
+            i2c::BitaxeRawI2c,
+        },
+        ControlChannel,
+    },
+    peripheral::{
+        emc2101::{Emc2101, Percent},
+        tps546::{Tps546, Tps546Config},
+    },
+    tracing::prelude::*,
+    transport::serial::{SerialReader, SerialStream, SerialWriter},
 };
-use crate::{asic::ChipInfo, hash_thread::HashThread, transport::UsbDeviceInfo};
 
-/// EmberOne mining board (stub implementation).
+/// EmberOne hashboard with a 12-chip BM1362 chain.
 pub struct EmberOne {
-    device_info: UsbDeviceInfo,
+    /// Control channel for board management
+    control_channel: ControlChannel,
+    /// ASIC reset (active low), shared by the whole chain
+    asic_nrst: Option<BitaxeRawGpioPin>,
+    /// I2C bus controller
+    i2c: BitaxeRawI2c,
+    /// Limits and control settings from the configuration
+    hardware: HardwareConfig,
+    /// Voltage regulator (shared with thread, cached state)
+    regulator: Option<Arc<Mutex<Tps546<BitaxeRawI2c>>>>,
+    /// Fan controller, if the board has one fitted
+    fan: Option<Emc2101<BitaxeRawI2c>>,
+    /// Writer for sending commands to chips (transferred to hash thread)
+    data_writer: Option<FramedWrite<SerialWriter, bm13xx::FrameCodec>>,
+    /// Reader for receiving responses from chips (transferred to hash thread)
+    data_reader: Option<FramedRead<SerialReader, bm13xx::FrameCodec>>,
+    /// Chips in chain order, with the addresses they are given
+    chip_infos: Vec<ChipInfo>,
+    /// Channel for sending board events
+    event_tx: Option<mpsc::Sender<BoardEvent>>,
+    /// Channel for receiving board events (populated during initialization)
+    event_rx: Option<mpsc::Receiver<BoardEvent>>,
+    /// Thread shutdown signal (board-to-thread implementation detail)
+    thread_shutdown: watch::Sender<ThreadRemovalSignal>,
+    /// Frequency cap for the hash thread, set by the statistics task
+    frequency_limit: watch::Sender<Option<f32>>,
+    /// Input power limit, followed by the statistics task's governor
+    power_limit: watch::Sender<Option<f32>>,
+    /// Handle to the hash thread once created
+    thread: watch::Sender<Option<BM13xxControl>>,
+    /// Handle for the statistics task
+    stats_task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Latest sensor readings (written by the statistics task)
+    telemetry: Arc<RwLock<BoardTelemetry>>,
+    /// Serial number from USB device info
+    serial_number: Option<String>,
 }
 
 impl EmberOne {
-    /// Create a new EmberOne board instance.
-    pub fn new(device_info: UsbDeviceInfo) -> Result<Self, BoardError> {
-        Ok(Self { device_info })
+    /// GPIO pin number for ASIC reset control (active low)
+    const ASIC_RESET_PIN: u8 = 0;
+
+    /// Number of chips on the board
+    const CHAIN_LENGTH: usize = 12;
+
+    /// Chips per voltage domain: four stacked domains of three chips
+    const CHIPS_PER_DOMAIN: usize = 3;
+
+    /// Core rail voltage, ~0.33 V across each domain
+    const CORE_VOLTAGE_V: f32 = 1.32;
+
+    /// Creates a new EmberOne instance with the provided serial streams.
+    ///
+    /// # Arguments
+    /// * `control` - Serial stream for sending board control commands
+    /// * `data_path` - Path to the data serial port (e.g., "/dev/ttyACM1")
+    /// * `serial_number` - Serial number from USB device info
+    /// * `hardware` - Temperature, fan and power settings from the config
+    pub fn new(
+        control: tokio_serial::SerialStream,
+        data_path: &str,
+        serial_number: Option<String>,
+        hardware: HardwareConfig,
+    ) -> Result<Self, BoardError> {
+        let control_channel = ControlChannel::new(control);
+        let i2c = BitaxeRawI2c::new(control_channel.clone());
+
+        let data_stream = SerialStream::new(data_path, 115200).map_err(|e| {
+            BoardError::InitializationFailed(format!("Failed to open data port: {}", e))
+        })?;
+        let (data_reader, data_writer, _data_control) = data_stream.split();
+        let power_limit = watch::Sender::new(hardware.power_limit);
+
+        Ok(EmberOne {
+            control_channel,
+            asic_nrst: None,
+            i2c,
+            hardware,
+            regulator: None,
+            fan: None,
+            data_writer: Some(FramedWrite::new(data_writer, bm13xx::FrameCodec::default())),
+            data_reader: Some(FramedRead::new(data_reader, bm13xx::FrameCodec::default())),
+            chip_infos: Vec::new(),
+            event_tx: None,
+            event_rx: None,
+            thread_shutdown: watch::Sender::new(ThreadRemovalSignal::Running),
+            frequency_limit: watch::Sender::new(None),
+            power_limit,
+            thread: watch::Sender::new(None),
+            stats_task_handle: None,
+            telemetry: Arc::new(RwLock::new(BoardTelemetry::default())),
+            serial_number,
+        })
+    }
+
+    /// Drive the chain's reset line.
+    async fn write_reset(&mut self, value: PinValue) -> Result<(), BoardError> {
+        let reset_pin = self
+            .asic_nrst
+            .as_mut()
+            .ok_or_else(|| BoardError::HardwareControl("Reset pin not initialized".to_string()))?;
+
+        reset_pin
+            .write(value)
+            .await
+            .map_err(|e| BoardError::HardwareControl(format!("Failed to drive reset: {}", e)))
+    }
+
+    /// Initialize the TPS546 core regulator and bring the rail up.
+    #[instrument(skip_all)]
+    async fn init_power_controller(&mut self) -> Result<(), BoardError> {
+        // EmberOne power configuration for TPS546D24A on a 12 V input
+        let config = Tps546Config {
+            // Phase and frequency
+            phase: 0x00,
+            frequency_switch_khz: 650,
+
+            // Input voltage thresholds
+            vin_on: 11.0,
+            vin_off: 10.5,
+            vin_uv_warn_limit: 0.0, // Disabled due to TI bug
+            vin_ov_fault_limit: 14.0,
+            vin_ov_fault_response: 0xB7, // Immediate shutdown, 6 retries, 7xTON_RISE delay
+
+            // Output voltage configuration
+            vout_scale_loop: 0.25,
+            vout_min: 1.0,
+            vout_max: 2.0,
+            vout_command: Self::CORE_VOLTAGE_V,
+
+            // Output voltage protection (relative to vout_command)
+            vout_ov_fault_limit: 1.25, // 125% of VOUT_COMMAND
+            vout_ov_warn_limit: 1.16,  // 116% of VOUT_COMMAND
+            vout_margin_high: 1.10,    // 110% of VOUT_COMMAND
+            vout_margin_low: 0.90,     // 90% of VOUT_COMMAND
+            vout_uv_warn_limit: 0.90,  // 90% of VOUT_COMMAND
+            vout_uv_fault_limit: 0.75, // 75% of VOUT_COMMAND
+
+            // Output current protection, within the TPS546D24A's 40 A rating
+            iout_oc_warn_limit: 35.0,
+            iout_oc_fault_limit: 40.0,
+            iout_oc_fault_response: 0xC0, // Shutdown immediately, no retries
+
+            // Temperature protection
+            ot_warn_limit: 105,      // degC
+            ot_fault_limit: 145,     // degC
+            ot_fault_response: 0xFF, // Infinite retries
+
+            // Timing configuration
+            ton_delay: 0,
+            ton_rise: 3,
+            ton_max_fault_limit: 0,
+            ton_max_fault_response: 0x3B, // 3 retries, 91ms delay
+            toff_delay: 0,
+            toff_fall: 0,
+
+            // Pin configuration
+            pin_detect_override: 0xFFFF,
+        };
+
+        let mut tps546 = Tps546::new(self.i2c.clone(), config);
+        tps546.init().await.map_err(|e| {
+            BoardError::InitializationFailed(format!("Power controller init failed: {}", e))
+        })?;
+
+        time::sleep(Duration::from_millis(100)).await;
+        tps546.set_vout(Self::CORE_VOLTAGE_V).await.map_err(|e| {
+            BoardError::InitializationFailed(format!("Failed to set core voltage: {}", e))
+        })?;
+        debug!("Core voltage set to {}V", Self::CORE_VOLTAGE_V);
+
+        // Wait for the rail to stabilize
+        time::sleep(Duration::from_millis(500)).await;
+        match tps546.get_vout().await {
+            Ok(mv) => debug!("Core voltage readback: {:.3}V", mv as f32 / 1000.0),
+            Err(e) => warn!("Failed to read core voltage: {}", e),
+        }
+
+        self.regulator = Some(Arc::new(Mutex::new(tps546)));
+        Ok(())
+    }
+
+    /// Initialize the fan controller, if one answers.
+    #[instrument(skip_all)]
+    async fn init_fan_controller(&mut self) {
+        let mut fan = Emc2101::new(self.i2c.clone());
+        match fan.init().await {
+            Ok(()) => {
+                // Full speed until the statistics task takes over
+                if let Err(e) = fan.set_fan_speed(Percent::FULL).await {
+                    warn!("Failed to set fan speed: {}", e);
+                }
+                self.fan = Some(fan);
+            }
+            Err(e) => warn!("No EMC2101 fan controller: {}", e),
+        }
+    }
+
+    /// Count and address the chips on the chain.
+    #[instrument(skip_all)]
+    async fn discover_chips(&mut self) -> Result<(), BoardError> {
+        let reader = self.data_reader.as_mut().ok_or_else(|| {
+            BoardError::InitializationFailed("Data reader already taken".to_string())
+        })?;
+        let writer = self
+            .data_writer
+            .as_mut()
+            .expect("data_writer should be available during chip discovery");
+
+        let chips = bm13xx_thread::enumerate_chain(reader, writer)
+            .await
+            .map_err(|e| BoardError::InitializationFailed(e.to_string()))?;
+        check_chain(&chips)?;
+        self.chip_infos = chips;
+        Ok(())
+    }
+
+    /// Spawn a task to periodically read sensors and protect the chain.
+    ///
+    /// Readings are published to the shared telemetry snapshot; the
+    /// [`ThermalGovernor`] caps the hash thread's frequency as the chips or
+    /// regulator heat up, the [`PowerGovernor`] lowers frequency and core
+    /// voltage to stay under the power limit, and on overtemperature the task
+    /// removes the thread,
+    /// holds the chain in reset, turns off the core voltage and reports a
+    /// [`BoardEvent::BoardFault`]. A fitted fan follows a [`FanController`].
+    fn spawn_stats_monitor(&mut self) {
+        const TELEMETRY_INTERVAL: Duration = Duration::from_secs(5);
+        const LOG_EVERY_N_READINGS: u32 = 6; // 30 seconds
+        const VOLTAGE_SETTLE: Duration = Duration::from_secs(1);
+
+        let regulator = self
+            .regulator
+            .clone()
+            .expect("Regulator must be initialized before spawning stats monitor");
+        let mut asic_nrst = self
+            .asic_nrst
+            .clone()
+            .expect("Reset pin must be initialized before spawning stats monitor");
+        let mut fan = self.fan.as_ref().map(|_| Emc2101::new(self.i2c.clone()));
+        let mut fan_controller = FanController::new(&self.hardware);
+        let target_mhz = bm13xx_thread::target_frequency_mhz(&self.chip_infos);
+        let thermal = ThermalGovernor::new(&self.hardware, target_mhz);
+        let mut power = PowerGovernor::new(&self.hardware, target_mhz, Self::CORE_VOLTAGE_V);
+        let mut power_limit_rx = self.power_limit.subscribe();
+        let telemetry = Arc::clone(&self.telemetry);
+        let event_tx = self.event_tx.clone();
+        let frequency_limit = self.frequency_limit.clone();
+        let thread_shutdown = self.thread_shutdown.clone();
+        let board_serial = self.serial_number.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut interval = time::interval(TELEMETRY_INTERVAL);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+            let mut readings: u32 = 0;
+            let mut overtemperature = false;
+            let mut thermal_limit: Option<f32> = None;
+            let mut applied_voltage_v = Self::CORE_VOLTAGE_V;
+
+            // Discard first tick (fires immediately, ADC readings may not be settled)
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    Ok(()) = power_limit_rx.changed() => {}
+                }
+                let power_limit = *power_limit_rx.borrow_and_update();
+                if power_limit != power.limit() {
+                    power.set_limit(power_limit);
+                }
+
+                let (asic_temp_c, fan_rpm) = match fan.as_mut() {
+                    Some(fan) => (
+                        fan.get_external_temperature().await.ok(),
+                        fan.get_rpm().await.ok(),
+                    ),
+                    None => (None, None),
+                };
+
+                let mut vr = regulator.lock().await;
+                let input_voltage_v = vr.get_vin().await.ok().map(|mv| mv as f32 / 1000.0);
+                let core_voltage_v = vr.get_vout().await.ok().map(|mv| mv as f32 / 1000.0);
+                let current_a = vr.get_iout().await.ok().map(|ma| ma as f32 / 1000.0);
+                let power_w = vr.get_power().await.ok().map(|mw| mw as f32 / 1000.0);
+                let vr_temp_c = vr.get_temperature().await.ok().map(|t| t as f32);
+                let status = vr.check_status().await;
+                drop(vr);
+
+                let fan_percent = match fan.as_mut() {
+                    Some(fan) if !overtemperature => {
+                        let duty = fan_controller.update(asic_temp_c, fan_rpm, TELEMETRY_INTERVAL);
+                        if let Err(e) = fan.set_fan_speed(Percent::new_clamped(duty)).await {
+                            warn!(error = %e, "Failed to set fan speed.");
+                        }
+                        Some(duty)
+                    }
+                    Some(_) => Some(100),
+                    None => None,
+                };
+
+                *telemetry.write().unwrap() = BoardTelemetry {
+                    asic_temp_c,
+                    vr_temp_c,
+                    fan_percent,
+                    fan_rpm,
+                    fan_mode: None,
+                    profile: None,
+                    power_w,
+                    current_a,
+                    input_voltage_v,
+                    core_voltage_v,
+                };
+
+                match thermal.assess(asic_temp_c, vr_temp_c) {
+                    ThermalDecision::Run {
+                        frequency_limit_mhz,
+                    } if frequency_limit_mhz != thermal_limit => {
+                        match frequency_limit_mhz {
+                            Some(mhz) => warn!(
+                                asic_temp_c,
+                                vr_temp_c,
+                                frequency_mhz = mhz,
+                                "Approaching temperature limit; throttling chips."
+                            ),
+                            None => info!(
+                                asic_temp_c,
+                                vr_temp_c, "Temperatures back in range; thermal throttling lifted."
+                            ),
+                        }
+                        thermal_limit = frequency_limit_mhz;
+                    }
+                    ThermalDecision::Run { .. } => {}
+                    ThermalDecision::Overtemperature { fault } if !overtemperature => {
+                        overtemperature = true;
+                        error!(%fault, "CRITICAL: Overtemperature; shutting down chips.");
+
+                        thread_shutdown.send_replace(ThreadRemovalSignal::HardwareFault {
+                            description: fault.clone(),
+                        });
+                        if let Err(e) = asic_nrst.write(PinValue::Low).await {
+                            error!("Failed to hold chips in reset: {}", e);
+                        }
+                        if let Err(e) = regulator.lock().await.set_vout(0.0).await {
+                            error!("Failed to turn off core voltage: {}", e);
+                        }
+                        if let Some(fan) = fan.as_mut() {
+                            if let Err(e) = fan.set_fan_speed(Percent::FULL).await {
+                                warn!("Failed to set fan speed: {}", e);
+                            }
+                        }
+                        send_fault(&event_tx, "thermal", fault).await;
+                    }
+                    // Already shut down; keep reporting temperatures
+                    ThermalDecision::Overtemperature { .. } => {}
+                }
+
+                if !overtemperature {
+                    if let Some(point) = power.update(power_w) {
+                        match point.frequency_limit_mhz {
+                            Some(mhz) => info!(
+                                power_w,
+                                frequency_mhz = mhz,
+                                core_voltage_v = point.core_voltage_v,
+                                "Adjusting chips to the power limit."
+                            ),
+                            None => info!(power_w, "Power headroom restored; chips at full speed."),
+                        }
+                    }
+
+                    // Slow the chips before lowering their voltage, and raise
+                    // it before letting them speed up
+                    let point = power.operating_point();
+                    let limit = lowest_limit(thermal_limit, point.frequency_limit_mhz);
+                    let lowering = point.core_voltage_v < applied_voltage_v;
+                    if lowering {
+                        frequency_limit.send_replace(limit);
+                        time::sleep(VOLTAGE_SETTLE).await;
+                    }
+                    if point.core_voltage_v != applied_voltage_v {
+                        match regulator.lock().await.set_vout(point.core_voltage_v).await {
+                            Ok(()) => applied_voltage_v = point.core_voltage_v,
+                            Err(e) => warn!("Failed to set core voltage: {}", e),
+                        }
+                        if !lowering {
+                            time::sleep(VOLTAGE_SETTLE).await;
+                        }
+                    }
+                    frequency_limit.send_if_modified(|current| {
+                        let changed = *current != limit;
+                        *current = limit;
+                        changed
+                    });
+                }
+
+                if let Err(e) = status {
+                    error!("CRITICAL: Power controller fault detected: {}", e);
+                    send_fault(&event_tx, "power_controller", e.to_string()).await;
+
+                    warn!("Attempting to clear power controller faults...");
+                    if let Err(clear_err) = regulator.lock().await.clear_faults().await {
+                        error!("Failed to clear faults: {}", clear_err);
+                    }
+                    continue;
+                }
+
+                readings += 1;
+                if readings.is_multiple_of(LOG_EVERY_N_READINGS) {
+                    info!(
+                        board = "EmberOne",
+                        serial = ?board_serial,
+                        asic_temp_c,
+                        vr_temp_c,
+                        fan_percent,
+                        fan_rpm,
+                        power_w,
+                        current_a,
+                        vin = input_voltage_v,
+                        vout = core_voltage_v,
+                        "Board status."
+                    );
+                }
+            }
+        });
+
+        self.stats_task_handle = Some(handle);
     }
 }
 
+/// Report a non-recoverable fault of `component` on the board's event channel.
+async fn send_fault(event_tx: &Option<mpsc::Sender<BoardEvent>>, component: &str, fault: String) {
+    let Some(tx) = event_tx else {
+        return;
+    };
+    let event = BoardEvent::BoardFault {
+        component: component.to_string(),
+        fault,
+        recoverable: false,
+    };
+    if let Err(e) = tx.send(event).await {
+        error!("Failed to send board fault event: {}", e);
+    }
+}
+
+/// Check that the chips found on the chain are the EmberOne's.
+///
+/// A chain cut short by a chip that fails to answer still hashes with the
+/// chips before it.
+fn check_chain(chips: &[ChipInfo]) -> Result<(), BoardError> {
+    if let Some(chip) = chips
+        .iter()
+        .find(|chip| ChipType::from(chip.chip_id) != ChipType::BM1362)
+    {
+        return Err(BoardError::InitializationFailed(format!(
+            "Wrong chip type for EmberOne: expected BM1362 (1362), found {:02x}{:02x}",
+            chip.chip_id[0], chip.chip_id[1]
+        )));
+    }
+    if chips.len() != EmberOne::CHAIN_LENGTH {
+        warn!(
+            expected = EmberOne::CHAIN_LENGTH,
+            found = chips.len(),
+            "Chain is short of chips; hashing with those found."
+        );
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl Board for EmberOne {
     async fn reset(&mut self) -> Result<(), BoardError> {
-        Err(BoardError::InitializationFailed(
-            "EmberOne reset not yet implemented".into(),
-        ))
+        const WAIT: Duration = Duration::from_millis(100);
+
+        self.write_reset(PinValue::Low).await?;
+        time::sleep(WAIT).await;
+        self.write_reset(PinValue::High).await?;
+        time::sleep(WAIT).await;
+        Ok(())
     }
 
     async fn hold_in_reset(&mut self) -> Result<(), BoardError> {
-        Err(BoardError::InitializationFailed(
-            "EmberOne hold_in_reset not yet implemented".into(),
-        ))
+        self.write_reset(PinValue::Low).await
     }
 
     async fn initialize(&mut self) -> Result<mpsc::Receiver<BoardEvent>, BoardError> {
-        Err(BoardError::InitializationFailed(
-            "EmberOne initialize not yet implemented".into(),
-        ))
+        let mut gpio_controller = BitaxeRawGpioController::new(self.control_channel.clone());
+        let reset_pin = gpio_controller
+            .pin(Self::ASIC_RESET_PIN)
+            .await
+            .map_err(|e| {
+                BoardError::InitializationFailed(format!("Failed to get reset pin: {}", e))
+            })?;
+        self.asic_nrst = Some(reset_pin);
+
+        // Hold the chain in reset while the rail comes up
+        self.write_reset(PinValue::Low).await?;
+
+        self.i2c.set_frequency(100_000).await.map_err(|e| {
+            BoardError::InitializationFailed(format!("Failed to set I2C frequency: {}", e))
+        })?;
+        self.init_fan_controller().await;
+        self.init_power_controller().await?;
+
+        // Release the chain and count its chips
+        debug!("Releasing chain from reset for discovery");
+        self.write_reset(PinValue::High).await?;
+        time::sleep(Duration::from_millis(200)).await;
+
+        self.discover_chips().await?;
+        debug!(count = self.chip_infos.len(), "Discovered chips");
+
+        // The hash thread brings the chain up from reset
+        self.write_reset(PinValue::Low).await?;
+
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        self.event_rx = Some(rx);
+
+        self.spawn_stats_monitor();
+
+        // Return a dummy receiver for trait compatibility
+        let (dummy_tx, dummy_rx) = mpsc::channel(1);
+        drop(dummy_tx);
+        Ok(dummy_rx)
     }
 
     fn chip_count(&self) -> usize {
-        // EmberOne has 12 BM1362 chips, but not yet implemented
-        0
+        self.chip_infos.len()
     }
 
     fn chip_infos(&self) -> &[ChipInfo] {
-        // No chips discovered yet in stub
-        &[]
+        &self.chip_infos
     }
 
     fn board_info(&self) -> BoardInfo {
         BoardInfo {
-            model: "EmberOne (stub)".to_string(),
-            firmware_version: None,
-            serial_number: self.device_info.serial_number.clone(),
+            model: "EmberOne".to_string(),
+            firmware_version: Some("bitaxe-raw".to_string()),
+            serial_number: self.serial_number.clone(),
         }
     }
 
+    fn telemetry(&self) -> BoardTelemetry {
+        self.telemetry.read().unwrap().clone()
+    }
+
     fn take_event_receiver(&mut self) -> Option<mpsc::Receiver<BoardEvent>> {
-        // Stub has no event receiver yet
-        None
+        self.event_rx.take()
     }
 
     async fn shutdown(&mut self) -> Result<(), BoardError> {
-        tracing::info!("EmberOne stub shutdown (no-op)");
+        if self.thread_shutdown.receiver_count() > 0 {
+            self.thread_shutdown
+                .send_replace(ThreadRemovalSignal::Shutdown);
+            debug!("Sent shutdown signal to hash threads");
+            time::sleep(Duration::from_millis(200)).await;
+        }
+
+        self.hold_in_reset().await?;
+
+        if let Some(ref regulator) = self.regulator {
+            match regulator.lock().await.set_vout(0.0).await {
+                Ok(()) => debug!("Core voltage turned off"),
+                Err(e) => warn!("Failed to turn off core voltage: {}", e),
+            }
+        }
+
+        if let Some(handle) = self.stats_task_handle.take() {
+            handle.abort();
+        }
+        if let Some(ref mut fan) = self.fan {
+            if let Err(e) = fan.set_fan_speed(Percent::new_clamped(25)).await {
+                warn!("Failed to set fan speed: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn set_power_limit(&mut self, limit_w: Option<f32>) -> Result<(), BoardError> {
+        if matches!(limit_w, Some(limit) if limit <= 0.0) {
+            return Err(BoardError::HardwareControl(
+                "power limit must be positive".into(),
+            ));
+        }
+        self.power_limit.send_replace(limit_w);
+        info!(power_limit_w = limit_w, "Power limit set.");
+        Ok(())
+    }
+
+    async fn set_frequency(&mut self, frequency_mhz: f32) -> Result<(), BoardError> {
+        if bm13xx_thread::calculate_pll_for_frequency(frequency_mhz).is_none() {
+            return Err(BoardError::HardwareControl(format!(
+                "no PLL setting for {frequency_mhz} MHz"
+            )));
+        }
+        let thread =
+            self.thread.borrow().clone().ok_or_else(|| {
+                BoardError::HardwareControl("hash thread not running".to_string())
+            })?;
+        let running_mhz = thread
+            .set_frequency(frequency_mhz)
+            .await
+            .map_err(|e| BoardError::HardwareControl(e.to_string()))?;
+        info!(frequency_mhz, running_mhz, "Target frequency set.");
         Ok(())
     }
 
     async fn create_hash_threads(&mut self) -> Result<Vec<Box<dyn HashThread>>, BoardError> {
-        Err(BoardError::InitializationFailed(
-            "EmberOne hash threads not yet implemented".into(),
-        ))
+        let removal_rx = self.thread_shutdown.subscribe();
+
+        let data_reader = self
+            .data_reader
+            .take()
+            .ok_or(BoardError::InitializationFailed(
+                "No data reader available - already taken or not initialized".into(),
+            ))?;
+        let data_writer = self
+            .data_writer
+            .take()
+            .ok_or(BoardError::InitializationFailed(
+                "No data writer available - already taken or not initialized".into(),
+            ))?;
+
+        let peripherals = BitaxePeripherals {
+            asic_nrst: self
+                .asic_nrst
+                .clone()
+                .ok_or(BoardError::InitializationFailed(
+                    "Reset pin not initialized".into(),
+                ))?,
+            regulator: self
+                .regulator
+                .clone()
+                .ok_or(BoardError::InitializationFailed(
+                    "Voltage regulator not initialized".into(),
+                ))?,
+            frequency_limit: self.frequency_limit.subscribe(),
+        };
+
+        // One thread drives the whole chain, which shares a bus and a reset
+        let thread = BM13xxThread::new(
+            data_reader,
+            data_writer,
+            peripherals,
+            removal_rx,
+            &self.chip_infos,
+            Self::CHIPS_PER_DOMAIN,
+        );
+
+        self.thread.send_replace(Some(thread.control()));
+        debug!(
+            chips = self.chip_infos.len(),
+            "Created BM13xx hash thread from EmberOne"
+        );
+
+        Ok(vec![Box::new(thread)])
     }
 }
 
 // Factory function to create EmberOne board from USB device info
-async fn create_from_usb(device: UsbDeviceInfo) -> crate::error::Result<Box<dyn Board + Send>> {
-    // Get serial ports
+async fn create_from_usb(
+    device: crate::transport::UsbDeviceInfo,
+    hardware: HardwareConfig,
+) -> crate::error::Result<Box<dyn Board + Send>> {
+    use tokio_serial::SerialPortBuilderExt;
+
     let serial_ports = device.serial_ports()?;
 
     // EmberOne uses 2 serial ports like Bitaxe (control + data)
     if serial_ports.len() != 2 {
-        tracing::warn!(
-            expected = 2,
-            found = serial_ports.len(),
-            serial = ?device.serial_number,
-            "EmberOne expected 2 serial ports, creating stub anyway"
-        );
-    } else {
-        tracing::debug!(
-            serial = ?device.serial_number,
-            control = %serial_ports[0],
-            data = %serial_ports[1],
-            "EmberOne serial ports"
-        );
+        return Err(crate::error::Error::Hardware(format!(
+            "EmberOne requires exactly 2 serial ports, found {}",
+            serial_ports.len()
+        )));
     }
 
-    let board = EmberOne::new(device)
-        .map_err(|e| crate::error::Error::Hardware(format!("Failed to create board: {}", e)))?;
+    debug!(
+        serial = ?device.serial_number,
+        control = %serial_ports[0],
+        data = %serial_ports[1],
+        "Opening EmberOne serial ports"
+    );
+
+    let control_port = tokio_serial::new(&serial_ports[0], 115200).open_native_async()?;
+
+    let mut board = EmberOne::new(
+        control_port,
+        &serial_ports[1],
+        device.serial_number.clone(),
+        hardware,
+    )
+    .map_err(|e| crate::error::Error::Hardware(format!("Failed to create board: {}", e)))?;
+
+    let _dummy_rx = board
+        .initialize()
+        .await
+        .map_err(|e| crate::error::Error::Hardware(format!("Failed to initialize board: {}", e)))?;
+
+    debug!(
+        "EmberOne initialized successfully with {} chips",
+        board.chip_count()
+    );
 
     Ok(Box::new(board))
 }
@@ -134,30 +783,44 @@ inventory::submit! {
             serial_pattern: Match::Any,
         },
         name: "EmberOne",
-        create_fn: |device| Box::pin(create_from_usb(device)),
+        create_fn: |device, hardware| Box::pin(create_from_usb(device, hardware)),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::asic::bm13xx::BM13xxProtocol;
+
+    fn chips(chip_id: [u8; 2], count: usize) -> Vec<ChipInfo> {
+        BM13xxProtocol::chip_addresses(count)
+            .into_iter()
+            .map(|address| ChipInfo {
+                chip_id,
+                core_count: 0,
+                address,
+                supports_version_rolling: true,
+            })
+            .collect()
+    }
 
     #[test]
-    fn test_board_creation() {
-        let device = UsbDeviceInfo::new_for_test(
-            0xc0de,
-            0xcafe,
-            Some("TEST001".to_string()),
-            Some("EmberOne".to_string()),
-            Some("Mining Board".to_string()),
-            "/sys/devices/test".to_string(),
-        );
+    fn test_check_chain() {
+        assert!(check_chain(&chips([0x13, 0x62], EmberOne::CHAIN_LENGTH)).is_ok());
 
-        let board = EmberOne::new(device);
-        assert!(board.is_ok());
+        // A chip that fails to answer cuts the chain short; hash with the rest
+        assert!(check_chain(&chips([0x13, 0x62], 5)).is_ok());
+    }
 
-        let board = board.unwrap();
-        assert_eq!(board.chip_count(), 0); // Stub returns 0 chips
-        assert_eq!(board.board_info().model, "EmberOne (stub)");
+    #[test]
+    fn test_check_chain_rejects_foreign_chips() {
+        let mut chain = chips([0x13, 0x62], EmberOne::CHAIN_LENGTH);
+        chain[3].chip_id = [0x13, 0x70];
+        assert!(check_chain(&chain).is_err());
+    }
+
+    #[test]
+    fn test_domains_cover_chain() {
+        assert_eq!(EmberOne::CHAIN_LENGTH % EmberOne::CHIPS_PER_DOMAIN, 0);
     }
 }
@@ -0,0 +1,244 @@
+//! Closed-loop fan control.
+//!
+//! [`FanController`] turns ASIC temperature readings into a fan duty cycle
+//! with a PID loop toward [`FanControlConfig::target_temp`]. It holds no
+//! hardware handles; a board's fan task reads the sensors, feeds the readings
+//! in and writes the returned duty to its fan controller chip.
+//!
+//! ## RPM limits
+//!
+//! The configured `fan_min_rpm` and `fan_max_rpm` are honoured by learning
+//! duty bounds from the tachometer: while the loop sits at its lower bound
+//! and the fan still turns slower than the minimum, the bound is raised a
+//! step per update, and likewise the upper bound is lowered while the fan
+//! runs faster than the maximum. Until the tachometer has reported a
+//! turning fan the RPM limits cannot be checked, so the duty is held at or
+//! above [`FanControlConfig::min_percent`] rather than risk stopping a fan
+//! whose speed is unknown.
+//!
+//! ## Failsafe
+//!
+//! A missing or implausible temperature reading drives the fan to 100%,
+//! ignoring the RPM limits, and restarts the loop from full speed once
+//! readings return.
+
+use std::time::Duration;
+
+use crate::config::{FanControlConfig, HardwareConfig};
+
+/// Duty cycle applied when the temperature cannot be read.
+pub const FAILSAFE_PERCENT: u8 = 100;
+
+/// Duty step by which the learned bounds move per update.
+const BOUND_STEP: u8 = 2;
+
+/// Readings outside this range (degrees Celsius) are sensor faults, e.g. an
+/// open diode reading as the EMC2101's maximum.
+const PLAUSIBLE_TEMP: std::ops::RangeInclusive<f32> = -20.0..=125.0;
+
+/// Whether a temperature reading can be trusted for control.
+pub fn is_plausible(temp_c: f32) -> bool {
+    PLAUSIBLE_TEMP.contains(&temp_c)
+}
+
+/// PID fan controller for one board.
+#[derive(Debug, Clone)]
+pub struct FanController {
+    config: FanControlConfig,
+    min_rpm: u32,
+    max_rpm: u32,
+    /// Integral term, in percent duty
+    integral: f32,
+    /// Error at the previous update, for the derivative term
+    last_error: Option<f32>,
+    /// Duty returned by the previous update
+    last_duty: u8,
+    /// Learned duty bounds keeping the fan within the RPM limits
+    floor: u8,
+    ceiling: u8,
+    /// Whether the tachometer has ever reported a turning fan
+    tach_seen: bool,
+}
+
+impl FanController {
+    /// Create a controller starting from full speed.
+    pub fn new(hardware: &HardwareConfig) -> Self {
+        Self {
+            config: hardware.fan.clone(),
+            min_rpm: hardware.fan_min_rpm,
+            max_rpm: hardware.fan_max_rpm,
+            integral: f32::from(FAILSAFE_PERCENT),
+            last_error: None,
+            last_duty: FAILSAFE_PERCENT,
+            floor: 0,
+            ceiling: 100,
+            tach_seen: false,
+        }
+    }
+
+    /// Restart the loop from full speed, e.g. after a manual override.
+    ///
+    /// Learned RPM bounds are kept; they describe the fan, not the loop.
+    pub fn reset(&mut self) {
+        self.integral = f32::from(self.ceiling);
+        self.last_error = None;
+        self.last_duty = FAILSAFE_PERCENT;
+    }
+
+    /// Hold a different temperature, e.g. for a performance profile.
+    ///
+    /// The loop carries on from its current state toward the new target.
+    pub fn set_target_temp(&mut self, target_temp: f32) {
+        self.config.target_temp = target_temp;
+        self.last_error = None;
+    }
+
+    /// Compute the next duty cycle in percent.
+    ///
+    /// `temp_c` is the ASIC temperature (`None` if the read failed), `rpm`
+    /// the fan speed measured at the previous duty, and `dt` the time since
+    /// the previous update.
+    pub fn update(&mut self, temp_c: Option<f32>, rpm: Option<u32>, dt: Duration) -> u8 {
+        let Some(temp_c) = temp_c.filter(|&t| is_plausible(t)) else {
+            self.reset();
+            return FAILSAFE_PERCENT;
+        };
+
+        self.learn_bounds(rpm);
+
+        let floor = if self.tach_seen {
+            self.floor
+        } else {
+            self.floor.max(self.config.min_percent).min(self.ceiling)
+        };
+        let (floor, ceiling) = (f32::from(floor), f32::from(self.ceiling));
+        let dt = dt.as_secs_f32().max(f32::EPSILON);
+        let error = temp_c - self.config.target_temp;
+
+        // Clamping the integral to the output range keeps it from winding up
+        // while the fan is pinned at a bound
+        self.integral = (self.integral + self.config.ki * error * dt).clamp(floor, ceiling);
+        let derivative = self
+            .last_error
+            .map_or(0.0, |last| self.config.kd * (error - last) / dt);
+        self.last_error = Some(error);
+
+        let output = self.config.kp * error + self.integral + derivative;
+        self.last_duty = output.clamp(floor, ceiling).round() as u8;
+        self.last_duty
+    }
+
+    /// Move the duty bounds according to the fan speed at the last duty.
+    fn learn_bounds(&mut self, rpm: Option<u32>) {
+        // Zero RPM reads the same as a missing tachometer, so it only counts
+        // as a stopped fan once the fan has been seen turning
+        let Some(rpm) = rpm else {
+            return;
+        };
+        self.tach_seen |= rpm > 0;
+        if !self.tach_seen {
+            return;
+        }
+
+        if rpm < self.min_rpm && self.last_duty <= self.floor {
+            self.floor = (self.floor + BOUND_STEP).min(self.ceiling);
+        } else if rpm > self.max_rpm && self.last_duty >= self.ceiling {
+            self.ceiling = self.ceiling.saturating_sub(BOUND_STEP).max(self.floor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_secs(2);
+
+    fn controller() -> FanController {
+        FanController::new(&HardwareConfig::default())
+    }
+
+    #[test]
+    fn test_tracks_target_temperature() {
+        let mut fan = controller();
+
+        // Cool chip: the loop winds down from full speed
+        let mut duty = FAILSAFE_PERCENT;
+        for _ in 0..100 {
+            duty = fan.update(Some(45.0), Some(3000), TICK);
+        }
+        assert_eq!(duty, 0);
+
+        // Hot chip: more fan, more so the further from target
+        let warm = fan.update(Some(62.0), None, TICK);
+        let hot = fan.clone().update(Some(75.0), None, TICK);
+        assert!(warm > 0);
+        assert!(hot > warm);
+
+        // Sustained error keeps raising the duty through the integral term
+        let later = (0..10).map(|_| fan.update(Some(62.0), None, TICK)).last();
+        assert!(later.unwrap() > warm);
+    }
+
+    #[test]
+    fn test_holds_minimum_until_tach_reports() {
+        let mut fan = controller();
+        let min_percent = FanControlConfig::default().min_percent;
+
+        // No tachometer, or one reading zero: never below the minimum
+        for rpm in [None, Some(0)] {
+            let mut duty = FAILSAFE_PERCENT;
+            for _ in 0..100 {
+                duty = fan.update(Some(45.0), rpm, TICK);
+            }
+            assert_eq!(duty, min_percent);
+        }
+
+        // Once the fan is seen turning, the loop may stop it
+        let mut duty = min_percent;
+        for _ in 0..100 {
+            duty = fan.update(Some(45.0), Some(3000), TICK);
+        }
+        assert_eq!(duty, 0);
+    }
+
+    #[test]
+    fn test_failsafe_on_sensor_error() {
+        let mut fan = controller();
+        for _ in 0..100 {
+            fan.update(Some(45.0), None, TICK);
+        }
+
+        assert_eq!(fan.update(None, None, TICK), FAILSAFE_PERCENT);
+        assert_eq!(fan.update(Some(127.875), None, TICK), FAILSAFE_PERCENT);
+
+        // Winds down from full speed again rather than resuming at zero
+        assert!(fan.update(Some(45.0), None, TICK) > 0);
+    }
+
+    #[test]
+    fn test_honours_rpm_limits() {
+        let mut fan = controller();
+
+        // Fan too slow at the bottom of the range: the floor rises until the
+        // tachometer reads at least the minimum
+        let mut duty = FAILSAFE_PERCENT;
+        for _ in 0..100 {
+            let rpm = u32::from(duty) * 60;
+            duty = fan.update(Some(45.0), Some(rpm), TICK);
+        }
+        assert!(u32::from(duty) * 60 >= 1000, "duty {duty}% too low");
+        assert!(u32::from(duty) * 60 < 1200, "duty {duty}% overshoots");
+
+        // Fan too fast at the top: the ceiling comes down
+        let mut duty = FAILSAFE_PERCENT;
+        for _ in 0..100 {
+            let rpm = u32::from(duty) * 80;
+            duty = fan.update(Some(79.0), Some(rpm), TICK);
+        }
+        assert!(u32::from(duty) * 80 <= 6000, "duty {duty}% too high");
+
+        // The failsafe still goes to full speed
+        assert_eq!(fan.update(None, Some(8000), TICK), FAILSAFE_PERCENT);
+    }
+}
@@ -1,13 +1,19 @@
 pub(crate) mod bitaxe;
 pub(crate) mod emberone;
+pub mod fan_control;
 pub mod pattern;
+pub mod power;
+pub mod thermal;
+pub mod tuning;
 
 use async_trait::async_trait;
 use std::{error::Error, fmt, future::Future, pin::Pin};
 use tokio::sync::mpsc;
 
 use crate::{
+    api_client::types::{FanMode, PerformanceProfile},
     asic::{ChipError, ChipInfo, NonceResult},
+    config::HardwareConfig,
     hash_thread::HashThread,
     transport::UsbDeviceInfo,
 };
@@ -97,6 +103,12 @@ pub trait Board: Send {
     /// Board identification and metadata.
     fn board_info(&self) -> BoardInfo;
 
+    /// Latest sensor readings.
+    ///
+    /// This is cached and may be slightly stale (updated periodically by the
+    /// board's monitoring task).
+    fn telemetry(&self) -> BoardTelemetry;
+
     /// Take ownership of the event receiver for this board.
     ///
     /// Must be called after initialization to receive board events.
@@ -110,6 +122,49 @@ pub trait Board: Send {
     /// stopping hashing and ensuring chips are in a low-power or reset state.
     async fn shutdown(&mut self) -> Result<(), BoardError>;
 
+    /// Switch the fan between closed-loop control and a fixed duty cycle.
+    ///
+    /// Boards without fan control return an error.
+    async fn set_fan_mode(&mut self, mode: FanMode) -> Result<(), BoardError> {
+        let _ = mode;
+        Err(BoardError::HardwareControl(
+            "fan control not supported".into(),
+        ))
+    }
+
+    /// Select a performance profile, or `None` to return to the board's
+    /// default (or tuned) operating point.
+    ///
+    /// Boards without profiles for their chips return an error.
+    async fn set_profile(&mut self, profile: Option<PerformanceProfile>) -> Result<(), BoardError> {
+        let _ = profile;
+        Err(BoardError::HardwareControl(
+            "performance profiles not supported".into(),
+        ))
+    }
+
+    /// Hold input power under `limit_w` watts, or lift the limit with
+    /// `None`.
+    ///
+    /// Boards without power limiting return an error.
+    async fn set_power_limit(&mut self, limit_w: Option<f32>) -> Result<(), BoardError> {
+        let _ = limit_w;
+        Err(BoardError::HardwareControl(
+            "power limiting not supported".into(),
+        ))
+    }
+
+    /// Set the chips' target frequency by hand, until a profile is selected.
+    ///
+    /// Running chips are ramped to it and rolled back if they fail at it.
+    /// Boards without frequency control return an error.
+    async fn set_frequency(&mut self, frequency_mhz: f32) -> Result<(), BoardError> {
+        let _ = frequency_mhz;
+        Err(BoardError::HardwareControl(
+            "frequency control not supported".into(),
+        ))
+    }
+
     /// Create hash threads for this board
     ///
     /// Transfers serial channel ownership to threads. Board retains peripheral
@@ -131,6 +186,34 @@ pub struct BoardInfo {
     pub serial_number: Option<String>,
 }
 
+/// Latest sensor readings from a board.
+///
+/// Each reading is `None` if the board lacks the sensor or the last read
+/// failed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoardTelemetry {
+    /// ASIC temperature in degrees Celsius
+    pub asic_temp_c: Option<f32>,
+    /// Voltage regulator temperature in degrees Celsius
+    pub vr_temp_c: Option<f32>,
+    /// Fan duty cycle in percent
+    pub fan_percent: Option<u8>,
+    /// Fan speed in RPM
+    pub fan_rpm: Option<u32>,
+    /// How the fan duty cycle is chosen, if the board controls its fan
+    pub fan_mode: Option<FanMode>,
+    /// Selected performance profile, if any
+    pub profile: Option<PerformanceProfile>,
+    /// Input power in watts
+    pub power_w: Option<f32>,
+    /// Core output current in amperes
+    pub current_a: Option<f32>,
+    /// Input voltage in volts
+    pub input_voltage_v: Option<f32>,
+    /// Core voltage in volts
+    pub core_voltage_v: Option<f32>,
+}
+
 /// Board-specific errors
 #[derive(Debug)]
 pub enum BoardError {
@@ -178,8 +261,12 @@ impl From<ChipError> for BoardError {
 type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 /// Type alias for board factory function
-pub type BoardFactoryFn =
-    fn(UsbDeviceInfo) -> BoxFuture<'static, crate::error::Result<Box<dyn Board + Send>>>;
+///
+/// Receives the device and the hardware limits the board must respect.
+pub type BoardFactoryFn = fn(
+    UsbDeviceInfo,
+    HardwareConfig,
+) -> BoxFuture<'static, crate::error::Result<Box<dyn Board + Send>>>;
 
 /// Board descriptor that gets collected by inventory.
 ///
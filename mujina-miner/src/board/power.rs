@@ -0,0 +1,301 @@
+//! Input power limiting.
+//!
+//! [`PowerGovernor`] keeps a board's input power under
+//! [`HardwareConfig::power_limit`]. Each reading over the limit moves the
+//! chips one [`OperatingPoint`] down: [`FREQUENCY_STEP_MHZ`] slower, with the
+//! core voltage lowered in proportion, toward [`MIN_FREQUENCY_FRACTION`] of
+//! full speed at [`MIN_VOLTAGE_FRACTION`] of the nominal voltage. Stepping
+//! back up waits until power has stayed low enough for the next point, as
+//! predicted by the usual CMOS scaling of power with frequency times voltage
+//! squared, for [`RAISE_AFTER_READINGS`] readings in a row. Lowering the
+//! limit below the last reading skips straight to the first point predicted
+//! to fit, so demand response sheds load within one reading.
+//!
+//! Like the thermal governor, it holds no hardware handles; the board's
+//! monitoring task feeds it power readings and applies the operating points
+//! it returns. Its frequency cap is combined with the thermal one via
+//! [`lowest_limit`].
+
+use crate::config::HardwareConfig;
+
+use super::thermal::MIN_FREQUENCY_FRACTION;
+
+/// Frequency change per step, a multiple of the PLL ramp step.
+pub const FREQUENCY_STEP_MHZ: f32 = 25.0;
+
+/// Core voltage at the lowest operating point, as a fraction of nominal.
+pub const MIN_VOLTAGE_FRACTION: f32 = 0.92;
+
+/// Consecutive readings with headroom required before stepping back up.
+pub const RAISE_AFTER_READINGS: u32 = 3;
+
+/// Margin kept below the limit when predicting the next point's power.
+const RAISE_MARGIN: f32 = 0.95;
+
+/// Frequency and core voltage for the chips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperatingPoint {
+    /// Frequency cap, `None` at full speed
+    pub frequency_limit_mhz: Option<f32>,
+    /// Core voltage in volts
+    pub core_voltage_v: f32,
+}
+
+/// Steps operating points down and up to hold input power under a limit.
+#[derive(Debug, Clone)]
+pub struct PowerGovernor {
+    limit_w: Option<f32>,
+    max_frequency_mhz: f32,
+    nominal_voltage_v: f32,
+    /// Steps below full speed, 0 at full speed
+    level: u32,
+    max_level: u32,
+    /// Consecutive readings with headroom for the next point up
+    headroom_readings: u32,
+    /// Latest reading and the level it was taken at
+    last_reading: Option<(f32, u32)>,
+}
+
+impl PowerGovernor {
+    /// Governor for chips running at up to `max_frequency_mhz` on a core
+    /// voltage of `nominal_voltage_v`.
+    pub fn new(hardware: &HardwareConfig, max_frequency_mhz: f32, nominal_voltage_v: f32) -> Self {
+        let range_mhz = max_frequency_mhz * (1.0 - MIN_FREQUENCY_FRACTION);
+        Self {
+            limit_w: hardware.power_limit,
+            max_frequency_mhz,
+            nominal_voltage_v,
+            level: 0,
+            max_level: (range_mhz / FREQUENCY_STEP_MHZ).floor() as u32,
+            headroom_readings: 0,
+            last_reading: None,
+        }
+    }
+
+    /// Input power limit in watts, `None` for no limit.
+    pub fn limit(&self) -> Option<f32> {
+        self.limit_w
+    }
+
+    /// Change the limit. Lifting it returns to full speed at once. A limit
+    /// below the last reading moves straight to the fastest point predicted
+    /// to fit under it; otherwise the next readings step toward it.
+    pub fn set_limit(&mut self, limit_w: Option<f32>) {
+        self.limit_w = limit_w;
+        self.headroom_readings = 0;
+        let Some(limit_w) = limit_w else {
+            self.level = 0;
+            return;
+        };
+        let Some((power_w, level)) = self.last_reading else {
+            return;
+        };
+        let measured = self.point_at(level).relative_power(self.max_frequency_mhz);
+        while self.level < self.max_level {
+            let predicted_w = power_w
+                * self
+                    .point_at(self.level)
+                    .relative_power(self.max_frequency_mhz)
+                / measured;
+            if predicted_w <= limit_w {
+                break;
+            }
+            self.level += 1;
+        }
+    }
+
+    /// Current operating point.
+    pub fn operating_point(&self) -> OperatingPoint {
+        self.point_at(self.level)
+    }
+
+    /// Take a power reading in watts; returns the new operating point if it
+    /// changed. A missing reading changes nothing.
+    pub fn update(&mut self, power_w: Option<f32>) -> Option<OperatingPoint> {
+        if let Some(power_w) = power_w {
+            self.last_reading = Some((power_w, self.level));
+        }
+        let (Some(limit_w), Some(power_w)) = (self.limit_w, power_w) else {
+            return None;
+        };
+
+        if power_w > limit_w {
+            self.headroom_readings = 0;
+            if self.level == self.max_level {
+                return None;
+            }
+            self.level += 1;
+            return Some(self.operating_point());
+        }
+
+        if self.level == 0 {
+            return None;
+        }
+        let current = self.point_at(self.level);
+        let next = self.point_at(self.level - 1);
+        let predicted_w = power_w * next.relative_power(self.max_frequency_mhz)
+            / current.relative_power(self.max_frequency_mhz);
+        if predicted_w > limit_w * RAISE_MARGIN {
+            self.headroom_readings = 0;
+            return None;
+        }
+
+        self.headroom_readings += 1;
+        if self.headroom_readings < RAISE_AFTER_READINGS {
+            return None;
+        }
+        self.headroom_readings = 0;
+        self.level -= 1;
+        Some(self.operating_point())
+    }
+
+    fn point_at(&self, level: u32) -> OperatingPoint {
+        if level == 0 {
+            return OperatingPoint {
+                frequency_limit_mhz: None,
+                core_voltage_v: self.nominal_voltage_v,
+            };
+        }
+
+        let frequency_mhz = self.max_frequency_mhz - level as f32 * FREQUENCY_STEP_MHZ;
+        let min_frequency_mhz = self.max_frequency_mhz * MIN_FREQUENCY_FRACTION;
+        let fraction =
+            (frequency_mhz - min_frequency_mhz) / (self.max_frequency_mhz - min_frequency_mhz);
+        let min_voltage_v = self.nominal_voltage_v * MIN_VOLTAGE_FRACTION;
+        OperatingPoint {
+            frequency_limit_mhz: Some(frequency_mhz),
+            core_voltage_v: min_voltage_v + fraction * (self.nominal_voltage_v - min_voltage_v),
+        }
+    }
+}
+
+impl OperatingPoint {
+    /// Power relative to some reference, proportional to f * V^2.
+    fn relative_power(&self, max_frequency_mhz: f32) -> f32 {
+        self.frequency_limit_mhz.unwrap_or(max_frequency_mhz) * self.core_voltage_v.powi(2)
+    }
+}
+
+/// The tighter of two caps, e.g. frequency or power limits.
+pub fn lowest_limit(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn governor(limit_w: Option<f32>) -> PowerGovernor {
+        let hardware = HardwareConfig {
+            power_limit: limit_w,
+            ..HardwareConfig::default()
+        };
+        PowerGovernor::new(&hardware, 525.0, 1.15)
+    }
+
+    #[test]
+    fn test_unlimited_never_steps() {
+        let mut governor = governor(None);
+        assert_eq!(governor.update(Some(1000.0)), None);
+        assert_eq!(governor.operating_point().frequency_limit_mhz, None);
+    }
+
+    #[test]
+    fn test_steps_down_to_floor() {
+        let mut governor = governor(Some(15.0));
+
+        let first = governor.update(Some(18.0)).unwrap();
+        assert_eq!(first.frequency_limit_mhz, Some(500.0));
+        assert!(first.core_voltage_v < 1.15);
+
+        // Keeps stepping while over the limit, down to the lowest point
+        let mut last = first;
+        while let Some(point) = governor.update(Some(18.0)) {
+            assert!(point.frequency_limit_mhz < last.frequency_limit_mhz);
+            assert!(point.core_voltage_v < last.core_voltage_v);
+            last = point;
+        }
+        assert!(last.frequency_limit_mhz.unwrap() >= 525.0 * MIN_FREQUENCY_FRACTION);
+        assert!(last.core_voltage_v >= 1.15 * MIN_VOLTAGE_FRACTION - 1e-6);
+
+        // Caps sit on the PLL step grid
+        assert_eq!(last.frequency_limit_mhz.unwrap() % 6.25, 0.0);
+    }
+
+    #[test]
+    fn test_steps_up_with_headroom() {
+        let mut governor = governor(Some(15.0));
+        governor.update(Some(16.0));
+        governor.update(Some(16.0));
+        assert_eq!(governor.operating_point().frequency_limit_mhz, Some(475.0));
+
+        // Just under the limit: the next point up would exceed it
+        for _ in 0..10 {
+            assert_eq!(governor.update(Some(14.5)), None);
+        }
+
+        // Well under: steps up after consecutive readings with headroom
+        assert_eq!(governor.update(Some(10.0)), None);
+        assert_eq!(governor.update(Some(10.0)), None);
+        let point = governor.update(Some(10.0)).unwrap();
+        assert_eq!(point.frequency_limit_mhz, Some(500.0));
+
+        // A missing reading changes nothing
+        assert_eq!(governor.update(None), None);
+    }
+
+    #[test]
+    fn test_set_limit() {
+        let mut governor = governor(Some(15.0));
+        governor.update(Some(16.0));
+        assert_eq!(governor.operating_point().frequency_limit_mhz, Some(500.0));
+
+        // A limit above the last reading leaves the point alone
+        governor.set_limit(Some(20.0));
+        assert_eq!(governor.operating_point().frequency_limit_mhz, Some(500.0));
+
+        // A tighter one moves down at once
+        governor.set_limit(Some(12.0));
+        let point = governor.operating_point();
+        assert!(point.frequency_limit_mhz.unwrap() < 500.0);
+        assert_eq!(governor.update(Some(11.5)), None);
+
+        // Lifting it restores full speed
+        governor.set_limit(None);
+        assert_eq!(governor.limit(), None);
+        assert_eq!(governor.operating_point().frequency_limit_mhz, None);
+        assert_eq!(governor.update(Some(14.0)), None);
+    }
+
+    #[test]
+    fn test_set_limit_sheds_at_once() {
+        let mut governor = governor(None);
+        governor.update(Some(20.0));
+
+        // Half the power in one move, to a point predicted to fit
+        governor.set_limit(Some(10.0));
+        let point = governor.operating_point();
+        let predicted_w =
+            20.0 * point.relative_power(525.0) / governor.point_at(0).relative_power(525.0);
+        assert!(predicted_w <= 10.0);
+        let faster = governor.point_at(governor.level - 1);
+        assert!(
+            20.0 * faster.relative_power(525.0) / governor.point_at(0).relative_power(525.0) > 10.0
+        );
+
+        // An unreachable limit goes to the lowest point
+        governor.set_limit(Some(1.0));
+        assert_eq!(governor.level, governor.max_level);
+    }
+
+    #[test]
+    fn test_lowest_limit() {
+        assert_eq!(lowest_limit(None, None), None);
+        assert_eq!(lowest_limit(Some(400.0), None), Some(400.0));
+        assert_eq!(lowest_limit(None, Some(450.0)), Some(450.0));
+        assert_eq!(lowest_limit(Some(400.0), Some(450.0)), Some(400.0));
+    }
+}
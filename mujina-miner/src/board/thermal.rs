@@ -0,0 +1,151 @@
+//! Thermal protection.
+//!
+//! [`ThermalGovernor`] enforces [`HardwareConfig::temp_limit`] for the ASICs
+//! and [`HardwareConfig::vr_temp_limit`] for the core voltage regulator.
+//! Within [`THROTTLE_BAND_C`] of a limit the chips' frequency is capped,
+//! linearly from full speed at the start of the band down to
+//! [`MIN_FREQUENCY_FRACTION`] of it just below the limit. Reaching a limit is
+//! an overtemperature fault: the board stops its hash threads, holds the
+//! chips in reset and turns the regulator off.
+//!
+//! Like the fan controller, the governor holds no hardware handles; a
+//! board's monitoring task feeds it readings and acts on its decisions.
+
+use crate::config::HardwareConfig;
+
+/// Width of the throttling band below each limit, in degrees Celsius.
+pub const THROTTLE_BAND_C: f32 = 10.0;
+
+/// Lowest frequency throttling goes to, as a fraction of full speed.
+pub const MIN_FREQUENCY_FRACTION: f32 = 0.5;
+
+/// Frequency caps are multiples of the PLL ramp step, so small temperature
+/// changes do not retune the chips.
+const FREQUENCY_STEP_MHZ: f32 = 6.25;
+
+/// What the board should do about its temperatures.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThermalDecision {
+    /// Keep hashing, at no more than the given frequency if any.
+    Run { frequency_limit_mhz: Option<f32> },
+
+    /// A limit was reached; shut the chips down.
+    Overtemperature { fault: String },
+}
+
+/// Maps temperatures to frequency caps and overtemperature faults.
+#[derive(Debug, Clone)]
+pub struct ThermalGovernor {
+    asic_limit_c: f32,
+    vr_limit_c: f32,
+    max_frequency_mhz: f32,
+}
+
+impl ThermalGovernor {
+    /// Governor for chips running at up to `max_frequency_mhz`.
+    pub fn new(hardware: &HardwareConfig, max_frequency_mhz: f32) -> Self {
+        Self {
+            asic_limit_c: hardware.temp_limit,
+            vr_limit_c: hardware.vr_temp_limit,
+            max_frequency_mhz,
+        }
+    }
+
+    /// Decide from the latest readings; a missing reading is ignored.
+    pub fn assess(&self, asic_temp_c: Option<f32>, vr_temp_c: Option<f32>) -> ThermalDecision {
+        let sensors = [
+            ("ASIC", asic_temp_c, self.asic_limit_c),
+            ("voltage regulator", vr_temp_c, self.vr_limit_c),
+        ];
+
+        let mut throttle: f32 = 0.0;
+        for (sensor, temp_c, limit_c) in sensors {
+            let Some(temp_c) = temp_c else {
+                continue;
+            };
+            if temp_c >= limit_c {
+                return ThermalDecision::Overtemperature {
+                    fault: format!(
+                        "{sensor} temperature {temp_c:.1} degC reached limit {limit_c:.1} degC"
+                    ),
+                };
+            }
+            let into_band = (temp_c - (limit_c - THROTTLE_BAND_C)) / THROTTLE_BAND_C;
+            throttle = throttle.max(into_band.clamp(0.0, 1.0));
+        }
+
+        if throttle == 0.0 {
+            return ThermalDecision::Run {
+                frequency_limit_mhz: None,
+            };
+        }
+
+        let max = self.max_frequency_mhz;
+        let min = max * MIN_FREQUENCY_FRACTION;
+        let limit = max - throttle * (max - min);
+        let limit = ((limit / FREQUENCY_STEP_MHZ).floor() * FREQUENCY_STEP_MHZ).max(min);
+        ThermalDecision::Run {
+            frequency_limit_mhz: (limit < max).then_some(limit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn governor() -> ThermalGovernor {
+        // Limits 80 degC (ASIC) and 105 degC (VR)
+        ThermalGovernor::new(&HardwareConfig::default(), 525.0)
+    }
+
+    fn limit(decision: ThermalDecision) -> Option<f32> {
+        match decision {
+            ThermalDecision::Run {
+                frequency_limit_mhz,
+            } => frequency_limit_mhz,
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_full_speed_below_band() {
+        let governor = governor();
+        assert_eq!(limit(governor.assess(Some(65.0), Some(90.0))), None);
+        assert_eq!(limit(governor.assess(None, None)), None);
+    }
+
+    #[test]
+    fn test_throttles_progressively() {
+        let governor = governor();
+
+        let warm = limit(governor.assess(Some(72.0), None)).unwrap();
+        let hot = limit(governor.assess(Some(78.0), None)).unwrap();
+        let hottest = limit(governor.assess(Some(79.9), None)).unwrap();
+        assert!(warm < 525.0);
+        assert!(hot < warm);
+        assert!(hottest >= 525.0 * MIN_FREQUENCY_FRACTION);
+
+        // Caps sit on the PLL step grid
+        assert_eq!(warm % FREQUENCY_STEP_MHZ, 0.0);
+
+        // The hotter sensor, relative to its limit, decides
+        assert_eq!(limit(governor.assess(Some(60.0), Some(103.0))), Some(hot));
+    }
+
+    #[test]
+    fn test_overtemperature() {
+        let governor = governor();
+
+        match governor.assess(Some(80.0), Some(50.0)) {
+            ThermalDecision::Overtemperature { fault } => assert!(fault.contains("ASIC")),
+            other => panic!("expected Overtemperature, got {other:?}"),
+        }
+        match governor.assess(Some(50.0), Some(110.0)) {
+            ThermalDecision::Overtemperature { fault } => {
+                assert!(fault.contains("voltage regulator"))
+            }
+            other => panic!("expected Overtemperature, got {other:?}"),
+        }
+    }
+}
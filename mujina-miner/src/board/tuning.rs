@@ -0,0 +1,486 @@
+//! Frequency and voltage autotuning.
+//!
+//! [`Autotuner`] sweeps a board through operating points as described on
+//! [`TuningConfig`](crate::config::TuningConfig). Each point is applied, left to settle for [`SETTLE`],
+//! then measured for the configured dwell: hashrate from the hashes the
+//! chips' nonces stand for, the hardware error rate from the same counts,
+//! and average input power. A point with more than
+//! [`HW_ERROR_RATE_LIMIT`] hardware errors is retried [`VOLTAGE_STEP_V`]
+//! higher; a stable one is recorded and the sweep moves up a frequency step,
+//! keeping its voltage, since faster chips never need less.
+//!
+//! The sweep stops early once the chips come within the thermal governor's
+//! throttling band or input power exceeds the power limit, so the chosen
+//! point can be held without either governor stepping in. Among the stable
+//! points, the lowest J/TH or the highest hashrate wins.
+//!
+//! Like the governors, the tuner holds no hardware handles; the board's
+//! monitoring task feeds it readings and applies the points it asks for.
+//! [`load_profile`] and [`save_profile`] keep the result per board serial.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{power, thermal::THROTTLE_BAND_C};
+use crate::{
+    config::{HardwareConfig, TuningConfig, TuningGoal},
+    hash_thread::health::HW_ERROR_RATE_LIMIT,
+};
+
+/// Time a new point is left to settle before it is measured.
+pub const SETTLE: Duration = Duration::from_secs(30);
+
+/// Core voltage increase when a point has too many hardware errors.
+pub const VOLTAGE_STEP_V: f32 = 0.02;
+
+/// Frequency and core voltage to run the chips at.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TunedPoint {
+    pub frequency_mhz: f32,
+    pub core_voltage_v: f32,
+}
+
+/// Result of a completed sweep, as persisted per board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TunedProfile {
+    /// Goal the point was chosen for
+    pub goal: TuningGoal,
+    pub frequency_mhz: f32,
+    pub core_voltage_v: f32,
+    /// Measured hashrate in H/s
+    pub hashrate: f64,
+    /// Measured input power in watts
+    pub power_w: f32,
+    /// When the sweep finished, in Unix seconds
+    pub tuned_at: u64,
+}
+
+impl TunedProfile {
+    /// The tuned operating point.
+    pub fn point(&self) -> TunedPoint {
+        TunedPoint {
+            frequency_mhz: self.frequency_mhz,
+            core_voltage_v: self.core_voltage_v,
+        }
+    }
+
+    /// Energy per hash in J/TH.
+    pub fn efficiency_j_th(&self) -> f64 {
+        efficiency_j_th(self.power_w, self.hashrate)
+    }
+
+    /// Whether a sweep under `config` could have chosen this point for chips
+    /// with a full speed of `max_frequency_mhz`. Profiles saved under other
+    /// limits, or edited by hand, may not be.
+    pub fn within(&self, config: &TuningConfig, max_frequency_mhz: f32) -> bool {
+        let min_frequency_mhz = config.min_frequency_mhz.min(max_frequency_mhz);
+        (config.min_voltage_v..=config.max_voltage_v).contains(&self.core_voltage_v)
+            && (min_frequency_mhz..=max_frequency_mhz).contains(&self.frequency_mhz)
+    }
+}
+
+/// Counters and sensors sampled by the board for the tuner.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TuningReading {
+    /// Whether the hash thread is hashing
+    pub hashing: bool,
+    /// Hashes done since the thread started
+    pub hashes: f64,
+    /// Nonces and hardware errors reported since the thread started
+    pub nonces: u64,
+    pub hardware_errors: u64,
+    pub power_w: Option<f32>,
+    pub asic_temp_c: Option<f32>,
+    pub vr_temp_c: Option<f32>,
+}
+
+/// What the board should do next.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TuningUpdate {
+    /// Apply this point and keep feeding readings
+    Try(TunedPoint),
+
+    /// The sweep is over; run at the profile's point, or the defaults if no
+    /// point was stable
+    Finished(Option<TunedProfile>),
+}
+
+#[derive(Debug, Clone)]
+enum Phase {
+    Settling {
+        until: Instant,
+    },
+    Measuring {
+        since: Instant,
+        start: TuningReading,
+        power_sum_w: f32,
+        power_readings: u32,
+    },
+    Done,
+}
+
+/// A stable point and what it delivered.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    point: TunedPoint,
+    hashrate: f64,
+    power_w: f32,
+}
+
+/// Sweeps operating points and picks the best for a goal.
+#[derive(Debug, Clone)]
+pub struct Autotuner {
+    goal: TuningGoal,
+    dwell: Duration,
+    max_voltage_v: f32,
+    asic_limit_c: f32,
+    vr_limit_c: f32,
+    power_limit_w: Option<f32>,
+    /// Frequencies to visit, in order
+    frequencies: Vec<f32>,
+    /// Index into `frequencies` of the point being tried
+    step: usize,
+    voltage_v: f32,
+    phase: Phase,
+    samples: Vec<Sample>,
+}
+
+impl Autotuner {
+    /// Tuner for chips reaching full speed at `max_frequency_mhz`, starting
+    /// its sweep at `now`.
+    pub fn new(hardware: &HardwareConfig, max_frequency_mhz: f32, now: Instant) -> Self {
+        let config = &hardware.tuning;
+        let mut frequencies = Vec::new();
+        let mut frequency = config.min_frequency_mhz.min(max_frequency_mhz);
+        while frequency < max_frequency_mhz {
+            frequencies.push(frequency);
+            frequency += power::FREQUENCY_STEP_MHZ;
+        }
+        frequencies.push(max_frequency_mhz);
+
+        Self {
+            goal: config.goal,
+            dwell: Duration::from_secs(config.dwell_s),
+            max_voltage_v: config.max_voltage_v,
+            asic_limit_c: hardware.temp_limit - THROTTLE_BAND_C,
+            vr_limit_c: hardware.vr_temp_limit - THROTTLE_BAND_C,
+            power_limit_w: hardware.power_limit,
+            frequencies,
+            step: 0,
+            voltage_v: config.min_voltage_v,
+            phase: Phase::Settling {
+                until: now + SETTLE,
+            },
+            samples: Vec::new(),
+        }
+    }
+
+    /// Point being tried.
+    pub fn point(&self) -> TunedPoint {
+        TunedPoint {
+            frequency_mhz: self.frequencies[self.step],
+            core_voltage_v: self.voltage_v,
+        }
+    }
+
+    /// Take a reading made at `now`; returns what to do if the sweep moved
+    /// on.
+    pub fn update(&mut self, now: Instant, reading: TuningReading) -> Option<TuningUpdate> {
+        if matches!(self.phase, Phase::Done) {
+            return None;
+        }
+
+        // Too hot or too hungry: this point and anything above it are out
+        let too_hot = reading.asic_temp_c.is_some_and(|t| t >= self.asic_limit_c)
+            || reading.vr_temp_c.is_some_and(|t| t >= self.vr_limit_c);
+        let too_hungry = matches!(
+            (reading.power_w, self.power_limit_w),
+            (Some(power), Some(limit)) if power > limit
+        );
+        if too_hot || too_hungry {
+            return Some(self.finish());
+        }
+
+        // Measure only while hashing; otherwise start over once it resumes
+        if !reading.hashing {
+            self.phase = Phase::Settling {
+                until: now + SETTLE,
+            };
+            return None;
+        }
+
+        match &mut self.phase {
+            Phase::Settling { until } => {
+                if now >= *until {
+                    self.phase = Phase::Measuring {
+                        since: now,
+                        start: reading,
+                        power_sum_w: 0.0,
+                        power_readings: 0,
+                    };
+                }
+                None
+            }
+            Phase::Measuring {
+                since,
+                start,
+                power_sum_w,
+                power_readings,
+            } => {
+                if let Some(power) = reading.power_w {
+                    *power_sum_w += power;
+                    *power_readings += 1;
+                }
+                let elapsed = now.saturating_duration_since(*since);
+                if elapsed < self.dwell || *power_readings == 0 {
+                    return None;
+                }
+
+                let hashrate = (reading.hashes - start.hashes) / elapsed.as_secs_f64();
+                let nonces = reading.nonces.saturating_sub(start.nonces) as f64;
+                let errors = reading
+                    .hardware_errors
+                    .saturating_sub(start.hardware_errors) as f64;
+                let error_rate = if errors > 0.0 {
+                    errors / (errors + nonces)
+                } else {
+                    0.0
+                };
+                let power_w = *power_sum_w / *power_readings as f32;
+                Some(self.measured(now, hashrate, error_rate, power_w))
+            }
+            Phase::Done => None,
+        }
+    }
+
+    /// Act on a completed measurement of the current point.
+    fn measured(
+        &mut self,
+        now: Instant,
+        hashrate: f64,
+        error_rate: f64,
+        power_w: f32,
+    ) -> TuningUpdate {
+        if error_rate > HW_ERROR_RATE_LIMIT {
+            self.voltage_v += VOLTAGE_STEP_V;
+            if self.voltage_v > self.max_voltage_v + f32::EPSILON {
+                return self.finish();
+            }
+        } else {
+            self.samples.push(Sample {
+                point: self.point(),
+                hashrate,
+                power_w,
+            });
+            self.step += 1;
+            if self.step == self.frequencies.len() {
+                return self.finish();
+            }
+        }
+
+        self.phase = Phase::Settling {
+            until: now + SETTLE,
+        };
+        TuningUpdate::Try(self.point())
+    }
+
+    /// End the sweep with the best sample for the goal.
+    fn finish(&mut self) -> TuningUpdate {
+        self.phase = Phase::Done;
+
+        let samples = self.samples.iter().filter(|s| s.hashrate > 0.0);
+        let best = match self.goal {
+            TuningGoal::Hashrate => samples.max_by(|a, b| a.hashrate.total_cmp(&b.hashrate)),
+            _ => samples.min_by(|a, b| {
+                efficiency_j_th(a.power_w, a.hashrate)
+                    .total_cmp(&efficiency_j_th(b.power_w, b.hashrate))
+            }),
+        };
+
+        TuningUpdate::Finished(best.map(|sample| {
+            TunedProfile {
+                goal: self.goal,
+                frequency_mhz: sample.point.frequency_mhz,
+                core_voltage_v: sample.point.core_voltage_v,
+                hashrate: sample.hashrate,
+                power_w: sample.power_w,
+                tuned_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+            }
+        }))
+    }
+}
+
+fn efficiency_j_th(power_w: f32, hashrate: f64) -> f64 {
+    power_w as f64 / (hashrate / 1e12)
+}
+
+/// File holding the profile of the board with `serial` in `dir`.
+fn profile_path(dir: &Path, serial: &str) -> PathBuf {
+    let name: String = serial
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    dir.join(format!("{name}.json"))
+}
+
+/// Load the tuned profile of the board with `serial`, if there is one.
+pub fn load_profile(dir: &Path, serial: &str) -> io::Result<Option<TunedProfile>> {
+    match std::fs::read(profile_path(dir, serial)) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Store the tuned profile of the board with `serial`.
+pub fn save_profile(dir: &Path, serial: &str, profile: &TunedProfile) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let path = profile_path(dir, serial);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(profile)?)?;
+    std::fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hardware(goal: TuningGoal) -> HardwareConfig {
+        let mut hardware = HardwareConfig::default();
+        hardware.tuning.goal = goal;
+        hardware.tuning.min_frequency_mhz = 450.0;
+        hardware.tuning.dwell_s = 60;
+        hardware
+    }
+
+    /// Simulated board: hashes at the point's frequency, needs 1.1 V above
+    /// 480 MHz, and draws power growing with frequency and voltage squared.
+    fn run(tuner: &mut Autotuner, mut now: Instant) -> Option<TunedProfile> {
+        let tick = Duration::from_secs(5);
+        let mut reading = TuningReading {
+            hashing: true,
+            ..Default::default()
+        };
+        for _ in 0..10_000 {
+            let point = tuner.point();
+            let unstable = point.frequency_mhz > 480.0 && point.core_voltage_v < 1.1 - 1e-3;
+            let hashrate = point.frequency_mhz as f64 * 2.0e9;
+            reading.hashes += hashrate * tick.as_secs_f64();
+            reading.nonces += 100;
+            reading.hardware_errors += if unstable { 10 } else { 0 };
+            reading.power_w = Some(point.frequency_mhz * point.core_voltage_v.powi(2) * 0.03 + 2.0);
+
+            now += tick;
+            match tuner.update(now, reading) {
+                Some(TuningUpdate::Finished(profile)) => return profile,
+                Some(TuningUpdate::Try(next)) => assert_ne!(next, point),
+                None => {}
+            }
+        }
+        panic!("sweep did not finish");
+    }
+
+    #[test]
+    fn test_sweeps_to_best_point() {
+        let now = Instant::now();
+
+        let mut tuner = Autotuner::new(&hardware(TuningGoal::Hashrate), 525.0, now);
+        let profile = run(&mut tuner, now).unwrap();
+        assert_eq!(profile.frequency_mhz, 525.0);
+        assert!((profile.core_voltage_v - 1.11).abs() < 1e-3);
+        assert!((profile.hashrate - 525.0 * 2.0e9).abs() < 1e9);
+
+        // The fixed 2 W overhead favours speed until the voltage step costs
+        // more than it brings
+        let mut tuner = Autotuner::new(&hardware(TuningGoal::Efficiency), 525.0, now);
+        let profile = run(&mut tuner, now).unwrap();
+        assert_eq!(profile.frequency_mhz, 475.0);
+        assert!((profile.core_voltage_v - 1.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_stops_at_limits() {
+        let now = Instant::now();
+
+        // 525 MHz at 1.11 V draws about 21.4 W
+        let mut hardware = hardware(TuningGoal::Hashrate);
+        hardware.power_limit = Some(20.0);
+        let mut tuner = Autotuner::new(&hardware, 525.0, now);
+        let profile = run(&mut tuner, now).unwrap();
+        assert!(profile.frequency_mhz < 525.0);
+        assert!(profile.power_w <= 20.0);
+
+        // No voltage in range is stable above 480 MHz
+        let mut hardware = self::hardware(TuningGoal::Hashrate);
+        hardware.tuning.max_voltage_v = 1.08;
+        let mut tuner = Autotuner::new(&hardware, 525.0, now);
+        assert_eq!(run(&mut tuner, now).unwrap().frequency_mhz, 475.0);
+
+        // Too hot from the start: nothing to choose from
+        let mut tuner = Autotuner::new(&self::hardware(TuningGoal::Hashrate), 525.0, now);
+        let reading = TuningReading {
+            hashing: true,
+            asic_temp_c: Some(75.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            tuner.update(now, reading),
+            Some(TuningUpdate::Finished(None))
+        );
+    }
+
+    #[test]
+    fn test_profile_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("mujina-tuning-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(load_profile(&dir, "ab/12").unwrap(), None);
+        let profile = TunedProfile {
+            goal: TuningGoal::Efficiency,
+            frequency_mhz: 475.0,
+            core_voltage_v: 1.05,
+            hashrate: 9.5e11,
+            power_w: 17.7,
+            tuned_at: 1_700_000_000,
+        };
+        save_profile(&dir, "ab/12", &profile).unwrap();
+        assert_eq!(load_profile(&dir, "ab/12").unwrap(), Some(profile));
+        assert!(dir.join("ab_12.json").exists());
+    }
+
+    #[test]
+    fn test_profile_within_limits() {
+        let config = TuningConfig::default();
+        let profile = TunedProfile {
+            goal: TuningGoal::Efficiency,
+            frequency_mhz: 475.0,
+            core_voltage_v: 1.10,
+            hashrate: 9.5e11,
+            power_w: 17.7,
+            tuned_at: 1_700_000_000,
+        };
+        assert!(profile.within(&config, 525.0));
+
+        // Over-volted, e.g. saved before max_voltage_v was lowered
+        let over_volted = TunedProfile {
+            core_voltage_v: 1.30,
+            ..profile.clone()
+        };
+        assert!(!over_volted.within(&config, 525.0));
+
+        // Faster than the chips' full speed
+        assert!(!profile.within(&config, 450.0));
+    }
+}
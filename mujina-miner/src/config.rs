@@ -3,12 +3,35 @@
 //! This module handles loading and validating configuration from TOML files,
 //! environment variables, and command-line arguments. It supports hot-reload
 //! via file watching.
+//!
+//! Every section and field has a default, so an empty file (or no file at
+//! all) yields a working configuration and a file only needs to mention what
+//! it changes.
 
+use anyhow::{bail, Context};
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use utoipa::ToSchema;
+
+use crate::api_client::types::{OperatingMode, PerformanceProfile};
+use crate::schedule::Cron;
+
+/// Environment variable naming an explicit configuration file.
+pub const CONFIG_ENV: &str = "MUJINA_CONFIG";
+
+/// System-wide configuration file.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/mujina/mujina.toml";
+
+/// Accepted values of `daemon.log_level`.
+const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
 
 /// Main configuration structure for the miner.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     /// Daemon configuration
     pub daemon: DaemonConfig,
@@ -21,24 +44,57 @@ pub struct Config {
 
     /// API server configuration
     pub api: ApiConfig,
+
+    /// cgminer-compatible API server configuration
+    pub cgminer_api: CgminerApiConfig,
+
+    /// Statistics history configuration
+    pub history: HistoryConfig,
+
+    /// Alert rules and notification targets
+    pub alerts: AlertsConfig,
+
+    /// MQTT telemetry and Home Assistant integration
+    pub mqtt: MqttConfig,
+
+    /// Log output configuration
+    pub logging: LoggingConfig,
+
+    /// OpenTelemetry trace export
+    pub otlp: OtlpConfig,
+
+    /// Operating modes switched at scheduled times
+    pub schedule: ScheduleConfig,
 }
 
 /// Daemon process configuration.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct DaemonConfig {
     /// PID file location
+    #[schema(value_type = Option<String>)]
     pub pid_file: Option<PathBuf>,
 
     /// Log level
     pub log_level: String,
 
     /// Use systemd notification
-    #[serde(default)]
     pub systemd: bool,
 }
 
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            pid_file: None,
+            log_level: "info".to_string(),
+            systemd: false,
+        }
+    }
+}
+
 /// Pool connection configuration.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct PoolConfig {
     /// Pool URL (stratum+tcp://...)
     pub url: String,
@@ -55,47 +111,1057 @@ pub struct PoolConfig {
 }
 
 /// Hardware configuration.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct HardwareConfig {
-    /// Temperature limits
+    /// Temperature limits for the ASICs and the voltage regulator, in degrees
+    /// Celsius; boards throttle approaching them and shut down reaching them
     pub temp_limit: f32,
+    pub vr_temp_limit: f32,
 
     /// Fan control settings
     pub fan_min_rpm: u32,
     pub fan_max_rpm: u32,
 
-    /// Power limits
+    /// Closed-loop fan control
+    pub fan: FanControlConfig,
+
+    /// Input power limit in watts; boards step frequency and core voltage
+    /// down to stay under it
     pub power_limit: Option<f32>,
+
+    /// Frequency and voltage autotuning
+    pub tuning: TuningConfig,
+
+    /// Performance profiles by chip type, e.g. `bm1370`. Defining any chip
+    /// type replaces the built-in table.
+    pub profiles: BTreeMap<String, ChipProfiles>,
+}
+
+impl Default for HardwareConfig {
+    fn default() -> Self {
+        Self {
+            temp_limit: 80.0,
+            vr_temp_limit: 105.0,
+            fan_min_rpm: 1000,
+            fan_max_rpm: 6000,
+            fan: FanControlConfig::default(),
+            power_limit: None,
+            tuning: TuningConfig::default(),
+            profiles: BTreeMap::from([(
+                "bm1370".to_string(),
+                ChipProfiles {
+                    eco: ProfileSettings {
+                        frequency_mhz: 400.0,
+                        core_voltage_v: 1.10,
+                        fan_target_temp: 55.0,
+                    },
+                    balanced: ProfileSettings {
+                        frequency_mhz: 525.0,
+                        core_voltage_v: 1.15,
+                        fan_target_temp: 60.0,
+                    },
+                    turbo: ProfileSettings {
+                        frequency_mhz: 600.0,
+                        core_voltage_v: 1.20,
+                        fan_target_temp: 65.0,
+                    },
+                },
+            )]),
+        }
+    }
+}
+
+/// Performance profiles of one chip type.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ChipProfiles {
+    pub eco: ProfileSettings,
+    pub balanced: ProfileSettings,
+    pub turbo: ProfileSettings,
+}
+
+impl ChipProfiles {
+    /// Settings of `profile`.
+    pub fn get(&self, profile: PerformanceProfile) -> &ProfileSettings {
+        match profile {
+            PerformanceProfile::Eco => &self.eco,
+            PerformanceProfile::Balanced => &self.balanced,
+            PerformanceProfile::Turbo => &self.turbo,
+        }
+    }
+}
+
+/// Operating point of a performance profile.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSettings {
+    /// Chip frequency in MHz
+    pub frequency_mhz: f32,
+
+    /// Core voltage in volts
+    pub core_voltage_v: f32,
+
+    /// ASIC temperature the fan holds, in degrees Celsius
+    pub fan_target_temp: f32,
+}
+
+/// Closed-loop fan control configuration.
+///
+/// The fan duty cycle follows a PID loop on the ASIC temperature. Gains are
+/// in percent duty per degree Celsius of error (proportional), per degree
+/// and second (integral) and per degree per second (derivative).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct FanControlConfig {
+    /// ASIC temperature to hold, in degrees Celsius
+    pub target_temp: f32,
+
+    /// Proportional gain
+    pub kp: f32,
+
+    /// Integral gain
+    pub ki: f32,
+
+    /// Derivative gain
+    pub kd: f32,
+
+    /// Lowest duty cycle in percent until the tachometer has reported a
+    /// turning fan, so a fan that cannot be seen is never stopped
+    pub min_percent: u8,
+}
+
+impl Default for FanControlConfig {
+    fn default() -> Self {
+        Self {
+            target_temp: 60.0,
+            kp: 4.0,
+            ki: 0.2,
+            kd: 0.0,
+            min_percent: 30,
+        }
+    }
+}
+
+/// Frequency and voltage autotuning configuration.
+///
+/// With a goal set, each board without a tuned profile for that goal, or
+/// with one outside the current voltage and frequency limits, sweeps
+/// frequency upward from `min_frequency_mhz`, at each step raising the core
+/// voltage from `min_voltage_v` until hardware errors are rare, and measures
+/// hashrate and power over `dwell_s`. The sweep ends at the chips' full
+/// speed, `max_voltage_v`, the thermal throttling band or the power limit,
+/// and the best point for the goal is kept in `dir`, one file per board
+/// serial number. Delete a board's file to tune it again.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuningConfig {
+    /// What to tune for
+    pub goal: TuningGoal,
+
+    /// Frequency the sweep starts at, in MHz
+    pub min_frequency_mhz: f32,
+
+    /// Core voltage range to search, in volts
+    pub min_voltage_v: f32,
+    pub max_voltage_v: f32,
+
+    /// Time each point is measured for, in seconds
+    pub dwell_s: u64,
+
+    /// Directory for tuned profiles
+    #[schema(value_type = String)]
+    pub dir: PathBuf,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        Self {
+            goal: TuningGoal::Off,
+            min_frequency_mhz: 400.0,
+            min_voltage_v: 1.05,
+            max_voltage_v: 1.20,
+            dwell_s: 120,
+            dir: PathBuf::from("/var/lib/mujina/tuning"),
+        }
+    }
+}
+
+/// Autotuning goal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TuningGoal {
+    /// Run at the default frequency and voltage
+    #[default]
+    Off,
+
+    /// Lowest energy per hash (J/TH)
+    Efficiency,
+
+    /// Highest hashrate
+    Hashrate,
 }
 
 /// API server configuration.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
 pub struct ApiConfig {
     /// Listen address
     pub listen: String,
 
     /// Enable TLS
-    #[serde(default)]
     pub tls: bool,
 
     /// TLS certificate path
+    #[schema(value_type = Option<String>)]
     pub cert_path: Option<PathBuf>,
 
     /// TLS key path
+    #[schema(value_type = Option<String>)]
     pub key_path: Option<PathBuf>,
 }
 
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            listen: crate::api::ApiConfig::default().bind_addr,
+            tls: false,
+            cert_path: None,
+            key_path: None,
+        }
+    }
+}
+
+/// cgminer-compatible API server configuration.
+///
+/// Serves the cgminer JSON API for farm management and monitoring tools
+/// that do not speak mujina's own API.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct CgminerApiConfig {
+    /// Start the server
+    pub enabled: bool,
+
+    /// Listen address
+    pub listen: String,
+
+    /// Allow commands that change miner state (switchpool, addpool)
+    pub writable: bool,
+}
+
+impl Default for CgminerApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:4028".to_string(),
+            writable: false,
+        }
+    }
+}
+
+/// Statistics history configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Keep history on disk so it survives restarts
+    pub persist: bool,
+
+    /// Directory for the history files
+    #[schema(value_type = String)]
+    pub dir: PathBuf,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            persist: true,
+            dir: PathBuf::from("/var/lib/mujina/history"),
+        }
+    }
+}
+
+/// Alerting configuration.
+///
+/// Rules are evaluated against the miner's status and events; alerts they
+/// raise are published on the event stream and posted to every webhook.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// Rules to evaluate
+    pub rules: Vec<AlertRule>,
+
+    /// Webhooks to notify
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Re-send a still-firing alert after this many seconds (0: never)
+    pub repeat_s: u64,
+
+    /// Suppress identical one-off alerts within this many seconds
+    pub dedupe_s: u64,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            webhooks: Vec::new(),
+            repeat_s: 0,
+            dedupe_s: 300,
+        }
+    }
+}
+
+/// A condition to alert on.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AlertRule {
+    /// Total hashrate (5-minute window) below `hashrate` H/s for `for_s`
+    /// seconds while mining
+    HashrateBelow {
+        hashrate: f64,
+        #[serde(default)]
+        for_s: u64,
+    },
+
+    /// A board's ASIC temperature above `celsius` for `for_s` seconds
+    TemperatureAbove {
+        celsius: f32,
+        #[serde(default)]
+        for_s: u64,
+    },
+
+    /// A pool without work for `for_s` seconds
+    PoolDown {
+        #[serde(default)]
+        for_s: u64,
+    },
+
+    /// A board was disconnected
+    BoardDisconnected,
+
+    /// A board reported a fault (e.g. voltage regulator)
+    BoardFault,
+
+    /// A chip or core domain is underperforming
+    ChipUnderperforming,
+
+    /// A block was found
+    BlockFound,
+}
+
+/// A webhook alerts are posted to.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// URL to POST to
+    pub url: String,
+
+    /// Request body template; defaults to the alert as JSON
+    ///
+    /// `{{rule}}`, `{{state}}`, `{{subject}}`, `{{summary}}`, `{{value}}`
+    /// and `{{time}}` are replaced by the alert's fields, escaped for use
+    /// inside a JSON string.
+    pub template: Option<String>,
+
+    /// Content type of the request body
+    #[serde(default = "default_content_type")]
+    pub content_type: String,
+
+    /// Extra request headers, e.g. for authorization
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+fn default_content_type() -> String {
+    "application/json".to_string()
+}
+
+/// MQTT client configuration.
+///
+/// Publishes miner and per-board state to a broker, announces it to Home
+/// Assistant via MQTT discovery, and accepts commands on topics under
+/// `topic_prefix`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct MqttConfig {
+    /// Connect to the broker
+    pub enabled: bool,
+
+    /// Broker host name or address
+    pub host: String,
+
+    /// Broker port
+    pub port: u16,
+
+    /// Username, if the broker requires authentication
+    pub username: Option<String>,
+
+    /// Password, if the broker requires authentication
+    pub password: Option<String>,
+
+    /// MQTT client identifier
+    pub client_id: String,
+
+    /// Prefix of every state and command topic
+    pub topic_prefix: String,
+
+    /// Publish Home Assistant discovery configuration
+    pub discovery: bool,
+
+    /// Home Assistant discovery topic prefix
+    pub discovery_prefix: String,
+
+    /// Seconds between state publications
+    pub interval_s: u64,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+            client_id: "mujina".to_string(),
+            topic_prefix: "mujina".to_string(),
+            discovery: true,
+            discovery_prefix: "homeassistant".to_string(),
+            interval_s: 10,
+        }
+    }
+}
+
+/// Log output configuration.
+///
+/// The base level is `daemon.log_level`; `levels` raises or lowers it for
+/// individual modules. `RUST_LOG`, when set, replaces both.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Format of the console log; journald is used instead of `text` when
+    /// running under systemd
+    pub format: LogFormat,
+
+    /// Levels by module path, e.g. `"mujina_miner::asic" = "debug"`
+    pub levels: BTreeMap<String, String>,
+
+    /// Also write the log to a file
+    pub file: Option<LogFileConfig>,
+
+    /// Recent events kept in memory for `/api/v1/logs` (0: none)
+    pub buffer_events: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            levels: BTreeMap::new(),
+            file: None,
+            buffer_events: 5000,
+        }
+    }
+}
+
+/// Log line format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+
+    /// One JSON object per line, with the fields of every enclosing span
+    Json,
+}
+
+/// Log file configuration.
+///
+/// The file is rotated when its period ends or it reaches `max_size_mb`,
+/// whichever comes first; `mujina.log` becomes `mujina.log.1`, and so on.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogFileConfig {
+    /// Log file location
+    #[schema(value_type = String)]
+    pub path: PathBuf,
+
+    /// Format of the file's lines
+    pub format: LogFormat,
+
+    /// Start a new file every period
+    pub rotation: LogRotation,
+
+    /// Start a new file once it reaches this many megabytes
+    pub max_size_mb: Option<u64>,
+
+    /// Rotated files to keep; older ones are deleted
+    pub max_files: usize,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("/var/log/mujina/mujina.log"),
+            format: LogFormat::Json,
+            rotation: LogRotation::Daily,
+            max_size_mb: Some(100),
+            max_files: 7,
+        }
+    }
+}
+
+/// Time-based log file rotation period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    /// Rotate on size only
+    Never,
+    Hourly,
+    Daily,
+}
+
+/// OpenTelemetry trace export configuration.
+///
+/// Spans (board and chip initialization, share handling, pool requests) are
+/// sent over OTLP/HTTP to a collector such as Jaeger or Tempo. Export is only
+/// available in builds with the `otel` feature.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct OtlpConfig {
+    /// Export spans
+    pub enabled: bool,
+
+    /// Collector traces endpoint
+    pub endpoint: String,
+
+    /// Fraction of traces to export, from 0 to 1
+    pub sample_ratio: f64,
+
+    /// Service name reported to the collector
+    pub service_name: String,
+
+    /// Extra HTTP headers, e.g. for authentication
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:4318/v1/traces".to_string(),
+            sample_ratio: 1.0,
+            service_name: "mujina-miner".to_string(),
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+/// Operating mode schedule.
+///
+/// Each rule puts every board in its mode at the times its cron expression
+/// matches, until the next rule starts. Before any rule has started, and
+/// with no rules at all, boards keep their configured operating point.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleConfig {
+    /// IANA time zone rules are evaluated in, e.g. `Europe/Berlin`;
+    /// defaults to the system time zone
+    pub timezone: Option<String>,
+
+    /// Rules in order; a later rule wins when two start at the same time
+    pub rules: Vec<ScheduleRule>,
+}
+
+/// Operating mode and the times it starts.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleRule {
+    /// Name shown in the schedule status
+    pub name: String,
+
+    /// Cron expression for the start times: minute, hour, day of month,
+    /// month and day of week, e.g. `0 22 * * mon-fri`
+    pub cron: String,
+
+    /// Performance profile; none for the tuned or default operating point
+    pub profile: Option<PerformanceProfile>,
+
+    /// Input power limit in watts; none for `hardware.power_limit`
+    pub power_limit: Option<f32>,
+
+    /// Stop hashing
+    #[serde(default)]
+    pub idle: bool,
+}
+
+impl ScheduleRule {
+    /// Mode the rule selects.
+    pub fn mode(&self) -> OperatingMode {
+        OperatingMode {
+            profile: self.profile,
+            power_limit: self.power_limit,
+            idle: self.idle,
+        }
+    }
+}
+
 impl Config {
     /// Load configuration from the default location.
+    ///
+    /// Uses the first file found among `$MUJINA_CONFIG`,
+    /// `~/.config/mujina/mujina.toml`, and `/etc/mujina/mujina.toml`, or the
+    /// defaults if none exists. A file named by `$MUJINA_CONFIG` must exist.
     pub fn load() -> anyhow::Result<Self> {
-        // TODO: Implement config loading from /etc/mujina/mujina.toml
-        // and ~/.config/mujina/mujina.toml with proper merging
-        unimplemented!("Config loading not yet implemented")
+        // TODO: Merge the user file over the system file instead of
+        // using whichever is found first
+        if let Ok(path) = std::env::var(CONFIG_ENV) {
+            return Self::load_from(Path::new(&path));
+        }
+
+        Ok(match Self::default_path() {
+            Some(path) => Self::load_from(&path)?,
+            None => Self::default(),
+        })
+    }
+
+    /// Path of the configuration file [`Config::load`] would read, if any.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+
+        let user = std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".config/mujina/mujina.toml"));
+
+        user.into_iter()
+            .chain([PathBuf::from(SYSTEM_CONFIG_PATH)])
+            .find(|path| path.exists())
     }
 
     /// Load configuration from a specific file.
-    pub fn load_from(_path: &Path) -> anyhow::Result<Self> {
-        // TODO: Implement TOML parsing
-        unimplemented!("Config loading not yet implemented")
+    ///
+    /// The file is parsed and validated; either failure is an error.
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let config = Self::parse(&contents)
+            .with_context(|| format!("invalid configuration in {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Parse and validate configuration from TOML text.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let config: Self = toml::from_str(contents)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check values that parse but make no sense together.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (i, pool) in self.pools.iter().enumerate() {
+            if pool.url.trim().is_empty() {
+                bail!("pools[{i}]: url must not be empty");
+            }
+            if pool.worker.trim().is_empty() {
+                bail!("pools[{i}]: worker must not be empty");
+            }
+        }
+
+        let hw = &self.hardware;
+        if hw.temp_limit <= 0.0 {
+            bail!("hardware.temp_limit must be positive");
+        }
+        if hw.vr_temp_limit <= 0.0 {
+            bail!("hardware.vr_temp_limit must be positive");
+        }
+        if hw.fan_min_rpm > hw.fan_max_rpm {
+            bail!("hardware.fan_min_rpm must not exceed hardware.fan_max_rpm");
+        }
+        if hw.fan.target_temp >= hw.temp_limit {
+            bail!("hardware.fan.target_temp must be below hardware.temp_limit");
+        }
+        if [hw.fan.kp, hw.fan.ki, hw.fan.kd]
+            .iter()
+            .any(|gain| !gain.is_finite() || *gain < 0.0)
+        {
+            bail!("hardware.fan gains must be non-negative");
+        }
+        if hw.fan.min_percent > 100 {
+            bail!("hardware.fan.min_percent must be at most 100");
+        }
+        if matches!(hw.power_limit, Some(limit) if limit <= 0.0) {
+            bail!("hardware.power_limit must be positive");
+        }
+        if hw.tuning.min_frequency_mhz <= 0.0 {
+            bail!("hardware.tuning.min_frequency_mhz must be positive");
+        }
+        if hw.tuning.min_voltage_v <= 0.0 || hw.tuning.min_voltage_v > hw.tuning.max_voltage_v {
+            bail!(
+                "hardware.tuning voltages must be positive and min_voltage_v at most max_voltage_v"
+            );
+        }
+        if hw.tuning.dwell_s == 0 {
+            bail!("hardware.tuning.dwell_s must be positive");
+        }
+        for (chip, profiles) in &hw.profiles {
+            for name in [
+                PerformanceProfile::Eco,
+                PerformanceProfile::Balanced,
+                PerformanceProfile::Turbo,
+            ] {
+                let profile = profiles.get(name);
+                let name = name.as_str();
+                if !(profile.frequency_mhz > 0.0 && profile.frequency_mhz <= 1000.0) {
+                    bail!("hardware.profiles.{chip}.{name}.frequency_mhz must be 0-1000");
+                }
+                if !(profile.core_voltage_v > 0.0 && profile.core_voltage_v <= 1.5) {
+                    bail!("hardware.profiles.{chip}.{name}.core_voltage_v must be 0-1.5");
+                }
+                if profile.fan_target_temp >= hw.temp_limit {
+                    bail!(
+                        "hardware.profiles.{chip}.{name}.fan_target_temp must be below hardware.temp_limit"
+                    );
+                }
+            }
+        }
+
+        if self.api.listen.parse::<SocketAddr>().is_err() {
+            bail!("api.listen must be an address like 127.0.0.1:7785");
+        }
+        if self.api.tls && (self.api.cert_path.is_none() || self.api.key_path.is_none()) {
+            bail!("api.tls requires api.cert_path and api.key_path");
+        }
+
+        if self.cgminer_api.listen.parse::<SocketAddr>().is_err() {
+            bail!("cgminer_api.listen must be an address like 127.0.0.1:4028");
+        }
+
+        for (i, rule) in self.alerts.rules.iter().enumerate() {
+            match rule {
+                AlertRule::HashrateBelow { hashrate, .. } if *hashrate <= 0.0 => {
+                    bail!("alerts.rules[{i}]: hashrate must be positive");
+                }
+                AlertRule::TemperatureAbove { celsius, .. } if *celsius <= 0.0 => {
+                    bail!("alerts.rules[{i}]: celsius must be positive");
+                }
+                _ => {}
+            }
+        }
+        for (i, webhook) in self.alerts.webhooks.iter().enumerate() {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                bail!("alerts.webhooks[{i}]: url must be an http:// or https:// URL");
+            }
+        }
+
+        let mqtt = &self.mqtt;
+        for (name, prefix) in [
+            ("topic_prefix", &mqtt.topic_prefix),
+            ("discovery_prefix", &mqtt.discovery_prefix),
+        ] {
+            if prefix.is_empty() || prefix.contains(['+', '#']) || prefix.ends_with('/') {
+                bail!("mqtt.{name} must be a topic without wildcards or a trailing /");
+            }
+        }
+        if mqtt.interval_s == 0 {
+            bail!("mqtt.interval_s must be positive");
+        }
+        if mqtt.password.is_some() && mqtt.username.is_none() {
+            bail!("mqtt.password requires mqtt.username");
+        }
+
+        if !LOG_LEVELS.contains(&self.daemon.log_level.as_str()) {
+            bail!("daemon.log_level must be one of error, warn, info, debug, trace");
+        }
+        for (module, level) in &self.logging.levels {
+            if module.is_empty() || module.contains(['=', ',', '[', ' ']) {
+                bail!("logging.levels: {module:?} is not a module path");
+            }
+            if !LOG_LEVELS.contains(&level.as_str()) && level != "off" {
+                bail!(
+                    "logging.levels.{module} must be one of off, error, warn, info, debug, trace"
+                );
+            }
+        }
+        if !(0.0..=1.0).contains(&self.otlp.sample_ratio) {
+            bail!("otlp.sample_ratio must be between 0 and 1");
+        }
+        if !self.otlp.endpoint.starts_with("http://") && !self.otlp.endpoint.starts_with("https://")
+        {
+            bail!("otlp.endpoint must be an http:// or https:// URL");
+        }
+        if let Some(timezone) = &self.schedule.timezone {
+            if jiff::tz::TimeZone::get(timezone).is_err() {
+                bail!("schedule.timezone: unknown time zone {timezone:?}");
+            }
+        }
+        for (i, rule) in self.schedule.rules.iter().enumerate() {
+            if rule.name.trim().is_empty() {
+                bail!("schedule.rules[{i}]: name must not be empty");
+            }
+            if let Err(e) = Cron::parse(&rule.cron) {
+                bail!("schedule.rules[{i}]: cron: {e}");
+            }
+            if matches!(rule.power_limit, Some(limit) if limit <= 0.0) {
+                bail!("schedule.rules[{i}]: power_limit must be positive");
+            }
+        }
+        if let Some(file) = &self.logging.file {
+            if file.path.as_os_str().is_empty() {
+                bail!("logging.file.path must not be empty");
+            }
+            if file.max_size_mb == Some(0) {
+                bail!("logging.file.max_size_mb must be positive");
+            }
+            if file.max_files == 0 {
+                bail!("logging.file.max_files must be positive");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy of the configuration with secrets replaced, for display.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        for pool in &mut config.pools {
+            if pool.password.is_some() {
+                pool.password = Some("<redacted>".to_string());
+            }
+        }
+        if config.mqtt.password.is_some() {
+            config.mqtt.password = Some("<redacted>".to_string());
+        }
+        for webhook in &mut config.alerts.webhooks {
+            // Chat services put the token in the path, so keep only the host
+            webhook.url = match reqwest::Url::parse(&webhook.url) {
+                Ok(url) => format!(
+                    "{}://{}/<redacted>",
+                    url.scheme(),
+                    url.host_str().unwrap_or_default()
+                ),
+                Err(_) => "<redacted>".to_string(),
+            };
+            for value in webhook.headers.values_mut() {
+                *value = "<redacted>".to_string();
+            }
+        }
+        for value in config.otlp.headers.values_mut() {
+            *value = "<redacted>".to_string();
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_file_gives_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_partial_config() {
+        let config = Config::parse(
+            r#"
+            [daemon]
+            log_level = "debug"
+
+            [[pools]]
+            url = "stratum+tcp://pool.example.com:3333"
+            worker = "bc1qexample.worker1"
+            password = "secret"
+
+            [hardware]
+            temp_limit = 70.0
+
+            [hardware.fan]
+            target_temp = 55.0
+
+            [hardware.tuning]
+            goal = "efficiency"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.daemon.log_level, "debug");
+        assert_eq!(config.pools.len(), 1);
+        assert_eq!(config.hardware.temp_limit, 70.0);
+        assert_eq!(config.hardware.fan.target_temp, 55.0);
+        assert_eq!(config.hardware.fan.kp, FanControlConfig::default().kp);
+        assert_eq!(config.hardware.tuning.goal, TuningGoal::Efficiency);
+        assert_eq!(config.hardware.tuning.dwell_s, 120);
+        assert_eq!(
+            config.hardware.fan_max_rpm,
+            HardwareConfig::default().fan_max_rpm
+        );
+        assert_eq!(config.api, ApiConfig::default());
+        assert_eq!(
+            config.redacted().pools[0].password.as_deref(),
+            Some("<redacted>")
+        );
+    }
+
+    #[test]
+    fn test_parse_alerts() {
+        let config = Config::parse(
+            r#"
+            [alerts]
+            repeat_s = 3600
+
+            [[alerts.rules]]
+            kind = "temperature_above"
+            celsius = 75.0
+            for_s = 60
+
+            [[alerts.rules]]
+            kind = "block_found"
+
+            [[alerts.webhooks]]
+            url = "https://hooks.example.com/mujina"
+            template = '{"text": "{{summary}}"}'
+            headers = { Authorization = "Bearer secret" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.alerts.rules,
+            vec![
+                AlertRule::TemperatureAbove {
+                    celsius: 75.0,
+                    for_s: 60
+                },
+                AlertRule::BlockFound
+            ]
+        );
+        assert_eq!(config.alerts.repeat_s, 3600);
+        assert_eq!(config.alerts.dedupe_s, 300);
+        assert_eq!(config.alerts.webhooks[0].content_type, "application/json");
+        assert_eq!(
+            config.redacted().alerts.webhooks[0].headers["Authorization"],
+            "<redacted>"
+        );
+        assert_eq!(
+            config.redacted().alerts.webhooks[0].url,
+            "https://hooks.example.com/<redacted>"
+        );
+
+        assert!(Config::parse("[[alerts.rules]]\nkind = \"pool_down\"\nfor = 60").is_err());
+        assert!(Config::parse("[[alerts.webhooks]]\nurl = \"hooks.example.com\"").is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        assert!(Config::parse("[hardware]\nfan_min_rpm = 9000\nfan_max_rpm = 100").is_err());
+        assert!(Config::parse("[hardware.fan]\ntarget_temp = 90.0").is_err());
+        assert!(Config::parse("[hardware.fan]\nki = -1.0").is_err());
+        assert!(Config::parse("[hardware.fan]\nmin_percent = 101").is_err());
+        assert!(Config::parse("[hardware.tuning]\nmin_voltage_v = 1.3").is_err());
+        assert!(Config::parse("[hardware.tuning]\ngoal = \"fastest\"").is_err());
+        assert!(Config::parse(
+            "[hardware.profiles.bm1366]\neco = { frequency_mhz = 400.0, core_voltage_v = 1.1, fan_target_temp = 55.0 }"
+        )
+        .is_err());
+        assert!(Config::parse("[api]\nlisten = \"not an address\"").is_err());
+        assert!(Config::parse("[cgminer_api]\nlisten = \"4028\"").is_err());
+        assert!(Config::parse("[daemon]\nlog_levle = \"info\"").is_err());
+        assert!(Config::parse("[mqtt]\ntopic_prefix = \"miners/#\"").is_err());
+        assert!(Config::parse("[logging.levels]\n\"mujina_miner::asic\" = \"loud\"").is_err());
+        assert!(Config::parse("[logging.file]\nmax_files = 0").is_err());
+        assert!(Config::parse("[otlp]\nsample_ratio = 1.5").is_err());
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let default = Config::default();
+        assert_eq!(
+            default.hardware.profiles["bm1370"]
+                .get(PerformanceProfile::Balanced)
+                .frequency_mhz,
+            525.0
+        );
+
+        let config = Config::parse(
+            r#"
+            [hardware.profiles.bm1366]
+            eco = { frequency_mhz = 400.0, core_voltage_v = 1.10, fan_target_temp = 55.0 }
+            balanced = { frequency_mhz = 485.0, core_voltage_v = 1.20, fan_target_temp = 60.0 }
+            turbo = { frequency_mhz = 575.0, core_voltage_v = 1.25, fan_target_temp = 65.0 }
+            "#,
+        )
+        .unwrap();
+        assert!(!config.hardware.profiles.contains_key("bm1370"));
+        let turbo = config.hardware.profiles["bm1366"].get(PerformanceProfile::Turbo);
+        assert_eq!(turbo.core_voltage_v, 1.25);
+
+        let too_hot = "[hardware.profiles.bm1370]\n\
+            eco = { frequency_mhz = 400.0, core_voltage_v = 1.1, fan_target_temp = 55.0 }\n\
+            balanced = { frequency_mhz = 525.0, core_voltage_v = 1.15, fan_target_temp = 60.0 }\n\
+            turbo = { frequency_mhz = 600.0, core_voltage_v = 1.2, fan_target_temp = 85.0 }";
+        assert!(Config::parse(too_hot).is_err());
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let config = Config::parse(
+            r#"
+            [schedule]
+            timezone = "UTC"
+
+            [[schedule.rules]]
+            name = "night"
+            cron = "0 22 * * *"
+            profile = "turbo"
+
+            [[schedule.rules]]
+            name = "peak"
+            cron = "0 17 * * mon-fri"
+            idle = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.schedule.timezone.as_deref(), Some("UTC"));
+        assert_eq!(
+            config.schedule.rules[0].mode(),
+            OperatingMode {
+                profile: Some(PerformanceProfile::Turbo),
+                power_limit: None,
+                idle: false,
+            }
+        );
+        assert!(config.schedule.rules[1].idle);
+
+        let rule = |cron: &str| format!("[[schedule.rules]]\nname = \"r\"\ncron = \"{cron}\"");
+        assert!(Config::parse(&rule("0 22 * *")).is_err());
+        assert!(Config::parse(&rule("60 22 * * *")).is_err());
+        assert!(Config::parse(&format!("{}\npower_limit = 0.0", rule("0 7 * * *"))).is_err());
+        assert!(Config::parse("[schedule]\ntimezone = \"Mars/Olympus_Mons\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_logging() {
+        let config = Config::parse(
+            r#"
+            [logging]
+            format = "json"
+            levels = { "mujina_miner::asic" = "debug", "hyper" = "warn" }
+
+            [logging.file]
+            path = "/tmp/mujina.log"
+            rotation = "hourly"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.logging.format, LogFormat::Json);
+        assert_eq!(config.logging.levels["mujina_miner::asic"], "debug");
+        let file = config.logging.file.unwrap();
+        assert_eq!(file.rotation, LogRotation::Hourly);
+        assert_eq!(file.max_files, LogFileConfig::default().max_files);
     }
 }
@@ -3,42 +3,87 @@
 //! This module handles the core daemon functionality including initialization,
 //! task management, signal handling, and graceful shutdown.
 
+use std::sync::Arc;
+
 use tokio::signal::unix::{self, SignalKind};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
 use crate::tracing::prelude::*;
 use crate::{
-    api::{self, ApiConfig},
-    backplane::Backplane,
-    hash_thread::HashThread,
+    alerts,
+    api::{self, ApiConfig, ApiState},
+    api_client::types::AddPoolRequest,
+    backplane::{Backplane, BackplaneCommand},
+    config::Config,
+    history::{self, History},
     job_source::{dummy::DummySource, stratum_v1::StratumV1Source, SourceEvent},
-    scheduler::{self, SourceRegistration},
+    pid_file::PidFile,
+    schedule::{self, ScheduleCommand},
+    scheduler::{self, SchedulerCommand, SourceRegistration, ThreadRegistration},
     stratum_v1::PoolConfig as StratumPoolConfig,
+    systemd::{self, Heartbeat, Notifier},
+    telemetry::Telemetry,
+    tracing::Logs,
     transport::{TransportEvent, UsbTransport},
 };
 
 /// The main daemon.
 pub struct Daemon {
+    config: Config,
+    logs: Logs,
     shutdown: CancellationToken,
     tracker: TaskTracker,
 }
 
 impl Daemon {
     /// Create a new daemon instance.
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         Self {
+            logs: Logs::new(0),
+            config,
             shutdown: CancellationToken::new(),
             tracker: TaskTracker::new(),
         }
     }
 
+    /// Serve `logs`, the handle returned by [`tracing::init`], through the
+    /// API.
+    ///
+    /// [`tracing::init`]: crate::tracing::init
+    pub fn with_logs(mut self, logs: Logs) -> Self {
+        self.logs = logs;
+        self
+    }
+
     /// Run the daemon until shutdown is requested.
     pub async fn run(self) -> anyhow::Result<()> {
+        // Refuse to start beside another daemon; held until we return
+        let _pid_file = self
+            .config
+            .daemon
+            .pid_file
+            .as_deref()
+            .map(PidFile::acquire)
+            .transpose()?;
+
+        // Bind the API before starting anything, so a taken address stops
+        // the daemon instead of leaving it running unreachable
+        let api_listener = api::bind(&ApiConfig {
+            bind_addr: self.config.api.listen.clone(),
+        })
+        .await?;
+
         // Create channels for component communication
         let (transport_tx, transport_rx) = mpsc::channel::<TransportEvent>(100);
-        let (thread_tx, thread_rx) = mpsc::channel::<Vec<Box<dyn HashThread>>>(10);
+        let (thread_tx, thread_rx) = mpsc::channel::<ThreadRegistration>(10);
         let (source_reg_tx, source_reg_rx) = mpsc::channel::<SourceRegistration>(10);
+        let (scheduler_cmd_tx, scheduler_cmd_rx) = mpsc::channel::<SchedulerCommand>(10);
+        let (backplane_cmd_tx, backplane_cmd_rx) = mpsc::channel::<BackplaneCommand>(10);
+        let (schedule_cmd_tx, schedule_cmd_rx) = mpsc::channel::<ScheduleCommand>(10);
+
+        // Status and events shared between the actors and the API
+        let telemetry = Telemetry::new();
 
         // Create and start USB transport discovery
         let usb_transport = UsbTransport::new(transport_tx.clone());
@@ -47,7 +92,13 @@ impl Daemon {
         }
 
         // Create and start backplane
-        let mut backplane = Backplane::new(transport_rx, thread_tx);
+        let mut backplane = Backplane::new(
+            transport_rx,
+            backplane_cmd_rx,
+            thread_tx,
+            telemetry.clone(),
+            self.config.hardware.clone(),
+        );
         self.tracker.spawn({
             let shutdown = self.shutdown.clone();
             async move {
@@ -64,7 +115,19 @@ impl Daemon {
             }
         });
 
-        // Create job source (Stratum v1 or Dummy)
+        // Start the scheduler
+        let heartbeat = Heartbeat::new();
+        self.tracker.spawn(scheduler::task(
+            self.shutdown.clone(),
+            thread_rx,
+            source_reg_rx,
+            scheduler_cmd_rx,
+            telemetry.clone(),
+            heartbeat.clone(),
+        ));
+
+        // Create job source (Stratum v1 from the environment, pools from the
+        // config file, or Dummy)
         // Controlled by environment variables:
         // - MUJINA_POOL_URL: Pool address (e.g., stratum+tcp://localhost:3333)
         // - MUJINA_POOL_USER: Worker username (optional, defaults to "mujina-testing")
@@ -72,6 +135,8 @@ impl Daemon {
         let (source_event_tx, source_event_rx) = mpsc::channel::<SourceEvent>(100);
         let (source_cmd_tx, source_cmd_rx) = mpsc::channel(10);
 
+        let source_shutdown = self.shutdown.child_token();
+
         if let Ok(pool_url) = std::env::var("MUJINA_POOL_URL") {
             // Use Stratum v1 source
             let pool_user =
@@ -79,7 +144,7 @@ impl Daemon {
             let pool_pass = std::env::var("MUJINA_POOL_PASS").unwrap_or_else(|_| "x".to_string());
 
             let stratum_config = StratumPoolConfig {
-                url: pool_url,
+                url: pool_url.clone(),
                 username: pool_user,
                 password: pool_pass,
                 user_agent: "mujina-miner/0.1.0-alpha".to_string(),
@@ -90,14 +155,16 @@ impl Daemon {
                 stratum_config,
                 source_cmd_rx,
                 source_event_tx,
-                self.shutdown.clone(),
+                source_shutdown.clone(),
             );
 
             source_reg_tx
                 .send(SourceRegistration {
                     name: "stratum-v1".into(),
+                    url: Some(pool_url),
                     event_rx: source_event_rx,
                     command_tx: source_cmd_tx,
+                    shutdown: source_shutdown,
                 })
                 .await?;
 
@@ -106,6 +173,29 @@ impl Daemon {
                     error!("Stratum v1 source error: {}", e);
                 }
             });
+        } else if !self.config.pools.is_empty() {
+            // Use pools from the config file, highest priority (lowest
+            // number) first; the first pool added becomes active
+            let mut pools = self.config.pools.clone();
+            pools.sort_by_key(|pool| pool.priority);
+
+            for pool in pools {
+                let (response_tx, response_rx) = oneshot::channel();
+                scheduler_cmd_tx
+                    .send(SchedulerCommand::AddPool {
+                        request: AddPoolRequest {
+                            name: None,
+                            url: pool.url,
+                            worker: pool.worker,
+                            password: pool.password,
+                        },
+                        response: response_tx,
+                    })
+                    .await?;
+                if let Err(e) = response_rx.await? {
+                    error!(error = %e, "Failed to add pool from config");
+                }
+            }
         } else {
             // Use DummySource
             info!("Using dummy job source (set MUJINA_POOL_URL to use Stratum v1)");
@@ -113,15 +203,17 @@ impl Daemon {
             let dummy_source = DummySource::new(
                 source_cmd_rx,
                 source_event_tx,
-                self.shutdown.clone(),
+                source_shutdown.clone(),
                 tokio::time::Duration::from_secs(30),
             )?;
 
             source_reg_tx
                 .send(SourceRegistration {
                     name: "dummy".into(),
+                    url: None,
                     event_rx: source_event_rx,
                     command_tx: source_cmd_tx,
+                    shutdown: source_shutdown,
                 })
                 .await?;
 
@@ -132,19 +224,86 @@ impl Daemon {
             });
         }
 
-        // Start the scheduler
-        self.tracker.spawn(scheduler::task(
+        // Start recording statistics history
+        let history = if self.config.history.persist {
+            History::open(&self.config.history.dir).unwrap_or_else(|e| {
+                warn!(
+                    dir = %self.config.history.dir.display(),
+                    error = %e,
+                    "Cannot open statistics history; keeping it in memory only."
+                );
+                History::in_memory()
+            })
+        } else {
+            History::in_memory()
+        };
+        self.tracker.spawn(history::task(
+            history.clone(),
+            telemetry.clone(),
+            self.shutdown.clone(),
+        ));
+
+        // Start evaluating alert rules
+        if !self.config.alerts.rules.is_empty() {
+            self.tracker.spawn(alerts::task(
+                self.config.alerts.clone(),
+                telemetry.clone(),
+                self.shutdown.clone(),
+            ));
+        }
+
+        // Switch operating modes on schedule
+        self.tracker.spawn(schedule::task(
+            self.config.schedule.clone(),
+            self.config.hardware.power_limit,
+            telemetry.clone(),
+            scheduler_cmd_tx.clone(),
+            backplane_cmd_tx.clone(),
+            schedule_cmd_rx,
+            self.shutdown.clone(),
+        ));
+
+        // Report status and liveness to systemd
+        let notifier = Notifier::new(self.config.daemon.systemd);
+        self.tracker.spawn(systemd::task(
+            notifier.clone(),
+            telemetry.clone(),
+            heartbeat,
             self.shutdown.clone(),
-            thread_rx,
-            source_reg_rx,
         ));
 
-        // Start the API server
+        // Start the API servers
+        let state = ApiState {
+            telemetry,
+            history,
+            scheduler: scheduler_cmd_tx,
+            backplane: backplane_cmd_tx,
+            schedule: schedule_cmd_tx,
+            config: Arc::new(self.config.clone()),
+            logs: self.logs.clone(),
+        };
+
+        if self.config.cgminer_api.enabled {
+            self.tracker.spawn({
+                let shutdown = self.shutdown.clone();
+                let state = state.clone();
+                async move {
+                    if let Err(e) = api::cgminer::serve(state, shutdown).await {
+                        error!("cgminer API server error: {}", e);
+                    }
+                }
+            });
+        }
+
+        if self.config.mqtt.enabled {
+            self.tracker
+                .spawn(api::mqtt::task(state.clone(), self.shutdown.clone()));
+        }
+
         self.tracker.spawn({
             let shutdown = self.shutdown.clone();
             async move {
-                let config = ApiConfig::default();
-                if let Err(e) = api::serve(config, shutdown).await {
+                if let Err(e) = api::serve(api_listener, state, shutdown).await {
                     error!("API server error: {}", e);
                 }
             }
@@ -152,6 +311,7 @@ impl Daemon {
 
         self.tracker.close();
 
+        notifier.ready();
         info!("Started.");
         info!("For debugging, set RUST_LOG=mujina_miner=debug or trace.");
 
@@ -170,6 +330,7 @@ impl Daemon {
         }
 
         // Initiate shutdown
+        notifier.stopping();
         self.shutdown.cancel();
 
         // Wait for all tasks to complete
@@ -182,6 +343,6 @@ impl Daemon {
 
 impl Default for Daemon {
     fn default() -> Self {
-        Self::new()
+        Self::new(Config::default())
     }
 }
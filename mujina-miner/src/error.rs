@@ -43,3 +43,24 @@ pub enum Error {
 
 /// Convenience type alias for Results using our Error type.
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned when a control command sent to a running component
+/// (scheduler, backplane) cannot be carried out.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// The named board, pool, or thread does not exist
+    #[error("{0} not found")]
+    NotFound(String),
+
+    /// The command conflicts with current state (e.g., duplicate name)
+    #[error("{0}")]
+    Conflict(String),
+
+    /// The command's arguments are invalid
+    #[error("{0}")]
+    Invalid(String),
+
+    /// The component is not running
+    #[error("{0} is not running")]
+    Unavailable(&'static str),
+}
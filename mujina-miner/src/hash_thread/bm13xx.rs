@@ -8,6 +8,7 @@
 //! chip responses, filters shares, and manages work assignment.
 
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use async_trait::async_trait;
 use bitcoin::block::Header as BlockHeader;
@@ -16,17 +17,54 @@ use tokio::sync::{mpsc, oneshot, watch};
 use tokio_stream::StreamExt;
 
 use super::{
-    task::HashTask, HashThread, HashThreadCapabilities, HashThreadError, HashThreadEvent,
-    HashThreadStatus,
+    health::NonceCounters,
+    ramp::{FrequencyRamp, RampAction, RampSample},
+    task::HashTask,
+    HashThread, HashThreadCapabilities, HashThreadError, HashThreadEvent, HashThreadStatus,
 };
 use crate::{
-    asic::bm13xx::{self, protocol},
+    asic::{
+        bm13xx::{self, protocol},
+        ChipInfo,
+    },
     board::bitaxe::{BitaxePeripherals, ThreadRemovalSignal},
     hw_trait::gpio::{GpioPin, PinValue},
     tracing::prelude::*,
     types::DisplayDifficulty,
 };
 
+/// PLL frequency a chain of `chips` is ramped to during initialization.
+///
+/// Depends on the chip model; chains of unknown chips, which cannot be
+/// initialized anyway, get the BM1370's.
+pub(crate) fn target_frequency_mhz(chips: &[ChipInfo]) -> f32 {
+    chips
+        .first()
+        .and_then(|chip| ChipInit::for_chip(protocol::ChipType::from(chip.chip_id)))
+        .or_else(|| ChipInit::for_chip(protocol::ChipType::BM1370))
+        .map_or(525.0, |init| init.frequency_mhz)
+}
+
+/// PLL ramp step size.
+const RAMP_STEP_MHZ: f32 = 6.25;
+
+/// Settling time after each PLL ramp step.
+const RAMP_STEP_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Interval at which per-chip health is recomputed and published.
+const HEALTH_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(10);
+
+/// Nonce reporting interval configured in the chips' ticket mask.
+///
+/// Targets ~1 nonce per second at 1 TH/s (1000 GiH/s = 1.074 TH/s).
+fn reporting_interval() -> protocol::ReportingInterval {
+    use protocol::{Hashrate, ReportingInterval, ReportingRate};
+    ReportingInterval::from_rate(
+        Hashrate::gibihashes_per_sec(1000.0),
+        ReportingRate::nonces_per_sec(1.0),
+    )
+}
+
 /// Tracks tasks sent to chip hardware, indexed by chip_job_id.
 ///
 /// BM13xx chips use 4-bit job IDs. This tracker maintains snapshots of
@@ -83,6 +121,13 @@ enum ThreadCommand {
         response_tx: oneshot::Sender<std::result::Result<Option<HashTask>, HashThreadError>>,
     },
 
+    /// Set the target frequency, ramping running chips to it; answered once
+    /// the chips reach it or the ramp rolls back
+    SetFrequency {
+        frequency_mhz: f32,
+        response_tx: oneshot::Sender<std::result::Result<f32, HashThreadError>>,
+    },
+
     /// Shutdown the thread
     #[expect(unused)]
     Shutdown,
@@ -118,11 +163,17 @@ impl BM13xxThread {
     /// * `chip_commands` - Sink for sending encoded commands to chips
     /// * `peripherals` - Shared peripheral handles (reset pin, voltage regulator)
     /// * `removal_rx` - Watch channel for board-triggered removal
+    /// * `chips` - Chips on the chain, in chain order, as [`enumerate_chain`]
+    ///   reports them
+    /// * `chips_per_domain` - Chips sharing each voltage domain; the chips at
+    ///   domain boundaries get stronger IO drivers and UART relays
     pub fn new<R, W>(
         chip_responses: R,
         chip_commands: W,
         peripherals: BitaxePeripherals,
         removal_rx: watch::Receiver<ThreadRemovalSignal>,
+        chips: &[ChipInfo],
+        chips_per_domain: usize,
     ) -> Self
     where
         R: Stream<Item = Result<protocol::Response, std::io::Error>> + Unpin + Send + 'static,
@@ -132,7 +183,31 @@ impl BM13xxThread {
         let (cmd_tx, cmd_rx) = mpsc::channel(10);
         let (evt_tx, evt_rx) = mpsc::channel(100);
 
-        let status = Arc::new(RwLock::new(HashThreadStatus::default()));
+        let chain = Chain {
+            chip_type: chips
+                .first()
+                .map(|chip| protocol::ChipType::from(chip.chip_id))
+                .unwrap_or(protocol::ChipType::BM1370),
+            addresses: chips.iter().map(|chip| chip.address).collect(),
+            chips_per_domain,
+        };
+        let target_mhz = target_frequency_mhz(chips);
+        let counters = NonceCounters::new(
+            chain.chip_type,
+            chain.addresses.iter().copied(),
+            target_mhz,
+            reporting_interval().hashes_per_nonce(),
+            Instant::now(),
+        );
+        let hashrate_estimate = counters
+            .expected_hashrate()
+            .map(|per_chip| per_chip * chips.len() as f64)
+            .unwrap_or(1_000_000_000.0); // Unknown chip: 1 GH/s stub
+
+        let status = Arc::new(RwLock::new(HashThreadStatus {
+            chips: counters.health(),
+            ..Default::default()
+        }));
         let status_clone = Arc::clone(&status);
 
         // Spawn the actor task
@@ -145,6 +220,9 @@ impl BM13xxThread {
                 chip_responses,
                 chip_commands,
                 peripherals,
+                chain,
+                target_mhz,
+                counters,
             )
             .await;
         });
@@ -152,12 +230,61 @@ impl BM13xxThread {
         Self {
             command_tx: cmd_tx,
             event_rx: Some(evt_rx),
-            capabilities: HashThreadCapabilities {
-                hashrate_estimate: 1_000_000_000.0, // Stub: 1 GH/s
-            },
+            capabilities: HashThreadCapabilities { hashrate_estimate },
             status,
         }
     }
+
+    /// Handle for the board to monitor and retune the thread.
+    pub(crate) fn control(&self) -> BM13xxControl {
+        BM13xxControl {
+            command_tx: self.command_tx.clone(),
+            status: Arc::clone(&self.status),
+        }
+    }
+}
+
+/// Board-side handle to a [`BM13xxThread`].
+///
+/// The scheduler owns the thread; the board keeps this to read its status
+/// and change its frequency for performance profiles and tuning.
+#[derive(Clone)]
+pub(crate) struct BM13xxControl {
+    command_tx: mpsc::Sender<ThreadCommand>,
+    status: Arc<RwLock<HashThreadStatus>>,
+}
+
+impl BM13xxControl {
+    /// Latest status of the thread.
+    pub fn status(&self) -> HashThreadStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    /// Set the thread's target frequency; returns the frequency it runs at,
+    /// which the board's frequency cap may hold lower.
+    ///
+    /// Running chips are ramped there in steps. A raise is verified for
+    /// [`VERIFY_WINDOW`](super::ramp::VERIFY_WINDOW) after this returns and
+    /// rolled back, with a warning, if the chips fail it; errors while
+    /// stepping roll it back before this returns, as an error.
+    pub async fn set_frequency(
+        &self,
+        frequency_mhz: f32,
+    ) -> std::result::Result<f32, HashThreadError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.command_tx
+            .send(ThreadCommand::SetFrequency {
+                frequency_mhz,
+                response_tx,
+            })
+            .await
+            .map_err(|_| HashThreadError::ChannelClosed("command channel closed".into()))?;
+
+        response_rx
+            .await
+            .map_err(|_| HashThreadError::FrequencyChangeFailed("no response from thread".into()))?
+    }
 }
 
 #[async_trait]
@@ -226,24 +353,165 @@ impl HashThread for BM13xxThread {
     }
 }
 
-/// Initialize BM13xx chip for mining.
+/// Chips of a thread's chain, in chain order.
+#[derive(Debug, Clone)]
+struct Chain {
+    chip_type: protocol::ChipType,
+    /// Address assigned to each chip during initialization
+    addresses: Vec<u8>,
+    /// Chips per voltage domain
+    chips_per_domain: usize,
+}
+
+impl Chain {
+    /// Whether the chain spans more than one voltage domain.
+    fn has_domains(&self) -> bool {
+        self.chips_per_domain > 0 && self.addresses.len() > self.chips_per_domain
+    }
+}
+
+/// Count the chips on a chain fresh out of reset and give each an address.
 ///
-/// Releases chip from reset, configures all registers, and ramps frequency to
-/// target. This is Bitaxe-specific initialization for a single BM1370 chip.
+/// Broadcasts a `ChipId` read and collects the answers for a short window.
+/// Chips answer in chain order, so the n-th answer is the n-th chip, which
+/// [`configure_chain`] later addresses at the n-th of
+/// [`BM13xxProtocol::chip_addresses`](protocol::BM13xxProtocol::chip_addresses).
+/// Fails if no chip answers, or the chain mixes chip models or holds one
+/// whose initialization sequence is unknown.
+pub(crate) async fn enumerate_chain<R, W>(
+    chip_responses: &mut R,
+    chip_commands: &mut W,
+) -> Result<Vec<ChipInfo>, HashThreadError>
+where
+    R: Stream<Item = Result<protocol::Response, std::io::Error>> + Unpin,
+    W: Sink<protocol::Command> + Unpin,
+    W::Error: std::fmt::Debug,
+{
+    const DISCOVERY_WINDOW: tokio::time::Duration = tokio::time::Duration::from_millis(500);
+
+    send_init(
+        chip_commands,
+        protocol::BM13xxProtocol::discover_chips(),
+        "chip discovery",
+    )
+    .await?;
+
+    let mut discovered = Vec::new();
+    let deadline = tokio::time::Instant::now() + DISCOVERY_WINDOW;
+    loop {
+        tokio::select! {
+            response = chip_responses.next() => match response {
+                Some(Ok(protocol::Response::ReadRegister {
+                    register: protocol::Register::ChipId { chip_type, core_count, address },
+                    ..
+                })) => {
+                    trace!(?chip_type, address, "Chip answered discovery.");
+                    discovered.push((chip_type, core_count));
+                }
+                Some(Ok(_)) => warn!("Unexpected response during chip discovery"),
+                Some(Err(e)) => error!("Error during chip discovery: {e}"),
+                None => break,
+            },
+            _ = tokio::time::sleep_until(deadline) => break,
+        }
+    }
+
+    let Some(&(chip_type, _)) = discovered.first() else {
+        return Err(HashThreadError::InitializationFailed(
+            "No chips discovered".into(),
+        ));
+    };
+    if let Some((other, _)) = discovered.iter().find(|(t, _)| *t != chip_type) {
+        return Err(HashThreadError::InitializationFailed(format!(
+            "chain mixes {chip_type:?} and {other:?} chips"
+        )));
+    }
+    if ChipInit::for_chip(chip_type).is_none() {
+        return Err(HashThreadError::InitializationFailed(format!(
+            "no initialization sequence for {chip_type:?}"
+        )));
+    }
+
+    let addresses = protocol::BM13xxProtocol::chip_addresses(discovered.len());
+    Ok(discovered
+        .into_iter()
+        .zip(addresses)
+        .map(|((chip_type, core_count), address)| ChipInfo {
+            chip_id: chip_type.id_bytes(),
+            core_count: core_count.into(),
+            address,
+            supports_version_rolling: true,
+        })
+        .collect())
+}
+
+/// Register values that differ between chip models during initialization.
+#[derive(Debug, Clone, Copy)]
+struct ChipInit {
+    /// InitControl, broadcast before addressing
+    init_control: u32,
+    /// InitControl, written to each chip
+    chip_init_control: u32,
+    /// MiscControl, broadcast and written to each chip
+    misc_control: u32,
+    /// Core register writes, broadcast and repeated to each chip
+    core: [u32; 2],
+    /// AnalogMux, broadcast
+    analog_mux: u32,
+    /// MiscSettings, written around AnalogMux on chips that have it
+    misc_settings: Option<u32>,
+    /// Core register write closing the configuration, on chips that need it
+    final_core: Option<u32>,
+    /// PLL frequency the chips are ramped to
+    frequency_mhz: f32,
+}
+
+impl ChipInit {
+    /// Values for `chip_type`, if its initialization sequence is known.
+    fn for_chip(chip_type: protocol::ChipType) -> Option<Self> {
+        match chip_type {
+            protocol::ChipType::BM1370 => Some(Self {
+                init_control: 0x0000_0700,
+                chip_init_control: 0xF001_0700,
+                misc_control: 0x00C1_00F0,
+                core: [0x8000_8B00, 0x8000_800C],
+                analog_mux: 0x0200_0000,
+                misc_settings: Some(0x8044_0000),
+                final_core: Some(0x8000_8DEE),
+                frequency_mhz: 525.0, // esp-miner default
+            }),
+            // From the BM1362 values in PROTOCOL.md; not yet checked on hardware
+            protocol::ChipType::BM1362 => Some(Self {
+                init_control: 0x0000_0000,
+                chip_init_control: 0x0200_0000,
+                misc_control: 0x00C1_00B0,
+                core: [0x8000_8540, 0x8000_8008],
+                analog_mux: 0x0300_0000,
+                misc_settings: None,
+                final_core: None,
+                frequency_mhz: 400.0, // ~820 GH/s per chip, as on a stock S19 J Pro
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Initialize the chain's chips for mining.
 ///
-/// This function contains the complete chip initialization sequence that was
-/// previously done by the board. The chip starts in reset and is configured
-/// for mining when the scheduler assigns first work.
+/// Releases the chips from reset, then configures them with
+/// [`configure_chain`]. The chips start in reset and are configured for
+/// mining when the scheduler assigns first work.
+#[instrument(name = "chip_init", skip(chip_commands, peripherals))]
 async fn initialize_chip<W>(
     chip_commands: &mut W,
     peripherals: &mut BitaxePeripherals,
+    chain: &Chain,
+    frequency_mhz: f32,
 ) -> Result<(), HashThreadError>
 where
     W: Sink<bm13xx::protocol::Command> + Unpin,
     W::Error: std::fmt::Debug,
 {
-    use protocol::{Command, Register};
-
     // Release from reset
     tracing::debug!("Releasing ASIC from reset");
     peripherals
@@ -256,22 +524,42 @@ where
 
     tokio::time::sleep(std::time::Duration::from_millis(200)).await;
 
+    configure_chain(chip_commands, chain, frequency_mhz).await
+}
+
+/// Configure all registers of chips fresh out of reset, address them, and
+/// ramp them to `frequency_mhz`.
+///
+/// Chips are addressed in chain order with the addresses in `chain`, then
+/// configured with the values for its chip type.
+async fn configure_chain<W>(
+    chip_commands: &mut W,
+    chain: &Chain,
+    frequency_mhz: f32,
+) -> Result<(), HashThreadError>
+where
+    W: Sink<bm13xx::protocol::Command> + Unpin,
+    W::Error: std::fmt::Debug,
+{
+    use protocol::{Command, Register};
+
+    let init = ChipInit::for_chip(chain.chip_type).ok_or_else(|| {
+        HashThreadError::InitializationFailed(format!(
+            "no initialization sequence for {:?}",
+            chain.chip_type
+        ))
+    })?;
+    let broadcast = |register| Command::WriteRegister {
+        broadcast: true,
+        chip_address: 0x00,
+        register,
+    };
+
     // Send version mask configuration (3 times)
     tracing::debug!("Configuring version mask");
     for _ in 1..=3 {
-        chip_commands
-            .send(Command::WriteRegister {
-                broadcast: true,
-                chip_address: 0x00,
-                register: Register::VersionMask(protocol::VersionMask::full_rolling()),
-            })
-            .await
-            .map_err(|e| {
-                HashThreadError::InitializationFailed(format!(
-                    "Failed to send version mask: {:?}",
-                    e
-                ))
-            })?;
+        let version_mask = Register::VersionMask(protocol::VersionMask::full_rolling());
+        send_init(chip_commands, broadcast(version_mask), "version mask").await?;
         tokio::time::sleep(std::time::Duration::from_millis(5)).await;
     }
 
@@ -279,241 +567,127 @@ where
 
     // Pre-configuration registers
     tracing::debug!("Sending pre-configuration registers");
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::InitControl {
-                raw_value: 0x00000700,
-            },
-        })
-        .await
-        .map_err(|e| HashThreadError::InitializationFailed(format!("Init send failed: {:?}", e)))?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::MiscControl {
-                raw_value: 0x00C100F0,
-            },
-        })
-        .await
-        .map_err(|e| HashThreadError::InitializationFailed(format!("Misc send failed: {:?}", e)))?;
-
-    chip_commands
-        .send(Command::ChainInactive)
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("ChainInactive failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::SetChipAddress { chip_address: 0x00 })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("SetChipAddress failed: {:?}", e))
-        })?;
+    let init_control = Register::InitControl {
+        raw_value: init.init_control,
+    };
+    send_init(chip_commands, broadcast(init_control), "InitControl").await?;
+    let misc_control = Register::MiscControl {
+        raw_value: init.misc_control,
+    };
+    send_init(chip_commands, broadcast(misc_control), "MiscControl").await?;
+
+    // Address the chips in chain order
+    tracing::debug!(chips = chain.addresses.len(), "Assigning chip addresses");
+    send_init(chip_commands, Command::ChainInactive, "ChainInactive").await?;
+    for &chip_address in &chain.addresses {
+        send_init(
+            chip_commands,
+            Command::SetChipAddress { chip_address },
+            "SetChipAddress",
+        )
+        .await?;
+    }
 
     // Core configuration (broadcast)
     tracing::debug!("Sending broadcast core configuration");
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::Core {
-                raw_value: 0x8000_8B00,
-            },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Core1 send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::Core {
-                raw_value: 0x8000_800C,
-            },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Core2 send failed: {:?}", e))
-        })?;
+    for raw_value in init.core {
+        send_init(
+            chip_commands,
+            broadcast(Register::Core { raw_value }),
+            "Core",
+        )
+        .await?;
+    }
 
     // Ticket mask, IO strength
-    // Target: ~1 nonce per second at 1 TH/s (1000 GiH/s = 1.074 TH/s)
-    use protocol::{Hashrate, ReportingInterval, ReportingRate, TicketMask};
-    let reporting_interval = ReportingInterval::from_rate(
-        Hashrate::gibihashes_per_sec(1000.0),
-        ReportingRate::nonces_per_sec(1.0),
-    );
-    let ticket_mask = TicketMask::new(reporting_interval);
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::TicketMask(ticket_mask),
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("TicketMask send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::IoDriverStrength(protocol::IoDriverStrength::normal()),
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("IoDriver send failed: {:?}", e))
-        })?;
+    let ticket_mask = protocol::TicketMask::new(reporting_interval());
+    send_init(
+        chip_commands,
+        broadcast(Register::TicketMask(ticket_mask)),
+        "TicketMask",
+    )
+    .await?;
+    let io_driver = Register::IoDriverStrength(protocol::IoDriverStrength::normal());
+    send_init(chip_commands, broadcast(io_driver), "IoDriverStrength").await?;
+
+    // Stronger drivers and UART relays where voltage domains meet
+    if chain.has_domains() {
+        tracing::debug!(
+            chips_per_domain = chain.chips_per_domain,
+            "Configuring domain boundaries"
+        );
+        let domains = protocol::BM13xxProtocol::new()
+            .configure_domains(chain.addresses.len(), chain.chips_per_domain);
+        for command in domains {
+            send_init(chip_commands, command, "domain configuration").await?;
+        }
+    }
 
     // Chip-specific configuration
     tracing::debug!("Sending chip-specific configuration");
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: false,
-            chip_address: 0x00,
-            register: Register::InitControl {
-                raw_value: 0xF0010700,
+    for &chip_address in &chain.addresses {
+        let registers = [
+            Register::InitControl {
+                raw_value: init.chip_init_control,
             },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("InitControl chip send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: false,
-            chip_address: 0x00,
-            register: Register::MiscControl {
-                raw_value: 0x00C100F0,
+            Register::MiscControl {
+                raw_value: init.misc_control,
             },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("MiscControl chip send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: false,
-            chip_address: 0x00,
-            register: Register::Core {
-                raw_value: 0x8000_8B00,
+            Register::Core {
+                raw_value: init.core[0],
             },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Core chip1 send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: false,
-            chip_address: 0x00,
-            register: Register::Core {
-                raw_value: 0x8000_800C,
+            Register::Core {
+                raw_value: init.core[1],
             },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Core chip2 send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: false,
-            chip_address: 0x00,
-            register: Register::Core {
+            Register::Core {
                 raw_value: 0x8000_82AA,
             },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Core chip3 send failed: {:?}", e))
-        })?;
+        ];
+        for register in registers {
+            let command = Command::WriteRegister {
+                broadcast: false,
+                chip_address,
+                register,
+            };
+            send_init(chip_commands, command, "chip configuration").await?;
+        }
+    }
 
     // Additional settings
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::MiscSettings {
-                raw_value: 0x80440000,
-            },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("MiscSettings send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::AnalogMux {
-                raw_value: 0x02000000,
-            },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("AnalogMux send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::MiscSettings {
-                raw_value: 0x80440000,
-            },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("MiscSettings2 send failed: {:?}", e))
-        })?;
-
-    chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::Core {
-                raw_value: 0x8000_8DEE,
-            },
-        })
-        .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Core final send failed: {:?}", e))
-        })?;
+    if let Some(raw_value) = init.misc_settings {
+        let misc_settings = Register::MiscSettings { raw_value };
+        send_init(chip_commands, broadcast(misc_settings), "MiscSettings").await?;
+    }
+    let analog_mux = Register::AnalogMux {
+        raw_value: init.analog_mux,
+    };
+    send_init(chip_commands, broadcast(analog_mux), "AnalogMux").await?;
+    if let Some(raw_value) = init.misc_settings {
+        let misc_settings = Register::MiscSettings { raw_value };
+        send_init(chip_commands, broadcast(misc_settings), "MiscSettings").await?;
+    }
+    if let Some(raw_value) = init.final_core {
+        send_init(
+            chip_commands,
+            broadcast(Register::Core { raw_value }),
+            "Core",
+        )
+        .await?;
+    }
 
-    // Frequency ramping (56.25 MHz -> 525 MHz)
-    tracing::debug!("Ramping frequency from 56.25 MHz to 525 MHz");
-    let frequency_steps = generate_frequency_ramp_steps(56.25, 525.0, 6.25);
+    // Frequency ramping (56.25 MHz -> target)
+    tracing::debug!("Ramping frequency from 56.25 MHz to {frequency_mhz} MHz");
+    let frequency_steps = generate_frequency_ramp_steps(56.25, frequency_mhz, RAMP_STEP_MHZ);
 
     for (i, pll_config) in frequency_steps.iter().enumerate() {
-        chip_commands
-            .send(Command::WriteRegister {
-                broadcast: true,
-                chip_address: 0x00,
-                register: Register::PllDivider(*pll_config),
-            })
-            .await
-            .map_err(|e| {
-                HashThreadError::InitializationFailed(format!("PLL ramp failed: {:?}", e))
-            })?;
+        send_init(
+            chip_commands,
+            broadcast(Register::PllDivider(*pll_config)),
+            "PLL ramp",
+        )
+        .await?;
 
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        tokio::time::sleep(RAMP_STEP_DELAY).await;
 
         if i % 10 == 0 || i == frequency_steps.len() - 1 {
             tracing::trace!("Frequency ramp step {}/{}", i + 1, frequency_steps.len());
@@ -522,33 +696,101 @@ where
 
     tracing::debug!("Frequency ramping complete");
 
-    // Final configuration
+    // Final configuration: split the nonce space between the chips
+    let nonce_range = match chain.addresses.len() {
+        // Single-chip value from esp-miner
+        1 => protocol::NonceRangeConfig::from_raw(0xB51E0000),
+        chips => protocol::NonceRangeConfig::multi_chip(chips),
+    };
+    send_init(
+        chip_commands,
+        broadcast(Register::NonceRange(nonce_range)),
+        "NonceRange",
+    )
+    .await?;
+
+    // Final version mask
+    let version_mask = Register::VersionMask(protocol::VersionMask::full_rolling());
+    send_init(chip_commands, broadcast(version_mask), "final version mask").await?;
+
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+    Ok(())
+}
+
+/// Send one initialization command, naming it in the error if it fails.
+async fn send_init<W>(
+    chip_commands: &mut W,
+    command: protocol::Command,
+    what: &str,
+) -> Result<(), HashThreadError>
+where
+    W: Sink<bm13xx::protocol::Command> + Unpin,
+    W::Error: std::fmt::Debug,
+{
     chip_commands
-        .send(Command::WriteRegister {
-            broadcast: true,
-            chip_address: 0x00,
-            register: Register::NonceRange(protocol::NonceRangeConfig::from_raw(0xB51E0000)),
-        })
+        .send(command)
         .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("NonceRange send failed: {:?}", e))
-        })?;
+        .map_err(|e| HashThreadError::InitializationFailed(format!("{what} send failed: {:?}", e)))
+}
 
-    // Final version mask
+/// Set the PLL of all chips to `frequency_mhz` while they keep hashing.
+///
+/// Frequencies the PLL cannot produce are skipped; ramps step through them.
+async fn set_pll<W>(chip_commands: &mut W, frequency_mhz: f32) -> Result<(), HashThreadError>
+where
+    W: Sink<bm13xx::protocol::Command> + Unpin,
+    W::Error: std::fmt::Debug,
+{
+    let Some(pll_config) = calculate_pll_for_frequency(frequency_mhz) else {
+        trace!(frequency_mhz, "No PLL setting for ramp step; skipped.");
+        return Ok(());
+    };
     chip_commands
-        .send(Command::WriteRegister {
+        .send(protocol::Command::WriteRegister {
             broadcast: true,
             chip_address: 0x00,
-            register: Register::VersionMask(protocol::VersionMask::full_rolling()),
+            register: protocol::Register::PllDivider(pll_config),
         })
         .await
-        .map_err(|e| {
-            HashThreadError::InitializationFailed(format!("Final version mask failed: {:?}", e))
-        })?;
+        .map_err(|e| HashThreadError::FrequencyChangeFailed(format!("PLL write failed: {:?}", e)))
+}
 
-    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+/// Start a ramp to `to_mhz`, or redirect the one in progress.
+fn ramp_to(ramp: &mut Option<FrequencyRamp>, frequency_mhz: f32, to_mhz: f32, sample: RampSample) {
+    let now = Instant::now();
+    match ramp {
+        Some(active) if active.target_mhz() != to_mhz => active.retarget(to_mhz, now),
+        Some(_) => {}
+        None if to_mhz != frequency_mhz => {
+            *ramp = Some(FrequencyRamp::new(
+                frequency_mhz,
+                to_mhz,
+                RAMP_STEP_MHZ,
+                RAMP_STEP_DELAY,
+                now,
+                sample,
+            ));
+        }
+        None => {}
+    }
+}
 
-    Ok(())
+/// Counters the frequency ramp judges the chips by.
+fn ramp_sample(counters: &NonceCounters, hashing: bool) -> RampSample {
+    let (nonces, hardware_errors) = counters.totals();
+    RampSample {
+        hashing,
+        nonces,
+        hardware_errors,
+        expected_nonce_rate: counters.expected_nonce_rate(),
+    }
+}
+
+/// Frequency to run at for `target_mhz` under the board's frequency cap, if
+/// any.
+fn capped_frequency(target_mhz: f32, limit_mhz: Option<f32>) -> f32 {
+    limit_mhz.map_or(target_mhz, |limit| limit.min(target_mhz))
 }
 
 /// Generate frequency ramp steps for smooth PLL transitions
@@ -620,7 +862,7 @@ fn task_to_job_full(
 }
 
 /// Calculate PLL configuration for a specific frequency
-fn calculate_pll_for_frequency(target_freq: f32) -> Option<protocol::PllConfig> {
+pub(crate) fn calculate_pll_for_frequency(target_freq: f32) -> Option<protocol::PllConfig> {
     const CRYSTAL_FREQ: f32 = 25.0;
     const MAX_FREQ_ERROR: f32 = 1.0;
 
@@ -688,6 +930,10 @@ fn calculate_pll_for_frequency(target_freq: f32) -> Option<protocol::PllConfig>
 ///
 /// Thread starts with chip in reset (uninit). Chip is configured when scheduler
 /// assigns first work.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "the actor owns every resource of the thread"
+)]
 async fn bm13xx_thread_actor<R, W>(
     mut cmd_rx: mpsc::Receiver<ThreadCommand>,
     evt_tx: mpsc::Sender<HashThreadEvent>,
@@ -696,6 +942,9 @@ async fn bm13xx_thread_actor<R, W>(
     mut chip_responses: R,
     mut chip_commands: W,
     mut peripherals: BitaxePeripherals,
+    chain: Chain,
+    mut target_mhz: f32,
+    mut counters: NonceCounters,
 ) where
     R: Stream<Item = Result<bm13xx::protocol::Response, std::io::Error>> + Unpin,
     W: Sink<bm13xx::protocol::Command> + Unpin,
@@ -706,6 +955,14 @@ async fn bm13xx_thread_actor<R, W>(
     let mut chip_jobs = ChipJobTracker::new();
     let mut ntime_ticker = tokio::time::interval(tokio::time::Duration::from_secs(1));
     ntime_ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let ticket_mask = protocol::TicketMask::new(reporting_interval());
+    let mut health_ticker = tokio::time::interval(HEALTH_INTERVAL);
+    health_ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut frequency_limit = peripherals.frequency_limit.clone();
+    let mut frequency_mhz = capped_frequency(target_mhz, *frequency_limit.borrow_and_update());
+    counters.set_frequency(frequency_mhz);
+    let mut ramp: Option<FrequencyRamp> = None;
+    let mut ramp_waiter: Option<oneshot::Sender<std::result::Result<f32, HashThreadError>>> = None;
 
     loop {
         tokio::select! {
@@ -745,7 +1002,7 @@ async fn bm13xx_thread_actor<R, W>(
 
                         if !chip_initialized {
                             trace!("Initializing chip on first assignment.");
-                            if let Err(e) = initialize_chip(&mut chip_commands, &mut peripherals).await {
+                            if let Err(e) = initialize_chip(&mut chip_commands, &mut peripherals, &chain, frequency_mhz).await {
                                 error!(error = %e, "Chip initialization failed");
                                 response_tx.send(Err(e)).ok();
                                 continue;
@@ -796,7 +1053,7 @@ async fn bm13xx_thread_actor<R, W>(
 
                         if !chip_initialized {
                             trace!("Initializing chip on first assignment.");
-                            if let Err(e) = initialize_chip(&mut chip_commands, &mut peripherals).await {
+                            if let Err(e) = initialize_chip(&mut chip_commands, &mut peripherals, &chain, frequency_mhz).await {
                                 error!(error = %e, "Chip initialization failed");
                                 response_tx.send(Err(e)).ok();
                                 continue;
@@ -850,6 +1107,35 @@ async fn bm13xx_thread_actor<R, W>(
                         response_tx.send(Ok(old_task)).ok();
                     }
 
+                    ThreadCommand::SetFrequency { frequency_mhz: requested_mhz, response_tx } => {
+                        if calculate_pll_for_frequency(requested_mhz).is_none() {
+                            response_tx.send(Err(HashThreadError::FrequencyChangeFailed(
+                                format!("no PLL setting for {requested_mhz} MHz")
+                            ))).ok();
+                            continue;
+                        }
+                        target_mhz = requested_mhz;
+                        let to_mhz = capped_frequency(target_mhz, *frequency_limit.borrow());
+
+                        // Uninitialized chips pick the new frequency up when they ramp
+                        if !chip_initialized {
+                            frequency_mhz = to_mhz;
+                            counters.set_frequency(frequency_mhz);
+                            response_tx.send(Ok(frequency_mhz)).ok();
+                            continue;
+                        }
+
+                        info!(from_mhz = frequency_mhz, to_mhz, "Retuning chip frequency.");
+                        ramp_to(&mut ramp, frequency_mhz, to_mhz, ramp_sample(&counters, current_task.is_some()));
+                        if ramp.is_none() {
+                            response_tx.send(Ok(frequency_mhz)).ok();
+                        } else if let Some(superseded) = ramp_waiter.replace(response_tx) {
+                            superseded.send(Err(HashThreadError::FrequencyChangeFailed(
+                                "superseded by a newer frequency change".into()
+                            ))).ok();
+                        }
+                    }
+
                     ThreadCommand::Shutdown => {
                         info!("Shutdown command received");
                         // Exit actor loop (channel closure signals shutdown to scheduler)
@@ -858,13 +1144,88 @@ async fn bm13xx_thread_actor<R, W>(
                 }
             }
 
+            // Frequency cap from the board (e.g. thermal throttling)
+            Ok(()) = frequency_limit.changed() => {
+                let to_mhz = capped_frequency(target_mhz, *frequency_limit.borrow_and_update());
+                if to_mhz == frequency_mhz {
+                    continue;
+                }
+
+                // Uninitialized chips pick the new frequency up when they ramp
+                if chip_initialized {
+                    info!(from_mhz = frequency_mhz, to_mhz, "Retuning chip frequency.");
+                    ramp_to(&mut ramp, frequency_mhz, to_mhz, ramp_sample(&counters, current_task.is_some()));
+                } else {
+                    frequency_mhz = to_mhz;
+                    counters.set_frequency(frequency_mhz);
+                }
+            }
+
+            // Frequency ramp in progress, stepping while nonces keep coming
+            _ = tokio::time::sleep_until(
+                ramp.as_ref().map_or_else(tokio::time::Instant::now, |r| r.next_at().into())
+            ), if ramp.is_some() => {
+                let Some(active) = ramp.as_mut() else {
+                    continue;
+                };
+                let sample = ramp_sample(&counters, current_task.is_some());
+                match active.poll(Instant::now(), sample) {
+                    RampAction::Step(mhz) => {
+                        if let Err(e) = set_pll(&mut chip_commands, mhz).await {
+                            error!(error = %e, "Failed to retune chip frequency");
+                            ramp = None;
+                            if let Some(waiter) = ramp_waiter.take() {
+                                waiter.send(Err(e)).ok();
+                            }
+                            continue;
+                        }
+                        frequency_mhz = mhz;
+                        counters.set_frequency(frequency_mhz);
+                    }
+                    RampAction::Wait => {}
+                    RampAction::Reached(mhz) => {
+                        debug!(frequency_mhz = mhz, "Chip frequency reached; verifying.");
+                        if let Some(waiter) = ramp_waiter.take() {
+                            waiter.send(Ok(mhz)).ok();
+                        }
+                    }
+                    RampAction::Done(mhz) => {
+                        info!(frequency_mhz = mhz, "Chip frequency retuned.");
+                        ramp = None;
+                        if let Some(waiter) = ramp_waiter.take() {
+                            waiter.send(Ok(mhz)).ok();
+                        }
+                    }
+                    RampAction::RolledBack { frequency_mhz: mhz, reason } => {
+                        warn!(frequency_mhz = mhz, reason = %reason, "Chip frequency rolled back.");
+                        ramp = None;
+
+                        // Stay at the last good frequency until told otherwise
+                        target_mhz = mhz;
+                        if let Some(waiter) = ramp_waiter.take() {
+                            waiter.send(Err(HashThreadError::FrequencyChangeFailed(format!(
+                                "rolled back to {mhz} MHz: {reason}"
+                            )))).ok();
+                        }
+                    }
+                }
+            }
+
             // Chip responses from serial stream
             Some(result) = chip_responses.next() => {
                 match result {
                     Ok(response) => {
                         match response {
-                            bm13xx::protocol::Response::Nonce { nonce, job_id, version, midstate_num, subcore_id } => {
+                            bm13xx::protocol::Response::Nonce {
+                                nonce,
+                                job_id,
+                                version,
+                                // Identifies the version-rolled midstate, not hardware
+                                midstate_num: _,
+                                subcore_id,
+                            } => {
                                 // Look up the task for this job_id
+                                let chip = counters.chip_index(protocol::nonce_chip_address(nonce));
                                 if let Some(task) = chip_jobs.get(job_id) {
                                     let template = &task.job.template;
 
@@ -887,6 +1248,24 @@ async fn bm13xx_thread_actor<R, W>(
                                             // Compute hash
                                             let hash = header.block_hash();
 
+                                            // The chip only reports nonces meeting its ticket
+                                            // mask; one that doesn't was miscomputed
+                                            if !ticket_mask.is_met_by(&hash) {
+                                                counters.record_error(chip);
+                                                status.write().unwrap().hardware_errors += 1;
+                                                debug!(
+                                                    chip_job_id = job_id,
+                                                    nonce = format!("{:#x}", nonce),
+                                                    hash = %hash,
+                                                    "Nonce does not meet ticket mask (hardware error)"
+                                                );
+                                                continue;
+                                            }
+
+                                            // Every good nonce counts toward chip health,
+                                            // whether or not it makes a share.
+                                            counters.record(chip, nonce, subcore_id);
+
                                             // Validate against task share target
                                             if task.share_target.is_met_by(hash) {
                                                 // Create share with threshold difficulty for hashrate calculation
@@ -930,17 +1309,18 @@ async fn bm13xx_thread_actor<R, W>(
                                                 chip_job_id = job_id,
                                                 "Failed to compute merkle root for nonce"
                                             );
+                                            counters.record(chip, nonce, subcore_id);
                                         }
                                     }
                                 } else {
+                                    // Can't be validated, but still evidence of hashing
                                     trace!(
                                         chip_job_id = job_id,
                                         nonce = format!("{:#x}", nonce),
                                         "Nonce for unknown job_id (possibly stale)"
                                     );
+                                    counters.record(chip, nonce, subcore_id);
                                 }
-
-                                let _ = (midstate_num, subcore_id); // Unused for now
                             }
 
                             bm13xx::protocol::Response::ReadRegister { chip_address, register } => {
@@ -956,6 +1336,15 @@ async fn bm13xx_thread_actor<R, W>(
                 }
             }
 
+            // Per-chip health
+            _ = health_ticker.tick() => {
+                let mut s = status.write().unwrap();
+                counters.update(Instant::now(), s.is_active);
+                s.chips = counters.health();
+                s.hardware_error_rate = counters.hardware_error_rate();
+                s.hashes = counters.hashes();
+            }
+
             // ntime rolling timer (roll forward every second)
             _ = ntime_ticker.tick(), if current_task.is_some() => {
                 let task = current_task.as_mut().unwrap();
@@ -987,6 +1376,368 @@ async fn bm13xx_thread_actor<R, W>(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_capped_frequency() {
+        assert_eq!(capped_frequency(600.0, None), 600.0);
+        assert_eq!(capped_frequency(600.0, Some(400.0)), 400.0);
+        assert_eq!(capped_frequency(525.0, Some(900.0)), 525.0);
+    }
+
+    #[test]
+    fn test_pll_calculations_match_reference() {
+        // Test cases from the Bitaxe Gamma protocol capture
+        // Format: (freq_mhz, expected_fb_div, expected_ref_div, expected_post_div)
+        // Note: The capture shows bytes [55 AA 51 09 00 08 FLAG FB_DIV REF POST CRC]
+        // Format: (freq_mhz, expected_flag, expected_fb_div, expected_ref_div, expected_post_div)
+        let test_cases = vec![
+            // From the capture document:
+            (62.50, 0x50, 0xD2, 0x02, 0x65), // tx: [55 AA 51 09 00 08 50 D2 02 65 05]
+            (68.75, 0x50, 0xE7, 0x02, 0x65), // tx: [55 AA 51 09 00 08 50 E7 02 65 1C]
+            (75.00, 0x50, 0xD2, 0x02, 0x64), // tx: [55 AA 51 09 00 08 50 D2 02 64 00]
+            (81.25, 0x50, 0xE4, 0x02, 0x64), // tx: [55 AA 51 09 00 08 50 E4 02 64 14]
+            (87.50, 0x50, 0xC4, 0x02, 0x63), // tx: [55 AA 51 09 00 08 50 C4 02 63 18]
+            (93.75, 0x50, 0xD2, 0x02, 0x63), // tx: [55 AA 51 09 00 08 50 D2 02 63 1B]
+            (100.00, 0x50, 0xE0, 0x02, 0x63), // tx: [55 AA 51 09 00 08 50 E0 02 63 00]
+            (525.00, 0x50, 0xD2, 0x02, 0x40), // tx: [55 AA 51 09 00 08 50 D2 02 40 05] (final)
+        ];
+
+        for (freq_mhz, expected_flag, expected_fb, expected_ref, expected_post) in test_cases {
+            let config = calculate_pll_for_frequency(freq_mhz)
+                .unwrap_or_else(|| panic!("Failed to calculate PLL for {} MHz", freq_mhz));
+
+            assert_eq!(
+                config.flag, expected_flag,
+                "Flag mismatch for {} MHz: expected 0x{:02X}, got 0x{:02X}",
+                freq_mhz, expected_flag, config.flag
+            );
+
+            assert_eq!(
+                config.fb_div, expected_fb,
+                "FB divider mismatch for {} MHz: expected 0x{:02X}, got 0x{:02X}",
+                freq_mhz, expected_fb, config.fb_div
+            );
+
+            assert_eq!(
+                config.ref_div, expected_ref,
+                "Ref divider mismatch for {} MHz: expected {}, got {}",
+                freq_mhz, expected_ref, config.ref_div
+            );
+
+            assert_eq!(
+                config.post_div, expected_post,
+                "Post divider mismatch for {} MHz: expected 0x{:02X}, got 0x{:02X}",
+                freq_mhz, expected_post, config.post_div
+            );
+
+            // Verify the frequency calculation
+            let post_div1 = ((config.post_div >> 4) & 0xF) + 1;
+            let post_div2 = (config.post_div & 0xF) + 1;
+            let calculated_freq =
+                25.0 * config.fb_div as f32 / (config.ref_div * post_div1 * post_div2) as f32;
+
+            assert!(
+                (calculated_freq - freq_mhz).abs() < 1.0,
+                "Frequency calculation error for {} MHz: calculated {} MHz",
+                freq_mhz,
+                calculated_freq
+            );
+        }
+    }
+
+    #[test]
+    fn test_frequency_ramp_generation() {
+        // Verify correct number of steps are generated
+        let steps = generate_frequency_ramp_steps(56.25, 525.0, 6.25);
+
+        // Should have steps from 56.25 to 525.0 in 6.25 MHz increments
+        // That's (525 - 56.25) / 6.25 + 1 = 75.0 steps
+        assert_eq!(steps.len(), 76, "Expected 76 frequency steps");
+
+        // Verify first and last frequencies by calculating them back
+        if let Some(first) = steps.first() {
+            let post_div1 = ((first.post_div >> 4) & 0xF) + 1;
+            let post_div2 = (first.post_div & 0xF) + 1;
+            let first_freq =
+                25.0 * first.fb_div as f32 / (first.ref_div * post_div1 * post_div2) as f32;
+            assert!(
+                (first_freq - 56.25).abs() < 1.0,
+                "First frequency should be ~56.25 MHz"
+            );
+        }
+
+        if let Some(last) = steps.last() {
+            let post_div1 = ((last.post_div >> 4) & 0xF) + 1;
+            let post_div2 = (last.post_div & 0xF) + 1;
+            let last_freq =
+                25.0 * last.fb_div as f32 / (last.ref_div * post_div1 * post_div2) as f32;
+            assert!(
+                (last_freq - 525.0).abs() < 1.0,
+                "Last frequency should be ~525 MHz"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pll_flag_setting() {
+        // Test that the VCO-based flag is set correctly
+        // Flag is 0x50 when VCO frequency >= 2400 MHz, 0x40 otherwise
+        // VCO frequency = fb_div * 25.0 / ref_div
+
+        // Low frequency (100 MHz) - VCO will be >= 2400, so should use 0x50
+        let low_freq = calculate_pll_for_frequency(100.0).unwrap();
+        assert_eq!(low_freq.flag, 0x50, "Should have 0x50 flag for 100 MHz");
+
+        // High frequency (525 MHz) - VCO will be >= 2400, so should use 0x50
+        let high_freq = calculate_pll_for_frequency(525.0).unwrap();
+        assert_eq!(high_freq.flag, 0x50, "Should have 0x50 flag for 525 MHz");
+
+        // The 0x40 flag would be used for configurations with VCO < 2400 MHz,
+        // which aren't typically reached in the 56.25-525 MHz output range
+    }
+
+    /// Encode commands into the frames sent on the wire.
+    fn wire_frames(commands: Vec<protocol::Command>) -> Vec<Vec<u8>> {
+        use tokio_util::codec::Encoder;
+
+        let mut codec = bm13xx::FrameCodec::default();
+        commands
+            .into_iter()
+            .map(|command| {
+                let mut frame = bytes::BytesMut::new();
+                codec.encode(command, &mut frame).unwrap();
+                frame.to_vec()
+            })
+            .collect()
+    }
+
+    /// Position of `frame` in `frames` at or after `from`.
+    fn find_frame(frames: &[Vec<u8>], from: usize, frame: &[u8]) -> usize {
+        frames[from..]
+            .iter()
+            .position(|sent| sent == frame)
+            .map(|offset| from + offset)
+            .unwrap_or_else(|| panic!("frame {:02x?} not sent after #{from}", frame))
+    }
+
+    /// Chip addresses written to `register` by unicast frames, in order.
+    fn unicast_targets(frames: &[Vec<u8>], register: u8) -> Vec<u8> {
+        frames
+            .iter()
+            .filter(|frame| frame[2] == 0x41 && frame[5] == register)
+            .map(|frame| frame[4])
+            .collect()
+    }
+
+    // The BM1362 sequence follows the register values in PROTOCOL.md and has
+    // not been checked against a capture; this covers how the chain is
+    // addressed and configured, not the values themselves.
+    #[tokio::test(start_paused = true)]
+    async fn test_configure_bm1362_chain() {
+        let addresses = protocol::BM13xxProtocol::chip_addresses(12);
+        let chain = Chain {
+            chip_type: protocol::ChipType::BM1362,
+            addresses: addresses.clone(),
+            chips_per_domain: 3,
+        };
+        let mut commands = Vec::new();
+        configure_chain(&mut commands, &chain, 525.0).await.unwrap();
+
+        // Every chip is addressed, in chain order, right after ChainInactive
+        let inactive = commands
+            .iter()
+            .position(|c| matches!(c, protocol::Command::ChainInactive))
+            .expect("chain should be made inactive");
+        let assigned: Vec<u8> = commands[inactive + 1..]
+            .iter()
+            .map_while(|c| match c {
+                protocol::Command::SetChipAddress { chip_address } => Some(*chip_address),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(assigned, addresses);
+
+        // Each chip is configured at its own address
+        let frames = wire_frames(commands);
+        assert_eq!(unicast_targets(&frames, 0xA8), addresses);
+
+        // Domains of three chips end on every third chip
+        assert_eq!(unicast_targets(&frames, 0x58), [0x20, 0x50, 0x80, 0xB0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_configure_s21_pro_chain() {
+        let chain = Chain {
+            chip_type: protocol::ChipType::BM1370,
+            addresses: protocol::BM13xxProtocol::chip_addresses(65),
+            chips_per_domain: 5,
+        };
+        let mut commands = Vec::new();
+        configure_chain(&mut commands, &chain, 525.0).await.unwrap();
+
+        // Sequence starts by enabling version rolling on all chips
+        assert!(matches!(
+            &commands[0],
+            protocol::Command::WriteRegister {
+                broadcast: true,
+                register: protocol::Register::VersionMask(_),
+                ..
+            }
+        ));
+
+        // 65 chips are addressed 2 apart, as in the S21 Pro captures
+        let inactive = commands
+            .iter()
+            .position(|c| matches!(c, protocol::Command::ChainInactive))
+            .expect("chain should be made inactive");
+        let assigned: Vec<u8> = commands[inactive + 1..]
+            .iter()
+            .map_while(|c| match c {
+                protocol::Command::SetChipAddress { chip_address } => Some(*chip_address),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(assigned, (0..=0x80).step_by(2).collect::<Vec<u8>>());
+
+        // Nonce space is partitioned with the S21 Pro value
+        let nonce_range = commands.iter().find_map(|c| match c {
+            protocol::Command::WriteRegister {
+                register: protocol::Register::NonceRange(config),
+                ..
+            } => Some(<[u8; 4]>::from(*config)),
+            _ => None,
+        });
+        assert_eq!(nonce_range, Some([0x00, 0x00, 0x1e, 0xb5]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_configure_single_bm1370() {
+        let chain = Chain {
+            chip_type: protocol::ChipType::BM1370,
+            addresses: vec![0x00],
+            chips_per_domain: 1,
+        };
+        let mut commands = Vec::new();
+        configure_chain(&mut commands, &chain, 525.0).await.unwrap();
+        let frames = wire_frames(commands);
+
+        // Bitaxe capture: InitControl and MiscControl, then one address
+        let at = find_frame(
+            &frames,
+            0,
+            &[
+                0x55, 0xaa, 0x51, 0x09, 0x00, 0xa8, 0x00, 0x07, 0x00, 0x00, 0x03,
+            ],
+        );
+        assert_eq!(
+            frames[at + 1],
+            [0x55, 0xaa, 0x51, 0x09, 0x00, 0x18, 0xf0, 0x00, 0xc1, 0x00, 0x04]
+        );
+        assert_eq!(frames[at + 2], [0x55, 0xaa, 0x53, 0x05, 0x00, 0x00, 0x03]);
+        assert_eq!(frames[at + 3], [0x55, 0xaa, 0x40, 0x05, 0x00, 0x00, 0x1c]);
+        assert_ne!(frames[at + 4][2], 0x40);
+
+        // A lone chip has no domain boundaries to configure
+        assert!(!frames.iter().any(|frame| frame[5] == 0x2C));
+    }
+
+    /// ChipId answer of the Bitaxe Gamma's BM1370, from a capture.
+    const BM1370_CHIP_ID: [u8; 11] = [
+        0xaa, 0x55, 0x13, 0x70, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+    ];
+
+    /// Response decoded from a captured frame.
+    fn decoded(frame: &[u8]) -> protocol::Response {
+        use tokio_util::codec::Decoder;
+
+        let mut buf = bytes::BytesMut::from(frame);
+        bm13xx::FrameCodec::default()
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap()
+    }
+
+    /// ChipId answer of an unaddressed chip of type `chip_type`.
+    fn chip_id(chip_type: protocol::ChipType) -> protocol::Response {
+        protocol::Response::ReadRegister {
+            chip_address: 0x00,
+            register: protocol::Register::ChipId {
+                chip_type,
+                core_count: 0,
+                address: 0x00,
+            },
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_enumerate_chain() {
+        let answers = (0..12).map(|_| Ok(chip_id(protocol::ChipType::BM1362)));
+        let mut responses = futures::stream::iter(answers);
+        let mut commands = Vec::new();
+        let chips = enumerate_chain(&mut responses, &mut commands)
+            .await
+            .unwrap();
+
+        // Broadcast ChipId read, as sent by esp-miner
+        assert_eq!(
+            wire_frames(commands),
+            [vec![0x55, 0xaa, 0x52, 0x05, 0x00, 0x00, 0x0a]]
+        );
+        let addresses: Vec<u8> = chips.iter().map(|chip| chip.address).collect();
+        assert_eq!(addresses, protocol::BM13xxProtocol::chip_addresses(12));
+        assert!(chips.iter().all(|chip| chip.chip_id == [0x13, 0x62]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_enumerate_single_bm1370() {
+        let mut responses = futures::stream::iter([Ok(decoded(&BM1370_CHIP_ID))]);
+        let chips = enumerate_chain(&mut responses, &mut Vec::new())
+            .await
+            .unwrap();
+
+        assert_eq!(chips.len(), 1);
+        assert_eq!(chips[0].chip_id, [0x13, 0x70]);
+        assert_eq!(chips[0].address, 0x00);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_enumerate_rejects_bad_chains() {
+        // Nobody answers
+        let mut silent = futures::stream::pending();
+        let result = enumerate_chain(&mut silent, &mut Vec::new()).await;
+        assert!(matches!(
+            result,
+            Err(HashThreadError::InitializationFailed(_))
+        ));
+
+        // Mixed chip models
+        let answers = [
+            Ok(chip_id(protocol::ChipType::BM1362)),
+            Ok(decoded(&BM1370_CHIP_ID)),
+        ];
+        let mut responses = futures::stream::iter(answers);
+        let result = enumerate_chain(&mut responses, &mut Vec::new()).await;
+        assert!(matches!(
+            result,
+            Err(HashThreadError::InitializationFailed(_))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_configure_unknown_chip_fails() {
+        let chain = Chain {
+            chip_type: protocol::ChipType::Unknown([0x12, 0x34]),
+            addresses: vec![0x00],
+            chips_per_domain: 1,
+        };
+        let mut commands = Vec::new();
+        let result = configure_chain(&mut commands, &chain, 525.0).await;
+        assert!(matches!(
+            result,
+            Err(HashThreadError::InitializationFailed(_))
+        ));
+        assert!(commands.is_empty());
+    }
+
     #[test]
     fn test_task_to_job_full_converts_high_level_types() {
         use crate::asic::bm13xx::test_data::esp_miner_job;
@@ -1018,6 +1769,7 @@ mod tests {
             job: Arc::new(ActiveJob {
                 source_id: slotmap::DefaultKey::default(),
                 template,
+                assigned: std::time::Instant::now(),
             }),
             en2_range: None,
             en2: Some(dummy_en2),
@@ -0,0 +1,422 @@
+//! Per-chip nonce counting and health scoring.
+//!
+//! Chips report every nonce meeting their ticket mask, and each reported
+//! nonce stands for `2^exponent` hashes (see [`ReportingInterval`]). That is
+//! a far denser signal than shares at the thread's share target, enough to
+//! measure each chip, and each core domain on chips that report which one
+//! found a nonce, against what its frequency and core count should deliver.
+//!
+//! Nonces whose recomputed hash doesn't meet the ticket mask were
+//! miscomputed by the chip and are counted as hardware errors instead. Their
+//! rate is the first sign of a chip clocked too fast or fed too little
+//! voltage, and tuning must keep it under [`HW_ERROR_RATE_LIMIT`].
+//!
+//! Counts are kept twice: lifetime totals for display, and exponentially
+//! decayed "recent" counts for scoring, so a chip that degrades after hours
+//! of good work shows up within [`HALF_LIFE`] rather than being averaged
+//! away. Time only counts while the thread is hashing; idle periods neither
+//! decay the counts nor lower the score.
+//!
+//! [`ReportingInterval`]: crate::asic::bm13xx::protocol::ReportingInterval
+
+use std::time::{Duration, Instant};
+
+use crate::api_client::types::ChipHealth;
+use crate::asic::bm13xx::protocol::ChipType;
+
+/// Half-life of the recent counts used for scoring.
+pub const HALF_LIFE: Duration = Duration::from_secs(30 * 60);
+
+/// Health below which a chip is reported as underperforming.
+pub const UNDERPERFORMING: f64 = 0.75;
+
+/// Fraction of reported nonces that may be hardware errors before a chip is
+/// considered over-driven.
+pub const HW_ERROR_RATE_LIMIT: f64 = 0.01;
+
+/// Nonces a chip must be expected to have reported before it is scored.
+///
+/// Nonce counts are Poisson distributed; with 30 expected, a healthy chip
+/// scores below [`UNDERPERFORMING`] less than 1% of the time.
+const MIN_EXPECTED_NONCES: f64 = 30.0;
+
+/// Fraction of the chip's mean domain count below which a domain is weak.
+const WEAK_DOMAIN: f64 = 0.25;
+
+/// Mean recent nonces per domain before domains are compared.
+const MIN_DOMAIN_NONCES: f64 = 20.0;
+
+/// Engines per core domain, as numbered by a nonce's `subcore_id`.
+const ENGINES: usize = 16;
+
+/// Nonce counters for the chips of one thread.
+#[derive(Debug)]
+pub struct NonceCounters {
+    chips: Vec<ChipCounters>,
+
+    /// Hashes represented by one reported nonce.
+    hashes_per_nonce: f64,
+
+    /// Chip frequency in MHz.
+    frequency_mhz: f32,
+
+    /// Decay-weighted seconds of hashing, the denominator of the recent
+    /// counts.
+    exposure: f64,
+
+    /// When counts were last decayed.
+    updated_at: Instant,
+}
+
+#[derive(Debug)]
+struct ChipCounters {
+    address: u8,
+    chip_type: ChipType,
+    nonces: u64,
+    recent: f64,
+    hardware_errors: u64,
+    recent_errors: f64,
+    domains: Vec<u64>,
+    recent_domains: Vec<f64>,
+    engines: [u64; ENGINES],
+}
+
+impl NonceCounters {
+    /// Counters for chips at `addresses`, hashing at `frequency_mhz` with
+    /// each nonce standing for `hashes_per_nonce` hashes.
+    pub fn new(
+        chip_type: ChipType,
+        addresses: impl IntoIterator<Item = u8>,
+        frequency_mhz: f32,
+        hashes_per_nonce: f64,
+        now: Instant,
+    ) -> Self {
+        let domains = chip_type.domain_count().unwrap_or(0) as usize;
+        Self {
+            chips: addresses
+                .into_iter()
+                .map(|address| ChipCounters {
+                    address,
+                    chip_type,
+                    nonces: 0,
+                    recent: 0.0,
+                    hardware_errors: 0,
+                    recent_errors: 0.0,
+                    domains: vec![0; domains],
+                    recent_domains: vec![0.0; domains],
+                    engines: [0; ENGINES],
+                })
+                .collect(),
+            hashes_per_nonce,
+            frequency_mhz,
+            exposure: 0.0,
+            updated_at: now,
+        }
+    }
+
+    /// Index in the chain of the chip a nonce stamped with `address` came
+    /// from: the one with the highest address not above it.
+    pub fn chip_index(&self, address: u8) -> usize {
+        self.chips
+            .iter()
+            .rposition(|chip| chip.address <= address)
+            .unwrap_or(0)
+    }
+
+    /// Count a nonce reported by the chip at `index` in the chain.
+    pub fn record(&mut self, index: usize, nonce: u32, subcore_id: u8) {
+        let Some(chip) = self.chips.get_mut(index) else {
+            return;
+        };
+
+        chip.nonces += 1;
+        chip.recent += 1.0;
+        if let Some(domain) = chip.chip_type.nonce_domain(nonce) {
+            if let Some(count) = chip.domains.get_mut(domain as usize) {
+                *count += 1;
+                chip.recent_domains[domain as usize] += 1.0;
+            }
+        }
+        if let Some(count) = chip.engines.get_mut(subcore_id as usize) {
+            *count += 1;
+        }
+    }
+
+    /// Count a nonce from the chip at `index` that failed validation.
+    pub fn record_error(&mut self, index: usize) {
+        if let Some(chip) = self.chips.get_mut(index) {
+            chip.hardware_errors += 1;
+            chip.recent_errors += 1.0;
+        }
+    }
+
+    /// Recent fraction of all the thread's reported nonces that were
+    /// hardware errors.
+    pub fn hardware_error_rate(&self) -> f64 {
+        let (errors, nonces) = self.chips.iter().fold((0.0, 0.0), |(e, n), chip| {
+            (e + chip.recent_errors, n + chip.recent)
+        });
+        error_rate(errors, nonces)
+    }
+
+    /// Advance time to `now`, during which the thread was hashing if
+    /// `active`.
+    pub fn update(&mut self, now: Instant, active: bool) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.updated_at = now;
+        if !active {
+            return;
+        }
+
+        let half_life = HALF_LIFE.as_secs_f64();
+        let factor = 0.5f64.powf(elapsed / half_life);
+        self.exposure =
+            self.exposure * factor + half_life / std::f64::consts::LN_2 * (1.0 - factor);
+        for chip in &mut self.chips {
+            chip.recent *= factor;
+            chip.recent_errors *= factor;
+            for count in &mut chip.recent_domains {
+                *count *= factor;
+            }
+        }
+    }
+
+    /// Record a change of chip frequency, e.g. from thermal throttling.
+    ///
+    /// Expectations use the new frequency from now on; the decaying window
+    /// catches up with the actual nonce rate within a few half-lives.
+    pub fn set_frequency(&mut self, frequency_mhz: f32) {
+        self.frequency_mhz = frequency_mhz;
+    }
+
+    /// Hashes done by all chips since the counters were created.
+    pub fn hashes(&self) -> f64 {
+        let nonces: u64 = self.chips.iter().map(|chip| chip.nonces).sum();
+        nonces as f64 * self.hashes_per_nonce
+    }
+
+    /// Valid nonces and hardware errors of all the thread's chips since the
+    /// counters were created.
+    pub fn totals(&self) -> (u64, u64) {
+        self.chips.iter().fold((0, 0), |(nonces, errors), chip| {
+            (nonces + chip.nonces, errors + chip.hardware_errors)
+        })
+    }
+
+    /// Nonces per second all the thread's chips should report at the
+    /// current frequency, if their core count is known.
+    pub fn expected_nonce_rate(&self) -> Option<f64> {
+        self.expected_hashrate()
+            .map(|rate| rate * self.chips.len() as f64 / self.hashes_per_nonce)
+    }
+
+    /// Expected hashrate of one chip in H/s, if its core count is known.
+    pub fn expected_hashrate(&self) -> Option<f64> {
+        let chip_type = self.chips.first()?.chip_type;
+        chip_type
+            .core_count()
+            .map(|cores| self.frequency_mhz as f64 * 1e6 * cores as f64)
+    }
+
+    /// Counts and scores of each chip.
+    pub fn health(&self) -> Vec<ChipHealth> {
+        let expected_hashrate = self.expected_hashrate();
+        let expected_nonces = expected_hashrate
+            .map(|rate| rate * self.exposure / self.hashes_per_nonce)
+            .filter(|&n| n >= MIN_EXPECTED_NONCES);
+
+        self.chips
+            .iter()
+            .map(|chip| {
+                let hashrate = if self.exposure > 0.0 {
+                    chip.recent * self.hashes_per_nonce / self.exposure
+                } else {
+                    0.0
+                };
+
+                ChipHealth {
+                    address: chip.address,
+                    nonces: chip.nonces,
+                    hashrate,
+                    expected_hashrate,
+                    health: expected_nonces.map(|expected| chip.recent / expected),
+                    hardware_errors: chip.hardware_errors,
+                    hardware_error_rate: error_rate(chip.recent_errors, chip.recent),
+                    domain_nonces: chip.domains.clone(),
+                    engine_nonces: chip.engines.to_vec(),
+                    weak_domains: chip.weak_domains(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn error_rate(errors: f64, nonces: f64) -> f64 {
+    if errors > 0.0 {
+        errors / (errors + nonces)
+    } else {
+        0.0
+    }
+}
+
+impl ChipCounters {
+    fn weak_domains(&self) -> Vec<u8> {
+        if self.recent_domains.is_empty() {
+            return Vec::new();
+        }
+
+        let mean = self.recent_domains.iter().sum::<f64>() / self.recent_domains.len() as f64;
+        if mean < MIN_DOMAIN_NONCES {
+            return Vec::new();
+        }
+
+        self.recent_domains
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count < mean * WEAK_DOMAIN)
+            .map(|(domain, _)| domain as u8)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2^32 hashes per nonce, so a BM1370 at 500 MHz expects 149 nonces/s.
+    const HASHES_PER_NONCE: f64 = 4_294_967_296.0;
+
+    fn nonce_in_domain(domain: u8) -> u32 {
+        (domain as u32) << 25 | 0x00a6_0018
+    }
+
+    /// Report `per_domain(chip, domain)` nonces from each domain of each
+    /// chip every second for `seconds`.
+    fn run(
+        counters: &mut NonceCounters,
+        start: Instant,
+        seconds: u64,
+        per_domain: impl Fn(usize, u8) -> u32,
+    ) {
+        let chips = counters.chips.len();
+        for s in 1..=seconds {
+            for index in 0..chips {
+                for domain in 0..80 {
+                    for i in 0..per_domain(index, domain) {
+                        counters.record(index, nonce_in_domain(domain), (i % 16) as u8);
+                    }
+                }
+            }
+            counters.update(start + Duration::from_secs(s), true);
+        }
+    }
+
+    #[test]
+    fn test_chip_index() {
+        let counters = NonceCounters::new(
+            ChipType::BM1362,
+            [0, 16, 32],
+            500.0,
+            HASHES_PER_NONCE,
+            Instant::now(),
+        );
+        assert_eq!(counters.chip_index(0x00), 0);
+        assert_eq!(counters.chip_index(0x0f), 0);
+        assert_eq!(counters.chip_index(0x10), 1);
+        assert_eq!(counters.chip_index(0x2a), 2);
+        assert_eq!(counters.chip_index(0xff), 2);
+
+        let single = NonceCounters::new(
+            ChipType::BM1370,
+            [0],
+            500.0,
+            HASHES_PER_NONCE,
+            Instant::now(),
+        );
+        assert_eq!(single.chip_index(0xa9), 0);
+    }
+
+    #[test]
+    fn test_healthy_chip() {
+        let start = Instant::now();
+        let mut counters =
+            NonceCounters::new(ChipType::BM1370, [0], 500.0, HASHES_PER_NONCE, start);
+        assert_eq!(counters.health()[0].health, None);
+
+        // 160 nonces/s, about 107% of expected
+        run(&mut counters, start, 600, |_, _| 2);
+
+        let health = &counters.health()[0];
+        assert_eq!(health.nonces, 600 * 160);
+        assert_eq!(health.expected_hashrate, Some(640e9));
+        let score = health.health.unwrap();
+        assert!((score - 1.07).abs() < 0.02, "{score}");
+        assert!((health.hashrate / 687e9 - 1.0).abs() < 0.02);
+        assert_eq!(health.domain_nonces[32], 1200);
+        assert_eq!(health.engine_nonces[0], 600 * 80);
+        assert!(health.weak_domains.is_empty());
+    }
+
+    #[test]
+    fn test_degraded_chip_and_weak_domain() {
+        let start = Instant::now();
+        let mut counters =
+            NonceCounters::new(ChipType::BM1370, [0, 2], 500.0, HASHES_PER_NONCE, start);
+
+        // Chip 0 healthy apart from a dead domain; chip 1 at half rate
+        run(&mut counters, start, 300, |chip, domain| {
+            match (chip, domain) {
+                (0, 7) => 0,
+                (0, _) => 2,
+                _ => 1,
+            }
+        });
+
+        let health = counters.health();
+        assert_eq!(health[0].weak_domains, vec![7]);
+        assert!(health[0].health.unwrap() > UNDERPERFORMING);
+        assert_eq!(health[1].address, 2);
+        assert!(health[1].weak_domains.is_empty());
+        assert!(health[1].health.unwrap() < UNDERPERFORMING);
+    }
+
+    #[test]
+    fn test_hardware_errors() {
+        let start = Instant::now();
+        let mut counters =
+            NonceCounters::new(ChipType::BM1370, [0, 2], 500.0, HASHES_PER_NONCE, start);
+        assert_eq!(counters.hardware_error_rate(), 0.0);
+
+        // Chip 1 miscomputes one nonce in 20
+        for s in 1..=60 {
+            for domain in 0..80 {
+                counters.record(0, nonce_in_domain(domain), 0);
+                counters.record(1, nonce_in_domain(domain), 0);
+            }
+            for _ in 0..4 {
+                counters.record_error(1);
+            }
+            counters.update(start + Duration::from_secs(s), true);
+        }
+
+        let health = counters.health();
+        assert_eq!(health[0].hardware_errors, 0);
+        assert_eq!(health[0].hardware_error_rate, 0.0);
+        assert_eq!(health[1].hardware_errors, 240);
+        assert!((health[1].hardware_error_rate - 4.0 / 84.0).abs() < 1e-9);
+        assert!((counters.hardware_error_rate() - 4.0 / 164.0).abs() < 1e-9);
+        assert!(counters.hardware_error_rate() > HW_ERROR_RATE_LIMIT);
+    }
+
+    #[test]
+    fn test_idle_time_is_not_counted() {
+        let start = Instant::now();
+        let mut counters =
+            NonceCounters::new(ChipType::BM1370, [0], 500.0, HASHES_PER_NONCE, start);
+        run(&mut counters, start, 60, |_, _| 2);
+        let before = counters.health()[0].health.unwrap();
+
+        counters.update(start + Duration::from_secs(3600), false);
+        assert_eq!(counters.health()[0].health, Some(before));
+    }
+}
@@ -11,7 +11,8 @@
 //!
 //! 1. **Chip TicketMask (hardware pre-filter):**
 //!    Thread configures chip with low difficulty for frequent health signals.
-//!    Chip only reports nonces meeting this hardware threshold.
+//!    Chip only reports nonces meeting this hardware threshold; the thread
+//!    rechecks each one and counts those that fail as hardware errors.
 //!
 //! 2. **HashTask.share_target (thread-to-scheduler filter):**
 //!    Scheduler sets when assigning work. Thread computes hash for every chip
@@ -27,12 +28,15 @@
 //! is manageable: ~1-2 shares/sec to scheduler, fewer to pool.
 
 pub mod bm13xx;
+pub mod health;
+pub mod ramp;
 pub mod task;
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 
 use self::task::{HashTask, Share};
+use crate::api_client::types::ChipHealth;
 
 /// HashThread capabilities reported to scheduler for work assignment decisions.
 #[derive(Debug, Clone)]
@@ -62,11 +66,22 @@ pub struct HashThreadStatus {
     /// Number of hardware errors detected
     pub hardware_errors: u64,
 
+    /// Hashes done since the thread started, estimated from chip nonces
+    pub hashes: f64,
+
+    /// Recent fraction of chip nonces that were hardware errors, the signal
+    /// frequency and voltage tuning backs off on (see
+    /// [`health::HW_ERROR_RATE_LIMIT`])
+    pub hardware_error_rate: f64,
+
     /// Current chip temperature if available
     pub temperature_c: Option<f32>,
 
     /// Whether thread is actively working
     pub is_active: bool,
+
+    /// Per-chip nonce counts and health scores (see [`health`])
+    pub chips: Vec<ChipHealth>,
 }
 
 /// Events emitted by HashThreads back to the scheduler.
@@ -115,6 +130,9 @@ pub enum HashThreadError {
 
     #[error("Chip initialization failed: {0}")]
     InitializationFailed(String),
+
+    #[error("Frequency change failed: {0}")]
+    FrequencyChangeFailed(String),
 }
 
 /// HashThread trait - the scheduler's view of a schedulable worker.
@@ -0,0 +1,377 @@
+//! Frequency changes on running chips.
+//!
+//! [`FrequencyRamp`] moves a thread's chips from one frequency to another in
+//! small steps while they keep hashing. The thread's actor polls it between
+//! handling chip responses, so nonces and hardware errors keep being counted
+//! throughout and the ramp judges the new frequency by them.
+//!
+//! Raising the frequency is the risky direction: after the last step the
+//! ramp holds for [`VERIFY_WINDOW`], and if hardware errors since the ramp
+//! began exceed [`MAX_ERROR_RATE`], or the nonce rate falls below
+//! [`MIN_NONCE_FRACTION`] of what the new frequency should deliver, it steps
+//! back down to the last good frequency. Errors during the steps themselves
+//! roll back at once. Lowering the frequency is never rolled back; it is
+//! what thermal and power limits rely on.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Time held at a raised frequency before it counts as good.
+pub const VERIFY_WINDOW: Duration = Duration::from_secs(10);
+
+/// Fraction of nonces since the ramp began that may be hardware errors.
+pub const MAX_ERROR_RATE: f64 = 0.05;
+
+/// Fraction of the expected nonce rate the chips must deliver after a raise.
+pub const MIN_NONCE_FRACTION: f64 = 0.25;
+
+/// Hardware errors tolerated regardless of rate, so one bad nonce among few
+/// doesn't undo a ramp.
+const MIN_ERRORS: u64 = 3;
+
+/// Nonces the verify window must be expected to see before the nonce rate is
+/// judged; fewer are too noisy.
+const MIN_EXPECTED_NONCES: f64 = 8.0;
+
+/// Counters read from the thread each time the ramp is polled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RampSample {
+    /// Whether the chips have work; idle chips report no nonces
+    pub hashing: bool,
+    /// Valid nonces reported since the thread started
+    pub nonces: u64,
+    /// Hardware errors since the thread started
+    pub hardware_errors: u64,
+    /// Nonces per second the chips should report at the current frequency
+    pub expected_nonce_rate: Option<f64>,
+}
+
+/// What the thread should do next for a ramp.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RampAction {
+    /// Set the chips to this frequency.
+    Step(f32),
+
+    /// Nothing yet; poll again at [`FrequencyRamp::next_at`].
+    Wait,
+
+    /// The chips reached the raised target, which is now being verified.
+    Reached(f32),
+
+    /// The chips run at the target frequency, and the ramp is over.
+    Done(f32),
+
+    /// The new frequency failed and the chips are back at the last good one.
+    RolledBack { frequency_mhz: f32, reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Phase {
+    /// Stepping toward the target; raises are verified afterwards
+    Stepping { verify: bool },
+    /// Holding at the raised target since the given sample
+    Verifying { since: RampSample },
+    /// Stepping back to the last good frequency
+    RollingBack { reason: String },
+}
+
+/// A frequency change in progress.
+#[derive(Debug, Clone)]
+pub struct FrequencyRamp {
+    current_mhz: f32,
+    target_mhz: f32,
+    last_good_mhz: f32,
+    step_mhz: f32,
+    step_delay: Duration,
+    steps: VecDeque<f32>,
+    phase: Phase,
+    /// Counters when the ramp began
+    start: RampSample,
+    next_at: Instant,
+}
+
+impl FrequencyRamp {
+    /// Ramp from `from_mhz`, where the chips run now, to `to_mhz` in steps of
+    /// `step_mhz` taken every `step_delay`.
+    pub fn new(
+        from_mhz: f32,
+        to_mhz: f32,
+        step_mhz: f32,
+        step_delay: Duration,
+        now: Instant,
+        sample: RampSample,
+    ) -> Self {
+        Self {
+            current_mhz: from_mhz,
+            target_mhz: to_mhz,
+            last_good_mhz: from_mhz,
+            step_mhz,
+            step_delay,
+            steps: steps(from_mhz, to_mhz, step_mhz),
+            phase: Phase::Stepping {
+                verify: to_mhz > from_mhz,
+            },
+            start: sample,
+            next_at: now,
+        }
+    }
+
+    /// Frequency the chips were last set to.
+    pub fn current_mhz(&self) -> f32 {
+        self.current_mhz
+    }
+
+    /// Frequency the ramp is heading for.
+    pub fn target_mhz(&self) -> f32 {
+        self.target_mhz
+    }
+
+    /// When the ramp next needs polling.
+    pub fn next_at(&self) -> Instant {
+        self.next_at
+    }
+
+    /// Advance the ramp at `now` given the thread's latest counters.
+    pub fn poll(&mut self, now: Instant, sample: RampSample) -> RampAction {
+        if now < self.next_at {
+            return RampAction::Wait;
+        }
+
+        if let Phase::Stepping { verify: true } | Phase::Verifying { .. } = self.phase {
+            if let Some(reason) = excess_errors(&self.start, &sample) {
+                return self.roll_back(now, reason);
+            }
+        }
+
+        match &self.phase {
+            Phase::Stepping { verify } => {
+                if let Some(step) = self.steps.pop_front() {
+                    return self.step(now, step);
+                }
+                if !*verify {
+                    self.last_good_mhz = self.current_mhz;
+                    return RampAction::Done(self.current_mhz);
+                }
+                self.phase = Phase::Verifying { since: sample };
+                self.next_at = now + VERIFY_WINDOW;
+                RampAction::Reached(self.current_mhz)
+            }
+            Phase::Verifying { since } => {
+                if let Some(reason) = low_nonce_rate(since, &sample) {
+                    return self.roll_back(now, reason);
+                }
+                self.last_good_mhz = self.current_mhz;
+                RampAction::Done(self.current_mhz)
+            }
+            Phase::RollingBack { reason } => {
+                if let Some(step) = self.steps.pop_front() {
+                    return self.step(now, step);
+                }
+                RampAction::RolledBack {
+                    frequency_mhz: self.current_mhz,
+                    reason: reason.clone(),
+                }
+            }
+        }
+    }
+
+    /// Head for `to_mhz` instead, from wherever the ramp has got to.
+    ///
+    /// The last good frequency is kept, so a raise is still verified against
+    /// and rolled back to it.
+    pub fn retarget(&mut self, to_mhz: f32, now: Instant) {
+        self.steps = steps(self.current_mhz, to_mhz, self.step_mhz);
+        self.target_mhz = to_mhz;
+        self.phase = Phase::Stepping {
+            verify: to_mhz > self.last_good_mhz,
+        };
+        self.next_at = self.next_at.min(now);
+    }
+
+    fn step(&mut self, now: Instant, frequency_mhz: f32) -> RampAction {
+        self.current_mhz = frequency_mhz;
+        self.next_at = now + self.step_delay;
+        RampAction::Step(frequency_mhz)
+    }
+
+    fn roll_back(&mut self, now: Instant, reason: String) -> RampAction {
+        self.steps = steps(self.current_mhz, self.last_good_mhz, self.step_mhz);
+        self.target_mhz = self.last_good_mhz;
+        let action = match self.steps.pop_front() {
+            Some(step) => self.step(now, step),
+            None => RampAction::RolledBack {
+                frequency_mhz: self.current_mhz,
+                reason: reason.clone(),
+            },
+        };
+        self.phase = Phase::RollingBack { reason };
+        action
+    }
+}
+
+/// Frequencies from `from_mhz` to `to_mhz` in either direction, excluding
+/// the start and ending exactly on the target.
+fn steps(from_mhz: f32, to_mhz: f32, step_mhz: f32) -> VecDeque<f32> {
+    let count = ((to_mhz - from_mhz).abs() / step_mhz).ceil() as usize;
+    let direction = if to_mhz >= from_mhz { 1.0 } else { -1.0 };
+    (1..count)
+        .map(|i| from_mhz + direction * step_mhz * i as f32)
+        .chain((count > 0).then_some(to_mhz))
+        .collect()
+}
+
+fn excess_errors(start: &RampSample, now: &RampSample) -> Option<String> {
+    let errors = now.hardware_errors.saturating_sub(start.hardware_errors);
+    let nonces = now.nonces.saturating_sub(start.nonces);
+    let rate = errors as f64 / (errors + nonces).max(1) as f64;
+    (errors >= MIN_ERRORS && rate > MAX_ERROR_RATE)
+        .then(|| format!("{errors} hardware errors in {} nonces", errors + nonces))
+}
+
+fn low_nonce_rate(since: &RampSample, now: &RampSample) -> Option<String> {
+    if !(since.hashing && now.hashing) {
+        return None;
+    }
+    let expected = now.expected_nonce_rate? * VERIFY_WINDOW.as_secs_f64();
+    if expected < MIN_EXPECTED_NONCES {
+        return None;
+    }
+    let nonces = now.nonces.saturating_sub(since.nonces);
+    ((nonces as f64) < expected * MIN_NONCE_FRACTION)
+        .then(|| format!("{nonces} nonces where {expected:.0} were expected"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP_DELAY: Duration = Duration::from_millis(100);
+
+    fn hashing(nonces: u64, hardware_errors: u64) -> RampSample {
+        RampSample {
+            hashing: true,
+            nonces,
+            hardware_errors,
+            expected_nonce_rate: Some(2.0),
+        }
+    }
+
+    /// Poll until the ramp finishes, feeding it `sample(elapsed)`; returns
+    /// the frequencies stepped through and the final action.
+    fn run(
+        ramp: &mut FrequencyRamp,
+        start: Instant,
+        sample: impl Fn(Duration) -> RampSample,
+    ) -> (Vec<f32>, RampAction) {
+        let mut stepped = Vec::new();
+        for _ in 0..1000 {
+            let now = ramp.next_at();
+            match ramp.poll(now, sample(now - start)) {
+                RampAction::Step(mhz) => stepped.push(mhz),
+                RampAction::Wait | RampAction::Reached(_) => {}
+                done => return (stepped, done),
+            }
+        }
+        panic!("ramp did not finish");
+    }
+
+    #[test]
+    fn test_steps() {
+        assert_eq!(steps(500.0, 525.0, 6.25), [506.25, 512.5, 518.75, 525.0]);
+        assert_eq!(steps(525.0, 510.0, 6.25), [518.75, 512.5, 510.0]);
+        assert!(steps(525.0, 525.0, 6.25).is_empty());
+    }
+
+    #[test]
+    fn test_raise_verified() {
+        let start = Instant::now();
+        let mut ramp = FrequencyRamp::new(500.0, 525.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+
+        // Two nonces a second, as expected
+        let (stepped, done) = run(&mut ramp, start, |t| hashing(2 * t.as_secs(), 0));
+        assert_eq!(stepped, [506.25, 512.5, 518.75, 525.0]);
+        assert_eq!(done, RampAction::Done(525.0));
+
+        // Held for the verify window before counting as good
+        assert!(ramp.next_at() - start >= VERIFY_WINDOW);
+    }
+
+    #[test]
+    fn test_retarget_keeps_last_good() {
+        let start = Instant::now();
+        let mut ramp = FrequencyRamp::new(500.0, 525.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+        assert_eq!(ramp.poll(start, hashing(0, 0)), RampAction::Step(506.25));
+        assert_eq!(
+            ramp.poll(start + STEP_DELAY, hashing(0, 0)),
+            RampAction::Step(512.5)
+        );
+
+        // Heading lower than where it started needs no verifying
+        ramp.retarget(490.0, start + STEP_DELAY);
+        let (stepped, done) = run(&mut ramp, start, |_| hashing(0, 50));
+        assert_eq!(stepped, [506.25, 500.0, 493.75, 490.0]);
+        assert_eq!(done, RampAction::Done(490.0));
+
+        // A raise after an interrupted one is rolled back to the last good
+        let mut ramp = FrequencyRamp::new(500.0, 525.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+        ramp.poll(start, hashing(0, 0));
+        ramp.retarget(512.5, start);
+        let (_, done) = run(&mut ramp, start, |_| hashing(0, 50));
+        assert!(matches!(
+            done,
+            RampAction::RolledBack { frequency_mhz, .. } if frequency_mhz == 500.0
+        ));
+    }
+
+    #[test]
+    fn test_rolls_back_on_errors() {
+        let start = Instant::now();
+        let mut ramp = FrequencyRamp::new(500.0, 525.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+
+        // Errors appear after the step to 512.5 MHz
+        let (stepped, done) = run(&mut ramp, start, |t| {
+            let errors = if t >= STEP_DELAY * 2 { 5 } else { 0 };
+            hashing(10, errors)
+        });
+        assert_eq!(stepped, [506.25, 512.5, 506.25, 500.0]);
+        match done {
+            RampAction::RolledBack {
+                frequency_mhz,
+                reason,
+            } => {
+                assert_eq!(frequency_mhz, 500.0);
+                assert!(reason.contains("hardware errors"));
+            }
+            other => panic!("expected RolledBack, got {other:?}"),
+        }
+        assert_eq!(ramp.target_mhz(), 500.0);
+    }
+
+    #[test]
+    fn test_rolls_back_on_low_nonce_rate() {
+        let start = Instant::now();
+        let mut ramp = FrequencyRamp::new(500.0, 525.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+
+        // Nonces stop once at the new frequency
+        let (_, done) = run(&mut ramp, start, |_| hashing(3, 0));
+        assert!(matches!(
+            done,
+            RampAction::RolledBack { frequency_mhz, .. } if frequency_mhz == 500.0
+        ));
+
+        // Idle chips report nothing, which says nothing about the frequency
+        let mut ramp = FrequencyRamp::new(500.0, 525.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+        let (_, done) = run(&mut ramp, start, |_| RampSample::default());
+        assert_eq!(done, RampAction::Done(525.0));
+    }
+
+    #[test]
+    fn test_lowering_not_rolled_back() {
+        let start = Instant::now();
+        let mut ramp = FrequencyRamp::new(525.0, 500.0, 6.25, STEP_DELAY, start, hashing(0, 0));
+
+        let (stepped, done) = run(&mut ramp, start, |_| hashing(0, 50));
+        assert_eq!(stepped, [518.75, 512.5, 506.25, 500.0]);
+        assert_eq!(done, RampAction::Done(500.0));
+    }
+}
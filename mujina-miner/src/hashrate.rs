@@ -0,0 +1,255 @@
+//! Windowed hashrate estimation.
+//!
+//! Hashrate is measured from shares found at a known threshold difficulty:
+//! each share stands for `D_t * 2^32` expected hashes and shares arrive as a
+//! Poisson process (see the scheduler's `MiningStats` for the model). A
+//! lifetime average hides recent changes, so [`HashrateEstimator`] reports
+//! each of [`WINDOWS`] two ways:
+//!
+//! - **Sliding window**: work in the last window divided by its length.
+//!   Exact over the window, but jumps as shares enter and leave it.
+//! - **EWMA**: work decayed exponentially with the window as time constant.
+//!   Smoother, and reacts to a change without waiting for the window to
+//!   turn over.
+//!
+//! With few shares in a window either number can be far from the true rate,
+//! so each estimate carries a 95% confidence interval from the share count.
+//! The exact (Garwood) interval for a Poisson count uses chi-squared
+//! quantiles; the Wilson-Hilferty approximation used here is within a few
+//! percent of it even for a handful of shares.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::api_client::types::HashrateEstimate;
+
+/// Windows hashrate is estimated over.
+pub const WINDOWS: [Duration; 4] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+    Duration::from_secs(60 * 60),
+];
+
+/// Standard normal quantile for a two-sided 95% interval.
+const Z_95: f64 = 1.959_964;
+
+/// Hashrate estimates over [`WINDOWS`] from a stream of shares.
+#[derive(Debug, Clone)]
+pub struct HashrateEstimator {
+    /// Start of measurement; windows are shortened to this.
+    started: Instant,
+
+    /// Shares within the longest window: when, and hashes represented.
+    shares: VecDeque<(Instant, f64)>,
+
+    /// Exponentially decayed hash count per window, as of `decayed_at`.
+    decayed: [f64; WINDOWS.len()],
+    decayed_at: Instant,
+
+    /// Hashes represented by the latest share, to bound the rate when a
+    /// window holds none.
+    last_share_hashes: Option<f64>,
+}
+
+impl HashrateEstimator {
+    /// Start measuring at `now`.
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            shares: VecDeque::new(),
+            decayed: [0.0; WINDOWS.len()],
+            decayed_at: now,
+            last_share_hashes: None,
+        }
+    }
+
+    /// Record a share representing `hashes` expected hashes.
+    pub fn record(&mut self, now: Instant, hashes: f64) {
+        self.decay_to(now);
+        for decayed in &mut self.decayed {
+            *decayed += hashes;
+        }
+
+        self.shares.push_back((now, hashes));
+        self.last_share_hashes = Some(hashes);
+
+        let horizon = WINDOWS[WINDOWS.len() - 1];
+        while let Some(&(time, _)) = self.shares.front() {
+            if now.duration_since(time) <= horizon {
+                break;
+            }
+            self.shares.pop_front();
+        }
+    }
+
+    /// Estimates for each of [`WINDOWS`] as of `now`.
+    pub fn estimates(&self, now: Instant) -> Vec<HashrateEstimate> {
+        let age = now.saturating_duration_since(self.started).as_secs_f64();
+        let since_decay = now.saturating_duration_since(self.decayed_at).as_secs_f64();
+
+        WINDOWS
+            .iter()
+            .zip(self.decayed)
+            .map(|(window, decayed)| {
+                let tau = window.as_secs_f64();
+                let span = tau.min(age);
+                if span <= 0.0 {
+                    return HashrateEstimate {
+                        window_s: window.as_secs(),
+                        ..Default::default()
+                    };
+                }
+
+                let (shares, hashes) = self
+                    .shares
+                    .iter()
+                    .rev()
+                    .take_while(|(time, _)| now.saturating_duration_since(*time) <= *window)
+                    .fold((0u64, 0.0), |(n, sum), (_, hashes)| (n + 1, sum + hashes));
+
+                // Bias-correct the EWMA while it has seen less than a few
+                // time constants, else it would start out near zero
+                let weight = tau * (1.0 - (-age / tau).exp());
+                let ewma = decayed * (-since_decay / tau).exp() / weight;
+
+                let per_share = if shares > 0 {
+                    Some(hashes / shares as f64)
+                } else {
+                    self.last_share_hashes
+                };
+                let (lower, upper) = match per_share {
+                    Some(per_share) => {
+                        let (lower, upper) = poisson_interval(shares);
+                        (
+                            Some(lower * per_share / span),
+                            Some(upper * per_share / span),
+                        )
+                    }
+                    None => (None, None),
+                };
+
+                HashrateEstimate {
+                    window_s: window.as_secs(),
+                    hashrate: hashes / span,
+                    ewma,
+                    lower,
+                    upper,
+                    shares,
+                }
+            })
+            .collect()
+    }
+
+    fn decay_to(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.decayed_at).as_secs_f64();
+        for (decayed, window) in self.decayed.iter_mut().zip(WINDOWS) {
+            *decayed *= (-elapsed / window.as_secs_f64()).exp();
+        }
+        self.decayed_at = now;
+    }
+}
+
+/// Approximate 95% confidence interval for the mean of a Poisson variable
+/// observed as `n`.
+fn poisson_interval(n: u64) -> (f64, f64) {
+    let wilson_hilferty =
+        |k: f64, z: f64| k * (1.0 - 1.0 / (9.0 * k) + z / (3.0 * k.sqrt())).powi(3);
+
+    let n = n as f64;
+    let lower = if n == 0.0 {
+        0.0
+    } else {
+        wilson_hilferty(n, -Z_95)
+    };
+    (lower, wilson_hilferty(n + 1.0, Z_95))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASHES: f64 = 1e9;
+
+    #[test]
+    fn test_poisson_interval() {
+        // Exact Garwood intervals: 0 -> (0, 3.689), 10 -> (4.795, 18.390)
+        let (lower, upper) = poisson_interval(0);
+        assert_eq!(lower, 0.0);
+        assert!((upper - 3.689).abs() < 0.05, "{upper}");
+
+        let (lower, upper) = poisson_interval(10);
+        assert!((lower - 4.795).abs() < 0.05, "{lower}");
+        assert!((upper - 18.390).abs() < 0.05, "{upper}");
+    }
+
+    #[test]
+    fn test_steady_rate() {
+        let start = Instant::now();
+        let mut estimator = HashrateEstimator::new(start);
+
+        // One share per second for two hours
+        for s in 1..=7200 {
+            estimator.record(start + Duration::from_secs(s), HASHES);
+        }
+        let estimates = estimator.estimates(start + Duration::from_secs(7200));
+
+        for estimate in &estimates {
+            let window = estimate.window_s as f64;
+            assert!(
+                (estimate.hashrate - HASHES).abs() / HASHES < 0.02,
+                "{estimate:?}"
+            );
+            assert!(
+                (estimate.ewma - HASHES).abs() / HASHES < 0.02,
+                "{estimate:?}"
+            );
+            assert!((estimate.shares as f64 - window).abs() <= 1.0);
+            assert!(estimate.lower.unwrap() < HASHES && estimate.upper.unwrap() > HASHES);
+        }
+
+        // Longer windows are more certain
+        let width = |e: &HashrateEstimate| (e.upper.unwrap() - e.lower.unwrap()) / e.hashrate;
+        assert!(width(&estimates[0]) > width(&estimates[3]));
+    }
+
+    #[test]
+    fn test_short_windows_see_a_drop() {
+        let start = Instant::now();
+        let mut estimator = HashrateEstimator::new(start);
+
+        // One share per second for an hour, then one every ten seconds
+        for s in 1..=3600 {
+            estimator.record(start + Duration::from_secs(s), HASHES);
+        }
+        for s in 1..=30 {
+            estimator.record(start + Duration::from_secs(3600 + 10 * s), HASHES);
+        }
+        let estimates = estimator.estimates(start + Duration::from_secs(3900));
+
+        let one_minute = &estimates[0];
+        assert!((one_minute.hashrate - HASHES / 10.0).abs() / HASHES < 0.05);
+        assert!(one_minute.upper.unwrap() < HASHES);
+
+        let one_hour = &estimates[3];
+        assert!(one_hour.hashrate > HASHES * 0.8);
+        assert!(one_minute.ewma < one_hour.ewma);
+    }
+
+    #[test]
+    fn test_without_shares() {
+        let start = Instant::now();
+        let mut estimator = HashrateEstimator::new(start);
+        let estimates = estimator.estimates(start + Duration::from_secs(30));
+        assert_eq!(estimates.len(), WINDOWS.len());
+        assert_eq!(estimates[0].hashrate, 0.0);
+        assert_eq!(estimates[0].upper, None);
+
+        // Once the share size is known, no shares still bounds the rate
+        estimator.record(start + Duration::from_secs(30), HASHES);
+        let estimates = estimator.estimates(start + Duration::from_secs(120));
+        assert_eq!(estimates[0].shares, 0);
+        assert_eq!(estimates[0].lower, Some(0.0));
+        assert!(estimates[0].upper.unwrap() > 0.0);
+    }
+}
@@ -0,0 +1,698 @@
+//! Persistent statistics history.
+//!
+//! Samples the miner status periodically and keeps hashrate, share count,
+//! temperature and power at three resolutions: one-minute intervals for the
+//! last day, one-hour intervals for the last month and one-day intervals for
+//! the last year. The API serves these as series for charts.
+//!
+//! Each resolution is a fixed-size ring of records, optionally backed by a
+//! file of the same layout so history survives restarts. A record's slot is
+//! its interval number modulo the ring size, so the file needs no head
+//! pointer and a slot holding a record from an earlier lap is simply stale.
+//! Only changed slots are written, once a minute and on shutdown.
+//!
+//! File layout (little endian): a 16-byte header of magic `MJHS`, format
+//! version, interval length in seconds and slot count, followed by the slots
+//! as [`Record::SIZE`]-byte records.
+
+use parking_lot::Mutex;
+use std::{
+    fs::{File, OpenOptions},
+    os::unix::fs::FileExt,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::api_client::types::{HistoryMetric, HistoryPoint, HistoryResponse, MinerStatus};
+use crate::telemetry::Telemetry;
+
+/// How often the miner status is sampled.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+const MAGIC: &[u8; 4] = b"MJHS";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+
+/// One of the resolutions history is kept at.
+#[derive(Debug)]
+struct Resolution {
+    /// File name within the history directory.
+    file: &'static str,
+
+    /// Interval length in seconds.
+    secs: u64,
+
+    /// Number of intervals kept.
+    slots: usize,
+}
+
+const RESOLUTIONS: [Resolution; 3] = [
+    Resolution {
+        file: "history-1m.bin",
+        secs: 60,
+        slots: 24 * 60,
+    },
+    Resolution {
+        file: "history-1h.bin",
+        secs: 60 * 60,
+        slots: 30 * 24,
+    },
+    Resolution {
+        file: "history-1d.bin",
+        secs: 24 * 60 * 60,
+        slots: 366,
+    },
+];
+
+/// Aggregated statistics for one interval.
+///
+/// Unmeasured values are stored as NaN. An empty slot has no samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Record {
+    /// Start of the interval (Unix time, seconds).
+    start: u64,
+
+    /// Number of samples aggregated.
+    samples: u32,
+
+    /// Mean hashrate (H/s).
+    hashrate: f32,
+
+    /// Shares submitted.
+    shares: u32,
+
+    /// Mean hottest ASIC temperature (°C).
+    temperature: f32,
+
+    /// Mean power (W).
+    power: f32,
+}
+
+impl Record {
+    const SIZE: usize = 28;
+
+    const EMPTY: Record = Record {
+        start: 0,
+        samples: 0,
+        hashrate: 0.0,
+        shares: 0,
+        temperature: f32::NAN,
+        power: f32::NAN,
+    };
+
+    fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..8].copy_from_slice(&self.start.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.samples.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.hashrate.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.shares.to_le_bytes());
+        bytes[20..24].copy_from_slice(&self.temperature.to_le_bytes());
+        bytes[24..28].copy_from_slice(&self.power.to_le_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        Self {
+            start: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            samples: u32_at(8),
+            hashrate: f32::from_bits(u32_at(12)),
+            shares: u32_at(16),
+            temperature: f32::from_bits(u32_at(20)),
+            power: f32::from_bits(u32_at(24)),
+        }
+    }
+
+    fn value(&self, metric: HistoryMetric) -> Option<f64> {
+        let value = match metric {
+            HistoryMetric::Hashrate => self.hashrate as f64,
+            HistoryMetric::Shares => self.shares as f64,
+            HistoryMetric::Temperature => self.temperature as f64,
+            HistoryMetric::Power => self.power as f64,
+        };
+        (!value.is_nan()).then_some(value)
+    }
+}
+
+/// One reading of the miner status.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Sample {
+    hashrate: f64,
+
+    /// Shares submitted since the previous sample.
+    shares: u64,
+
+    temperature: Option<f32>,
+    power: Option<f32>,
+}
+
+/// Running mean of an optional reading.
+#[derive(Debug, Clone, Copy, Default)]
+struct Mean {
+    sum: f64,
+    count: u32,
+}
+
+impl Mean {
+    fn add(&mut self, value: Option<f32>) {
+        if let Some(value) = value {
+            self.sum += value as f64;
+            self.count += 1;
+        }
+    }
+
+    fn get(&self) -> f32 {
+        if self.count == 0 {
+            f32::NAN
+        } else {
+            (self.sum / self.count as f64) as f32
+        }
+    }
+
+    /// Rebuild from a stored mean, assuming every sample had a reading.
+    fn from_stored(mean: f32, samples: u32) -> Self {
+        if mean.is_nan() {
+            Self::default()
+        } else {
+            Self {
+                sum: mean as f64 * samples as f64,
+                count: samples,
+            }
+        }
+    }
+}
+
+/// The interval currently being aggregated.
+#[derive(Debug, Clone, Copy, Default)]
+struct Accumulator {
+    start: u64,
+    samples: u32,
+    hashrate: f64,
+    shares: u64,
+    temperature: Mean,
+    power: Mean,
+}
+
+impl Accumulator {
+    /// Continue an interval from its stored record.
+    fn resume(record: &Record) -> Self {
+        Self {
+            start: record.start,
+            samples: record.samples,
+            hashrate: record.hashrate as f64 * record.samples as f64,
+            shares: record.shares as u64,
+            temperature: Mean::from_stored(record.temperature, record.samples),
+            power: Mean::from_stored(record.power, record.samples),
+        }
+    }
+
+    fn add(&mut self, sample: &Sample) {
+        self.samples += 1;
+        self.hashrate += sample.hashrate;
+        self.shares += sample.shares;
+        self.temperature.add(sample.temperature);
+        self.power.add(sample.power);
+    }
+
+    fn record(&self) -> Record {
+        if self.samples == 0 {
+            return Record::EMPTY;
+        }
+        Record {
+            start: self.start,
+            samples: self.samples,
+            hashrate: (self.hashrate / self.samples as f64) as f32,
+            shares: self.shares.min(u32::MAX as u64) as u32,
+            temperature: self.temperature.get(),
+            power: self.power.get(),
+        }
+    }
+}
+
+/// History at one resolution.
+struct Ring {
+    resolution: &'static Resolution,
+    records: Vec<Record>,
+    current: Accumulator,
+    file: Option<Arc<File>>,
+
+    /// Slots stored since they were last written to `file`.
+    dirty: Vec<usize>,
+}
+
+impl Ring {
+    /// Ring kept in memory only.
+    fn new(resolution: &'static Resolution) -> Self {
+        Self {
+            resolution,
+            records: vec![Record::EMPTY; resolution.slots],
+            current: Accumulator::default(),
+            file: None,
+            dirty: Vec::new(),
+        }
+    }
+
+    /// Ring backed by a file in `dir`, loading what it already holds.
+    ///
+    /// A file with a different layout is started over.
+    fn open(resolution: &'static Resolution, dir: &Path) -> std::io::Result<Self> {
+        let mut ring = Self::new(resolution);
+        let path = dir.join(resolution.file);
+        let header = ring.header();
+
+        match std::fs::read(&path) {
+            Ok(contents) if contents.len() >= HEADER_SIZE && contents[..HEADER_SIZE] == header => {
+                for (slot, bytes) in contents[HEADER_SIZE..]
+                    .chunks_exact(Record::SIZE)
+                    .take(resolution.slots)
+                    .enumerate()
+                {
+                    ring.records[slot] = Record::decode(bytes);
+                }
+            }
+            Ok(_) => warn!(path = %path.display(), "Discarding history file with unknown layout."),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        file.set_len((HEADER_SIZE + resolution.slots * Record::SIZE) as u64)?;
+        file.write_all_at(&header, 0)?;
+        for (slot, record) in ring.records.iter().enumerate() {
+            file.write_all_at(&record.encode(), ring.offset(slot))?;
+        }
+        ring.file = Some(Arc::new(file));
+
+        Ok(ring)
+    }
+
+    fn header(&self) -> [u8; HEADER_SIZE] {
+        let mut header = [0u8; HEADER_SIZE];
+        header[0..4].copy_from_slice(MAGIC);
+        header[4..8].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header[8..12].copy_from_slice(&(self.resolution.secs as u32).to_le_bytes());
+        header[12..16].copy_from_slice(&(self.resolution.slots as u32).to_le_bytes());
+        header
+    }
+
+    fn offset(&self, slot: usize) -> u64 {
+        (HEADER_SIZE + slot * Record::SIZE) as u64
+    }
+
+    fn slot(&self, start: u64) -> usize {
+        (start / self.resolution.secs) as usize % self.resolution.slots
+    }
+
+    /// Add a sample taken at `time`, closing the previous interval if
+    /// `time` is past it.
+    fn add(&mut self, time: u64, sample: &Sample) {
+        let start = time - time % self.resolution.secs;
+        if start != self.current.start {
+            if self.current.samples > 0 {
+                self.store();
+            }
+            let stored = &self.records[self.slot(start)];
+            self.current = if stored.start == start && stored.samples > 0 {
+                Accumulator::resume(stored)
+            } else {
+                Accumulator {
+                    start,
+                    ..Default::default()
+                }
+            };
+        }
+        self.current.add(sample);
+    }
+
+    /// Store the current interval, marking its slot to be written to the
+    /// file.
+    fn store(&mut self) {
+        if self.current.samples == 0 {
+            return;
+        }
+        let record = self.current.record();
+        let slot = self.slot(record.start);
+        self.records[slot] = record;
+
+        if self.file.is_some() && !self.dirty.contains(&slot) {
+            self.dirty.push(slot);
+        }
+    }
+
+    /// Encoded dirty records with their file offsets, clearing them.
+    fn take_dirty(&mut self) -> Vec<(u64, [u8; Record::SIZE])> {
+        std::mem::take(&mut self.dirty)
+            .into_iter()
+            .map(|slot| (self.offset(slot), self.records[slot].encode()))
+            .collect()
+    }
+
+    /// Intervals overlapping `[since, now]`, including the one in progress.
+    fn points(&self, since: u64, metric: HistoryMetric) -> Vec<HistoryPoint> {
+        let current = self.current.record();
+        let mut records: Vec<&Record> = self
+            .records
+            .iter()
+            .filter(|r| r.samples > 0 && r.start + self.resolution.secs > since)
+            .filter(|r| current.samples == 0 || r.start != current.start)
+            .collect();
+        if current.samples > 0 {
+            records.push(&current);
+        }
+        records.sort_by_key(|r| r.start);
+
+        records
+            .into_iter()
+            .map(|r| HistoryPoint {
+                time: r.start,
+                value: r.value(metric),
+            })
+            .collect()
+    }
+}
+
+struct Inner {
+    rings: Vec<Ring>,
+
+    /// Submitted share counter at the previous sample.
+    last_shares: Option<u64>,
+}
+
+/// Statistics history at all resolutions.
+///
+/// Cheap to clone; clones share the same history.
+#[derive(Clone)]
+pub struct History {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl History {
+    /// History kept in memory only.
+    pub fn in_memory() -> Self {
+        Self::with_rings(RESOLUTIONS.iter().map(Ring::new).collect())
+    }
+
+    /// History persisted in `dir`, created if needed.
+    pub fn open(dir: &Path) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let rings = RESOLUTIONS
+            .iter()
+            .map(|resolution| Ring::open(resolution, dir))
+            .collect::<std::io::Result<_>>()?;
+        info!(dir = %dir.display(), "Statistics history opened.");
+        Ok(Self::with_rings(rings))
+    }
+
+    fn with_rings(rings: Vec<Ring>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                rings,
+                last_shares: None,
+            })),
+        }
+    }
+
+    /// Record a status sample taken at `time` (Unix seconds).
+    pub fn record(&self, time: u64, status: &MinerStatus) {
+        let mut inner = self.inner.lock();
+
+        // The counter restarts with the scheduler; count from zero then
+        let shares = match inner.last_shares {
+            Some(last) if status.shares_submitted >= last => status.shares_submitted - last,
+            Some(_) => status.shares_submitted,
+            None => 0,
+        };
+        inner.last_shares = Some(status.shares_submitted);
+
+        let sample = Sample {
+            hashrate: status.hashrate,
+            shares,
+            temperature: status
+                .boards
+                .iter()
+                .filter_map(|b| b.asic_temp_c)
+                .reduce(f32::max),
+            power: status
+                .boards
+                .iter()
+                .filter_map(|b| b.power_w)
+                .reduce(|a, b| a + b),
+        };
+
+        // Write through all resolutions once per minute, so a crash loses
+        // at most a minute of the longer intervals
+        let minute_done = inner.rings[0].current.start != time - time % RESOLUTIONS[0].secs;
+        for ring in &mut inner.rings {
+            ring.add(time, &sample);
+            if minute_done {
+                ring.store();
+            }
+        }
+    }
+
+    /// Write the records stored since the last write to their files.
+    ///
+    /// Blocks on file I/O; the lock is only held while the dirty records
+    /// are copied out, so queries aren't held up by the disk.
+    pub fn write_dirty(&self) {
+        let writes: Vec<_> = self
+            .inner
+            .lock()
+            .rings
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, ring)| {
+                let file = ring.file.clone()?;
+                Some((index, ring.resolution.file, file, ring.take_dirty()))
+            })
+            .collect();
+
+        for (index, name, file, records) in writes {
+            for (offset, bytes) in records {
+                if let Err(e) = file.write_all_at(&bytes, offset) {
+                    warn!(
+                        file = name,
+                        error = %e,
+                        "Failed to write history; keeping it in memory only."
+                    );
+                    self.inner.lock().rings[index].file = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Store and write the intervals in progress.
+    pub fn flush(&self) {
+        for ring in &mut self.inner.lock().rings {
+            ring.store();
+        }
+        self.write_dirty();
+    }
+
+    /// Series for `metric` covering the `range` before `now`.
+    ///
+    /// Uses the finest resolution that keeps the whole range.
+    pub fn query(&self, metric: HistoryMetric, range: Duration, now: u64) -> HistoryResponse {
+        let inner = self.inner.lock();
+        let range = range.as_secs();
+        let ring = inner
+            .rings
+            .iter()
+            .find(|r| r.resolution.secs * r.resolution.slots as u64 >= range)
+            .unwrap_or_else(|| inner.rings.last().unwrap());
+
+        HistoryResponse {
+            metric,
+            resolution_s: ring.resolution.secs,
+            points: ring.points(now.saturating_sub(range), metric),
+        }
+    }
+}
+
+/// Parse a range like `30m`, `24h`, `7d` or `52w`.
+pub fn parse_range(range: &str) -> Option<Duration> {
+    let unit = match range.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    let count: u64 = range[..range.len() - 1].parse().ok()?;
+    (count > 0).then(|| Duration::from_secs(count.saturating_mul(unit)))
+}
+
+/// Current Unix time in seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Sample the miner status into `history` until shutdown, then flush.
+pub async fn task(history: History, telemetry: Telemetry, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                history.record(unix_now(), &telemetry.status());
+                let history = history.clone();
+                if let Err(e) = tokio::task::spawn_blocking(move || history.write_dirty()).await {
+                    warn!(error = %e, "History write task failed.");
+                }
+            }
+            _ = shutdown.cancelled() => break,
+        }
+    }
+
+    if let Err(e) = tokio::task::spawn_blocking(move || history.flush()).await {
+        warn!(error = %e, "History flush task failed.");
+        return;
+    }
+    debug!("Statistics history flushed.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::types::BoardStatus;
+
+    fn status(hashrate: f64, shares: u64, temps: &[f32]) -> MinerStatus {
+        MinerStatus {
+            hashrate,
+            shares_submitted: shares,
+            boards: temps
+                .iter()
+                .map(|&t| BoardStatus {
+                    asic_temp_c: Some(t),
+                    power_w: Some(10.0),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("mujina-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = Record {
+            start: 1_700_000_040,
+            samples: 6,
+            hashrate: 1.2e12,
+            shares: 3,
+            temperature: 61.5,
+            power: f32::NAN,
+        };
+        let decoded = Record::decode(&record.encode());
+        assert_eq!(decoded.start, record.start);
+        assert_eq!(
+            decoded.value(HistoryMetric::Hashrate),
+            Some(1.2e12f32 as f64)
+        );
+        assert_eq!(decoded.value(HistoryMetric::Power), None);
+    }
+
+    #[test]
+    fn test_aggregates_intervals() {
+        let history = History::in_memory();
+        let t0 = 1_700_000_040; // minute boundary
+
+        history.record(t0, &status(100.0, 10, &[50.0, 60.0]));
+        history.record(t0 + 30, &status(200.0, 13, &[50.0, 70.0]));
+        history.record(t0 + 60, &status(300.0, 14, &[]));
+
+        let now = t0 + 61;
+        let hashrate = history.query(HistoryMetric::Hashrate, Duration::from_secs(3600), now);
+        assert_eq!(hashrate.resolution_s, 60);
+        let values: Vec<_> = hashrate.points.iter().map(|p| (p.time, p.value)).collect();
+        assert_eq!(values, [(t0, Some(150.0)), (t0 + 60, Some(300.0))]);
+
+        let shares = history.query(HistoryMetric::Shares, Duration::from_secs(3600), now);
+        assert_eq!(shares.points[0].value, Some(3.0));
+        assert_eq!(shares.points[1].value, Some(1.0));
+
+        let temperature = history.query(HistoryMetric::Temperature, Duration::from_secs(3600), now);
+        assert_eq!(temperature.points[0].value, Some(65.0));
+        assert_eq!(temperature.points[1].value, None);
+
+        let week = history.query(HistoryMetric::Power, Duration::from_secs(7 * 86400), now);
+        assert_eq!(week.resolution_s, 3600);
+        assert_eq!(week.points.len(), 1);
+        assert_eq!(week.points[0].value, Some(20.0));
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let dir = temp_dir("history");
+        let t0 = 1_700_000_040;
+
+        let history = History::open(&dir).unwrap();
+        history.record(t0, &status(100.0, 0, &[55.0]));
+        history.record(t0 + 10, &status(300.0, 0, &[55.0]));
+        history.flush();
+        drop(history);
+
+        // Resumes the hour in progress rather than starting it over
+        let history = History::open(&dir).unwrap();
+        history.record(t0 + 20, &status(500.0, 0, &[55.0]));
+        let day = history.query(HistoryMetric::Hashrate, Duration::from_secs(86400), t0 + 30);
+        assert_eq!(day.points[0].value, Some(300.0));
+        let week = history.query(
+            HistoryMetric::Hashrate,
+            Duration::from_secs(7 * 86400),
+            t0 + 30,
+        );
+        assert_eq!(week.points.len(), 1);
+        assert_eq!(week.points[0].value, Some(300.0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_writes_dirty_records() {
+        let dir = temp_dir("history-dirty");
+        let t0 = 1_700_000_040;
+
+        // Starting a new minute stores both; writing them needs no flush
+        let history = History::open(&dir).unwrap();
+        history.record(t0, &status(100.0, 0, &[55.0]));
+        history.record(t0 + 60, &status(300.0, 0, &[55.0]));
+        history.write_dirty();
+        drop(history);
+
+        let history = History::open(&dir).unwrap();
+        let day = history.query(HistoryMetric::Hashrate, Duration::from_secs(86400), t0 + 70);
+        let values: Vec<_> = day.points.iter().map(|p| p.value).collect();
+        assert_eq!(values, [Some(100.0), Some(300.0)]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_range("24h"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_range("7d"), Some(Duration::from_secs(7 * 86400)));
+        assert_eq!(parse_range("0h"), None);
+        assert_eq!(parse_range("h"), None);
+        assert_eq!(parse_range("10y"), None);
+    }
+}
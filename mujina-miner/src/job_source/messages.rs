@@ -138,6 +138,15 @@ pub enum SourceEvent {
     /// Scheduler should cancel all work from this source and wait for new job.
     /// Used during pool disconnection or when awaiting new block.
     ClearJobs,
+
+    /// A previously submitted share was accepted.
+    ShareAccepted,
+
+    /// A previously submitted share was rejected.
+    ShareRejected {
+        /// Rejection reason reported by the pool
+        reason: String,
+    },
 }
 
 /// Commands to sources (pull, coordinator-initiated).
@@ -217,10 +217,15 @@ impl StratumV1Source {
                         "Share accepted."
                     );
                 }
+
+                self.event_tx.send(SourceEvent::ShareAccepted).await?;
             }
 
             ClientEvent::ShareRejected { job_id, reason } => {
                 warn!(job_id = %job_id, reason = %reason, "Share rejected by pool");
+                self.event_tx
+                    .send(SourceEvent::ShareRejected { reason })
+                    .await?;
             }
 
             ClientEvent::Disconnected => {
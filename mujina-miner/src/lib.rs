@@ -1,3 +1,4 @@
+pub mod alerts;
 pub mod api;
 pub mod api_client;
 pub mod asic;
@@ -7,14 +8,20 @@ pub mod config;
 pub mod daemon;
 pub mod error;
 pub mod hash_thread;
+pub mod hashrate;
+pub mod history;
 pub mod hw_trait;
 pub mod job_generator;
 pub mod job_source;
 pub mod mgmt_protocol;
 pub mod peripheral;
+pub mod pid_file;
 pub mod pool;
+pub mod schedule;
 pub mod scheduler;
 pub mod stratum_v1;
+pub mod systemd;
+pub mod telemetry;
 pub mod tracing;
 pub mod transport;
 pub mod types;
@@ -0,0 +1,163 @@
+//! PID file with stale-lock detection.
+//!
+//! The file holds the daemon's process ID and is kept under an exclusive
+//! `flock` for as long as the daemon runs. The kernel drops the lock when the
+//! process exits, however it exits, so a file that exists but is not locked
+//! was left behind by a daemon that died without cleaning up and is taken
+//! over. A locked file means another daemon is running.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use nix::fcntl::{Flock, FlockArg};
+use thiserror::Error;
+
+use crate::tracing::prelude::*;
+
+/// Errors from acquiring a PID file.
+#[derive(Error, Debug)]
+pub enum PidFileError {
+    /// Another process holds the lock
+    #[error("already running (PID {}) according to {}", pid.map_or("unknown".into(), |p| p.to_string()), path.display())]
+    AlreadyRunning { path: PathBuf, pid: Option<u32> },
+
+    /// The file could not be created, locked or written
+    #[error("PID file {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Locked PID file, removed when dropped.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+    // Held for the lifetime of the daemon; unlocked on drop
+    _lock: Flock<File>,
+}
+
+impl PidFile {
+    /// Create or take over the PID file at `path` and write our PID to it.
+    pub fn acquire(path: &Path) -> Result<Self, PidFileError> {
+        let io_error = |source| PidFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+
+        // Not truncated on open: if another daemon holds the lock, its PID
+        // is still needed for the error
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(io_error)?;
+
+        let mut lock = match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+            Ok(lock) => lock,
+            Err((mut file, nix::errno::Errno::EWOULDBLOCK)) => {
+                return Err(PidFileError::AlreadyRunning {
+                    path: path.to_path_buf(),
+                    pid: read_pid(&mut file),
+                });
+            }
+            Err((_, errno)) => return Err(io_error(errno.into())),
+        };
+
+        if let Some(stale) = read_pid(&mut lock) {
+            warn!(
+                path = %path.display(),
+                pid = stale,
+                "Taking over stale PID file."
+            );
+        }
+
+        let pid = std::process::id();
+        lock.set_len(0).map_err(io_error)?;
+        lock.rewind().map_err(io_error)?;
+        writeln!(lock, "{pid}").map_err(io_error)?;
+        lock.sync_all().map_err(io_error)?;
+        debug!(path = %path.display(), pid, "Wrote PID file.");
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            _lock: lock,
+        })
+    }
+
+    /// Location of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Removed while still locked so a starting daemon never sees our
+        // PID in an unlocked file
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!(path = %self.path.display(), error = %e, "Failed to remove PID file.");
+        }
+    }
+}
+
+/// PID recorded in `file`, if it holds one.
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mujina-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("run").join("minerd.pid")
+    }
+
+    #[test]
+    fn test_second_acquire_fails_while_held() {
+        let path = temp_path("pid-held");
+
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{}\n", std::process::id())
+        );
+
+        // flock locks belong to the open file, so a second open in this
+        // process conflicts just like another process would
+        match PidFile::acquire(&path) {
+            Err(PidFileError::AlreadyRunning { pid, .. }) => {
+                assert_eq!(pid, Some(std::process::id()))
+            }
+            other => panic!("expected AlreadyRunning, got {other:?}"),
+        }
+
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_takes_over_stale_file() {
+        let path = temp_path("pid-stale");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "4194305123\n").unwrap();
+
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(pid_file.path()).unwrap(),
+            format!("{}\n", std::process::id())
+        );
+    }
+}
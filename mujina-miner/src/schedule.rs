@@ -0,0 +1,1018 @@
+//! Scheduled operating modes.
+//!
+//! [`Schedule`] switches every board between operating modes (a performance
+//! profile, a power limit, or no hashing at all) at the times given by the
+//! configured [`ScheduleRule`]s, e.g. to run hard on cheap night-time
+//! electricity and idle through the evening peak. Each rule's start times
+//! are a [`Cron`] expression evaluated in the schedule's time zone, so they
+//! follow daylight saving changes. The mode in effect is that of the rule
+//! that started most recently; an override set through the API takes its
+//! place until it expires or is cleared.
+//!
+//! A demand-response curtailment takes precedence over both. It caps the
+//! miner's total input power until it expires, splitting the target between
+//! boards in proportion to what each drew when the curtailment started (or
+//! evenly, without readings), or stops hashing altogether. Boards' power
+//! governors move straight to an operating point predicted to fit, so load
+//! is shed within a reading or two; when the curtailment expires the
+//! scheduled mode returns.
+//!
+//! The [`task`] applies the mode through the same commands the API uses:
+//! profiles and power limits go to each board via the backplane, and idling
+//! pauses the scheduler. Boards connected later get the mode in effect. The
+//! curtailment in effect and the power achieved are published in
+//! [`MinerStatus::curtailment`].
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use jiff::{
+    civil::{self, Date, DateTime},
+    tz::TimeZone,
+    Timestamp,
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+use crate::api::send_command;
+use crate::api_client::types::{
+    BoardStatus, CurtailRequest, CurtailmentStatus, MinerStatus, OperatingMode, ScheduleOverride,
+    ScheduleStatus, SetScheduleOverrideRequest,
+};
+use crate::backplane::BackplaneCommand;
+use crate::board::power::lowest_limit;
+use crate::config::{ScheduleConfig, ScheduleRule};
+use crate::error::CommandError;
+use crate::history::unix_now;
+use crate::scheduler::SchedulerCommand;
+use crate::telemetry::Telemetry;
+use crate::tracing::prelude::*;
+
+/// Interval at which the mode in effect is re-evaluated and applied, as
+/// often as boards report power.
+const EVALUATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How far to search for a rule's previous or next start, long enough to
+/// find one on February 29th.
+const SEARCH_DAYS: u32 = 4 * 366;
+
+/// Day-of-week names, Sunday first as in cron.
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Month names, January first.
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Five-field cron expression: minute, hour, day of month, month and day of
+/// week.
+///
+/// Fields take `*`, numbers, ranges (`1-5`), steps (`*/15`, `8-18/2`) and
+/// comma-separated lists of those; months and days of week also take
+/// three-letter English names. Day of week counts from Sunday as 0 (or 7).
+/// As in cron, when both day of month and day of week are restricted, a day
+/// matching either one matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cron {
+    /// Bit per minute, 0-59
+    minutes: u64,
+    /// Bit per hour, 0-23
+    hours: u64,
+    /// Bit per day of month, 1-31
+    days: u64,
+    /// Bit per month, 1-12
+    months: u64,
+    /// Bit per day of week, Sunday 0
+    weekdays: u64,
+    /// Both day fields are restricted, so either one matching will do
+    either_day: bool,
+}
+
+impl Cron {
+    /// Parse `expression`, describing what is wrong with it on failure.
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), found {}",
+                fields.len()
+            ));
+        };
+
+        let mut weekdays = parse_field(weekday, "day of week", 0, 7, &WEEKDAY_NAMES)?;
+        // Both 0 and 7 are Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59, &[])?,
+            hours: parse_field(hour, "hour", 0, 23, &[])?,
+            days: parse_field(day, "day of month", 1, 31, &[])?,
+            months: parse_field(month, "month", 1, 12, &MONTH_NAMES)?,
+            weekdays,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+
+    /// Whether the expression matches some time on `date`.
+    fn matches_date(&self, date: Date) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().to_sunday_zero_offset());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// Latest matching time of day at or before `bound`, or in the whole day
+    /// without one.
+    fn last_time(&self, bound: Option<(i8, i8)>) -> Option<civil::Time> {
+        let (max_hour, max_minute) = bound.unwrap_or((23, 59));
+        (0..=max_hour)
+            .rev()
+            .filter(|&hour| has(self.hours, hour))
+            .find_map(|hour| {
+                let max_minute = if hour == max_hour { max_minute } else { 59 };
+                (0..=max_minute)
+                    .rev()
+                    .find(|&minute| has(self.minutes, minute))
+                    .map(|minute| civil::time(hour, minute, 0, 0))
+            })
+    }
+
+    /// Earliest matching time of day after `bound`, or in the whole day
+    /// without one.
+    fn first_time(&self, bound: Option<(i8, i8)>) -> Option<civil::Time> {
+        (0..=23)
+            .filter(|&hour| has(self.hours, hour))
+            .find_map(|hour| {
+                let min_minute = match bound {
+                    Some((h, _)) if hour < h => return None,
+                    Some((h, m)) if hour == h => m + 1,
+                    _ => 0,
+                };
+                (min_minute..=59)
+                    .find(|&minute| has(self.minutes, minute))
+                    .map(|minute| civil::time(hour, minute, 0, 0))
+            })
+    }
+
+    /// Most recent matching minute at or before `now`.
+    pub fn last_at_or_before(&self, now: DateTime) -> Option<DateTime> {
+        let mut date = now.date();
+        for day in 0..=SEARCH_DAYS {
+            if self.matches_date(date) {
+                let bound = (day == 0).then(|| (now.hour(), now.minute()));
+                if let Some(time) = self.last_time(bound) {
+                    return Some(date.to_datetime(time));
+                }
+            }
+            date = date.yesterday().ok()?;
+        }
+        None
+    }
+
+    /// First matching minute after `now`.
+    pub fn next_after(&self, now: DateTime) -> Option<DateTime> {
+        let mut date = now.date();
+        for day in 0..=SEARCH_DAYS {
+            if self.matches_date(date) {
+                let bound = (day == 0).then(|| (now.hour(), now.minute()));
+                if let Some(time) = self.first_time(bound) {
+                    return Some(date.to_datetime(time));
+                }
+            }
+            date = date.tomorrow().ok()?;
+        }
+        None
+    }
+}
+
+/// Whether bit `value` is set in `bits`.
+fn has(bits: u64, value: i8) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// Parse one cron field into a bit per allowed value between `min` and
+/// `max`. `names` spell the values from `min` up.
+fn parse_field(field: &str, what: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let value = match names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            Some(index) => min + index as u32,
+            None => text
+                .parse()
+                .map_err(|_| format!("invalid {what} {text:?}"))?,
+        };
+        if !(min..=max).contains(&value) {
+            return Err(format!("{what} {value} out of range {min}-{max}"));
+        }
+        Ok(value)
+    };
+
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("invalid step {step:?} in {what}")),
+            },
+            None => (part, None),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (value(first)?, value(last)?),
+            // `5/15` runs from 5 to the end of the range
+            None if step.is_some() => (value(range)?, max),
+            None => {
+                let value = value(range)?;
+                (value, value)
+            }
+        };
+        if first > last {
+            return Err(format!("{what} range {range:?} runs backwards"));
+        }
+        for value in (first..=last).step_by(step.unwrap_or(1) as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// Demand-response curtailment of the miner's input power.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Curtailment {
+    /// Total input power to stay under in watts, `None` to stop hashing
+    pub target_w: Option<f32>,
+    /// When it ends, Unix time in seconds
+    pub until: u64,
+}
+
+/// Rules, the time zone they are evaluated in, any override and any
+/// curtailment.
+pub struct Schedule {
+    timezone: TimeZone,
+    rules: Vec<(ScheduleRule, Cron)>,
+    active_override: Option<ScheduleOverride>,
+    curtailment: Option<Curtailment>,
+}
+
+impl Schedule {
+    /// Schedule from a validated configuration.
+    ///
+    /// Rules whose cron expression does not parse are left out.
+    pub fn new(config: &ScheduleConfig) -> Self {
+        let timezone = match &config.timezone {
+            Some(name) => TimeZone::get(name).unwrap_or_else(|e| {
+                warn!(timezone = %name, error = %e, "Unknown time zone; using UTC.");
+                TimeZone::UTC
+            }),
+            None => TimeZone::system(),
+        };
+        Self::with_timezone(config, timezone)
+    }
+
+    fn with_timezone(config: &ScheduleConfig, timezone: TimeZone) -> Self {
+        let rules = config
+            .rules
+            .iter()
+            .filter_map(|rule| match Cron::parse(&rule.cron) {
+                Ok(cron) => Some((rule.clone(), cron)),
+                Err(e) => {
+                    warn!(rule = %rule.name, error = %e, "Ignoring schedule rule.");
+                    None
+                }
+            })
+            .collect();
+        Self {
+            timezone,
+            rules,
+            active_override: None,
+            curtailment: None,
+        }
+    }
+
+    /// Local time at Unix time `now`.
+    fn local(&self, now: u64) -> DateTime {
+        let now = Timestamp::from_second(now as i64).unwrap_or(Timestamp::UNIX_EPOCH);
+        now.to_zoned(self.timezone.clone()).datetime()
+    }
+
+    /// Unix time of local time `time`.
+    fn unix(&self, time: DateTime) -> Option<u64> {
+        let timestamp = self.timezone.to_timestamp(time).ok()?;
+        u64::try_from(timestamp.as_second()).ok()
+    }
+
+    /// Rule that started most recently at Unix time `now`.
+    pub fn active_rule(&self, now: u64) -> Option<&ScheduleRule> {
+        let now = self.local(now);
+        // On a tie the later rule wins, and max_by_key keeps the last
+        self.rules
+            .iter()
+            .filter_map(|(rule, cron)| Some((rule, cron.last_at_or_before(now)?)))
+            .max_by_key(|(_, start)| *start)
+            .map(|(rule, _)| rule)
+    }
+
+    /// Rule that starts next after Unix time `now`, and when.
+    pub fn next_rule(&self, now: u64) -> Option<(&ScheduleRule, u64)> {
+        let local = self.local(now);
+        // On a tie the later rule wins, so search from the end
+        let (rule, start) = self
+            .rules
+            .iter()
+            .rev()
+            .filter_map(|(rule, cron)| Some((rule, cron.next_after(local)?)))
+            .min_by_key(|(_, start)| *start)?;
+        Some((rule, self.unix(start)?))
+    }
+
+    /// Run `mode` instead of the scheduled one from Unix time `now`, for
+    /// `duration_s` seconds or until the next rule starts.
+    pub fn set_override(&mut self, mode: OperatingMode, duration_s: Option<u64>, now: u64) {
+        let until = match duration_s {
+            Some(duration_s) => Some(now + duration_s),
+            None => self.next_rule(now).map(|(_, start)| start),
+        };
+        self.active_override = Some(ScheduleOverride { mode, until });
+    }
+
+    /// Return to the scheduled mode.
+    pub fn clear_override(&mut self) -> Option<ScheduleOverride> {
+        self.active_override.take()
+    }
+
+    /// Mode in effect at Unix time `now`, dropping an expired override.
+    /// `None` when neither a rule nor an override applies.
+    pub fn mode(&mut self, now: u64) -> Option<OperatingMode> {
+        if let Some(ScheduleOverride {
+            until: Some(until), ..
+        }) = self.active_override
+        {
+            if now >= until {
+                info!("Schedule override expired.");
+                self.active_override = None;
+            }
+        }
+        match self.active_override {
+            Some(active) => Some(active.mode),
+            None => self.active_rule(now).map(ScheduleRule::mode),
+        }
+    }
+
+    /// Curtail power as `request`ed from Unix time `now`, replacing any
+    /// curtailment in effect.
+    pub fn curtail(
+        &mut self,
+        request: CurtailRequest,
+        now: u64,
+    ) -> Result<Curtailment, CommandError> {
+        let until = match (request.duration_s, request.until) {
+            (Some(duration_s), None) => now + duration_s,
+            (None, Some(until)) => until,
+            _ => {
+                return Err(CommandError::Invalid(
+                    "give exactly one of duration_s and until".into(),
+                ))
+            }
+        };
+        if until <= now {
+            return Err(CommandError::Invalid(
+                "curtailment must end in the future".into(),
+            ));
+        }
+        if matches!(request.target_w, Some(target_w) if target_w <= 0.0) {
+            return Err(CommandError::Invalid("target_w must be positive".into()));
+        }
+
+        let curtailment = Curtailment {
+            target_w: request.target_w,
+            until,
+        };
+        self.curtailment = Some(curtailment);
+        Ok(curtailment)
+    }
+
+    /// End the curtailment early.
+    pub fn end_curtailment(&mut self) -> Option<Curtailment> {
+        self.curtailment.take()
+    }
+
+    /// Curtailment in effect at Unix time `now`, dropping an expired one.
+    pub fn curtailment(&mut self, now: u64) -> Option<Curtailment> {
+        if matches!(self.curtailment, Some(c) if now >= c.until) {
+            info!("Curtailment expired; resuming.");
+            self.curtailment = None;
+        }
+        self.curtailment
+    }
+
+    /// Status at Unix time `now`.
+    pub fn status(&mut self, now: u64) -> ScheduleStatus {
+        let mode = self.mode(now);
+        let next = self.next_rule(now);
+        ScheduleStatus {
+            timezone: self.timezone.iana_name().unwrap_or("local").to_string(),
+            rules: self.rules.iter().map(|(rule, _)| rule.clone()).collect(),
+            active_rule: self.active_rule(now).map(|rule| rule.name.clone()),
+            next_rule: next.map(|(rule, _)| rule.name.clone()),
+            next_change: next.map(|(_, start)| start),
+            active_override: self.active_override,
+            mode,
+        }
+    }
+}
+
+/// Commands for viewing and overriding the schedule at runtime.
+///
+/// Sent by the API server; each carries a oneshot channel for the result.
+pub enum ScheduleCommand {
+    /// Report the schedule and the mode in effect.
+    Status {
+        response: oneshot::Sender<ScheduleStatus>,
+    },
+
+    /// Run a mode in place of the scheduled one for a while.
+    SetOverride {
+        request: SetScheduleOverrideRequest,
+        response: oneshot::Sender<Result<ScheduleStatus, CommandError>>,
+    },
+
+    /// Return to the scheduled mode.
+    ClearOverride {
+        response: oneshot::Sender<Result<(), CommandError>>,
+    },
+
+    /// Curtail power for a demand-response event.
+    Curtail {
+        request: CurtailRequest,
+        response: oneshot::Sender<Result<CurtailmentStatus, CommandError>>,
+    },
+
+    /// End the curtailment early.
+    EndCurtailment {
+        response: oneshot::Sender<Result<(), CommandError>>,
+    },
+}
+
+/// What the task last applied.
+#[derive(Default)]
+struct Applied {
+    /// Mode boards were put in, `None` if the schedule left them alone
+    mode: Option<OperatingMode>,
+    /// Curtailment the mode was limited by
+    curtailment: Option<Curtailment>,
+    /// Each board's share of the curtailment target, in watts
+    shares: HashMap<String, f32>,
+    /// Boards that accepted the mode, with the power limit they were given
+    boards: HashMap<String, Option<f32>>,
+    /// Whether the schedule paused mining
+    paused: bool,
+}
+
+/// Apply the scheduled mode until shutdown, answering commands.
+///
+/// `power_limit` is the configured `hardware.power_limit`, restored when a
+/// mode does not set its own.
+pub async fn task(
+    config: ScheduleConfig,
+    power_limit: Option<f32>,
+    telemetry: Telemetry,
+    scheduler: mpsc::Sender<SchedulerCommand>,
+    backplane: mpsc::Sender<BackplaneCommand>,
+    mut commands: mpsc::Receiver<ScheduleCommand>,
+    shutdown: CancellationToken,
+) {
+    let mut schedule = Schedule::new(&config);
+    let mut applied = Applied::default();
+    let mut interval = tokio::time::interval(EVALUATION_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+            Some(command) = commands.recv() => match command {
+                ScheduleCommand::Status { response } => {
+                    let _ = response.send(schedule.status(unix_now()));
+                    continue;
+                }
+                ScheduleCommand::SetOverride { request, response } => {
+                    if matches!(request.mode.power_limit, Some(limit) if limit <= 0.0) {
+                        let _ = response.send(Err(CommandError::Invalid(
+                            "power_limit must be positive".into(),
+                        )));
+                        continue;
+                    }
+                    let now = unix_now();
+                    schedule.set_override(request.mode, request.duration_s, now);
+                    info!(mode = ?request.mode, "Schedule overridden.");
+                    let _ = response.send(Ok(schedule.status(now)));
+                }
+                ScheduleCommand::ClearOverride { response } => {
+                    let result = match schedule.clear_override() {
+                        Some(_) => {
+                            info!("Schedule override cleared.");
+                            Ok(())
+                        }
+                        None => Err(CommandError::NotFound("schedule override".into())),
+                    };
+                    let _ = response.send(result);
+                }
+                ScheduleCommand::Curtail { request, response } => {
+                    let result = schedule.curtail(request, unix_now()).map(|curtailment| {
+                        info!(
+                            target_w = curtailment.target_w,
+                            until = curtailment.until,
+                            "Curtailing power."
+                        );
+                        curtailment_status(curtailment, &telemetry.status())
+                    });
+                    let _ = response.send(result);
+                }
+                ScheduleCommand::EndCurtailment { response } => {
+                    let result = match schedule.end_curtailment() {
+                        Some(_) => {
+                            info!("Curtailment ended; resuming.");
+                            Ok(())
+                        }
+                        None => Err(CommandError::NotFound("curtailment".into())),
+                    };
+                    let _ = response.send(result);
+                }
+            },
+        }
+
+        let now = unix_now();
+        let curtailment = schedule.curtailment(now);
+        apply(
+            schedule.mode(now),
+            curtailment,
+            &mut applied,
+            power_limit,
+            &telemetry,
+            &scheduler,
+            &backplane,
+        )
+        .await;
+
+        let status = curtailment.map(|c| curtailment_status(c, &telemetry.status()));
+        if telemetry.status().curtailment != status {
+            telemetry.update_status(|miner| miner.curtailment = status);
+        }
+    }
+}
+
+/// How well `curtailment` is met by the miner in `status`.
+fn curtailment_status(curtailment: Curtailment, status: &MinerStatus) -> CurtailmentStatus {
+    let readings: Vec<f32> = status.boards.iter().filter_map(|b| b.power_w).collect();
+    let power_w = (!readings.is_empty()).then(|| readings.iter().sum());
+    let met = match curtailment.target_w {
+        Some(target_w) => power_w.is_some_and(|power_w| power_w <= target_w),
+        None => status.paused,
+    };
+    CurtailmentStatus {
+        target_w: curtailment.target_w,
+        until: curtailment.until,
+        power_w,
+        met,
+    }
+}
+
+/// Split `target_w` between `boards` in proportion to their power readings,
+/// or evenly unless every board has one.
+fn power_shares(target_w: f32, boards: &[BoardStatus]) -> HashMap<String, f32> {
+    let readings: Option<Vec<f32>> = boards.iter().map(|b| b.power_w).collect();
+    let total_w: f32 = readings.iter().flatten().sum();
+    boards
+        .iter()
+        .enumerate()
+        .map(|(i, board)| {
+            let share = match &readings {
+                Some(readings) if total_w > 0.0 => target_w * readings[i] / total_w,
+                _ => target_w / boards.len() as f32,
+            };
+            (board.serial.clone(), share)
+        })
+        .collect()
+}
+
+/// Bring every board, and the scheduler, to `mode` within `curtailment`.
+///
+/// Without either, boards are returned to their configured operating point
+/// once and then left alone.
+async fn apply(
+    mode: Option<OperatingMode>,
+    curtailment: Option<Curtailment>,
+    applied: &mut Applied,
+    power_limit: Option<f32>,
+    telemetry: &Telemetry,
+    scheduler: &mpsc::Sender<SchedulerCommand>,
+    backplane: &mpsc::Sender<BackplaneCommand>,
+) {
+    let mode = mode.or(curtailment.map(|_| OperatingMode::default()));
+    let mut target = match (mode, applied.mode) {
+        (Some(mode), _) => mode,
+        (None, Some(_)) => OperatingMode::default(),
+        (None, None) => return,
+    };
+    if matches!(curtailment, Some(c) if c.target_w.is_none()) {
+        target.idle = true;
+    }
+    if Some(target) != applied.mode {
+        info!(
+            profile = target.profile.map(|p| p.as_str()),
+            power_limit = target.power_limit,
+            idle = target.idle,
+            "Switching operating mode."
+        );
+        applied.boards.clear();
+    }
+
+    let boards = telemetry.status().boards;
+    match curtailment.and_then(|c| c.target_w) {
+        Some(target_w) => {
+            let same_boards = boards.len() == applied.shares.len()
+                && boards
+                    .iter()
+                    .all(|b| applied.shares.contains_key(&b.serial));
+            if !same_boards || curtailment != applied.curtailment {
+                applied.shares = power_shares(target_w, &boards);
+            }
+        }
+        None => applied.shares.clear(),
+    }
+    applied.curtailment = curtailment;
+
+    applied
+        .boards
+        .retain(|serial, _| boards.iter().any(|b| &b.serial == serial));
+    for BoardStatus { serial, .. } in boards {
+        let limit = lowest_limit(
+            target.power_limit.or(power_limit),
+            applied.shares.get(&serial).copied(),
+        );
+        if applied.boards.get(&serial) == Some(&limit) {
+            continue;
+        }
+        let profile = send_command(backplane, "backplane", |response| {
+            BackplaneCommand::SetProfile {
+                serial: serial.clone(),
+                profile: target.profile,
+                response,
+            }
+        })
+        .await
+        .and_then(|result| result);
+        let power = send_command(backplane, "backplane", |response| {
+            BackplaneCommand::SetPowerLimit {
+                serial: serial.clone(),
+                limit_w: limit,
+                response,
+            }
+        })
+        .await
+        .and_then(|result| result);
+        // A board that refused either setting is retried next evaluation
+        let mut complete = true;
+        for (setting, result) in [("profile", profile), ("power limit", power)] {
+            if let Err(e) = result {
+                warn!(serial = %serial, setting, error = %e, "Cannot apply scheduled mode.");
+                complete = false;
+            }
+        }
+        if complete {
+            applied.boards.insert(serial, limit);
+        }
+    }
+
+    if target.idle != applied.paused {
+        let result = if target.idle {
+            send_command(scheduler, "scheduler", |response| SchedulerCommand::Pause {
+                response,
+            })
+            .await
+        } else {
+            send_command(scheduler, "scheduler", |response| {
+                SchedulerCommand::Resume { response }
+            })
+            .await
+        };
+        match result {
+            Ok(()) => applied.paused = target.idle,
+            Err(e) => warn!(error = %e, "Cannot apply scheduled mode."),
+        }
+    }
+
+    applied.mode = mode.map(|_| target);
+    if mode.is_none() {
+        applied.boards.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::types::PerformanceProfile;
+
+    fn rule(name: &str, cron: &str, profile: Option<PerformanceProfile>) -> ScheduleRule {
+        ScheduleRule {
+            name: name.into(),
+            cron: cron.into(),
+            profile,
+            power_limit: None,
+            idle: false,
+        }
+    }
+
+    fn schedule(rules: Vec<ScheduleRule>, timezone: &str) -> Schedule {
+        let config = ScheduleConfig {
+            timezone: Some(timezone.into()),
+            rules,
+        };
+        let timezone = TimeZone::get(timezone).unwrap();
+        Schedule::with_timezone(&config, timezone)
+    }
+
+    /// Unix time of local `time` in `timezone`.
+    fn at(timezone: &str, time: &str) -> u64 {
+        let time: DateTime = time.parse().unwrap();
+        let timezone = TimeZone::get(timezone).unwrap();
+        timezone.to_timestamp(time).unwrap().as_second() as u64
+    }
+
+    #[test]
+    fn test_parse_cron() {
+        let cron = Cron::parse("*/15 8-18/2 * jan,Jul mon-fri").unwrap();
+        assert_eq!(cron.minutes, 1 | 1 << 15 | 1 << 30 | 1 << 45);
+        assert_eq!(cron.hours, 0b101_0101_0101_0000_0000);
+        assert_eq!(cron.months, 1 << 1 | 1 << 7);
+        assert_eq!(cron.weekdays, 0b0111110);
+        assert!(!cron.either_day);
+
+        // Sunday is 0 or 7
+        assert_eq!(Cron::parse("0 0 * * 7").unwrap().weekdays, 1);
+        assert!(Cron::parse("0 0 1 * sun").unwrap().either_day);
+
+        assert!(Cron::parse("0 0 * *").is_err());
+        assert!(Cron::parse("0 24 * * *").is_err());
+        assert!(Cron::parse("0 0 0 * *").is_err());
+        assert!(Cron::parse("0 0 * * mon-sun").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("0 0 * foo *").is_err());
+    }
+
+    #[test]
+    fn test_cron_search() {
+        let cron = Cron::parse("30 22 * * mon-fri").unwrap();
+        let now: DateTime = "2026-10-18T12:00".parse().unwrap(); // Sunday
+
+        assert_eq!(
+            cron.last_at_or_before(now),
+            Some("2026-10-16T22:30".parse().unwrap())
+        );
+        assert_eq!(
+            cron.next_after(now),
+            Some("2026-10-19T22:30".parse().unwrap())
+        );
+
+        // A start at the current minute has happened, and is not next
+        let start: DateTime = "2026-10-19T22:30".parse().unwrap();
+        assert_eq!(cron.last_at_or_before(start), Some(start));
+        assert_eq!(
+            cron.next_after(start),
+            Some("2026-10-20T22:30".parse().unwrap())
+        );
+
+        // Day of month or day of week
+        let cron = Cron::parse("0 0 1 * fri").unwrap();
+        assert_eq!(
+            cron.next_after(now),
+            Some("2026-10-23T00:00".parse().unwrap())
+        );
+        assert_eq!(
+            cron.last_at_or_before(now),
+            Some("2026-10-16T00:00".parse().unwrap())
+        );
+
+        let leap = Cron::parse("0 12 29 feb *").unwrap();
+        assert_eq!(
+            leap.next_after(now),
+            Some("2028-02-29T12:00".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_active_and_next_rule() {
+        let schedule = schedule(
+            vec![
+                rule("night", "0 22 * * *", Some(PerformanceProfile::Turbo)),
+                rule("day", "0 7 * * *", Some(PerformanceProfile::Eco)),
+                ScheduleRule {
+                    idle: true,
+                    ..rule("peak", "0 17 * * mon-fri", None)
+                },
+            ],
+            "UTC",
+        );
+
+        let monday_noon = at("UTC", "2026-10-19T12:00");
+        assert_eq!(schedule.active_rule(monday_noon).unwrap().name, "day");
+        let (next, start) = schedule.next_rule(monday_noon).unwrap();
+        assert_eq!(next.name, "peak");
+        assert_eq!(start, at("UTC", "2026-10-19T17:00"));
+
+        // No peak on weekends
+        let sunday_evening = at("UTC", "2026-10-18T18:00");
+        assert_eq!(schedule.active_rule(sunday_evening).unwrap().name, "day");
+        let monday_night = at("UTC", "2026-10-19T03:00");
+        assert_eq!(schedule.active_rule(monday_night).unwrap().name, "night");
+    }
+
+    #[test]
+    fn test_rules_follow_time_zone() {
+        let schedule = schedule(
+            vec![
+                rule("night", "0 22 * * *", Some(PerformanceProfile::Turbo)),
+                rule("day", "0 7 * * *", Some(PerformanceProfile::Eco)),
+            ],
+            "Europe/Berlin",
+        );
+
+        // 22:00 in Berlin is 20:00 UTC in summer and 21:00 UTC in winter
+        let summer = at("UTC", "2026-07-01T20:30");
+        assert_eq!(schedule.active_rule(summer).unwrap().name, "night");
+        let winter = at("UTC", "2026-12-01T20:30");
+        assert_eq!(schedule.active_rule(winter).unwrap().name, "day");
+        assert_eq!(
+            schedule.next_rule(winter).unwrap().1,
+            at("Europe/Berlin", "2026-12-01T22:00")
+        );
+    }
+
+    #[test]
+    fn test_override() {
+        let mut schedule = schedule(
+            vec![
+                rule("night", "0 22 * * *", Some(PerformanceProfile::Turbo)),
+                rule("day", "0 7 * * *", Some(PerformanceProfile::Eco)),
+            ],
+            "UTC",
+        );
+        let noon = at("UTC", "2026-10-19T12:00");
+        let idle = OperatingMode {
+            idle: true,
+            ..Default::default()
+        };
+
+        // For a while
+        schedule.set_override(idle, Some(3600), noon);
+        assert_eq!(schedule.mode(noon + 60), Some(idle));
+        assert_eq!(
+            schedule.mode(noon + 3600).unwrap().profile,
+            Some(PerformanceProfile::Eco)
+        );
+        assert_eq!(schedule.status(noon).active_override, None);
+
+        // Until the next rule starts
+        schedule.set_override(idle, None, noon);
+        let status = schedule.status(noon);
+        assert_eq!(status.active_rule.as_deref(), Some("day"));
+        assert_eq!(status.mode, Some(idle));
+        assert_eq!(
+            status.active_override.unwrap().until,
+            Some(at("UTC", "2026-10-19T22:00"))
+        );
+        let night = at("UTC", "2026-10-19T22:00");
+        assert_eq!(
+            schedule.mode(night).unwrap().profile,
+            Some(PerformanceProfile::Turbo)
+        );
+
+        assert!(schedule.clear_override().is_none());
+    }
+
+    #[test]
+    fn test_no_rules_leave_boards_alone() {
+        let mut schedule = schedule(Vec::new(), "UTC");
+        let now = at("UTC", "2026-10-19T12:00");
+        assert_eq!(schedule.mode(now), None);
+
+        // An override without rules lasts until cleared
+        schedule.set_override(OperatingMode::default(), None, now);
+        assert_eq!(schedule.status(now).active_override.unwrap().until, None);
+        assert!(schedule.clear_override().is_some());
+        assert_eq!(schedule.mode(now), None);
+    }
+
+    #[test]
+    fn test_curtailment() {
+        let mut schedule = schedule(
+            vec![rule("day", "0 7 * * *", Some(PerformanceProfile::Eco))],
+            "UTC",
+        );
+        let now = at("UTC", "2026-10-19T12:00");
+        let request = |target_w, duration_s, until| CurtailRequest {
+            target_w,
+            duration_s,
+            until,
+        };
+
+        assert!(schedule
+            .curtail(request(Some(50.0), None, None), now)
+            .is_err());
+        assert!(schedule
+            .curtail(request(Some(50.0), Some(60), Some(now + 60)), now)
+            .is_err());
+        assert!(schedule
+            .curtail(request(None, None, Some(now)), now)
+            .is_err());
+        assert!(schedule
+            .curtail(request(Some(0.0), Some(60), None), now)
+            .is_err());
+        assert_eq!(schedule.curtailment(now), None);
+
+        let curtailment = schedule
+            .curtail(request(Some(50.0), Some(900), None), now)
+            .unwrap();
+        assert_eq!(curtailment.until, now + 900);
+        assert_eq!(schedule.curtailment(now + 899), Some(curtailment));
+
+        // Expires on its own, leaving the scheduled mode
+        assert_eq!(schedule.curtailment(now + 900), None);
+        assert_eq!(
+            schedule.mode(now + 900).unwrap().profile,
+            Some(PerformanceProfile::Eco)
+        );
+
+        schedule
+            .curtail(request(None, None, Some(now + 60)), now)
+            .unwrap();
+        assert!(schedule.end_curtailment().is_some());
+        assert!(schedule.end_curtailment().is_none());
+    }
+
+    #[test]
+    fn test_curtailment_status() {
+        let board = |serial: &str, power_w| BoardStatus {
+            serial: serial.into(),
+            power_w,
+            ..Default::default()
+        };
+        let mut status = MinerStatus {
+            boards: vec![board("a", Some(30.0)), board("b", Some(15.0))],
+            ..Default::default()
+        };
+        let curtailment = Curtailment {
+            target_w: Some(50.0),
+            until: 1000,
+        };
+
+        let report = curtailment_status(curtailment, &status);
+        assert_eq!(report.power_w, Some(45.0));
+        assert!(report.met);
+        status.boards[1].power_w = Some(25.0);
+        assert!(!curtailment_status(curtailment, &status).met);
+
+        // Full off is met once mining is paused
+        let off = Curtailment {
+            target_w: None,
+            until: 1000,
+        };
+        assert!(!curtailment_status(off, &status).met);
+        status.paused = true;
+        assert!(curtailment_status(off, &status).met);
+
+        status.boards.clear();
+        assert_eq!(curtailment_status(curtailment, &status).power_w, None);
+    }
+
+    #[test]
+    fn test_power_shares() {
+        let board = |serial: &str, power_w| BoardStatus {
+            serial: serial.into(),
+            power_w,
+            ..Default::default()
+        };
+
+        // In proportion to what each board draws
+        let shares = power_shares(60.0, &[board("a", Some(15.0)), board("b", Some(120.0))]);
+        assert!((shares["a"] - 60.0 * 15.0 / 135.0).abs() < 1e-3);
+        assert!((shares["b"] - 60.0 * 120.0 / 135.0).abs() < 1e-3);
+
+        // Evenly unless every board has a reading
+        let shares = power_shares(60.0, &[board("a", Some(15.0)), board("b", None)]);
+        assert_eq!(shares["a"], 30.0);
+        assert_eq!(shares["b"], 30.0);
+
+        assert!(power_shares(60.0, &[]).is_empty());
+    }
+}
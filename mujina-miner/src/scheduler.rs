@@ -30,15 +30,27 @@
 //! where it belongs.
 
 use slotmap::SlotMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::{StreamExt, StreamMap};
 use tokio_util::sync::CancellationToken;
 
-use crate::hash_thread::{task::HashTask, HashThread, HashThreadEvent};
-use crate::job_source::{JobTemplate, MerkleRootKind, SourceCommand, SourceEvent};
+use crate::api_client::types::{AddPoolRequest, MinerEvent, PoolStatus, ThreadStatus};
+use crate::error::CommandError;
+use crate::hash_thread::{
+    health,
+    task::{HashTask, Share},
+    HashThread, HashThreadEvent,
+};
+use crate::hashrate::HashrateEstimator;
+use crate::job_source::{
+    stratum_v1::StratumV1Source, JobTemplate, MerkleRootKind, SourceCommand, SourceEvent,
+};
+use crate::stratum_v1::PoolConfig as StratumPoolConfig;
+use crate::systemd::Heartbeat;
+use crate::telemetry::Telemetry;
 use crate::tracing::prelude::*;
 
 /// Unique identifier for a job source, assigned by the scheduler.
@@ -60,6 +72,9 @@ pub struct ActiveJob {
 
     /// Job template with block header fields
     pub template: JobTemplate,
+
+    /// When the job was handed to the threads, for job-to-share latency
+    pub assigned: std::time::Instant,
 }
 
 /// Registration message for adding a job source to the scheduler.
@@ -71,11 +86,65 @@ pub struct SourceRegistration {
     /// Source name for logging
     pub name: String,
 
+    /// Pool URL, if the source is a remote pool (for status reporting)
+    pub url: Option<String>,
+
     /// Event receiver for this source (UpdateJob, ReplaceJob, ClearJobs)
     pub event_rx: mpsc::Receiver<SourceEvent>,
 
     /// Command sender for this source (SubmitShare, etc.)
     pub command_tx: mpsc::Sender<SourceCommand>,
+
+    /// Cancelled by the scheduler to stop the source when it is removed
+    pub shutdown: CancellationToken,
+}
+
+/// Registration message for adding a board's hash threads to the scheduler.
+///
+/// The backplane sends one of these per board once the board's threads are
+/// created. Threads are named `<board>/<index>` in status reports.
+pub struct ThreadRegistration {
+    /// Serial number of the board the threads run on
+    pub board: String,
+
+    /// Hash threads created by the board
+    pub threads: Vec<Box<dyn HashThread>>,
+}
+
+/// Commands for controlling the scheduler at runtime.
+///
+/// Sent by the API server and the operating mode schedule; each carries a
+/// oneshot channel for the result.
+pub enum SchedulerCommand {
+    /// Idle all threads and stop assigning work.
+    Pause { response: oneshot::Sender<()> },
+
+    /// Resume assigning work from the active source.
+    Resume { response: oneshot::Sender<()> },
+
+    /// Start a Stratum v1 source and register it.
+    AddPool {
+        request: AddPoolRequest,
+        response: oneshot::Sender<Result<PoolStatus, CommandError>>,
+    },
+
+    /// Stop and remove a source by name.
+    RemovePool {
+        name: String,
+        response: oneshot::Sender<Result<(), CommandError>>,
+    },
+
+    /// Make the named source the one threads work for.
+    SwitchPool {
+        name: String,
+        response: oneshot::Sender<Result<(), CommandError>>,
+    },
+
+    /// Idle a board's threads until the board re-registers (e.g., restart).
+    IdleBoard {
+        serial: String,
+        response: oneshot::Sender<Result<(), CommandError>>,
+    },
 }
 
 /// Internal scheduler tracking for a registered source.
@@ -83,281 +152,126 @@ struct SourceEntry {
     /// Source name for logging
     name: String,
 
+    /// Pool URL, if any
+    url: Option<String>,
+
     /// Command channel for sending to this source
     command_tx: mpsc::Sender<SourceCommand>,
+
+    /// Stops the source when cancelled
+    shutdown: CancellationToken,
+
+    /// Most recent job, kept so work can be (re)assigned on switch/resume
+    current_job: Option<JobTemplate>,
+
+    /// Share difficulty of the most recent job
+    difficulty: Option<f64>,
+
+    shares_submitted: u64,
+    shares_accepted: u64,
+    shares_rejected: u64,
 }
 
-// TODO: Future enhancements for frequency ramping:
-// - Make ramp parameters configurable (step size, delay, target)
-// - Monitor chip temperature/errors during ramp
-// - Coordinate with board-level voltage regulators
-// - Implement adaptive ramping based on chip response
-// - Add rollback on errors during ramp
+/// Internal scheduler tracking for a registered hash thread.
+struct ThreadEntry {
+    thread: Box<dyn HashThread>,
 
-/// Run the scheduler task, receiving hash threads and job sources.
-pub async fn task(
-    running: CancellationToken,
-    mut thread_rx: mpsc::Receiver<Vec<Box<dyn HashThread>>>,
-    mut source_reg_rx: mpsc::Receiver<SourceRegistration>,
-) {
-    // Source storage and event multiplexing
-    let mut sources: SlotMap<SourceId, SourceEntry> = SlotMap::new();
-    let mut source_events: StreamMap<SourceId, ReceiverStream<SourceEvent>> = StreamMap::new();
+    /// Display name, `<board>/<index>`
+    name: String,
 
-    // Thread storage and event multiplexing
-    let mut threads: SlotMap<ThreadId, Box<dyn HashThread>> = SlotMap::new();
-    let mut thread_events: StreamMap<ThreadId, ReceiverStream<HashThreadEvent>> = StreamMap::new();
+    /// Serial number of the owning board
+    board: String,
 
-    // Track which job each thread is working on
-    let mut thread_assignments: HashMap<ThreadId, Arc<ActiveJob>> = HashMap::new();
+    /// When the thread was registered (start of its hashrate window)
+    registered_at: std::time::Instant,
 
-    // Wait for the first set of hash threads from the backplane
-    let initial_threads = match thread_rx.recv().await {
-        Some(threads) => threads,
-        None => return,
-    };
+    /// Hashes performed, estimated from shares (see [`MiningStats`])
+    hashes: u128,
 
-    if initial_threads.is_empty() {
-        error!("No hash threads received from backplane");
-        return;
-    }
+    /// Recent hashrate, from the same shares
+    estimator: HashrateEstimator,
+
+    /// Shares reported at the thread's share target
+    shares: u64,
 
-    debug!(
-        "Received {} hash thread(s) from backplane",
-        initial_threads.len()
-    );
+    /// Chips and core domains currently reported as underperforming
+    underperforming: HashSet<(u8, Option<u8>)>,
+}
 
-    // Insert threads into SlotMap and StreamMap
-    for mut thread in initial_threads {
-        let event_rx = thread
-            .take_event_receiver()
-            .expect("Thread missing event receiver");
+/// Interval at which the scheduler publishes its status to [`Telemetry`].
+const TELEMETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
 
-        let thread_id = threads.insert(thread);
-        thread_events.insert(thread_id, ReceiverStream::new(event_rx));
-        debug!(thread_id = ?thread_id, "Thread registered");
-    }
+/// Suggested starting difficulty for pools added at runtime.
+const SUGGESTED_DIFFICULTY: u64 = 1024;
+
+// TODO: Future enhancements for frequency ramping:
+// - Make ramp parameters configurable (step size, delay, target)
+// - Coordinate with board-level voltage regulators
+// - Implement adaptive ramping based on chip response
 
-    // Track mining statistics
-    let mut stats = MiningStats::default();
+/// Run the scheduler task, receiving hash threads, job sources, and commands.
+///
+/// `heartbeat` is beaten on every pass through the main loop, at least once
+/// per [`TELEMETRY_INTERVAL`], so a stuck scheduler can be detected.
+pub async fn task(
+    running: CancellationToken,
+    mut thread_rx: mpsc::Receiver<ThreadRegistration>,
+    mut source_reg_rx: mpsc::Receiver<SourceRegistration>,
+    mut command_rx: mpsc::Receiver<SchedulerCommand>,
+    telemetry: Telemetry,
+    heartbeat: Heartbeat,
+) {
+    let mut scheduler = Scheduler::new(running.clone(), telemetry);
 
     // Create interval for periodic status logging
     let mut status_interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
     status_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
     let mut first_tick = true;
 
-    debug!("Scheduler ready (awaiting job sources)");
+    // Create interval for publishing status to API consumers
+    let mut telemetry_interval = tokio::time::interval(TELEMETRY_INTERVAL);
+    telemetry_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    debug!("Scheduler ready (awaiting hash threads and job sources)");
 
     // Main scheduler loop
 
     while !running.is_cancelled() {
+        heartbeat.beat();
+
         tokio::select! {
+            // Hash thread registration
+            Some(registration) = thread_rx.recv() => {
+                scheduler.register_threads(registration).await;
+                scheduler.publish_status();
+            }
+
             // Source registration
             Some(registration) = source_reg_rx.recv() => {
-                let source_id = sources.insert(SourceEntry {
-                    name: registration.name.clone(),
-                    command_tx: registration.command_tx,
-                });
-                source_events.insert(source_id, ReceiverStream::new(registration.event_rx));
-                debug!(source_id = ?source_id, name = %registration.name, "Source registered");
+                scheduler.register_source(registration);
+                scheduler.publish_status();
             }
 
-            // Source events
-            Some((source_id, event)) = source_events.next() => {
-                let source = sources.get(source_id)
-                    .expect("StreamMap returned invalid source_id");
-
-                // TODO: Factor out common job assignment logic (EN2 extraction, ActiveJob
-                // creation, range splitting, work assignment) into helper function
-                match event {
-                    SourceEvent::UpdateJob(job_template) => {
-                        debug!(
-                            source = %source.name,
-                            job_id = %job_template.id,
-                            "UpdateJob received"
-                        );
-
-                        // Extract EN2 range (only supported for computed merkle roots)
-                        let full_en2_range = match &job_template.merkle_root {
-                            MerkleRootKind::Computed(template) => template.extranonce2_range.clone(),
-                            MerkleRootKind::Fixed(_) => {
-                                error!(job_id = %job_template.id, "Header-only jobs not supported");
-                                continue;
-                            }
-                        };
-
-                        // Create active job with source association
-                        let active_job = Arc::new(ActiveJob {
-                            source_id,
-                            template: job_template,
-                        });
-
-                        // Split EN2 range among all threads
-                        let en2_slices = full_en2_range.split(threads.len())
-                            .expect("Failed to split EN2 range among threads");
-
-                        // Assign work to all threads
-                        for ((thread_id, thread), en2_range) in threads.iter_mut().zip(en2_slices) {
-                            let starting_en2 = en2_range.iter().next();
-
-                            let task = HashTask {
-                                job: active_job.clone(),
-                                en2_range: Some(en2_range),
-                                en2: starting_en2,
-                                share_target: active_job.template.share_target,
-                                ntime: active_job.template.time,
-                            };
-
-                            if let Err(e) = thread.update_work(task).await {
-                                error!(thread_id = ?thread_id, error = %e, "Failed to assign work");
-                            } else {
-                                thread_assignments.insert(thread_id, active_job.clone());
-                            }
-                        }
-                    }
-
-                    SourceEvent::ReplaceJob(job_template) => {
-                        debug!(
-                            source = %source.name,
-                            job_id = %job_template.id,
-                            "ReplaceJob received"
-                        );
-
-                        // Extract EN2 range (only supported for computed merkle roots)
-                        let full_en2_range = match &job_template.merkle_root {
-                            MerkleRootKind::Computed(template) => template.extranonce2_range.clone(),
-                            MerkleRootKind::Fixed(_) => {
-                                error!(job_id = %job_template.id, "Header-only jobs not supported");
-                                continue;
-                            }
-                        };
-
-                        // Create active job with source association
-                        let active_job = Arc::new(ActiveJob {
-                            source_id,
-                            template: job_template,
-                        });
-
-                        // Split EN2 range among all threads
-                        let en2_slices = full_en2_range.split(threads.len())
-                            .expect("Failed to split EN2 range among threads");
-
-                        // Replace work on all threads (old shares invalid)
-                        for ((thread_id, thread), en2_range) in threads.iter_mut().zip(en2_slices) {
-                            let starting_en2 = en2_range.iter().next();
-
-                            let task = HashTask {
-                                job: active_job.clone(),
-                                en2_range: Some(en2_range),
-                                en2: starting_en2,
-                                share_target: active_job.template.share_target,
-                                ntime: active_job.template.time,
-                            };
-
-                            if let Err(e) = thread.replace_work(task).await {
-                                error!(thread_id = ?thread_id, error = %e, "Failed to replace work");
-                            } else {
-                                thread_assignments.insert(thread_id, active_job.clone());
-                            }
-                        }
-                    }
+            // Runtime control
+            Some(command) = command_rx.recv() => {
+                scheduler.handle_command(command).await;
+            }
 
-                    SourceEvent::ClearJobs => {
-                        debug!(source = %source.name, "ClearJobs received");
-
-                        let affected_threads: Vec<ThreadId> = thread_assignments
-                            .iter()
-                            .filter(|(_, job)| job.source_id == source_id)
-                            .map(|(tid, _)| *tid)
-                            .collect();
-
-                        for tid in affected_threads {
-                            if let Some(thread) = threads.get_mut(tid) {
-                                if let Err(e) = thread.go_idle().await {
-                                    error!(thread_id = ?tid, error = %e, "Failed to idle thread");
-                                }
-                            }
-                            thread_assignments.remove(&tid);
-                        }
-                    }
-                }
+            // Source events
+            Some((source_id, event)) = scheduler.source_events.next() => {
+                scheduler.handle_source_event(source_id, event).await;
             }
 
             // Thread events
-            Some((thread_id, event)) = thread_events.next() => {
-                match event {
-                    HashThreadEvent::ShareFound(share) => {
-                        debug!(
-                            thread_id = ?thread_id,
-                            job_id = %share.task.job.template.id,
-                            nonce = format!("{:#x}", share.nonce),
-                            hash = %share.hash,
-                            "Share found"
-                        );
-
-                        // Track hashes for hashrate measurement
-                        // Use threshold difficulty, not achieved difficulty (see MiningStats doc)
-                        let hashes = (share.threshold_difficulty * (u32::MAX as f64 + 1.0)) as u128;
-                        stats.total_hashes += hashes;
-
-                        // Check if share meets source threshold
-                        let source_id = share.task.job.source_id;
-                        let template = &share.task.job.template;
-
-                        if template.share_target.is_met_by(share.hash) {
-                            stats.shares_submitted += 1;
-
-                            // Submit share to originating source
-                            if let Some(source) = sources.get(source_id) {
-                                use crate::job_source::Share as SourceShare;
-                                let source_share = SourceShare {
-                                    job_id: template.id.clone(),
-                                    nonce: share.nonce,
-                                    time: share.ntime,
-                                    version: share.version,
-                                    extranonce2: share.extranonce2,
-                                };
-
-                                if let Err(e) = source.command_tx.send(SourceCommand::SubmitShare(source_share)).await {
-                                    error!(
-                                        source_id = ?source_id,
-                                        error = %e,
-                                        "Failed to submit share to source"
-                                    );
-                                } else {
-                                    debug!(source = %source.name, "Share submitted to source");
-                                }
-                            } else {
-                                error!(source_id = ?source_id, "Share for unknown source");
-                            }
-                        } else {
-                            trace!(
-                                thread_id = ?thread_id,
-                                nonce = format!("{:#x}", share.nonce),
-                                "Share below source threshold (not submitted)"
-                            );
-                        }
-                    }
-
-                    HashThreadEvent::WorkExhausted { en2_searched } => {
-                        info!(thread_id = ?thread_id, en2_searched, "Work exhausted");
-                        // TODO: Assign new work to this thread
-                    }
-
-                    HashThreadEvent::WorkDepletionWarning { estimated_remaining_ms } => {
-                        debug!(thread_id = ?thread_id, remaining_ms = estimated_remaining_ms, "Work depletion warning");
-                        // TODO: Prepare next work assignment
-                    }
+            Some((thread_id, event)) = scheduler.thread_events.next() => {
+                scheduler.handle_thread_event(thread_id, event).await;
+            }
 
-                    HashThreadEvent::StatusUpdate(status) => {
-                        trace!(
-                            thread_id = ?thread_id,
-                            hashrate_ghs = format!("{:.2}", status.hashrate / 1_000_000_000.0),
-                            active = status.is_active,
-                            "Thread status"
-                        );
-                    }
-                }
+            // Periodic status publication
+            _ = telemetry_interval.tick() => {
+                scheduler.prune_threads();
+                scheduler.check_chip_health();
+                scheduler.publish_status();
             }
 
             // Periodic status check
@@ -365,7 +279,7 @@ pub async fn task(
                 if first_tick {
                     first_tick = false;
                 } else {
-                    stats.log_summary();
+                    scheduler.stats.log_summary();
                 }
             }
 
@@ -378,11 +292,718 @@ pub async fn task(
     }
 
     // Log final statistics
-    stats.log_summary();
+    scheduler.stats.log_summary();
 
     debug!("Scheduler shutdown complete");
 }
 
+/// Scheduler state, owned by the scheduler task.
+struct Scheduler {
+    running: CancellationToken,
+    telemetry: Telemetry,
+
+    // Source storage and event multiplexing
+    sources: SlotMap<SourceId, SourceEntry>,
+    source_events: StreamMap<SourceId, ReceiverStream<SourceEvent>>,
+
+    /// Source whose jobs are assigned to threads
+    active_source: Option<SourceId>,
+
+    // Thread storage and event multiplexing
+    threads: SlotMap<ThreadId, ThreadEntry>,
+    thread_events: StreamMap<ThreadId, ReceiverStream<HashThreadEvent>>,
+
+    /// Which job each thread is working on
+    thread_assignments: HashMap<ThreadId, Arc<ActiveJob>>,
+
+    /// Boards whose threads are kept idle
+    idle_boards: HashSet<String>,
+
+    /// Whether work assignment is paused
+    paused: bool,
+
+    /// Counter for naming pools added without a name
+    next_pool_number: u32,
+
+    /// Recent hashrate of each board with registered threads
+    board_hashrate: HashMap<String, HashrateEstimator>,
+
+    stats: MiningStats,
+}
+
+impl Scheduler {
+    fn new(running: CancellationToken, telemetry: Telemetry) -> Self {
+        Self {
+            running,
+            telemetry,
+            sources: SlotMap::new(),
+            source_events: StreamMap::new(),
+            active_source: None,
+            threads: SlotMap::new(),
+            thread_events: StreamMap::new(),
+            thread_assignments: HashMap::new(),
+            idle_boards: HashSet::new(),
+            paused: false,
+            next_pool_number: 1,
+            board_hashrate: HashMap::new(),
+            stats: MiningStats::default(),
+        }
+    }
+
+    async fn register_threads(&mut self, registration: ThreadRegistration) {
+        debug!(
+            board = %registration.board,
+            count = registration.threads.len(),
+            "Received hash thread(s) from backplane"
+        );
+
+        // A board re-registering (e.g., after restart) starts out working
+        self.idle_boards.remove(&registration.board);
+        let now = std::time::Instant::now();
+        self.board_hashrate
+            .entry(registration.board.clone())
+            .or_insert_with(|| HashrateEstimator::new(now));
+
+        for (index, mut thread) in registration.threads.into_iter().enumerate() {
+            let Some(event_rx) = thread.take_event_receiver() else {
+                error!(board = %registration.board, index, "Thread missing event receiver");
+                continue;
+            };
+
+            let thread_id = self.threads.insert(ThreadEntry {
+                thread,
+                name: format!("{}/{}", registration.board, index),
+                board: registration.board.clone(),
+                registered_at: now,
+                hashes: 0,
+                estimator: HashrateEstimator::new(now),
+                shares: 0,
+                underperforming: HashSet::new(),
+            });
+            self.thread_events
+                .insert(thread_id, ReceiverStream::new(event_rx));
+            debug!(thread_id = ?thread_id, "Thread registered");
+        }
+
+        // Give the new threads a share of the current job
+        if let Some(source_id) = self.active_source {
+            self.assign_current_job(source_id, false).await;
+        }
+    }
+
+    fn register_source(&mut self, registration: SourceRegistration) -> SourceId {
+        let source_id = self.sources.insert(SourceEntry {
+            name: registration.name.clone(),
+            url: registration.url,
+            command_tx: registration.command_tx,
+            shutdown: registration.shutdown,
+            current_job: None,
+            difficulty: None,
+            shares_submitted: 0,
+            shares_accepted: 0,
+            shares_rejected: 0,
+        });
+        self.source_events
+            .insert(source_id, ReceiverStream::new(registration.event_rx));
+        debug!(source_id = ?source_id, name = %registration.name, "Source registered");
+
+        // The first source becomes active; later ones wait for a switch
+        if self.active_source.is_none() {
+            self.active_source = Some(source_id);
+        }
+
+        source_id
+    }
+
+    async fn handle_source_event(&mut self, source_id: SourceId, event: SourceEvent) {
+        let source = self
+            .sources
+            .get_mut(source_id)
+            .expect("StreamMap returned invalid source_id");
+
+        match event {
+            SourceEvent::UpdateJob(job_template) | SourceEvent::ReplaceJob(job_template)
+                if matches!(job_template.merkle_root, MerkleRootKind::Fixed(_)) =>
+            {
+                error!(job_id = %job_template.id, "Header-only jobs not supported");
+            }
+
+            SourceEvent::UpdateJob(job_template) => {
+                debug!(
+                    source = %source.name,
+                    job_id = %job_template.id,
+                    "UpdateJob received"
+                );
+                source.difficulty = Some(job_template.share_target.difficulty_float());
+                source.current_job = Some(job_template);
+                self.assign_current_job(source_id, false).await;
+            }
+
+            SourceEvent::ReplaceJob(job_template) => {
+                debug!(
+                    source = %source.name,
+                    job_id = %job_template.id,
+                    "ReplaceJob received"
+                );
+                source.difficulty = Some(job_template.share_target.difficulty_float());
+                source.current_job = Some(job_template);
+
+                // Old shares are invalid, so replace rather than update
+                self.assign_current_job(source_id, true).await;
+            }
+
+            SourceEvent::ClearJobs => {
+                debug!(source = %source.name, "ClearJobs received");
+                source.current_job = None;
+                self.idle_threads(|_, job| job.is_some_and(|job| job.source_id == source_id))
+                    .await;
+            }
+
+            SourceEvent::ShareAccepted => {
+                source.shares_accepted += 1;
+                self.telemetry.emit(MinerEvent::ShareAccepted {
+                    pool: source.name.clone(),
+                });
+            }
+
+            SourceEvent::ShareRejected { reason } => {
+                source.shares_rejected += 1;
+                self.telemetry.emit(MinerEvent::ShareRejected {
+                    pool: source.name.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    /// Split the source's current job among eligible threads and assign it.
+    ///
+    /// Does nothing if the source is not the active one, mining is paused, or
+    /// the source has no job. With `replace`, threads abandon their current
+    /// work (old shares become invalid); otherwise it is queued behind it.
+    async fn assign_current_job(&mut self, source_id: SourceId, replace: bool) {
+        if self.paused || self.active_source != Some(source_id) {
+            return;
+        }
+
+        let Some(job_template) = self
+            .sources
+            .get(source_id)
+            .and_then(|source| source.current_job.clone())
+        else {
+            return;
+        };
+
+        // Extract EN2 range (only supported for computed merkle roots)
+        let full_en2_range = match &job_template.merkle_root {
+            MerkleRootKind::Computed(template) => template.extranonce2_range.clone(),
+            MerkleRootKind::Fixed(_) => {
+                error!(job_id = %job_template.id, "Header-only jobs not supported");
+                return;
+            }
+        };
+
+        let eligible: Vec<ThreadId> = self
+            .threads
+            .iter()
+            .filter(|(_, entry)| !self.idle_boards.contains(&entry.board))
+            .map(|(id, _)| id)
+            .collect();
+
+        // Nothing to assign until a board registers threads
+        if eligible.is_empty() {
+            debug!(job_id = %job_template.id, "No hash threads; job not assigned");
+            return;
+        }
+
+        // Create active job with source association
+        let active_job = Arc::new(ActiveJob {
+            source_id,
+            template: job_template,
+            assigned: std::time::Instant::now(),
+        });
+
+        // Split EN2 range among eligible threads
+        let en2_slices = full_en2_range
+            .split(eligible.len())
+            .expect("Failed to split EN2 range among threads");
+
+        for (thread_id, en2_range) in eligible.into_iter().zip(en2_slices) {
+            let starting_en2 = en2_range.iter().next();
+
+            let task = HashTask {
+                job: active_job.clone(),
+                en2_range: Some(en2_range),
+                en2: starting_en2,
+                share_target: active_job.template.share_target,
+                ntime: active_job.template.time,
+            };
+
+            let thread = &mut self.threads[thread_id].thread;
+            let result = if replace {
+                thread.replace_work(task).await
+            } else {
+                thread.update_work(task).await
+            };
+
+            if let Err(e) = result {
+                error!(thread_id = ?thread_id, error = %e, "Failed to assign work");
+            } else {
+                self.thread_assignments
+                    .insert(thread_id, active_job.clone());
+            }
+        }
+    }
+
+    /// Idle the threads matching `filter`, given each thread's entry and
+    /// current assignment.
+    async fn idle_threads(
+        &mut self,
+        filter: impl Fn(&ThreadEntry, Option<&Arc<ActiveJob>>) -> bool,
+    ) {
+        let affected: Vec<ThreadId> = self
+            .threads
+            .iter()
+            .filter(|(id, entry)| filter(entry, self.thread_assignments.get(id)))
+            .map(|(id, _)| id)
+            .collect();
+
+        for thread_id in affected {
+            if let Err(e) = self.threads[thread_id].thread.go_idle().await {
+                error!(thread_id = ?thread_id, error = %e, "Failed to idle thread");
+            }
+            self.thread_assignments.remove(&thread_id);
+        }
+    }
+
+    /// Account for a share and submit it to its source if it meets the
+    /// source's target.
+    #[instrument(
+        name = "share",
+        skip_all,
+        fields(
+            thread = ?thread_id,
+            job_id = %share.task.job.template.id,
+            job_age_ms = share.task.job.assigned.elapsed().as_millis() as u64,
+        )
+    )]
+    async fn handle_share(&mut self, thread_id: ThreadId, share: Share) {
+        debug!(
+            thread_id = ?thread_id,
+            job_id = %share.task.job.template.id,
+            nonce = format!("{:#x}", share.nonce),
+            hash = %share.hash,
+            "Share found"
+        );
+
+        // Track hashes for hashrate measurement
+        // Use threshold difficulty, not achieved difficulty (see MiningStats doc)
+        let hashes = (share.threshold_difficulty * (u32::MAX as f64 + 1.0)) as u128;
+        let now = std::time::Instant::now();
+        self.stats.total_hashes += hashes;
+        self.stats.estimator.record(now, hashes as f64);
+        if let Some(entry) = self.threads.get_mut(thread_id) {
+            entry.hashes += hashes;
+            entry.estimator.record(now, hashes as f64);
+            entry.shares += 1;
+            if let Some(board) = self.board_hashrate.get_mut(&entry.board) {
+                board.record(now, hashes as f64);
+            }
+        }
+
+        // Check if share meets source threshold
+        let source_id = share.task.job.source_id;
+        let template = &share.task.job.template;
+
+        if !template.share_target.is_met_by(share.hash) {
+            trace!(
+                thread_id = ?thread_id,
+                nonce = format!("{:#x}", share.nonce),
+                "Share below source threshold (not submitted)"
+            );
+            return;
+        }
+
+        self.stats.shares_submitted += 1;
+
+        // Submit share to originating source
+        let Some(source) = self.sources.get_mut(source_id) else {
+            error!(source_id = ?source_id, "Share for unknown source");
+            return;
+        };
+
+        if template.target().is_met_by(share.hash) {
+            let thread = self
+                .threads
+                .get(thread_id)
+                .map(|entry| entry.name.clone())
+                .unwrap_or_default();
+            info!(pool = %source.name, thread = %thread, hash = %share.hash, "Block found!");
+            self.telemetry.emit(MinerEvent::BlockFound {
+                pool: source.name.clone(),
+                thread,
+                hash: share.hash.to_string(),
+            });
+        }
+
+        use crate::job_source::Share as SourceShare;
+        let source_share = SourceShare {
+            job_id: template.id.clone(),
+            nonce: share.nonce,
+            time: share.ntime,
+            version: share.version,
+            extranonce2: share.extranonce2,
+        };
+
+        if let Err(e) = source
+            .command_tx
+            .send(SourceCommand::SubmitShare(source_share))
+            .await
+        {
+            error!(
+                source_id = ?source_id,
+                error = %e,
+                "Failed to submit share to source"
+            );
+        } else {
+            debug!(source = %source.name, "Share submitted to source");
+            source.shares_submitted += 1;
+            self.telemetry.emit(MinerEvent::ShareSubmitted {
+                pool: source.name.clone(),
+                thread: self
+                    .threads
+                    .get(thread_id)
+                    .map(|entry| entry.name.clone())
+                    .unwrap_or_default(),
+                job_id: template.id.clone(),
+                difficulty: template.share_target.difficulty_float(),
+            });
+        }
+    }
+
+    async fn handle_thread_event(&mut self, thread_id: ThreadId, event: HashThreadEvent) {
+        match event {
+            HashThreadEvent::ShareFound(share) => self.handle_share(thread_id, share).await,
+
+            HashThreadEvent::WorkExhausted { en2_searched } => {
+                info!(thread_id = ?thread_id, en2_searched, "Work exhausted");
+                // TODO: Assign new work to this thread
+            }
+
+            HashThreadEvent::WorkDepletionWarning {
+                estimated_remaining_ms,
+            } => {
+                debug!(
+                    thread_id = ?thread_id,
+                    remaining_ms = estimated_remaining_ms,
+                    "Work depletion warning"
+                );
+                // TODO: Prepare next work assignment
+            }
+
+            HashThreadEvent::StatusUpdate(status) => {
+                trace!(
+                    thread_id = ?thread_id,
+                    hashrate_ghs = format!("{:.2}", status.hashrate / 1_000_000_000.0),
+                    active = status.is_active,
+                    "Thread status"
+                );
+            }
+        }
+    }
+
+    /// Carry out a command, publishing the resulting status before replying
+    /// so a client that reads status after the reply sees the change.
+    async fn handle_command(&mut self, command: SchedulerCommand) {
+        match command {
+            SchedulerCommand::Pause { response } => {
+                if !self.paused {
+                    info!("Mining paused.");
+                    self.paused = true;
+                    self.idle_threads(|_, _| true).await;
+                }
+                self.publish_status();
+                let _ = response.send(());
+            }
+
+            SchedulerCommand::Resume { response } => {
+                if self.paused {
+                    info!("Mining resumed.");
+                    self.paused = false;
+                    if let Some(source_id) = self.active_source {
+                        self.assign_current_job(source_id, true).await;
+                    }
+                }
+                self.publish_status();
+                let _ = response.send(());
+            }
+
+            SchedulerCommand::AddPool { request, response } => {
+                let result = self.add_pool(request);
+                self.publish_status();
+                let _ = response.send(result);
+            }
+
+            SchedulerCommand::RemovePool { name, response } => {
+                let result = self.remove_pool(&name).await;
+                self.publish_status();
+                let _ = response.send(result);
+            }
+
+            SchedulerCommand::SwitchPool { name, response } => {
+                let result = self.switch_pool(&name).await;
+                self.publish_status();
+                let _ = response.send(result);
+            }
+
+            SchedulerCommand::IdleBoard { serial, response } => {
+                let result = self.idle_board(&serial).await;
+                self.publish_status();
+                let _ = response.send(result);
+            }
+        }
+    }
+
+    fn source_by_name(&self, name: &str) -> Result<SourceId, CommandError> {
+        self.sources
+            .iter()
+            .find(|(_, source)| source.name == name)
+            .map(|(id, _)| id)
+            .ok_or_else(|| CommandError::NotFound(format!("pool {name}")))
+    }
+
+    fn add_pool(&mut self, request: AddPoolRequest) -> Result<PoolStatus, CommandError> {
+        if request.url.trim().is_empty() {
+            return Err(CommandError::Invalid("pool url must not be empty".into()));
+        }
+
+        let name = match request.name {
+            Some(name) if self.source_by_name(&name).is_ok() => {
+                return Err(CommandError::Conflict(format!(
+                    "a pool named {name} already exists"
+                )));
+            }
+            Some(name) => name,
+            None => loop {
+                let name = format!("pool-{}", self.next_pool_number);
+                self.next_pool_number += 1;
+                if self.source_by_name(&name).is_err() {
+                    break name;
+                }
+            },
+        };
+
+        let config = StratumPoolConfig {
+            url: request.url.clone(),
+            username: request.worker,
+            password: request.password.unwrap_or_else(|| "x".to_string()),
+            suggested_difficulty: SUGGESTED_DIFFICULTY,
+            ..Default::default()
+        };
+
+        let (event_tx, event_rx) = mpsc::channel(100);
+        let (command_tx, command_rx) = mpsc::channel(10);
+        let shutdown = self.running.child_token();
+        let source = StratumV1Source::new(config, command_rx, event_tx, shutdown.clone());
+
+        let task_name = name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = source.run().await {
+                error!(pool = %task_name, error = %e, "Stratum v1 source error");
+            }
+        });
+
+        info!(pool = %name, url = %request.url, "Pool added.");
+        let source_id = self.register_source(SourceRegistration {
+            name,
+            url: Some(request.url),
+            event_rx,
+            command_tx,
+            shutdown,
+        });
+
+        Ok(self.pool_status(source_id))
+    }
+
+    async fn remove_pool(&mut self, name: &str) -> Result<(), CommandError> {
+        let source_id = self.source_by_name(name)?;
+
+        let source = self.sources.remove(source_id).expect("source exists");
+        self.source_events.remove(&source_id);
+        source.shutdown.cancel();
+        info!(pool = %name, "Pool removed.");
+
+        self.idle_threads(|_, job| job.is_some_and(|job| job.source_id == source_id))
+            .await;
+
+        // Fall back to another source, if any
+        if self.active_source == Some(source_id) {
+            self.active_source = self.sources.keys().next();
+            if let Some(next) = self.active_source {
+                info!(pool = %self.sources[next].name, "Switched to pool.");
+                self.assign_current_job(next, true).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn switch_pool(&mut self, name: &str) -> Result<(), CommandError> {
+        let source_id = self.source_by_name(name)?;
+        if self.active_source == Some(source_id) {
+            return Ok(());
+        }
+
+        info!(pool = %name, "Switched to pool.");
+        self.active_source = Some(source_id);
+
+        // Stop working for the old source even if the new one has no job yet
+        self.idle_threads(|_, job| job.is_some_and(|job| job.source_id != source_id))
+            .await;
+        self.assign_current_job(source_id, true).await;
+
+        Ok(())
+    }
+
+    async fn idle_board(&mut self, serial: &str) -> Result<(), CommandError> {
+        if !self.threads.values().any(|entry| entry.board == serial) {
+            return Err(CommandError::NotFound(format!("board {serial}")));
+        }
+
+        info!(board = %serial, "Board idled.");
+        self.idle_boards.insert(serial.to_string());
+        self.idle_threads(|entry, _| entry.board == serial).await;
+
+        Ok(())
+    }
+
+    /// Forget threads whose event streams have ended (board removed).
+    fn prune_threads(&mut self) {
+        let thread_events = &self.thread_events;
+        self.threads.retain(|id, _| thread_events.contains_key(&id));
+        let threads = &self.threads;
+        self.thread_assignments
+            .retain(|id, _| threads.contains_key(*id));
+        self.board_hashrate
+            .retain(|board, _| threads.values().any(|entry| &entry.board == board));
+    }
+
+    fn pool_status(&self, source_id: SourceId) -> PoolStatus {
+        let source = &self.sources[source_id];
+        PoolStatus {
+            name: source.name.clone(),
+            url: source.url.clone(),
+            connected: source.current_job.is_some(),
+            active: self.active_source == Some(source_id),
+            difficulty: source.difficulty,
+            shares_submitted: source.shares_submitted,
+            shares_accepted: source.shares_accepted,
+            shares_rejected: source.shares_rejected,
+        }
+    }
+
+    /// Raise and clear alerts for chips and core domains whose health,
+    /// as scored by their threads, crosses the underperforming threshold.
+    fn check_chip_health(&mut self) {
+        for entry in self.threads.values_mut() {
+            let status = entry.thread.status();
+            let mut current = HashMap::new();
+            for chip in &status.chips {
+                if let Some(health) = chip.health.filter(|&h| h < health::UNDERPERFORMING) {
+                    current.insert((chip.address, None), health);
+                }
+                // Domains are judged against each other, not an expectation
+                let domain_mean = chip.domain_nonces.iter().sum::<u64>() as f64
+                    / chip.domain_nonces.len().max(1) as f64;
+                for &domain in &chip.weak_domains {
+                    let nonces = chip.domain_nonces[domain as usize] as f64;
+                    current.insert((chip.address, Some(domain)), nonces / domain_mean);
+                }
+            }
+
+            for (&(chip, domain), &health) in &current {
+                if entry.underperforming.insert((chip, domain)) {
+                    warn!(
+                        thread = %entry.name,
+                        chip,
+                        domain,
+                        health = format!("{:.0}%", health * 100.0),
+                        "Chip underperforming"
+                    );
+                    self.telemetry.emit(MinerEvent::ChipUnderperforming {
+                        thread: entry.name.clone(),
+                        chip,
+                        domain,
+                        health,
+                    });
+                }
+            }
+
+            entry.underperforming.retain(|&(chip, domain)| {
+                let still = current.contains_key(&(chip, domain));
+                if !still {
+                    info!(thread = %entry.name, chip, domain, "Chip recovered");
+                    self.telemetry.emit(MinerEvent::ChipRecovered {
+                        thread: entry.name.clone(),
+                        chip,
+                        domain,
+                    });
+                }
+                still
+            });
+        }
+    }
+
+    /// Publish thread, pool, and total statistics.
+    fn publish_status(&self) {
+        let now = std::time::Instant::now();
+        let threads = self
+            .threads
+            .values()
+            .map(|entry| {
+                let status = entry.thread.status();
+                ThreadStatus {
+                    name: entry.name.clone(),
+                    board: entry.board.clone(),
+                    hashrate: hashrate(entry.hashes, entry.registered_at.elapsed()),
+                    hashrate_windows: entry.estimator.estimates(now),
+                    shares: entry.shares,
+                    hardware_errors: status.hardware_errors,
+                    hardware_error_rate: status.hardware_error_rate,
+                    active: status.is_active,
+                    chips: status.chips,
+                }
+            })
+            .collect();
+        let pools = self
+            .sources
+            .keys()
+            .map(|source_id| self.pool_status(source_id))
+            .collect();
+
+        let boards: HashMap<&String, _> = self
+            .board_hashrate
+            .iter()
+            .map(|(board, estimator)| (board, estimator.estimates(now)))
+            .collect();
+
+        let stats = &self.stats;
+        let paused = self.paused;
+        self.telemetry.update_status(|status| {
+            status.uptime_s = stats.start_time.elapsed().as_secs();
+            status.hashrate = hashrate(stats.total_hashes, stats.start_time.elapsed());
+            status.hashrate_windows = stats.estimator.estimates(now);
+            for board in &mut status.boards {
+                board.hashrate_windows = boards.get(&board.serial).cloned().unwrap_or_default();
+            }
+            status.shares_submitted = stats.shares_submitted;
+            status.paused = paused;
+            status.threads = threads;
+            status.pools = pools;
+        });
+    }
+}
+
 /// Mining statistics tracker
 ///
 /// # Hashrate Calculation Methodology
@@ -412,6 +1033,8 @@ struct MiningStats {
     /// Uses u128 for overflow safety. At 1 TH/s, u64 would overflow in 5 hours
     /// but u128 won't overflow for 10 quadrillion years.
     total_hashes: u128,
+    /// Recent total hashrate (see [`crate::hashrate`]).
+    estimator: HashrateEstimator,
     shares_submitted: u64,
 }
 
@@ -421,11 +1044,22 @@ impl Default for MiningStats {
         Self {
             start_time: now,
             total_hashes: 0,
+            estimator: HashrateEstimator::new(now),
             shares_submitted: 0,
         }
     }
 }
 
+/// Average hashrate in H/s over `elapsed`, zero if no time has passed.
+fn hashrate(hashes: u128, elapsed: std::time::Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        hashes as f64 / secs
+    } else {
+        0.0
+    }
+}
+
 impl MiningStats {
     fn log_summary(&mut self) {
         let elapsed = self.start_time.elapsed().as_secs_f64();
@@ -440,9 +1074,25 @@ impl MiningStats {
 
         // Mining statistics
         if let Some(ghs) = hashrate_ghs {
+            // The 5-minute window, with its 95% confidence interval
+            let ghs_5m = |hs: Option<f64>| format!("{:.1}", hs.unwrap_or(0.0) / 1e9);
+            let hashrate_5m = self
+                .estimator
+                .estimates(std::time::Instant::now())
+                .into_iter()
+                .find(|w| w.window_s == 300)
+                .map(|recent| {
+                    format!(
+                        "{} GH/s ({}-{})",
+                        ghs_5m(Some(recent.hashrate)),
+                        ghs_5m(recent.lower),
+                        ghs_5m(recent.upper)
+                    )
+                });
             info!(
                 uptime_s = elapsed as u64,
                 hashrate = format!("{:.1} GH/s", ghs),
+                hashrate_5m,
                 shares = self.shares_submitted,
                 "Mining status."
             );
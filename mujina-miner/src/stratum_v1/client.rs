@@ -139,6 +139,7 @@ impl StratumV1Client {
     ///
     /// Times out after 30 seconds if no response is received. Responds immediately
     /// to shutdown requests.
+    #[instrument(name = "stratum_request", skip_all, fields(pool = %self.config.url, method = %method))]
     async fn send_request(
         &mut self,
         conn: &mut Connection,
@@ -409,6 +410,7 @@ impl StratumV1Client {
     ///
     /// Sends `mining.submit` and waits for acceptance/rejection. Emits
     /// ShareAccepted or ShareRejected events based on pool response.
+    #[instrument(skip_all, fields(job_id = %params.job_id, accepted = tracing::field::Empty))]
     async fn submit(&mut self, conn: &mut Connection, params: SubmitParams) -> StratumResult<bool> {
         use serde_json::Value;
 
@@ -430,6 +432,7 @@ impl StratumV1Client {
             } => {
                 // Result should be true for accepted
                 let accepted = result.as_bool().unwrap_or(false);
+                tracing::Span::current().record("accepted", accepted);
                 if accepted {
                     self.event_tx
                         .send(ClientEvent::ShareAccepted { job_id, nonce })
@@ -459,6 +462,7 @@ impl StratumV1Client {
                 } else {
                     format!("{:?}", error)
                 };
+                tracing::Span::current().record("accepted", false);
 
                 self.event_tx
                     .send(ClientEvent::ShareRejected {
@@ -886,7 +890,7 @@ mod tests {
                                 println!("  Extranonce2 size: {} bytes", extranonce2_size);
                                 assert!(!extranonce1.is_empty(), "extranonce1 should not be empty");
                                 assert!(
-                                    extranonce2_size >= 4 && extranonce2_size <= 8,
+                                    (4..=8).contains(&extranonce2_size),
                                     "extranonce2_size should be 4-8 bytes"
                                 );
                                 subscribed = true;
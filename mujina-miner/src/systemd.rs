@@ -0,0 +1,196 @@
+//! systemd service notification.
+//!
+//! With `daemon.systemd` enabled and the daemon started by systemd as a
+//! `Type=notify` service, this reports readiness once the scheduler and API
+//! are up, keeps the unit's status line showing the current hashrate, and
+//! tells systemd when shutdown begins.
+//!
+//! If the unit sets `WatchdogSec=`, watchdog pings are sent at half that
+//! interval, but only while the scheduler loop keeps beating its
+//! [`Heartbeat`]. A scheduler wedged on a hung board or a deadlock stops the
+//! pings and systemd restarts the daemon.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use sd_notify::NotifyState;
+use tokio_util::sync::CancellationToken;
+
+use crate::{telemetry::Telemetry, tracing::prelude::*, types::HashRate};
+
+/// How often the status line is refreshed.
+const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest the scheduler may go without beating before it is considered
+/// stuck. Its periodic work runs every five seconds, so this leaves room for
+/// a slow iteration or two.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Liveness signal from a task loop.
+///
+/// The owner calls [`beat`](Self::beat) on every iteration; observers check
+/// how long ago that last happened.
+#[derive(Clone, Debug)]
+pub struct Heartbeat(Arc<Mutex<Instant>>);
+
+impl Heartbeat {
+    /// Create a heartbeat that last beat now.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    /// Record that the loop is alive.
+    pub fn beat(&self) {
+        *self.0.lock() = Instant::now();
+    }
+
+    /// Whether the last beat was within `timeout` of `now`.
+    pub fn is_alive(&self, now: Instant, timeout: Duration) -> bool {
+        now.saturating_duration_since(*self.0.lock()) <= timeout
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends notifications to the service manager.
+///
+/// Disabled notifiers, and enabled ones outside a systemd service (no
+/// `NOTIFY_SOCKET`), send nothing.
+#[derive(Clone, Debug)]
+pub struct Notifier {
+    enabled: bool,
+}
+
+impl Notifier {
+    /// Create a notifier, sending only if `enabled`.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Report that startup is complete.
+    pub fn ready(&self) {
+        self.notify(&[NotifyState::Ready]);
+    }
+
+    /// Set the status line shown by `systemctl status`.
+    pub fn status(&self, status: &str) {
+        self.notify(&[NotifyState::Status(status)]);
+    }
+
+    /// Report that shutdown has begun.
+    pub fn stopping(&self) {
+        self.notify(&[NotifyState::Stopping, NotifyState::Status("Shutting down")]);
+    }
+
+    /// Interval to ping the watchdog at, if the unit has one enabled.
+    fn watchdog_interval(&self) -> Option<Duration> {
+        let mut usec = 0;
+        (self.enabled && sd_notify::watchdog_enabled(false, &mut usec))
+            .then(|| Duration::from_micros(usec) / 2)
+    }
+
+    fn watchdog(&self) {
+        self.notify(&[NotifyState::Watchdog]);
+    }
+
+    fn notify(&self, state: &[NotifyState]) {
+        if !self.enabled {
+            return;
+        }
+        if let Err(e) = sd_notify::notify(false, state) {
+            warn!(error = %e, "Failed to notify systemd.");
+        }
+    }
+}
+
+/// Status line for the given 5-minute hashrate.
+fn status_line(hashrate: f64) -> String {
+    format!(
+        "Hashing at {}",
+        HashRate(hashrate as u64).to_human_readable()
+    )
+}
+
+/// Keep the status line current and ping the watchdog while `heartbeat` is
+/// alive, until shutdown.
+pub async fn task(
+    notifier: Notifier,
+    telemetry: Telemetry,
+    heartbeat: Heartbeat,
+    shutdown: CancellationToken,
+) {
+    let mut status_interval = tokio::time::interval(STATUS_INTERVAL);
+    status_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    // Without a watchdog the interval exists but its branch is disabled
+    let watchdog = notifier.watchdog_interval();
+    if let Some(interval) = watchdog {
+        debug!(
+            interval_ms = interval.as_millis() as u64,
+            "systemd watchdog enabled."
+        );
+    }
+    let mut watchdog_interval = tokio::time::interval(watchdog.unwrap_or(STATUS_INTERVAL));
+    watchdog_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut reported_stuck = false;
+
+    loop {
+        tokio::select! {
+            _ = status_interval.tick() => {
+                let status = telemetry.status();
+                let hashrate = status
+                    .hashrate_windows
+                    .iter()
+                    .find(|window| window.window_s == 300)
+                    .map_or(status.hashrate, |window| window.hashrate);
+                notifier.status(&status_line(hashrate));
+            }
+
+            _ = watchdog_interval.tick(), if watchdog.is_some() => {
+                if heartbeat.is_alive(Instant::now(), HEARTBEAT_TIMEOUT) {
+                    notifier.watchdog();
+                    reported_stuck = false;
+                } else if !reported_stuck {
+                    error!("Scheduler is not responding; withholding watchdog ping.");
+                    reported_stuck = true;
+                }
+            }
+
+            _ = shutdown.cancelled() => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_freshness() {
+        let heartbeat = Heartbeat::new();
+        let now = Instant::now();
+        assert!(heartbeat.is_alive(now, HEARTBEAT_TIMEOUT));
+        assert!(!heartbeat.is_alive(now + HEARTBEAT_TIMEOUT * 2, HEARTBEAT_TIMEOUT));
+
+        // A clone observes beats from the original
+        let observer = heartbeat.clone();
+        let later = now + Duration::from_millis(2);
+        assert!(!observer.is_alive(later + HEARTBEAT_TIMEOUT, HEARTBEAT_TIMEOUT));
+        std::thread::sleep(Duration::from_millis(2));
+        heartbeat.beat();
+        assert!(observer.is_alive(later + HEARTBEAT_TIMEOUT, HEARTBEAT_TIMEOUT));
+    }
+
+    #[test]
+    fn test_status_line() {
+        assert_eq!(status_line(1.2e12), "Hashing at 1.20 TH/s");
+        assert_eq!(status_line(0.0), "Hashing at 0 H/s");
+    }
+}
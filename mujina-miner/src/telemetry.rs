@@ -0,0 +1,109 @@
+//! Shared miner status and event publication.
+//!
+//! The scheduler and backplane each own part of the miner's observable state:
+//! the scheduler knows threads, pools, and share counts; the backplane knows
+//! boards and their sensor readings. Both publish into a single [`Telemetry`]
+//! handle, and consumers (the API server today) read from it without
+//! reaching into either actor.
+//!
+//! State is kept in a `watch` channel so readers always see the latest
+//! snapshot, and discrete happenings (shares, board hotplug, faults) go out on
+//! a `broadcast` channel for subscribers that want a stream.
+
+use tokio::sync::{broadcast, watch};
+
+use crate::api_client::types::{MinerEvent, MinerStatus};
+
+/// Capacity of the event broadcast channel.
+///
+/// Subscribers that fall further behind than this skip ahead and miss events,
+/// which is preferable to slowing down the producers.
+const EVENT_CAPACITY: usize = 256;
+
+/// Cloneable handle for publishing and observing miner status.
+#[derive(Clone)]
+pub struct Telemetry {
+    status: watch::Sender<MinerStatus>,
+    events: broadcast::Sender<MinerEvent>,
+}
+
+impl Telemetry {
+    /// Create a new handle with an empty status.
+    pub fn new() -> Self {
+        let (status, _) = watch::channel(MinerStatus::default());
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self { status, events }
+    }
+
+    /// Latest status snapshot.
+    pub fn status(&self) -> MinerStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Subscribe to status changes.
+    pub fn subscribe_status(&self) -> watch::Receiver<MinerStatus> {
+        self.status.subscribe()
+    }
+
+    /// Modify the status in place and notify subscribers.
+    pub fn update_status(&self, modify: impl FnOnce(&mut MinerStatus)) {
+        self.status.send_modify(modify);
+    }
+
+    /// Publish an event to all current subscribers.
+    ///
+    /// Events published with no subscribers are dropped.
+    pub fn emit(&self, event: MinerEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Subscribe to events published after this call.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<MinerEvent> {
+        self.events.subscribe()
+    }
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_status_updates_are_observed() {
+        let telemetry = Telemetry::new();
+        let mut rx = telemetry.subscribe_status();
+
+        telemetry.update_status(|s| s.shares_submitted = 3);
+
+        rx.changed().await.unwrap();
+        assert_eq!(rx.borrow().shares_submitted, 3);
+        assert_eq!(telemetry.status().shares_submitted, 3);
+    }
+
+    #[tokio::test]
+    async fn test_events_reach_subscribers() {
+        let telemetry = Telemetry::new();
+
+        // Emitting without subscribers must not fail
+        telemetry.emit(MinerEvent::BoardDisconnected {
+            serial: "early".into(),
+        });
+
+        let mut rx = telemetry.subscribe_events();
+        telemetry.emit(MinerEvent::ShareAccepted {
+            pool: "pool".into(),
+        });
+
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            MinerEvent::ShareAccepted {
+                pool: "pool".into()
+            }
+        );
+    }
+}
@@ -1,38 +1,55 @@
 //! Provide tracing, tailored to this program.
 //!
-//! At startup, the program should call one of the init_* functions at startup
-//! to install a tracing subscriber (i.e., something that emits events to a
-//! log).
+//! At startup, the program should call [`init`] to install a tracing
+//! subscriber (i.e., something that emits events to a log), configured by
+//! the `[logging]` section of the configuration file.
 //!
 //! The rest of program the can include `use tracing::prelude::*` for convenient
 //! access to the `trace!()`, `debug!()`, `info!()`, `warn!()`, and `error!()`
 //! macros.
 
-use std::{env, fmt};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fmt, fs, io};
+
+use parking_lot::Mutex;
+use rolling_file::{RollingConditionBasic, RollingFileAppender};
 use time::OffsetDateTime;
+use tokio::sync::broadcast;
 use tracing::field::{Field, Visit};
 use tracing::{Event, Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
 use tracing_journald;
 use tracing_subscriber::{
     filter::{EnvFilter, LevelFilter},
     fmt::{
         format::{DefaultFields, Writer as FmtWriter},
         time::FormatTime,
-        FmtContext, FormatEvent, FormatFields,
+        FmtContext, FormatEvent, FormatFields, MakeWriter,
     },
+    layer::Context,
     prelude::*,
     registry::LookupSpan,
+    reload, Layer, Registry,
 };
 
+use crate::api_client::types::{LogEntry, LogLevels};
+use crate::config::{Config, LogFileConfig, LogFormat, LogRotation};
+use crate::error::CommandError;
+
+#[cfg(feature = "otel")]
+mod otlp;
+
 #[cfg(target_os = "linux")]
-use std::{io, os::unix::io::AsRawFd};
+use std::os::unix::io::AsRawFd;
 
 #[cfg(target_os = "linux")]
 use nix::libc;
 
 pub mod prelude {
     #[allow(unused_imports)]
-    pub use tracing::{debug, error, info, trace, warn};
+    pub use tracing::{debug, error, info, info_span, instrument, trace, warn, Instrument};
 }
 
 use prelude::*;
@@ -79,44 +96,401 @@ fn stderr_is_journal_stream() -> bool {
     stat.st_dev == expected_dev && stat.st_ino == expected_ino
 }
 
-/// Initialize logging.
+/// An output of the subscriber.
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Keeps the file log's background writer running.
+///
+/// Hold it until the program exits; dropping it flushes and closes the file.
+#[must_use = "file logging stops when the guard is dropped"]
+pub struct LogGuard {
+    logs: Logs,
+    _file: Option<WorkerGuard>,
+    #[cfg(feature = "otel")]
+    _otlp: Option<otlp::OtlpGuard>,
+}
+
+impl LogGuard {
+    /// The in-memory log buffer and runtime filter control.
+    pub fn logs(&self) -> Logs {
+        self.logs.clone()
+    }
+}
+
+/// Initialize logging from the configuration.
 ///
-/// If running under systemd, use journald; otherwise fall
-/// back to stdout.
-pub fn init_journald_or_stdout() {
+/// The console log goes to journald when running under systemd in the text
+/// format, otherwise to stdout. If a log file or OTLP export is configured
+/// it is written as well; failing to start either is logged but not fatal. Recent events are kept
+/// in memory for the API, which can also change levels at runtime.
+pub fn init(config: &Config) -> LogGuard {
+    let logging = &config.logging;
+    let logs = Logs::new(logging.buffer_events);
+    let mut layers: Vec<BoxedLayer> = vec![console_layer(logging.format)];
+    if logging.buffer_events > 0 {
+        layers.push(BufferLayer { logs: logs.clone() }.boxed());
+    }
+
+    let mut file_guard = None;
+    let mut file_error = None;
+    if let Some(file) = &logging.file {
+        match file_writer(file) {
+            Ok((writer, guard)) => {
+                layers.push(format_layer(file.format, writer, false));
+                file_guard = Some(guard);
+            }
+            Err(e) => file_error = Some(e),
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    let mut otlp_guard = None;
+    let mut otlp_error = None;
+    if config.otlp.enabled {
+        #[cfg(feature = "otel")]
+        match otlp::layer(&config.otlp) {
+            Ok((layer, guard)) => {
+                layers.push(layer.boxed());
+                otlp_guard = Some(guard);
+            }
+            Err(e) => otlp_error = Some(e.to_string()),
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            otlp_error = Some("built without the otel feature".to_string());
+        }
+    }
+
+    let directives = base_directives(&config.daemon.log_level, &logging.levels);
+    let (filter, handle) = reload::Layer::new(env_filter(&directives));
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter)
+        .init();
+    logs.attach_filter(directives, move |filter| {
+        handle.reload(filter).map_err(|e| e.to_string())
+    });
+
+    if let (Some(file), Some(e)) = (&logging.file, file_error) {
+        error!(path = %file.path.display(), error = %e, "Failed to open log file");
+    }
+    if let Some(e) = otlp_error {
+        error!(error = %e, "Failed to start OTLP span export");
+    }
+
+    LogGuard {
+        logs,
+        _file: file_guard,
+        #[cfg(feature = "otel")]
+        _otlp: otlp_guard,
+    }
+}
+
+fn console_layer(format: LogFormat) -> BoxedLayer {
     #[cfg(target_os = "linux")]
     {
-        if stderr_is_journal_stream() {
-            if let Ok(layer) = tracing_journald::layer() {
-                tracing_subscriber::registry().with(layer).init();
-                return;
-            } else {
-                error!("Failed to initialize journald logging, using stdout.");
+        if format == LogFormat::Text && stderr_is_journal_stream() {
+            match tracing_journald::layer() {
+                Ok(layer) => return layer.boxed(),
+                // Nothing is listening yet, so this can't be logged
+                Err(e) => eprintln!("Failed to initialize journald logging, using stdout: {e}"),
             }
         }
     }
 
-    use_stdout();
+    format_layer(format, io::stdout, true)
+}
+
+fn format_layer<W>(format: LogFormat, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .with_ansi(ansi)
+            .with_timer(LocalTimer)
+            .with_target(true)
+            .fmt_fields(DefaultFields::new())
+            .event_format(CustomFormatter)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(writer)
+            .with_current_span(true)
+            .with_span_list(true)
+            .flatten_event(true)
+            .boxed(),
+    }
+}
+
+/// Open the log file behind a background writer, so logging never blocks
+/// on disk I/O.
+fn file_writer(
+    config: &LogFileConfig,
+) -> io::Result<(tracing_appender::non_blocking::NonBlocking, WorkerGuard)> {
+    Ok(tracing_appender::non_blocking(file_appender(config)?))
+}
+
+fn file_appender(config: &LogFileConfig) -> io::Result<RollingFileAppender<RollingConditionBasic>> {
+    if let Some(dir) = config.path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut condition = match config.rotation {
+        LogRotation::Never => RollingConditionBasic::new(),
+        LogRotation::Hourly => RollingConditionBasic::new().hourly(),
+        LogRotation::Daily => RollingConditionBasic::new().daily(),
+    };
+    if let Some(mb) = config.max_size_mb {
+        condition = condition.max_size(mb * 1024 * 1024);
+    }
+
+    RollingFileAppender::new(&config.path, condition, config.max_files)
 }
 
-// Log to stdout, filtering according to environment variable RUST_LOG,
-// overriding the default level (ERROR) to INFO.
-fn use_stdout() {
-    let env_filter = EnvFilter::builder()
+/// Filter directives from `RUST_LOG` if set, otherwise from the configured
+/// base level and per-module levels.
+fn base_directives(base: &str, levels: &BTreeMap<String, String>) -> String {
+    match env::var("RUST_LOG") {
+        Ok(directives) => directives,
+        Err(_) => directives(base, levels),
+    }
+}
+
+/// Filter from directives, enabling INFO for anything they don't cover.
+fn env_filter(directives: &str) -> EnvFilter {
+    EnvFilter::builder()
         .with_default_directive(LevelFilter::INFO.into())
-        .with_env_var("RUST_LOG")
-        .from_env_lossy();
+        .parse_lossy(directives)
+}
 
-    tracing_subscriber::registry()
-        .with(env_filter)
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_timer(LocalTimer)
-                .with_target(true)
-                .fmt_fields(DefaultFields::new())
-                .event_format(CustomFormatter),
+fn directives(base: &str, levels: &BTreeMap<String, String>) -> String {
+    std::iter::once(base.to_string())
+        .filter(|base| !base.is_empty())
+        .chain(
+            levels
+                .iter()
+                .map(|(module, level)| format!("{module}={level}")),
         )
-        .init();
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Handle on the in-memory log buffer and the runtime log filter.
+///
+/// The buffer holds the most recent events, so a misbehaving miner can be
+/// debugged over the API without access to its journal. Levels raised here
+/// apply to every output, not just the buffer, until reset or restart.
+#[derive(Clone)]
+pub struct Logs {
+    inner: Arc<LogsInner>,
+}
+
+struct LogsInner {
+    capacity: usize,
+    entries: Mutex<LogEntries>,
+    live: broadcast::Sender<LogEntry>,
+    filter: Mutex<Option<RuntimeFilter>>,
+}
+
+struct LogEntries {
+    next_seq: u64,
+    buffer: VecDeque<LogEntry>,
+}
+
+/// Filter directives the subscriber was started with, overrides applied
+/// since, and how to install a changed filter.
+struct RuntimeFilter {
+    directives: String,
+    overrides: BTreeMap<String, String>,
+    reload: ReloadFn,
+}
+
+type ReloadFn = Box<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>;
+
+/// Subscribers to live events that may fall behind before missing some.
+const LIVE_CAPACITY: usize = 1024;
+
+impl Logs {
+    /// Buffer of the last `capacity` events, with no filter attached.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(LogsInner {
+                capacity,
+                entries: Mutex::new(LogEntries {
+                    next_seq: 0,
+                    buffer: VecDeque::with_capacity(capacity),
+                }),
+                live: broadcast::channel(LIVE_CAPACITY).0,
+                filter: Mutex::new(None),
+            }),
+        }
+    }
+
+    fn attach_filter(
+        &self,
+        directives: String,
+        reload: impl Fn(EnvFilter) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        *self.inner.filter.lock() = Some(RuntimeFilter {
+            directives,
+            overrides: BTreeMap::new(),
+            reload: Box::new(reload),
+        });
+    }
+
+    /// Append an event, assigning its sequence number.
+    pub(crate) fn push(&self, mut entry: LogEntry) {
+        if self.inner.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.inner.entries.lock();
+        entry.seq = entries.next_seq;
+        entries.next_seq += 1;
+        if entries.buffer.len() == self.inner.capacity {
+            entries.buffer.pop_front();
+        }
+        entries.buffer.push_back(entry.clone());
+        // Send under the lock so live subscribers see sequence order
+        let _ = self.inner.live.send(entry);
+    }
+
+    /// Buffered events matching `filter`, oldest first, at most `limit` of
+    /// the newest.
+    pub fn entries(&self, filter: &LogFilter, limit: usize) -> Vec<LogEntry> {
+        let entries = self.inner.entries.lock();
+        let mut matching: Vec<_> = entries
+            .buffer
+            .iter()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(limit)
+            .cloned()
+            .collect();
+        matching.reverse();
+        matching
+    }
+
+    /// Receive events as they are logged.
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.inner.live.subscribe()
+    }
+
+    /// The filter in effect, or `None` if none is attached.
+    pub fn levels(&self) -> Option<LogLevels> {
+        self.inner.filter.lock().as_ref().map(|filter| LogLevels {
+            directives: filter.directives.clone(),
+            overrides: filter.overrides.clone(),
+        })
+    }
+
+    /// Log `target` at `level` from now on, or as configured again if
+    /// `level` is `None`.
+    pub fn set_level(&self, target: &str, level: Option<&str>) -> Result<(), CommandError> {
+        if target.is_empty() || target.contains(['=', ',', '[', ']', ' ']) {
+            return Err(CommandError::Invalid(format!(
+                "'{target}' is not a module path"
+            )));
+        }
+        if let Some(level) = level {
+            level.parse::<LevelFilter>().map_err(|_| {
+                CommandError::Invalid(format!(
+                    "level must be one of off, error, warn, info, debug, trace, not '{level}'"
+                ))
+            })?;
+        }
+
+        let mut filter = self.inner.filter.lock();
+        let filter = filter
+            .as_mut()
+            .ok_or(CommandError::Unavailable("log filter"))?;
+        let mut overrides = filter.overrides.clone();
+        match level {
+            Some(level) => overrides.insert(target.to_string(), level.to_ascii_lowercase()),
+            None => overrides.remove(target),
+        };
+
+        (filter.reload)(env_filter(&directives(&filter.directives, &overrides)))
+            .map_err(CommandError::Invalid)?;
+        filter.overrides = overrides;
+        Ok(())
+    }
+}
+
+/// Which buffered events to return.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Least severe level to include
+    pub level: Option<Level>,
+
+    /// Module path prefix, matched at `::` boundaries
+    pub target: Option<String>,
+
+    /// Only events logged after this Unix time, in seconds
+    pub since: Option<f64>,
+}
+
+impl LogFilter {
+    /// Whether `entry` passes the filter.
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(level) = self.level {
+            match entry.level.parse::<Level>() {
+                // More verbose levels compare greater
+                Ok(entry_level) if entry_level <= level => {}
+                _ => return false,
+            }
+        }
+        if let Some(target) = &self.target {
+            let within = entry
+                .target
+                .strip_prefix(target.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"));
+            if !within {
+                return false;
+            }
+        }
+        self.since.is_none_or(|since| entry.time > since)
+    }
+}
+
+/// Layer copying every event that passes the filter into [`Logs`].
+struct BufferLayer {
+    logs: Logs,
+}
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldCollector::new();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let mut target = metadata.target().to_string();
+        let mut fields = BTreeMap::new();
+        for (key, value) in visitor.fields {
+            let value = value.trim_matches('"').to_string();
+            if key == "log.target" {
+                target = value;
+            } else if !key.starts_with("log.") {
+                fields.insert(key, value);
+            }
+        }
+
+        self.logs.push(LogEntry {
+            seq: 0,
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0.0, |d| d.as_secs_f64()),
+            level: metadata.level().as_str().to_ascii_lowercase(),
+            target,
+            message: visitor
+                .message
+                .map(|msg| msg.trim_matches('"').to_string())
+                .unwrap_or_default(),
+            fields,
+        });
+    }
 }
 
 /// Custom event formatter that strips crate prefix, colors the target,
@@ -184,7 +558,14 @@ where
             Level::DEBUG => ("\x1b[34m", "DEBUG"), // Blue
             Level::TRACE => ("\x1b[35m", "TRACE"), // Magenta
         };
-        write!(writer, "{}{}\x1b[0m ", level_color, level_text)?;
+        // Colors only where the layer allows them, not in files
+        let ansi = writer.has_ansi_escapes();
+        let (level_color, reset) = if ansi {
+            (level_color, "\x1b[0m")
+        } else {
+            ("", "")
+        };
+        write!(writer, "{}{}{} ", level_color, level_text, reset)?;
 
         // Write target (module path) intelligently:
         // - Strip "mujina_miner::" from our own code to reduce noise
@@ -227,7 +608,8 @@ where
             writeln!(writer)?;
             // Indent to align with module column
             // Timestamp (8 chars) + space + level (5 chars) + space = 15
-            write!(writer, "\x1b[90m               ")?; // 15 spaces, bright black (dark gray)
+            let gray = if ansi { "\x1b[90m" } else { "" }; // bright black (dark gray)
+            write!(writer, "{}               ", gray)?; // 15 spaces
             for (i, (key, value)) in display_fields.iter().enumerate() {
                 if i > 0 {
                     write!(writer, ", ")?;
@@ -236,7 +618,7 @@ where
                 let clean_value = value.trim_matches('"');
                 write!(writer, "{}={}", key, clean_value)?;
             }
-            write!(writer, "\x1b[0m")?;
+            write!(writer, "{}", reset)?;
         }
 
         writeln!(writer)
@@ -261,3 +643,149 @@ impl FormatTime for LocalTimer {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(directives("info", &BTreeMap::new()), "info");
+
+        let levels = BTreeMap::from([
+            ("hyper".to_string(), "warn".to_string()),
+            ("mujina_miner::asic".to_string(), "debug".to_string()),
+        ]);
+        assert_eq!(
+            directives("info", &levels),
+            "info,hyper=warn,mujina_miner::asic=debug"
+        );
+    }
+
+    fn entry(level: &str, target: &str, time: f64) -> LogEntry {
+        LogEntry {
+            seq: 0,
+            time,
+            level: level.to_string(),
+            target: target.to_string(),
+            message: String::new(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_buffer_keeps_newest_and_filters() {
+        let logs = Logs::new(3);
+        logs.push(entry("info", "mujina_miner::daemon", 1.0));
+        logs.push(entry("debug", "mujina_miner::asic::bm13xx", 2.0));
+        logs.push(entry("warn", "mujina_miner::asic", 3.0));
+        logs.push(entry("error", "mujina_miner::asics", 4.0));
+
+        let all = logs.entries(&LogFilter::default(), usize::MAX);
+        assert_eq!(all.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(logs.entries(&LogFilter::default(), 1)[0].seq, 3);
+
+        let filter = LogFilter {
+            level: Some(Level::WARN),
+            ..Default::default()
+        };
+        assert_eq!(logs.entries(&filter, usize::MAX).len(), 2);
+
+        let filter = LogFilter {
+            target: Some("mujina_miner::asic".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(logs.entries(&filter, usize::MAX).len(), 2);
+
+        let filter = LogFilter {
+            since: Some(3.0),
+            ..Default::default()
+        };
+        assert_eq!(logs.entries(&filter, usize::MAX)[0].seq, 3);
+    }
+
+    #[test]
+    fn test_layer_captures_events() {
+        let logs = Logs::new(10);
+        let subscriber = tracing_subscriber::registry().with(BufferLayer { logs: logs.clone() });
+        tracing::subscriber::with_default(subscriber, || {
+            warn!(serial = "abc", percent = 50, "Fan stalled");
+        });
+
+        let entries = logs.entries(&LogFilter::default(), 10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, "warn");
+        assert_eq!(entries[0].target, "mujina_miner::tracing::tests");
+        assert_eq!(entries[0].message, "Fan stalled");
+        assert_eq!(entries[0].fields["serial"], "abc");
+        assert_eq!(entries[0].fields["percent"], "50");
+    }
+
+    #[test]
+    fn test_runtime_levels() {
+        let logs = Logs::new(0);
+        assert_eq!(
+            logs.set_level("mujina_miner::asic", Some("debug")),
+            Err(CommandError::Unavailable("log filter"))
+        );
+
+        let installed = Arc::new(Mutex::new(String::new()));
+        logs.attach_filter("info".to_string(), {
+            let installed = installed.clone();
+            move |filter| {
+                *installed.lock() = filter.to_string();
+                Ok(())
+            }
+        });
+
+        logs.set_level("mujina_miner::asic", Some("DEBUG")).unwrap();
+        assert!(installed.lock().contains("mujina_miner::asic=debug"));
+        assert_eq!(
+            logs.levels().unwrap().overrides["mujina_miner::asic"],
+            "debug"
+        );
+
+        assert!(matches!(
+            logs.set_level("mujina_miner::asic", Some("loud")),
+            Err(CommandError::Invalid(_))
+        ));
+        assert!(matches!(
+            logs.set_level("a=b", Some("info")),
+            Err(CommandError::Invalid(_))
+        ));
+
+        logs.set_level("mujina_miner::asic", None).unwrap();
+        assert!(!installed.lock().contains("asic"));
+        assert!(logs.levels().unwrap().overrides.is_empty());
+    }
+
+    #[test]
+    fn test_file_rotation_and_retention() {
+        let dir = std::env::temp_dir().join(format!("mujina-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = LogFileConfig {
+            path: dir.join("logs/mujina.log"),
+            rotation: LogRotation::Never,
+            max_size_mb: Some(1),
+            max_files: 2,
+            ..Default::default()
+        };
+
+        // Four files' worth of lines; the directory is created on open
+        let mut appender = file_appender(&config).unwrap();
+        let line = [b'x'; 1023];
+        for _ in 0..4 * 1024 {
+            appender.write_all(&line).unwrap();
+            appender.write_all(b"\n").unwrap();
+        }
+        appender.flush().unwrap();
+
+        let logs = dir.join("logs");
+        assert!(logs.join("mujina.log").exists());
+        assert!(logs.join("mujina.log.1").exists());
+        assert!(logs.join("mujina.log.2").exists());
+        assert!(!logs.join("mujina.log.3").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
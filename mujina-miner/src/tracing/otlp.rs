@@ -0,0 +1,127 @@
+//! OpenTelemetry span export over OTLP/HTTP.
+//!
+//! Bridges `tracing` spans into OpenTelemetry and batches them to a
+//! collector, so board and chip initialization, share handling and pool
+//! round-trips can be followed in Jaeger, Tempo or similar. Spans are at
+//! INFO level and pass through the same filter as log events.
+
+use std::time::Duration;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::{
+    trace::{Sampler, SdkTracerProvider},
+    Resource,
+};
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+use crate::config::OtlpConfig;
+
+/// Time allowed for one export request.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Flushes pending spans and stops the exporter when dropped.
+pub(super) struct OtlpGuard(SdkTracerProvider);
+
+impl OtlpGuard {
+    /// Export every finished span now.
+    #[cfg(test)]
+    fn flush(&self) {
+        let _ = self.0.force_flush();
+    }
+}
+
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        let _ = self.0.shutdown();
+    }
+}
+
+/// Layer exporting spans as configured.
+pub(super) fn layer<S>(
+    config: &OtlpConfig,
+) -> Result<(impl Layer<S>, OtlpGuard), opentelemetry_otlp::ExporterBuildError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.endpoint)
+        .with_headers(config.headers.clone().into_iter().collect())
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+
+    Ok((
+        tracing_opentelemetry::layer().with_tracer(tracer),
+        OtlpGuard(provider),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, http::HeaderMap, routing::post, Router};
+    use tokio::sync::mpsc;
+    use tracing_subscriber::prelude::*;
+
+    /// Spans reach a collector: a stub accepting OTLP/HTTP exports.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_exports_to_collector() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let collector = Router::new().route(
+            "/v1/traces",
+            post(move |headers: HeaderMap, body: Bytes| {
+                let _ = tx.send((headers, body));
+                async {}
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, collector).await });
+
+        let config = OtlpConfig {
+            enabled: true,
+            endpoint: format!("http://{addr}/v1/traces"),
+            headers: [("x-token".to_string(), "secret".to_string())].into(),
+            ..Default::default()
+        };
+
+        // Export blocks on the collector, so keep it off the runtime threads
+        tokio::task::spawn_blocking(move || {
+            let (layer, guard) = layer(&config).unwrap();
+            let subscriber = tracing_subscriber::registry().with(layer);
+            tracing::subscriber::with_default(subscriber, || {
+                let _span = tracing::info_span!("board_init", serial = "abc123").entered();
+            });
+            guard.flush();
+        })
+        .await
+        .unwrap();
+
+        let (headers, body) = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("nothing exported")
+            .unwrap();
+        assert_eq!(headers["content-type"], "application/x-protobuf");
+        assert_eq!(headers["x-token"], "secret");
+        // Protobuf carries strings as raw UTF-8
+        let contains = |needle: &[u8]| body.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"board_init"));
+        assert!(contains(b"abc123"));
+        assert!(contains(b"mujina-miner"));
+    }
+}
@@ -86,6 +86,23 @@ impl UsbDeviceInfo {
     }
 }
 
+/// Cloning keeps the device identity but not the cached serial port scan, so
+/// the clone rescans when asked. This is what a board restart wants: the
+/// ports may have been re-enumerated since the first scan.
+impl Clone for UsbDeviceInfo {
+    fn clone(&self) -> Self {
+        Self {
+            vid: self.vid,
+            pid: self.pid,
+            serial_number: self.serial_number.clone(),
+            manufacturer: self.manufacturer.clone(),
+            product: self.product.clone(),
+            device_path: self.device_path.clone(),
+            serial_ports: OnceLock::new(),
+        }
+    }
+}
+
 /// Transport event emitted when devices are discovered or disconnected.
 #[derive(Debug)]
 pub enum TransportEvent {